@@ -0,0 +1,4 @@
+-- Track the content hash of the last published snapshot per xDS resource type
+-- Migration: 20250120000001_add_configuration_version_hash.sql
+
+ALTER TABLE configuration_versions ADD COLUMN content_hash TEXT;
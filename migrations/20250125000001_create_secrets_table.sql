@@ -0,0 +1,15 @@
+-- Create secrets table for TLS certificates served over SDS
+-- Migration: 20250125000001_create_secrets_table.sql
+
+CREATE TABLE IF NOT EXISTS secrets (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    certificate_chain TEXT NOT NULL,      -- PEM encoded certificate chain
+    encrypted_private_key TEXT NOT NULL,  -- AES-256-GCM encrypted PEM private key
+    version INTEGER NOT NULL DEFAULT 1,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Index for efficient timestamp queries
+CREATE INDEX IF NOT EXISTS idx_secrets_updated_at ON secrets(updated_at);
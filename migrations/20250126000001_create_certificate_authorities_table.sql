@@ -0,0 +1,18 @@
+-- Create certificate authority table for the built-in CA and track issued leaf certificates
+-- Migration: 20250126000001_create_certificate_authorities_table.sql
+
+CREATE TABLE IF NOT EXISTS certificate_authorities (
+    id TEXT PRIMARY KEY,
+    trust_domain TEXT NOT NULL UNIQUE,
+    certificate TEXT NOT NULL,            -- PEM encoded self-signed CA certificate
+    encrypted_private_key TEXT NOT NULL,  -- AES-256-GCM encrypted PEM private key
+    expires_at DATETIME NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- SPIFFE ID of certificates issued by the built-in CA (NULL for uploaded secrets)
+ALTER TABLE secrets ADD COLUMN spiffe_id TEXT;
+ALTER TABLE secrets ADD COLUMN expires_at DATETIME;
+
+-- Index for finding certificates due for renewal
+CREATE INDEX IF NOT EXISTS idx_secrets_expires_at ON secrets(expires_at);
@@ -0,0 +1,18 @@
+-- Create tables coordinating control plane replicas that share one database
+-- Migration: 20250128000001_create_coordination_tables.sql
+
+CREATE TABLE IF NOT EXISTS control_plane_leases (
+    name TEXT PRIMARY KEY,                -- lease name, e.g. 'leader'
+    holder TEXT NOT NULL,                 -- replica id of the current holder
+    expires_at DATETIME NOT NULL,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Canary rollout decisions shared by every replica
+CREATE TABLE IF NOT EXISTS xds_rollouts (
+    resource_type TEXT PRIMARY KEY,       -- 'cluster', 'route', 'listener', ...
+    candidate_version INTEGER NOT NULL,
+    phase TEXT NOT NULL DEFAULT 'baking', -- 'baking', 'promoted' or 'aborted'
+    nack_count INTEGER NOT NULL DEFAULT 0,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,13 @@
+-- Adjust audit_log constraints to support auth events (PostgreSQL)
+-- Migration: 20250101000002_relax_audit_log_constraints.sql
+
+ALTER TABLE audit_log DROP CONSTRAINT IF EXISTS audit_log_action_check;
+ALTER TABLE audit_log DROP CONSTRAINT IF EXISTS audit_log_resource_type_check;
+
+ALTER TABLE audit_log ALTER COLUMN resource_id DROP NOT NULL;
+ALTER TABLE audit_log ALTER COLUMN resource_name DROP NOT NULL;
+
+ALTER TABLE audit_log
+    ADD CONSTRAINT audit_log_resource_type_check CHECK (length(trim(resource_type)) > 0);
+ALTER TABLE audit_log
+    ADD CONSTRAINT audit_log_action_check CHECK (length(trim(action)) > 0);
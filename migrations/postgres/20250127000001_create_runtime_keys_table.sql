@@ -0,0 +1,14 @@
+-- Create runtime keys table for the runtime layer served over RTDS (PostgreSQL)
+-- Migration: 20250127000001_create_runtime_keys_table.sql
+
+CREATE TABLE IF NOT EXISTS runtime_keys (
+    id TEXT PRIMARY KEY,
+    key TEXT NOT NULL UNIQUE,             -- Envoy runtime key, e.g. cors.enabled
+    value TEXT NOT NULL,                  -- JSON encoded runtime value
+    version BIGINT NOT NULL DEFAULT 1,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Index for efficient timestamp queries
+CREATE INDEX IF NOT EXISTS idx_runtime_keys_updated_at ON runtime_keys(updated_at);
@@ -0,0 +1,59 @@
+#!/bin/bash
+set -euo pipefail
+
+# Regenerate proto/envoy_descriptors.bin, the descriptor set used to render
+# cached xDS resources as Envoy JSON (GET /api/v1/xds/snapshot).
+#
+# Compiles the protos shipped with the envoy-types crate, so run it after
+# bumping envoy-types or when Flowplane starts emitting a new typed config.
+# Requires protoc on PATH (or PROTOC) and jq.
+#
+# Usage: ./scripts/generate-envoy-descriptors.sh
+
+PROTOC="${PROTOC:-protoc}"
+OUTPUT="proto/envoy_descriptors.bin"
+
+MANIFEST=$(cargo metadata --format-version 1 \
+    | jq -r '.packages[] | select(.name == "envoy-types") | .manifest_path')
+if [ -z "$MANIFEST" ]; then
+    echo "Error: envoy-types not found in cargo metadata"
+    exit 1
+fi
+PROTO_ROOT="$(dirname "$MANIFEST")/proto"
+
+# Every resource type served over xDS, every typed config embedded in them,
+# and the admin config dump wrapping them.
+PROTOS=(
+    envoy/admin/v3/config_dump.proto
+    envoy/config/cluster/v3/cluster.proto
+    envoy/config/endpoint/v3/endpoint.proto
+    envoy/config/listener/v3/listener.proto
+    envoy/config/route/v3/route.proto
+    envoy/extensions/access_loggers/file/v3/file.proto
+    envoy/extensions/filters/http/cors/v3/cors.proto
+    envoy/extensions/filters/http/jwt_authn/v3/config.proto
+    envoy/extensions/filters/http/local_ratelimit/v3/local_rate_limit.proto
+    envoy/extensions/filters/http/router/v3/router.proto
+    envoy/extensions/filters/network/http_connection_manager/v3/http_connection_manager.proto
+    envoy/extensions/filters/network/tcp_proxy/v3/tcp_proxy.proto
+    envoy/extensions/path/match/uri_template/v3/uri_template_match.proto
+    envoy/extensions/path/rewrite/uri_template/v3/uri_template_rewrite.proto
+    envoy/extensions/transport_sockets/tls/v3/secret.proto
+    envoy/extensions/transport_sockets/tls/v3/tls.proto
+    envoy/extensions/upstreams/http/v3/http_protocol_options.proto
+    envoy/service/runtime/v3/rtds.proto
+)
+
+mkdir -p "$(dirname "$OUTPUT")"
+"$PROTOC" --include_imports -o "$OUTPUT" \
+    -I"$PROTO_ROOT/data-plane-api" \
+    -I"$PROTO_ROOT/xds" \
+    -I"$PROTO_ROOT/googleapis" \
+    -I"$PROTO_ROOT/protoc-gen-validate" \
+    -I"$PROTO_ROOT/opencensus-proto/src" \
+    -I"$PROTO_ROOT/opentelemetry-proto" \
+    -I"$PROTO_ROOT/client_model" \
+    -I"$PROTO_ROOT/cel-spec/proto" \
+    "${PROTOS[@]}"
+
+echo "Wrote $OUTPUT"
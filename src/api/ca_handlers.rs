@@ -0,0 +1,263 @@
+use std::sync::Arc;
+
+use axum::{extract::State, http::StatusCode, Json};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use utoipa::ToSchema;
+
+use crate::xds::ca::{CertificateAuthority, CertificateSubject, CA_TRUST_BUNDLE_SECRET};
+
+use super::{
+    error::ApiError,
+    routes::ApiState,
+    secret_handlers::{
+        refresh_secrets, require_secret_storage, secret_response_from_data, SecretResponse,
+    },
+};
+
+/// Built-in certificate authority as returned by the API.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CertificateAuthorityResponse {
+    #[schema(example = "flowplane.local")]
+    pub trust_domain: String,
+    /// PEM encoded CA certificate.
+    pub certificate: String,
+    pub expires_at: DateTime<Utc>,
+    /// SDS secret carrying the CA certificate as a validation context.
+    #[schema(example = "flowplane-ca")]
+    pub trust_bundle_secret: String,
+    /// Lifetime of issued leaf certificates.
+    pub leaf_ttl_seconds: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IssueCertificateBody {
+    /// Team the workload belongs to.
+    #[schema(example = "payments")]
+    pub team: String,
+    /// Envoy node ID; omit to issue one certificate for the whole team.
+    #[serde(default)]
+    #[schema(example = "payments-gateway-1")]
+    pub node_id: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/ca",
+    responses(
+        (status = 200, description = "Built-in CA details", body = CertificateAuthorityResponse),
+        (status = 503, description = "Built-in CA not enabled"),
+    ),
+    tag = "certificate-authority"
+)]
+pub async fn get_certificate_authority_handler(
+    State(state): State<ApiState>,
+) -> Result<Json<CertificateAuthorityResponse>, ApiError> {
+    let ca = require_certificate_authority(&state).await?;
+    let leaf_ttl_seconds = state.xds_state.config.ca.as_ref().map_or(0, |ca| ca.leaf_ttl_secs);
+
+    Ok(Json(CertificateAuthorityResponse {
+        trust_domain: ca.trust_domain().to_string(),
+        certificate: ca.certificate_pem().to_string(),
+        expires_at: ca.expires_at(),
+        trust_bundle_secret: CA_TRUST_BUNDLE_SECRET.to_string(),
+        leaf_ttl_seconds,
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/ca/certificates",
+    request_body = IssueCertificateBody,
+    responses(
+        (status = 201, description = "Certificate issued and stored as an SDS secret named after its SPIFFE ID", body = SecretResponse),
+        (status = 400, description = "Team or node ID cannot be used in a SPIFFE ID"),
+        (status = 503, description = "Built-in CA not enabled"),
+    ),
+    tag = "certificate-authority"
+)]
+pub async fn issue_certificate_handler(
+    State(state): State<ApiState>,
+    Json(payload): Json<IssueCertificateBody>,
+) -> Result<(StatusCode, Json<SecretResponse>), ApiError> {
+    require_secret_storage(&state)?;
+    require_certificate_authority(&state).await?;
+
+    let subject = CertificateSubject { team: payload.team, node_id: payload.node_id };
+    let issued = state.xds_state.issue_certificate(&subject).await.map_err(ApiError::from)?;
+    info!(
+        spiffe_id = issued.spiffe_id.as_deref().unwrap_or_default(),
+        version = issued.version,
+        "Certificate issued via API"
+    );
+
+    refresh_secrets(&state).await?;
+
+    Ok((StatusCode::CREATED, Json(secret_response_from_data(issued))))
+}
+
+async fn require_certificate_authority(
+    state: &ApiState,
+) -> Result<Arc<CertificateAuthority>, ApiError> {
+    state.xds_state.certificate_authority().await.map_err(ApiError::from)?.ok_or_else(|| {
+        ApiError::service_unavailable(
+            "The built-in CA is not enabled; set FLOWPLANE_CA_TRUST_DOMAIN",
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{CaConfig, SimpleXdsConfig};
+    use crate::storage::{create_pool, DatabaseConfig, SecretCipher, UpdateSecretRequest};
+    use crate::xds::{resources::SECRET_TYPE_URL, XdsState};
+
+    async fn setup_state(ca: Option<CaConfig>) -> ApiState {
+        let pool = create_pool(&DatabaseConfig {
+            url: "sqlite://:memory:".to_string(),
+            auto_migrate: true,
+            ..Default::default()
+        })
+        .await
+        .expect("pool");
+
+        let config = SimpleXdsConfig {
+            secret_cipher: Some(SecretCipher::new(&[5u8; 32]).unwrap()),
+            ca,
+            ..Default::default()
+        };
+        ApiState { xds_state: Arc::new(XdsState::with_database(config, pool)) }
+    }
+
+    fn ca_config(leaf_ttl_secs: u64) -> CaConfig {
+        CaConfig { trust_domain: "flowplane.local".into(), leaf_ttl_secs }
+    }
+
+    #[tokio::test]
+    async fn issues_spiffe_certificates_served_with_the_trust_bundle() {
+        let state = setup_state(Some(ca_config(3600))).await;
+
+        let Json(ca) = get_certificate_authority_handler(State(state.clone())).await.unwrap();
+        assert_eq!(ca.trust_domain, "flowplane.local");
+        assert_eq!(ca.trust_bundle_secret, CA_TRUST_BUNDLE_SECRET);
+
+        let (status, Json(issued)) = issue_certificate_handler(
+            State(state.clone()),
+            Json(IssueCertificateBody {
+                team: "payments".into(),
+                node_id: Some("gateway-1".into()),
+            }),
+        )
+        .await
+        .expect("issue certificate");
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(issued.name, "spiffe://flowplane.local/team/payments/node/gateway-1");
+        assert_eq!(issued.spiffe_id.as_deref(), Some(issued.name.as_str()));
+
+        let names: Vec<String> = state
+            .xds_state
+            .cached_resources(SECRET_TYPE_URL)
+            .into_iter()
+            .map(|resource| resource.name)
+            .collect();
+        assert!(names.contains(&issued.name));
+        assert!(names.contains(&CA_TRUST_BUNDLE_SECRET.to_string()));
+
+        let invalid = issue_certificate_handler(
+            State(state),
+            Json(IssueCertificateBody { team: "pay/ments".into(), node_id: None }),
+        )
+        .await;
+        assert!(matches!(invalid, Err(ApiError::BadRequest(_))));
+    }
+
+    #[tokio::test]
+    async fn renewal_reissues_certificates_near_expiry() {
+        let state = setup_state(Some(ca_config(3600))).await;
+        let subject = CertificateSubject { team: "payments".into(), node_id: None };
+        let issued = state.xds_state.issue_certificate(&subject).await.unwrap();
+        assert_eq!(state.xds_state.renew_expiring_certificates().await.unwrap(), 0);
+
+        // Move the certificate into the last third of its lifetime.
+        let repository = state.xds_state.secret_repository.clone().unwrap();
+        let expiring = repository
+            .update(
+                &issued.name,
+                UpdateSecretRequest {
+                    certificate_chain: issued.certificate_chain.clone(),
+                    encrypted_private_key: issued.encrypted_private_key.clone(),
+                    expires_at: Some(Utc::now() + chrono::Duration::minutes(5)),
+                },
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(state.xds_state.renew_expiring_certificates().await.unwrap(), 1);
+
+        let renewed = repository.get_by_name(&issued.name).await.unwrap();
+        assert_eq!(renewed.version, expiring.version + 1);
+        assert_ne!(renewed.certificate_chain, issued.certificate_chain);
+        assert!(renewed.expires_at > expiring.expires_at);
+    }
+
+    #[tokio::test]
+    async fn renewal_continues_past_a_certificate_that_fails_to_store() {
+        let state = setup_state(Some(ca_config(3600))).await;
+        let repository = state.xds_state.secret_repository.clone().unwrap();
+        let mut issued = Vec::new();
+        for team in ["billing", "payments"] {
+            let subject = CertificateSubject { team: team.into(), node_id: None };
+            let certificate = state.xds_state.issue_certificate(&subject).await.unwrap();
+            repository
+                .update(
+                    &certificate.name,
+                    UpdateSecretRequest {
+                        certificate_chain: certificate.certificate_chain.clone(),
+                        encrypted_private_key: certificate.encrypted_private_key.clone(),
+                        expires_at: Some(Utc::now() + chrono::Duration::minutes(5)),
+                    },
+                )
+                .await
+                .unwrap();
+            issued.push(certificate);
+        }
+
+        // Storing the renewed billing certificate fails.
+        let pool = state.xds_state.cluster_repository.as_ref().unwrap().pool();
+        sqlx::query(&format!(
+            "CREATE TRIGGER fail_billing_renewal BEFORE UPDATE ON secrets \
+             WHEN OLD.name = '{}' BEGIN SELECT RAISE(ABORT, 'disk full'); END",
+            issued[0].name
+        ))
+        .execute(pool.as_sqlite().unwrap())
+        .await
+        .unwrap();
+
+        let error = state.xds_state.renew_expiring_certificates().await.unwrap_err();
+        assert!(error.to_string().contains("1 of 2"), "{}", error);
+
+        let payments = repository.get_by_name(&issued[1].name).await.unwrap();
+        assert_ne!(payments.certificate_chain, issued[1].certificate_chain);
+        let billing = repository.get_by_name(&issued[0].name).await.unwrap();
+        assert_eq!(billing.certificate_chain, issued[0].certificate_chain);
+    }
+
+    #[tokio::test]
+    async fn disabled_ca_is_unavailable() {
+        let state = setup_state(None).await;
+
+        let missing = get_certificate_authority_handler(State(state.clone())).await;
+        assert!(matches!(missing, Err(ApiError::ServiceUnavailable(_))));
+
+        let issued = issue_certificate_handler(
+            State(state),
+            Json(IssueCertificateBody { team: "payments".into(), node_id: None }),
+        )
+        .await;
+        assert!(matches!(issued, Err(ApiError::ServiceUnavailable(_))));
+    }
+}
@@ -13,8 +13,11 @@ use crate::api::handlers::{
 use crate::auth::{models::PersonalAccessToken, token_service::TokenSecretResponse};
 #[allow(unused_imports)]
 use crate::xds::{
-    CircuitBreakerThresholdsSpec, CircuitBreakersSpec, ClusterSpec, EndpointSpec, HealthCheckSpec,
-    OutlierDetectionSpec,
+    CircuitBreakerThresholdsSpec, CircuitBreakersSpec, ClusterSpec, EndpointSpec,
+    FailurePercentageEjectionSpec, HealthCheckSpec, Http2KeepaliveSpec, Http2OptionsSpec,
+    LocalitySpec, OutlierDetectionSpec, RetryBudgetSpec, SubjectAltNameMatchType,
+    SubjectAltNameSpec, SubjectAltNameType, SuccessRateEjectionSpec, TlsVersionSpec,
+    UpstreamProtocol, ZoneAwareRoutingSpec,
 };
 
 #[derive(OpenApi)]
@@ -46,7 +49,25 @@ use crate::xds::{
         crate::api::platform_api_handlers::append_route_handler,
         crate::api::platform_api_handlers::list_api_definitions_handler,
         crate::api::platform_api_handlers::get_api_definition_handler
-        ,crate::api::platform_api_handlers::get_bootstrap_handler
+        ,crate::api::platform_api_handlers::get_bootstrap_handler,
+        crate::api::platform_api_handlers::get_team_bootstrap_handler,
+        crate::api::xds_handlers::list_nodes_handler,
+        crate::api::xds_handlers::get_node_handler,
+        crate::api::xds_handlers::list_rollouts_handler,
+        crate::api::xds_handlers::get_snapshot_handler,
+        crate::api::xds_handlers::promote_rollout_handler,
+        crate::api::xds_handlers::abort_rollout_handler,
+        crate::api::secret_handlers::create_secret_handler,
+        crate::api::secret_handlers::list_secrets_handler,
+        crate::api::secret_handlers::get_secret_handler,
+        crate::api::secret_handlers::update_secret_handler,
+        crate::api::secret_handlers::delete_secret_handler,
+        crate::api::ca_handlers::get_certificate_authority_handler,
+        crate::api::ca_handlers::issue_certificate_handler,
+        crate::api::runtime_handlers::list_runtime_keys_handler,
+        crate::api::runtime_handlers::get_runtime_key_handler,
+        crate::api::runtime_handlers::set_runtime_key_handler,
+        crate::api::runtime_handlers::delete_runtime_key_handler
     ),
     components(
         schemas(
@@ -67,6 +88,18 @@ use crate::xds::{
             CircuitBreakerThresholdsSpec,
             HealthCheckSpec,
             OutlierDetectionSpec,
+            SubjectAltNameSpec,
+            SubjectAltNameType,
+            SubjectAltNameMatchType,
+            TlsVersionSpec,
+            LocalitySpec,
+            ZoneAwareRoutingSpec,
+            UpstreamProtocol,
+            Http2OptionsSpec,
+            Http2KeepaliveSpec,
+            RetryBudgetSpec,
+            SuccessRateEjectionSpec,
+            FailurePercentageEjectionSpec,
             crate::api::route_handlers::RouteDefinition,
             crate::api::route_handlers::VirtualHostDefinition,
             crate::api::route_handlers::RouteRuleDefinition,
@@ -92,7 +125,19 @@ use crate::xds::{
             crate::api::platform_api_handlers::AppendRouteResponse,
             crate::api::platform_api_handlers::ApiDefinitionSummary,
             crate::api::platform_api_handlers::ListDefinitionsQuery
-            ,crate::api::platform_api_handlers::BootstrapQuery
+            ,crate::api::platform_api_handlers::BootstrapQuery,
+            crate::xds::NodeStatus,
+            crate::xds::ResourceTypeStatus,
+            crate::xds::NackDetail,
+            crate::xds::RolloutStatus,
+            crate::xds::RolloutPhase,
+            crate::api::secret_handlers::SecretResponse,
+            crate::api::secret_handlers::CreateSecretBody,
+            crate::api::secret_handlers::UpdateSecretBody,
+            crate::api::ca_handlers::CertificateAuthorityResponse,
+            crate::api::ca_handlers::IssueCertificateBody,
+            crate::api::runtime_handlers::RuntimeKeyResponse,
+            crate::api::runtime_handlers::SetRuntimeKeyBody
         )
     ),
     tags(
@@ -100,7 +145,11 @@ use crate::xds::{
         (name = "listeners", description = "Operations for managing Envoy listeners"),
         (name = "gateways", description = "Operations for importing gateway configurations from OpenAPI specifications"),
         (name = "tokens", description = "Personal access token management APIs"),
-        (name = "platform-api", description = "Platform API Abstraction endpoints")
+        (name = "platform-api", description = "Platform API Abstraction endpoints"),
+        (name = "xds", description = "Inspection of connected Envoy nodes and canary rollout control"),
+        (name = "secrets", description = "TLS certificates served to Envoy over SDS"),
+        (name = "certificate-authority", description = "Built-in CA issuing SPIFFE certificates delivered over SDS"),
+        (name = "runtime", description = "Runtime keys served to Envoy over RTDS")
     ),
     security(
         ("bearerAuth" = [])
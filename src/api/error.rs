@@ -69,7 +69,10 @@ impl From<Error> for ApiError {
             Error::Database { source, context } => {
                 if let Some(db_err) = source.as_database_error() {
                     if let Some(code) = db_err.code() {
-                        if code.as_ref() == "2067" || code.as_ref().starts_with("SQLITE_CONSTRAINT")
+                        // SQLite unique violation (2067) or PostgreSQL unique_violation (23505)
+                        if code.as_ref() == "2067"
+                            || code.as_ref() == "23505"
+                            || code.as_ref().starts_with("SQLITE_CONSTRAINT")
                         {
                             return ApiError::Conflict(context);
                         }
@@ -22,7 +22,8 @@ use crate::{
         GatewayOptions, GatewaySummary,
     },
     storage::{
-        ClusterRepository, ListenerRepository, RouteRepository, UpdateRouteRepositoryRequest,
+        Change, ChangeKind, ClusterRepository, ListenerRepository, RouteRepository,
+        UpdateRouteRepositoryRequest,
     },
     xds::route::{
         PathMatch as XdsPathMatch, RouteActionConfig as XdsRouteActionConfig,
@@ -340,9 +341,14 @@ pub async fn create_gateway_from_openapi_handler(
         }
     }
 
-    state.xds_state.refresh_clusters_from_repository().await?;
-    state.xds_state.refresh_routes_from_repository().await?;
-    state.xds_state.refresh_listeners_from_repository().await?;
+    let mut changes: Vec<Change> =
+        created_clusters.iter().map(|name| Change::row(ChangeKind::Clusters, name)).collect();
+    let route_name = plan.route_request.as_ref().map_or(DEFAULT_GATEWAY_ROUTES, |r| &r.name);
+    changes.push(Change::row(ChangeKind::Routes, route_name));
+    if let Some(listener_request) = plan.listener_request.as_ref() {
+        changes.push(Change::row(ChangeKind::Listeners, &listener_request.name));
+    }
+    state.xds_state.refresh_changes(&changes).await?;
 
     info!(
         gateway = %plan.summary.gateway,
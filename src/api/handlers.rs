@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
@@ -5,7 +7,7 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use tracing::{error, info};
+use tracing::info;
 use utoipa::ToSchema;
 use validator::Validate;
 
@@ -13,7 +15,11 @@ use crate::{
     errors::Error,
     openapi::defaults::is_default_gateway_cluster,
     storage::{ClusterData, ClusterRepository, CreateClusterRequest, UpdateClusterRequest},
-    xds::ClusterSpec,
+    xds::{
+        ClusterSpec, FailurePercentageEjectionSpec, Http2OptionsSpec, LocalitySpec,
+        RetryBudgetSpec, SubjectAltNameSpec, SuccessRateEjectionSpec, TlsVersionSpec,
+        UpstreamProtocol, ZoneAwareRoutingSpec,
+    },
 };
 
 use super::error::ApiError;
@@ -84,10 +90,84 @@ pub struct CreateClusterBody {
     #[schema(default = false)]
     pub use_tls: Option<bool>,
 
+    /// Deliver endpoints over EDS instead of inlining them in the cluster (default: false).
+    /// Requires IP address endpoints.
+    #[serde(default)]
+    #[schema(default = false)]
+    pub use_eds: Option<bool>,
+
     /// Optional SNI server name to present during TLS handshake.
     #[serde(default)]
     pub tls_server_name: Option<String>,
 
+    /// Name of a stored secret presented as the client certificate for upstream TLS.
+    /// Setting it enables TLS.
+    #[serde(default)]
+    #[schema(example = "inventory-client-cert")]
+    pub client_certificate_secret: Option<String>,
+
+    /// Name of a validation context secret used to verify the upstream certificate,
+    /// e.g. `flowplane-ca` for certificates issued by the built-in CA. Setting it enables TLS.
+    #[serde(default)]
+    #[schema(example = "flowplane-ca")]
+    pub validation_secret: Option<String>,
+
+    /// Path on the Envoy host to a PEM bundle of CAs trusted to sign the upstream certificate.
+    /// Setting it enables TLS.
+    #[serde(default)]
+    #[schema(example = "/etc/envoy/certs/backend-ca.pem")]
+    pub trusted_ca_file: Option<String>,
+
+    /// Subject alternative names accepted on the upstream certificate. Requires `trustedCaFile`
+    /// or `validationSecret`.
+    #[serde(default)]
+    #[schema(value_type = Vec<SubjectAltNameSpec>)]
+    pub subject_alt_names: Vec<SubjectAltNameSpec>,
+
+    /// Path on the Envoy host to the client certificate chain for upstream mTLS. Set together
+    /// with `clientPrivateKeyFile`, instead of `clientCertificateSecret`.
+    #[serde(default)]
+    #[schema(example = "/etc/envoy/certs/client.pem")]
+    pub client_certificate_file: Option<String>,
+
+    /// Path on the Envoy host to the private key of `clientCertificateFile`.
+    #[serde(default)]
+    #[schema(example = "/etc/envoy/certs/client.key")]
+    pub client_private_key_file: Option<String>,
+
+    /// Minimum TLS version (`TLSv1_0` to `TLSv1_3`).
+    #[serde(default)]
+    #[schema(value_type = Option<TlsVersionSpec>)]
+    pub tls_min_version: Option<TlsVersionSpec>,
+
+    /// Maximum TLS version (`TLSv1_0` to `TLSv1_3`).
+    #[serde(default)]
+    #[schema(value_type = Option<TlsVersionSpec>)]
+    pub tls_max_version: Option<TlsVersionSpec>,
+
+    /// ALPN protocols offered to the upstream, e.g. `["h2", "http/1.1"]`.
+    #[serde(default)]
+    pub alpn_protocols: Vec<String>,
+
+    /// HTTP protocol spoken to the upstream: `http1` (default), `http2` for gRPC backends, or
+    /// `auto` to negotiate over ALPN (requires TLS).
+    #[serde(default)]
+    #[schema(value_type = Option<UpstreamProtocol>, example = "http2")]
+    pub protocol: Option<UpstreamProtocol>,
+
+    /// HTTP/2 settings, applied when `protocol` is `http2` or `auto`.
+    #[serde(default)]
+    #[schema(value_type = Option<Http2OptionsSpec>)]
+    pub http2: Option<Http2OptionsSpec>,
+
+    /// Close upstream connections that carry no requests for this many seconds.
+    #[serde(default)]
+    pub idle_timeout_seconds: Option<u64>,
+
+    /// Close upstream connections after serving this many requests.
+    #[serde(default)]
+    pub max_requests_per_connection: Option<u32>,
+
     /// DNS lookup family for hostname endpoints (`AUTO`, `V4_ONLY`, `V6_ONLY`, `V4_PREFERRED`, `ALL`).
     #[serde(default)]
     #[schema(example = "AUTO")]
@@ -103,7 +183,8 @@ pub struct CreateClusterBody {
     #[schema(value_type = Vec<HealthCheckRequest>)]
     pub health_checks: Vec<HealthCheckRequest>,
 
-    /// Circuit breaker thresholds applied to the cluster.
+    /// Circuit breaker thresholds applied to the cluster. When a threshold sets both
+    /// `retryBudget` and `maxRetries`, the retry budget takes precedence.
     #[serde(default)]
     #[schema(value_type = CircuitBreakersRequest)]
     pub circuit_breakers: Option<CircuitBreakersRequest>,
@@ -111,6 +192,17 @@ pub struct CreateClusterBody {
     /// Passive outlier detection configuration.
     #[serde(default)]
     pub outlier_detection: Option<OutlierDetectionRequest>,
+
+    /// Prefer endpoints in the Envoy node's own zone. Cannot be combined with
+    /// `localityWeightedLb`.
+    #[serde(default)]
+    #[schema(value_type = Option<ZoneAwareRoutingSpec>)]
+    pub zone_aware_routing: Option<ZoneAwareRoutingSpec>,
+
+    /// Split traffic across endpoint localities by weight. A locality weighs the sum of its
+    /// endpoint weights.
+    #[serde(default)]
+    pub locality_weighted_lb: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate, ToSchema, Clone)]
@@ -124,6 +216,25 @@ pub struct EndpointRequest {
     #[validate(range(min = 1, max = 65535))]
     #[schema(example = 443)]
     pub port: u16,
+
+    /// Load balancing weight within the endpoint's locality (default: 1).
+    #[serde(default)]
+    #[validate(range(min = 1, max = 1000))]
+    pub weight: Option<u32>,
+
+    /// Region, zone and sub-zone the endpoint runs in.
+    #[serde(default)]
+    #[schema(value_type = Option<LocalitySpec>)]
+    pub locality: Option<LocalitySpec>,
+
+    /// Failover priority; 0 (the default) is highest. Priorities must be contiguous.
+    #[serde(default)]
+    pub priority: Option<u32>,
+
+    /// Metadata published under `envoy.lb` for subset load balancing.
+    #[serde(default)]
+    #[schema(value_type = Option<Object>)]
+    pub metadata: Option<serde_json::Map<String, Value>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate, ToSchema, Clone)]
@@ -139,6 +250,7 @@ pub struct EndpointRequest {
     "expectedStatuses": [200, 204]
 }))]
 pub struct HealthCheckRequest {
+    /// Probe type: `http` (default), `tcp` or `grpc`.
     #[serde(default = "default_health_check_type")]
     #[schema(example = "http")]
     pub r#type: String,
@@ -161,6 +273,22 @@ pub struct HealthCheckRequest {
     pub unhealthy_threshold: Option<u32>,
     /// HTTP status codes treated as successful responses.
     pub expected_statuses: Option<Vec<u32>>,
+    /// Headers added to HTTP health probe requests.
+    #[serde(default)]
+    pub request_headers: BTreeMap<String, String>,
+    /// Hex-encoded payload sent by TCP health checks after connecting.
+    pub send: Option<String>,
+    /// Hex-encoded payloads a TCP health check response must contain.
+    #[serde(default)]
+    pub receive: Vec<String>,
+    /// Service name checked by `grpc` health checks via `grpc.health.v1`.
+    pub service_name: Option<String>,
+    /// `:authority` of gRPC health probes (default: the cluster name).
+    pub authority: Option<String>,
+    /// Probe interval in seconds while the cluster has not carried traffic yet.
+    pub no_traffic_interval_seconds: Option<u64>,
+    /// File on the Envoy host receiving health check events.
+    pub event_log_path: Option<String>,
 }
 
 fn default_health_check_type() -> String {
@@ -201,6 +329,11 @@ pub struct CircuitBreakerThresholdsRequest {
     pub max_requests: Option<u32>,
     /// Maximum simultaneous retries.
     pub max_retries: Option<u32>,
+    /// Retry budget relative to active requests. Takes precedence over `maxRetries`.
+    #[schema(value_type = Option<RetryBudgetSpec>)]
+    pub retry_budget: Option<RetryBudgetSpec>,
+    /// Publish `remaining_*` gauges for these thresholds.
+    pub track_remaining: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Validate, ToSchema, Clone)]
@@ -220,6 +353,19 @@ pub struct OutlierDetectionRequest {
     pub base_ejection_time_seconds: Option<u64>,
     /// Maximum percentage of hosts that can be ejected simultaneously.
     pub max_ejection_percent: Option<u32>,
+    /// Number of consecutive 502/503/504 responses before ejecting a host.
+    pub consecutive_gateway_failure: Option<u32>,
+    /// Number of consecutive connect failures, timeouts or resets before ejecting a host.
+    /// Requires `splitExternalLocalOriginErrors`.
+    pub consecutive_local_origin_failure: Option<u32>,
+    /// Track locally originated failures separately from upstream responses.
+    pub split_external_local_origin_errors: Option<bool>,
+    /// Success-rate based ejection.
+    #[schema(value_type = Option<SuccessRateEjectionSpec>)]
+    pub success_rate: Option<SuccessRateEjectionSpec>,
+    /// Failure-percentage based ejection.
+    #[schema(value_type = Option<FailurePercentageEjectionSpec>)]
+    pub failure_percentage: Option<FailurePercentageEjectionSpec>,
 }
 
 #[derive(Debug, Serialize, ToSchema)]
@@ -289,12 +435,28 @@ fn cluster_parts_from_body(payload: CreateClusterBody) -> ClusterConfigParts {
         endpoints,
         connect_timeout_seconds,
         use_tls,
+        use_eds,
         tls_server_name,
+        client_certificate_secret,
+        validation_secret,
+        trusted_ca_file,
+        subject_alt_names,
+        client_certificate_file,
+        client_private_key_file,
+        tls_min_version,
+        tls_max_version,
+        alpn_protocols,
+        protocol,
+        http2,
+        idle_timeout_seconds,
+        max_requests_per_connection,
         dns_lookup_family,
         lb_policy,
         health_checks,
         circuit_breakers,
         outlier_detection,
+        zone_aware_routing,
+        locality_weighted_lb,
     } = payload;
 
     let service_name = service_name.unwrap_or_else(|| name.clone());
@@ -302,11 +464,32 @@ fn cluster_parts_from_body(payload: CreateClusterBody) -> ClusterConfigParts {
     let config = ClusterSpec {
         endpoints: endpoints
             .into_iter()
-            .map(|ep| crate::xds::EndpointSpec::Address { host: ep.host, port: ep.port })
+            .map(|ep| crate::xds::EndpointSpec::Address {
+                host: ep.host,
+                port: ep.port,
+                weight: ep.weight,
+                locality: ep.locality,
+                priority: ep.priority,
+                metadata: ep.metadata,
+            })
             .collect(),
         connect_timeout_seconds,
         use_tls,
+        use_eds,
         tls_server_name,
+        client_certificate_secret,
+        validation_secret,
+        trusted_ca_file,
+        subject_alt_names,
+        client_certificate_file,
+        client_private_key_file,
+        tls_min_version,
+        tls_max_version,
+        alpn_protocols,
+        protocol,
+        http2,
+        idle_timeout_seconds,
+        max_requests_per_connection,
         dns_lookup_family,
         lb_policy,
         health_checks: health_checks
@@ -322,6 +505,13 @@ fn cluster_parts_from_body(payload: CreateClusterBody) -> ClusterConfigParts {
                     healthy_threshold,
                     unhealthy_threshold,
                     expected_statuses,
+                    request_headers,
+                    send,
+                    receive,
+                    service_name,
+                    authority,
+                    no_traffic_interval_seconds,
+                    event_log_path,
                 } = hc;
 
                 match r#type.to_lowercase().as_str() {
@@ -330,6 +520,20 @@ fn cluster_parts_from_body(payload: CreateClusterBody) -> ClusterConfigParts {
                         timeout_seconds,
                         healthy_threshold,
                         unhealthy_threshold,
+                        send,
+                        receive,
+                        no_traffic_interval_seconds,
+                        event_log_path,
+                    },
+                    "grpc" => crate::xds::HealthCheckSpec::Grpc {
+                        interval_seconds,
+                        timeout_seconds,
+                        healthy_threshold,
+                        unhealthy_threshold,
+                        service_name,
+                        authority,
+                        no_traffic_interval_seconds,
+                        event_log_path,
                     },
                     _ => crate::xds::HealthCheckSpec::Http {
                         path: path.unwrap_or_else(|| "/health".to_string()),
@@ -340,6 +544,9 @@ fn cluster_parts_from_body(payload: CreateClusterBody) -> ClusterConfigParts {
                         healthy_threshold,
                         unhealthy_threshold,
                         expected_statuses,
+                        request_headers,
+                        no_traffic_interval_seconds,
+                        event_log_path,
                     },
                 }
             })
@@ -350,12 +557,16 @@ fn cluster_parts_from_body(payload: CreateClusterBody) -> ClusterConfigParts {
                 max_pending_requests: d.max_pending_requests,
                 max_requests: d.max_requests,
                 max_retries: d.max_retries,
+                retry_budget: d.retry_budget,
+                track_remaining: d.track_remaining,
             }),
             high: cb.high.map(|h| crate::xds::CircuitBreakerThresholdsSpec {
                 max_connections: h.max_connections,
                 max_pending_requests: h.max_pending_requests,
                 max_requests: h.max_requests,
                 max_retries: h.max_retries,
+                retry_budget: h.retry_budget,
+                track_remaining: h.track_remaining,
             }),
         }),
         outlier_detection: outlier_detection.map(|od| crate::xds::OutlierDetectionSpec {
@@ -363,7 +574,14 @@ fn cluster_parts_from_body(payload: CreateClusterBody) -> ClusterConfigParts {
             interval_seconds: od.interval_seconds,
             base_ejection_time_seconds: od.base_ejection_time_seconds,
             max_ejection_percent: od.max_ejection_percent,
+            consecutive_gateway_failure: od.consecutive_gateway_failure,
+            consecutive_local_origin_failure: od.consecutive_local_origin_failure,
+            split_external_local_origin_errors: od.split_external_local_origin_errors,
+            success_rate: od.success_rate,
+            failure_percentage: od.failure_percentage,
         }),
+        zone_aware_routing,
+        locality_weighted_lb,
         ..Default::default()
     };
 
@@ -409,6 +627,7 @@ pub async fn create_cluster_handler(
     payload.validate().map_err(|err| ApiError::from(Error::from(err)))?;
 
     let ClusterConfigParts { name, service_name, config } = cluster_parts_from_body(payload);
+    config.validate_model().map_err(ApiError::from)?;
 
     let repository = require_cluster_repository(&state)?;
 
@@ -428,11 +647,6 @@ pub async fn create_cluster_handler(
         "Cluster created via API"
     );
 
-    state.xds_state.refresh_clusters_from_repository().await.map_err(|err| {
-        error!(error = %err, "Failed to refresh xDS caches after cluster creation");
-        ApiError::from(err)
-    })?;
-
     Ok((
         StatusCode::CREATED,
         Json(ClusterResponse {
@@ -514,6 +728,7 @@ pub async fn update_cluster_handler(
 
     let ClusterConfigParts { name: payload_name, service_name, config } =
         cluster_parts_from_body(payload);
+    config.validate_model().map_err(ApiError::from)?;
 
     if payload_name != name {
         return Err(ApiError::BadRequest(format!(
@@ -539,14 +754,6 @@ pub async fn update_cluster_handler(
         "Cluster updated via API"
     );
 
-    state.xds_state.refresh_clusters_from_repository().await.map_err(|err| {
-        error!(
-            error = %err,
-            "Failed to refresh xDS caches after cluster update"
-        );
-        ApiError::from(err)
-    })?;
-
     let response = cluster_response_from_data(updated)?;
     Ok(Json(response))
 }
@@ -583,14 +790,6 @@ pub async fn delete_cluster_handler(
         "Cluster deleted via API"
     );
 
-    state.xds_state.refresh_clusters_from_repository().await.map_err(|err| {
-        error!(
-            error = %err,
-            "Failed to refresh xDS caches after cluster deletion"
-        );
-        ApiError::from(err)
-    })?;
-
     Ok(StatusCode::NO_CONTENT)
 }
 
@@ -620,8 +819,10 @@ mod tests {
         let pool = create_pool(&create_test_config()).await.expect("pool");
 
         // Create clusters table for repository usage.
-        pool.execute(
-            r#"
+        pool.as_sqlite()
+            .expect("sqlite pool")
+            .execute(
+                r#"
             CREATE TABLE IF NOT EXISTS clusters (
                 id TEXT PRIMARY KEY,
                 name TEXT NOT NULL,
@@ -633,9 +834,9 @@ mod tests {
                 UNIQUE(name, version)
             )
         "#,
-        )
-        .await
-        .expect("create table");
+            )
+            .await
+            .expect("create table");
 
         let state = XdsState::with_database(SimpleXdsConfig::default(), pool);
         ApiState { xds_state: Arc::new(state) }
@@ -645,10 +846,31 @@ mod tests {
         CreateClusterBody {
             name: "api-cluster".into(),
             service_name: None,
-            endpoints: vec![EndpointRequest { host: "10.0.0.1".into(), port: 8080 }],
+            endpoints: vec![EndpointRequest {
+                host: "10.0.0.1".into(),
+                port: 8080,
+                weight: None,
+                locality: None,
+                priority: None,
+                metadata: None,
+            }],
             connect_timeout_seconds: Some(7),
             use_tls: Some(true),
+            use_eds: None,
             tls_server_name: Some("api.local".into()),
+            client_certificate_secret: None,
+            validation_secret: None,
+            trusted_ca_file: None,
+            subject_alt_names: Vec::new(),
+            client_certificate_file: None,
+            client_private_key_file: None,
+            tls_min_version: None,
+            tls_max_version: None,
+            alpn_protocols: Vec::new(),
+            protocol: None,
+            http2: None,
+            idle_timeout_seconds: None,
+            max_requests_per_connection: None,
             dns_lookup_family: Some("AUTO".into()),
             lb_policy: Some("ROUND_ROBIN".into()),
             health_checks: vec![HealthCheckRequest {
@@ -661,6 +883,13 @@ mod tests {
                 healthy_threshold: Some(2),
                 unhealthy_threshold: Some(3),
                 expected_statuses: Some(vec![200]),
+                request_headers: BTreeMap::new(),
+                send: None,
+                receive: Vec::new(),
+                service_name: None,
+                authority: None,
+                no_traffic_interval_seconds: None,
+                event_log_path: None,
             }],
             circuit_breakers: Some(CircuitBreakersRequest {
                 default: Some(CircuitBreakerThresholdsRequest {
@@ -668,6 +897,8 @@ mod tests {
                     max_pending_requests: Some(50),
                     max_requests: Some(200),
                     max_retries: Some(3),
+                    retry_budget: None,
+                    track_remaining: None,
                 }),
                 high: None,
             }),
@@ -676,7 +907,14 @@ mod tests {
                 interval_seconds: Some(10),
                 base_ejection_time_seconds: Some(60),
                 max_ejection_percent: Some(50),
+                consecutive_gateway_failure: None,
+                consecutive_local_origin_failure: None,
+                split_external_local_origin_errors: None,
+                success_rate: None,
+                failure_percentage: None,
             }),
+            zone_aware_routing: None,
+            locality_weighted_lb: None,
         }
     }
 
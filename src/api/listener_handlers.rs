@@ -8,7 +8,7 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use tracing::{error, info};
+use tracing::info;
 use utoipa::ToSchema;
 
 use crate::{
@@ -110,7 +110,11 @@ pub enum ListenerFilterTypeInput {
 pub struct ListenerTlsContextInput {
     pub cert_chain_file: Option<String>,
     pub private_key_file: Option<String>,
+    /// Name of a stored secret served over SDS instead of the certificate files.
+    pub certificate_secret: Option<String>,
     pub ca_cert_file: Option<String>,
+    /// Name of a validation context secret, e.g. `flowplane-ca`, used instead of the CA file.
+    pub validation_secret: Option<String>,
     pub require_client_certificate: Option<bool>,
 }
 
@@ -166,11 +170,6 @@ pub async fn create_listener_handler(
     let created = repository.create(request).await.map_err(ApiError::from)?;
     info!(listener_id = %created.id, listener_name = %created.name, "Listener created via API");
 
-    state.xds_state.refresh_listeners_from_repository().await.map_err(|err| {
-        error!(error = %err, "Failed to refresh xDS caches after listener creation");
-        ApiError::from(err)
-    })?;
-
     let response = listener_response_from_data(created)?;
     Ok((StatusCode::CREATED, Json(response)))
 }
@@ -266,11 +265,6 @@ pub async fn update_listener_handler(
 
     info!(listener_id = %existing.id, listener_name = %name, "Listener updated via API");
 
-    state.xds_state.refresh_listeners_from_repository().await.map_err(|err| {
-        error!(error = %err, "Failed to refresh xDS caches after listener update");
-        ApiError::from(err)
-    })?;
-
     let response = listener_response_from_data(updated)?;
     Ok(Json(response))
 }
@@ -303,11 +297,6 @@ pub async fn delete_listener_handler(
 
     info!(listener_id = %existing.id, listener_name = %name, "Listener deleted via API");
 
-    state.xds_state.refresh_listeners_from_repository().await.map_err(|err| {
-        error!(error = %err, "Failed to refresh xDS caches after listener deletion");
-        ApiError::from(err)
-    })?;
-
     Ok(StatusCode::NO_CONTENT)
 }
 
@@ -430,7 +419,9 @@ fn convert_tls_context(input: &ListenerTlsContextInput) -> TlsContextConfig {
     TlsContextConfig {
         cert_chain_file: input.cert_chain_file.clone(),
         private_key_file: input.private_key_file.clone(),
+        certificate_secret: input.certificate_secret.clone(),
         ca_cert_file: input.ca_cert_file.clone(),
+        validation_secret: input.validation_secret.clone(),
         require_client_certificate: input.require_client_certificate,
     }
 }
@@ -556,7 +547,7 @@ mod tests {
             PathMatch, RouteActionConfig, RouteConfig as InlineRouteConfig, RouteMatchConfig,
             RouteRule, VirtualHostConfig,
         },
-        xds::XdsState,
+        xds::{DatabaseAggregatedDiscoveryService, XdsState},
     };
     use sqlx::sqlite::SqlitePoolOptions;
     use std::sync::Arc;
@@ -623,16 +614,30 @@ mod tests {
         .await
         .unwrap();
 
-        pool
+        pool.into()
     }
 
     async fn build_state() -> (Arc<XdsState>, ApiState) {
         let pool = create_test_pool().await;
         let state = Arc::new(XdsState::with_database(SimpleXdsConfig::default(), pool));
+        // Starts the change listener that refreshes the caches after writes.
+        let _service = DatabaseAggregatedDiscoveryService::new(state.clone());
         let api_state = ApiState { xds_state: state.clone() };
         (state, api_state)
     }
 
+    /// Wait for the listener cache to move past `version`, since the change
+    /// listener refreshes it after the handler returns.
+    async fn wait_for_listener_version(state: &XdsState, version: u64) {
+        for _ in 0..200 {
+            if state.get_type_version_number(LISTENER_TYPE_URL) > version {
+                return;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+        panic!("listener cache was not refreshed");
+    }
+
     #[test]
     fn convert_http_filter_requires_route_source() {
         let result = convert_filter_type(&ListenerFilterTypeInput::HttpConnectionManager {
@@ -715,6 +720,7 @@ mod tests {
             }],
         };
 
+        let before = state.get_type_version_number(LISTENER_TYPE_URL);
         let (status, Json(resp)) = create_listener_handler(State(api_state.clone()), Json(payload))
             .await
             .expect("create listener");
@@ -723,9 +729,7 @@ mod tests {
         assert_eq!(resp.name, "edge-listener");
         assert_eq!(resp.port, Some(10000));
 
-        // Allow async cache refresh to complete.
-        sleep(Duration::from_millis(50)).await;
-
+        wait_for_listener_version(&state, before).await;
         let cached = state.cached_resources(LISTENER_TYPE_URL);
         assert_eq!(cached.len(), 1, "listener cache should contain one entry");
     }
@@ -756,9 +760,12 @@ mod tests {
             }],
         };
 
+        let before = state.get_type_version_number(LISTENER_TYPE_URL);
         let _ = create_listener_handler(State(api_state.clone()), Json(initial))
             .await
             .expect("seed listener");
+        wait_for_listener_version(&state, before).await;
+        let seeded = state.get_type_version_number(LISTENER_TYPE_URL);
 
         let update_payload = UpdateListenerBody {
             address: "127.0.0.1".to_string(),
@@ -793,7 +800,7 @@ mod tests {
         assert_eq!(updated.version, 2);
 
         // Ensure cache reflects latest version.
-        sleep(Duration::from_millis(50)).await;
+        wait_for_listener_version(&state, seeded).await;
         let cached = state.cached_resources(LISTENER_TYPE_URL);
         assert_eq!(cached.len(), 1);
         assert_eq!(cached[0].name, "edge-listener");
@@ -3,6 +3,7 @@
 //! This module wires together the API router, handlers, and server boot logic.
 
 pub mod auth_handlers;
+pub mod ca_handlers;
 pub mod docs;
 pub mod error;
 pub mod gateway_handlers;
@@ -11,6 +12,9 @@ pub mod listener_handlers;
 pub mod platform_api_handlers;
 pub mod route_handlers;
 pub mod routes;
+pub mod runtime_handlers;
+pub mod secret_handlers;
 pub mod server;
+pub mod xds_handlers;
 
 pub use server::start_api_server;
@@ -9,6 +9,7 @@ use utoipa::{IntoParams, ToSchema};
 use crate::storage::repository_simple::ApiDefinitionData;
 use crate::{
     api::{error::ApiError, routes::ApiState},
+    platform_api::bootstrap::{render_bootstrap, BootstrapOptions, BootstrapScope},
     platform_api::materializer::{
         AppendRouteOutcome, CreateDefinitionOutcome, PlatformApiMaterializer,
     },
@@ -131,13 +132,125 @@ pub struct BootstrapQuery {
     pub format: Option<String>, // yaml|json (default yaml)
     #[serde(default)]
     #[param(required = false)]
-    pub scope: Option<String>, // all|team|allowlist (default all)
+    pub scope: Option<String>, // all|team|allowlist (default team when a team is known)
     #[serde(default)]
     #[param(required = false)]
-    pub allowlist: Option<Vec<String>>, // names when scope=allowlist
+    pub team: Option<String>, // required by /api/v1/bootstrap for team scope
+    #[serde(default)]
+    #[param(required = false)]
+    pub allowlist: Option<String>, // comma-separated listener names when scope=allowlist
     #[serde(default)]
     #[param(required = false)]
     pub include_default: Option<bool>, // default false in team scope
+    #[serde(default)]
+    #[param(required = false)]
+    pub node_id: Option<String>, // default team={team}/dp-{uuid}
+    #[serde(default)]
+    #[param(required = false)]
+    pub xds_address: Option<String>, // address Envoy dials for ADS
+    #[serde(default)]
+    #[param(required = false)]
+    pub admin_port: Option<u16>, // default 9901
+    #[serde(default)]
+    #[param(required = false)]
+    pub ca_path: Option<String>, // Envoy-side CA file when xDS uses TLS
+    #[serde(default)]
+    #[param(required = false)]
+    pub cert_path: Option<String>, // Envoy-side client certificate for mTLS
+    #[serde(default)]
+    #[param(required = false)]
+    pub key_path: Option<String>, // Envoy-side client key for mTLS
+}
+
+impl BootstrapQuery {
+    fn options(&self, team: Option<&str>) -> Result<BootstrapOptions, ApiError> {
+        let default_scope = if team.is_some() { "team" } else { "all" };
+        let scope = match self.scope.as_deref().unwrap_or(default_scope).to_lowercase().as_str() {
+            "all" => BootstrapScope::All,
+            "team" => BootstrapScope::Team {
+                team: team
+                    .ok_or_else(|| ApiError::BadRequest("scope=team requires a team".into()))?
+                    .to_string(),
+                include_default: self.include_default.unwrap_or(false),
+            },
+            "allowlist" => {
+                let listeners: Vec<String> = self
+                    .allowlist
+                    .as_deref()
+                    .unwrap_or_default()
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .map(str::to_string)
+                    .collect();
+                if listeners.is_empty() {
+                    return Err(ApiError::BadRequest(
+                        "scope=allowlist requires at least one listener in allowlist".into(),
+                    ));
+                }
+                BootstrapScope::Allowlist { team: team.map(str::to_string), listeners }
+            }
+            other => {
+                return Err(ApiError::BadRequest(format!(
+                    "Unknown bootstrap scope '{}'; expected all, team or allowlist",
+                    other
+                )))
+            }
+        };
+
+        let mut options = BootstrapOptions::new(scope);
+        if let Some(node_id) = &self.node_id {
+            options.node_id = node_id.clone();
+        }
+        options.xds_address = self.xds_address.clone();
+        if let Some(port) = self.admin_port {
+            options.admin_port = port;
+        }
+        if let Some(path) = &self.ca_path {
+            options.tls_files.ca_path = path.clone();
+        }
+        if let Some(path) = &self.cert_path {
+            options.tls_files.cert_path = path.clone();
+        }
+        if let Some(path) = &self.key_path {
+            options.tls_files.key_path = path.clone();
+        }
+        Ok(options)
+    }
+}
+
+fn bootstrap_response(
+    state: &ApiState,
+    query: &BootstrapQuery,
+    team: Option<&str>,
+) -> Result<Response, ApiError> {
+    let options = query.options(team)?;
+    let bootstrap = render_bootstrap(&state.xds_state.config, &options);
+
+    let (content_type, body) =
+        match query.format.as_deref().unwrap_or("yaml").to_lowercase().as_str() {
+            "json" => (
+                "application/json",
+                serde_json::to_vec(&bootstrap).map_err(|e| ApiError::Internal(e.to_string()))?,
+            ),
+            "yaml" => (
+                "application/yaml",
+                serde_yaml::to_string(&bootstrap)
+                    .map_err(|e| ApiError::Internal(e.to_string()))?
+                    .into_bytes(),
+            ),
+            other => {
+                return Err(ApiError::BadRequest(format!(
+                    "Unknown bootstrap format '{}'; expected yaml or json",
+                    other
+                )))
+            }
+        };
+
+    Response::builder()
+        .header(header::CONTENT_TYPE, content_type)
+        .body(axum::body::Body::from(body))
+        .map_err(|e| ApiError::Internal(e.to_string()))
 }
 
 #[utoipa::path(
@@ -145,7 +258,8 @@ pub struct BootstrapQuery {
     path = "/api/v1/api-definitions/{id}/bootstrap",
     params(("id" = String, Path, description = "API definition ID"), BootstrapQuery),
     responses(
-        (status = 200, description = "Envoy bootstrap"),
+        (status = 200, description = "Envoy ADS bootstrap scoped to the definition's team"),
+        (status = 400, description = "Invalid bootstrap options"),
         (status = 404, description = "Not found")
     ),
     tag = "platform-api"
@@ -160,78 +274,32 @@ pub async fn get_bootstrap_handler(
     })?;
 
     let def = repo.get_definition(&id).await.map_err(ApiError::from)?;
+    if q.team.as_deref().is_some_and(|team| team != def.team) {
+        return Err(ApiError::BadRequest(format!(
+            "API definition '{}' belongs to team '{}'",
+            def.id, def.team
+        )));
+    }
 
-    let format = q.format.as_deref().unwrap_or("yaml").to_lowercase();
-    let scope = q.scope.as_deref().unwrap_or("all").to_lowercase();
-    let include_default = q.include_default.unwrap_or(false);
-    let allowlist = q.allowlist.unwrap_or_default();
-
-    // Build ADS bootstrap with node metadata for scoping
-    let xds_addr = state.xds_state.config.bind_address.clone();
-    let xds_port = state.xds_state.config.port;
-    let node_id = format!("team={}/dp-{}", def.team, uuid::Uuid::new_v4());
-
-    let metadata = match scope.as_str() {
-        "team" => serde_json::json!({
-            "team": def.team,
-            "include_default": include_default,
-        }),
-        "allowlist" => serde_json::json!({
-            "team": def.team,
-            "listener_allowlist": allowlist,
-        }),
-        _ => serde_json::json!({}),
-    };
-
-    let bootstrap = serde_json::json!({
-        "admin": {
-            "access_log_path": "/tmp/envoy_admin.log",
-            "address": { "socket_address": { "address": "127.0.0.1", "port_value": 9901 } }
-        },
-        "node": { "id": node_id, "metadata": metadata },
-        "dynamic_resources": {
-            "lds_config": { "ads": {} },
-            "cds_config": { "ads": {} },
-            "ads_config": {
-                "api_type": "GRPC",
-                "transport_api_version": "V3",
-                "grpc_services": [ { "envoy_grpc": { "cluster_name": "xds_cluster" } } ]
-            }
-        },
-        "static_resources": {
-            "clusters": [
-                {
-                    "name": "xds_cluster",
-                    "type": "LOGICAL_DNS",
-                    "dns_lookup_family": "V4_ONLY",
-                    "connect_timeout": "1s",
-                    "http2_protocol_options": {},
-                    "load_assignment": {
-                        "cluster_name": "xds_cluster",
-                        "endpoints": [ { "lb_endpoints": [ { "endpoint": { "address": { "socket_address": { "address": xds_addr, "port_value": xds_port } } } } ] } ]
-                    }
-                }
-            ]
-        }
-    });
-
-    let response = if format == "json" {
-        let body = serde_json::to_vec(&bootstrap)
-            .map_err(|e| ApiError::service_unavailable(e.to_string()))?;
-        Response::builder()
-            .header(header::CONTENT_TYPE, "application/json")
-            .body(axum::body::Body::from(body))
-            .unwrap()
-    } else {
-        let yaml = serde_yaml::to_string(&bootstrap)
-            .map_err(|e| ApiError::service_unavailable(e.to_string()))?;
-        Response::builder()
-            .header(header::CONTENT_TYPE, "application/yaml")
-            .body(axum::body::Body::from(yaml))
-            .unwrap()
-    };
+    bootstrap_response(&state, &q, Some(&def.team))
+}
 
-    Ok(response)
+#[utoipa::path(
+    get,
+    path = "/api/v1/bootstrap",
+    params(BootstrapQuery),
+    responses(
+        (status = 200, description = "Envoy ADS bootstrap, scoped to `team` when given"),
+        (status = 400, description = "Invalid bootstrap options")
+    ),
+    tag = "platform-api"
+)]
+pub async fn get_team_bootstrap_handler(
+    State(state): State<ApiState>,
+    Query(q): Query<BootstrapQuery>,
+) -> Result<Response, ApiError> {
+    let team = q.team.clone().filter(|team| !team.trim().is_empty());
+    bootstrap_response(&state, &q, team.as_deref())
 }
 
 #[utoipa::path(
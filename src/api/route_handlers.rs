@@ -6,7 +6,7 @@ use axum::{
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
-use tracing::{error, info};
+use tracing::info;
 use utoipa::ToSchema;
 
 use validator::Validate;
@@ -249,11 +249,6 @@ pub async fn create_route_handler(
 
     info!(route_id = %created.id, route_name = %created.name, "Route created via API");
 
-    state.xds_state.refresh_routes_from_repository().await.map_err(|err| {
-        error!(error = %err, "Failed to refresh xDS caches after route creation");
-        ApiError::from(err)
-    })?;
-
     let response = RouteResponse {
         name: created.name,
         path_prefix: created.path_prefix,
@@ -358,11 +353,6 @@ pub async fn update_route_handler(
 
     info!(route_id = %updated.id, route_name = %updated.name, "Route updated via API");
 
-    state.xds_state.refresh_routes_from_repository().await.map_err(|err| {
-        error!(error = %err, "Failed to refresh xDS caches after route update");
-        ApiError::from(err)
-    })?;
-
     let response = RouteResponse {
         name: updated.name,
         path_prefix,
@@ -401,11 +391,6 @@ pub async fn delete_route_handler(
 
     info!(route_id = %existing.id, route_name = %existing.name, "Route deleted via API");
 
-    state.xds_state.refresh_routes_from_repository().await.map_err(|err| {
-        error!(error = %err, "Failed to refresh xDS caches after route deletion");
-        ApiError::from(err)
-    })?;
-
     Ok(StatusCode::NO_CONTENT)
 }
 
@@ -909,8 +894,10 @@ mod tests {
         .await
         .expect("pool");
 
-        pool.execute(
-            r#"
+        pool.as_sqlite()
+            .expect("sqlite pool")
+            .execute(
+                r#"
             CREATE TABLE IF NOT EXISTS clusters (
                 id TEXT PRIMARY KEY,
                 name TEXT NOT NULL,
@@ -934,9 +921,9 @@ mod tests {
                 UNIQUE(name, version)
             );
         "#,
-        )
-        .await
-        .expect("create tables");
+            )
+            .await
+            .expect("create tables");
 
         let state = XdsState::with_database(SimpleXdsConfig::default(), pool.clone());
         let api_state = ApiState { xds_state: Arc::new(state) };
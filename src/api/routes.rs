@@ -18,6 +18,7 @@ use super::{
         create_token_handler, get_token_handler, list_tokens_handler, revoke_token_handler,
         rotate_token_handler, update_token_handler,
     },
+    ca_handlers::{get_certificate_authority_handler, issue_certificate_handler},
     docs,
     gateway_handlers::create_gateway_from_openapi_handler,
     handlers::{
@@ -36,6 +37,18 @@ use super::{
         create_route_handler, delete_route_handler, get_route_handler, list_routes_handler,
         update_route_handler,
     },
+    runtime_handlers::{
+        delete_runtime_key_handler, get_runtime_key_handler, list_runtime_keys_handler,
+        set_runtime_key_handler,
+    },
+    secret_handlers::{
+        create_secret_handler, delete_secret_handler, get_secret_handler, list_secrets_handler,
+        update_secret_handler,
+    },
+    xds_handlers::{
+        abort_rollout_handler, get_node_handler, get_snapshot_handler, list_nodes_handler,
+        list_rollouts_handler, promote_rollout_handler,
+    },
 };
 
 #[derive(Clone)]
@@ -153,6 +166,14 @@ pub fn build_router(state: Arc<XdsState>) -> Router {
                 )
                 .route_layer(scope_layer(vec!["routes:read"])),
         )
+        .merge(
+            Router::new()
+                .route(
+                    "/api/v1/bootstrap",
+                    get(super::platform_api_handlers::get_team_bootstrap_handler),
+                )
+                .route_layer(scope_layer(vec!["routes:read"])),
+        )
         .merge(
             Router::new()
                 .route("/api/v1/routes/{name}", get(get_route_handler))
@@ -198,11 +219,99 @@ pub fn build_router(state: Arc<XdsState>) -> Router {
                 .route("/api/v1/listeners/{name}", delete(delete_listener_handler))
                 .route_layer(scope_layer(vec!["listeners:write"])),
         )
+        .merge(
+            Router::new()
+                .route("/api/v1/secrets", get(list_secrets_handler))
+                .route_layer(scope_layer(vec!["secrets:read"])),
+        )
+        .merge(
+            Router::new()
+                .route("/api/v1/secrets", post(create_secret_handler))
+                .route_layer(scope_layer(vec!["secrets:write"])),
+        )
+        .merge(
+            Router::new()
+                .route("/api/v1/secrets/{name}", get(get_secret_handler))
+                .route_layer(scope_layer(vec!["secrets:read"])),
+        )
+        .merge(
+            Router::new()
+                .route("/api/v1/secrets/{name}", put(update_secret_handler))
+                .route_layer(scope_layer(vec!["secrets:write"])),
+        )
+        .merge(
+            Router::new()
+                .route("/api/v1/secrets/{name}", delete(delete_secret_handler))
+                .route_layer(scope_layer(vec!["secrets:write"])),
+        )
+        .merge(
+            Router::new()
+                .route("/api/v1/ca", get(get_certificate_authority_handler))
+                .route_layer(scope_layer(vec!["secrets:read"])),
+        )
+        .merge(
+            Router::new()
+                .route("/api/v1/ca/certificates", post(issue_certificate_handler))
+                .route_layer(scope_layer(vec!["secrets:write"])),
+        )
+        .merge(
+            Router::new()
+                .route("/api/v1/runtime", get(list_runtime_keys_handler))
+                .route_layer(scope_layer(vec!["runtime:read"])),
+        )
+        .merge(
+            Router::new()
+                .route("/api/v1/runtime/{key}", get(get_runtime_key_handler))
+                .route_layer(scope_layer(vec!["runtime:read"])),
+        )
+        .merge(
+            Router::new()
+                .route("/api/v1/runtime/{key}", put(set_runtime_key_handler))
+                .route_layer(scope_layer(vec!["runtime:write"])),
+        )
+        .merge(
+            Router::new()
+                .route("/api/v1/runtime/{key}", delete(delete_runtime_key_handler))
+                .route_layer(scope_layer(vec!["runtime:write"])),
+        )
         .merge(
             Router::new()
                 .route("/api/v1/gateways/openapi", post(create_gateway_from_openapi_handler))
                 .route_layer(scope_layer(vec!["gateways:import"])),
         )
+        .merge(
+            Router::new()
+                .route("/api/v1/xds/nodes", get(list_nodes_handler))
+                .route_layer(scope_layer(vec!["xds:read"])),
+        )
+        .merge(
+            Router::new()
+                .route("/api/v1/xds/nodes/{id}", get(get_node_handler))
+                .route_layer(scope_layer(vec!["xds:read"])),
+        )
+        .merge(
+            Router::new()
+                .route("/api/v1/xds/rollouts", get(list_rollouts_handler))
+                .route_layer(scope_layer(vec!["xds:read"])),
+        )
+        .merge(
+            Router::new()
+                .route("/api/v1/xds/snapshot", get(get_snapshot_handler))
+                .route_layer(scope_layer(vec!["xds:read"])),
+        )
+        .merge(
+            Router::new()
+                .route(
+                    "/api/v1/xds/rollouts/{resource_type}/promote",
+                    post(promote_rollout_handler),
+                )
+                .route_layer(scope_layer(vec!["xds:write"])),
+        )
+        .merge(
+            Router::new()
+                .route("/api/v1/xds/rollouts/{resource_type}/abort", post(abort_rollout_handler))
+                .route_layer(scope_layer(vec!["xds:write"])),
+        )
         .with_state(api_state)
         .layer(auth_layer);
 
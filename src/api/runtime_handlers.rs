@@ -0,0 +1,284 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+use utoipa::ToSchema;
+
+use crate::{
+    storage::{RuntimeKeyData, RuntimeRepository, SetRuntimeKeyRequest},
+    xds::runtime::{validate_runtime_key, validate_runtime_value},
+};
+
+use super::{error::ApiError, routes::ApiState};
+
+/// Runtime key as returned by the API.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeKeyResponse {
+    #[schema(example = "cors.enabled")]
+    pub key: String,
+    /// Boolean, number, string or fractional percent.
+    #[schema(example = json!({"numerator": 25, "denominator": "HUNDRED"}))]
+    pub value: serde_json::Value,
+    pub version: i64,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SetRuntimeKeyBody {
+    /// Boolean, number, string or fractional percent
+    /// (`{"numerator": 25, "denominator": "HUNDRED"}`).
+    #[schema(example = json!(true))]
+    pub value: serde_json::Value,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ListRuntimeKeysQuery {
+    pub limit: Option<i32>,
+    pub offset: Option<i32>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/runtime",
+    params(
+        ("limit" = Option<i32>, Query, description = "Maximum number of runtime keys to return"),
+        ("offset" = Option<i32>, Query, description = "Offset for paginated results"),
+    ),
+    responses(
+        (status = 200, description = "List of runtime keys", body = [RuntimeKeyResponse]),
+        (status = 503, description = "Runtime repository not configured"),
+    ),
+    tag = "runtime"
+)]
+pub async fn list_runtime_keys_handler(
+    State(state): State<ApiState>,
+    Query(params): Query<ListRuntimeKeysQuery>,
+) -> Result<Json<Vec<RuntimeKeyResponse>>, ApiError> {
+    let repository = require_runtime_repository(&state)?;
+    let rows = repository.list(params.limit, params.offset).await.map_err(ApiError::from)?;
+    let keys = rows.into_iter().map(runtime_key_response_from_data).collect::<Result<_, _>>()?;
+    Ok(Json(keys))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/runtime/{key}",
+    params(("key" = String, Path, description = "Runtime key")),
+    responses(
+        (status = 200, description = "Runtime key details", body = RuntimeKeyResponse),
+        (status = 404, description = "Runtime key not set"),
+        (status = 503, description = "Runtime repository not configured"),
+    ),
+    tag = "runtime"
+)]
+pub async fn get_runtime_key_handler(
+    State(state): State<ApiState>,
+    Path(key): Path<String>,
+) -> Result<Json<RuntimeKeyResponse>, ApiError> {
+    let repository = require_runtime_repository(&state)?;
+    let data = repository.get_by_key(&key).await.map_err(ApiError::from)?;
+    Ok(Json(runtime_key_response_from_data(data)?))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/runtime/{key}",
+    request_body = SetRuntimeKeyBody,
+    params(("key" = String, Path, description = "Runtime key")),
+    responses(
+        (status = 200, description = "Runtime key set and pushed over RTDS", body = RuntimeKeyResponse),
+        (status = 400, description = "Invalid runtime key or value"),
+        (status = 503, description = "Runtime repository not configured"),
+    ),
+    tag = "runtime"
+)]
+pub async fn set_runtime_key_handler(
+    State(state): State<ApiState>,
+    Path(key): Path<String>,
+    Json(payload): Json<SetRuntimeKeyBody>,
+) -> Result<Json<RuntimeKeyResponse>, ApiError> {
+    validate_runtime_key(&key).map_err(ApiError::from)?;
+    validate_runtime_value(&key, &payload.value).map_err(ApiError::from)?;
+
+    let repository = require_runtime_repository(&state)?;
+    let request = SetRuntimeKeyRequest { key: key.clone(), value: payload.value.to_string() };
+    let data = repository.set(request).await.map_err(ApiError::from)?;
+    info!(runtime_key = %key, version = data.version, "Runtime key set via API");
+
+    refresh_runtime(&state).await?;
+
+    Ok(Json(runtime_key_response_from_data(data)?))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/runtime/{key}",
+    params(("key" = String, Path, description = "Runtime key")),
+    responses(
+        (status = 204, description = "Runtime key removed from the layer"),
+        (status = 404, description = "Runtime key not set"),
+        (status = 503, description = "Runtime repository not configured"),
+    ),
+    tag = "runtime"
+)]
+pub async fn delete_runtime_key_handler(
+    State(state): State<ApiState>,
+    Path(key): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    let repository = require_runtime_repository(&state)?;
+    repository.delete_by_key(&key).await.map_err(ApiError::from)?;
+    info!(runtime_key = %key, "Runtime key deleted via API");
+
+    refresh_runtime(&state).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+fn require_runtime_repository(state: &ApiState) -> Result<RuntimeRepository, ApiError> {
+    state
+        .xds_state
+        .runtime_repository
+        .as_ref()
+        .cloned()
+        .ok_or_else(|| ApiError::service_unavailable("Runtime repository not configured"))
+}
+
+async fn refresh_runtime(state: &ApiState) -> Result<(), ApiError> {
+    state.xds_state.refresh_runtime_from_repository().await.map_err(|err| {
+        error!(error = %err, "Failed to refresh xDS caches after runtime change");
+        ApiError::from(err)
+    })
+}
+
+fn runtime_key_response_from_data(data: RuntimeKeyData) -> Result<RuntimeKeyResponse, ApiError> {
+    let value = serde_json::from_str(&data.value).map_err(|err| {
+        ApiError::Internal(format!("Failed to parse value of runtime key '{}': {}", data.key, err))
+    })?;
+    Ok(RuntimeKeyResponse {
+        key: data.key,
+        value,
+        version: data.version,
+        created_at: data.created_at,
+        updated_at: data.updated_at,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SimpleXdsConfig;
+    use crate::storage::{create_pool, DatabaseConfig};
+    use crate::xds::{resources::RUNTIME_TYPE_URL, runtime::RUNTIME_LAYER_NAME, XdsState};
+    use envoy_types::pb::envoy::service::runtime::v3::Runtime;
+    use prost::Message;
+    use serde_json::json;
+    use std::sync::Arc;
+
+    async fn setup_state() -> ApiState {
+        let pool = create_pool(&DatabaseConfig {
+            url: "sqlite://:memory:".to_string(),
+            auto_migrate: false,
+            ..Default::default()
+        })
+        .await
+        .expect("pool");
+
+        sqlx::query(
+            r#"
+            CREATE TABLE runtime_keys (
+                id TEXT PRIMARY KEY,
+                key TEXT NOT NULL UNIQUE,
+                value TEXT NOT NULL,
+                version INTEGER NOT NULL DEFAULT 1,
+                created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+            )
+        "#,
+        )
+        .execute(pool.as_sqlite().unwrap())
+        .await
+        .expect("create table");
+
+        ApiState { xds_state: Arc::new(XdsState::with_database(SimpleXdsConfig::default(), pool)) }
+    }
+
+    fn served_layer_keys(state: &ApiState) -> Vec<String> {
+        let cached = state.xds_state.cached_resources(RUNTIME_TYPE_URL);
+        assert_eq!(cached.len(), 1);
+        assert_eq!(cached[0].name, RUNTIME_LAYER_NAME);
+        let runtime = Runtime::decode(cached[0].body.value.as_slice()).unwrap();
+        let mut keys: Vec<String> = runtime.layer.unwrap().fields.into_keys().collect();
+        keys.sort();
+        keys
+    }
+
+    #[tokio::test]
+    async fn runtime_keys_are_set_replaced_and_deleted() {
+        let state = setup_state().await;
+
+        let Json(created) = set_runtime_key_handler(
+            State(state.clone()),
+            Path("cors.enabled".into()),
+            Json(SetRuntimeKeyBody { value: json!(false) }),
+        )
+        .await
+        .expect("set runtime key");
+        assert_eq!(created.version, 1);
+        assert_eq!(served_layer_keys(&state), vec!["cors.enabled".to_string()]);
+        let first_version = state.xds_state.get_type_version_number(RUNTIME_TYPE_URL);
+
+        let Json(replaced) = set_runtime_key_handler(
+            State(state.clone()),
+            Path("cors.enabled".into()),
+            Json(SetRuntimeKeyBody { value: json!({"numerator": 10, "denominator": "HUNDRED"}) }),
+        )
+        .await
+        .expect("replace runtime key");
+        assert_eq!(replaced.version, 2);
+        assert_eq!(replaced.value["numerator"], json!(10));
+        assert!(state.xds_state.get_type_version_number(RUNTIME_TYPE_URL) > first_version);
+
+        let Json(listed) =
+            list_runtime_keys_handler(State(state.clone()), Query(ListRuntimeKeysQuery::default()))
+                .await
+                .expect("list runtime keys");
+        assert_eq!(listed.len(), 1);
+
+        let status = delete_runtime_key_handler(State(state.clone()), Path("cors.enabled".into()))
+            .await
+            .expect("delete runtime key");
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert!(served_layer_keys(&state).is_empty(), "the empty layer is still served");
+
+        let missing = get_runtime_key_handler(State(state), Path("cors.enabled".into())).await;
+        assert!(matches!(missing, Err(ApiError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn invalid_keys_and_values_are_rejected() {
+        let state = setup_state().await;
+
+        let invalid_key = set_runtime_key_handler(
+            State(state.clone()),
+            Path("cors..enabled".into()),
+            Json(SetRuntimeKeyBody { value: json!(true) }),
+        )
+        .await;
+        assert!(matches!(invalid_key, Err(ApiError::BadRequest(_))));
+
+        let invalid_value = set_runtime_key_handler(
+            State(state),
+            Path("cors.enabled".into()),
+            Json(SetRuntimeKeyBody { value: json!([1, 2]) }),
+        )
+        .await;
+        assert!(matches!(invalid_value, Err(ApiError::BadRequest(_))));
+    }
+}
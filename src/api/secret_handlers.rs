@@ -0,0 +1,396 @@
+use std::path::PathBuf;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+use utoipa::ToSchema;
+
+use crate::{
+    storage::{
+        CreateSecretRequest, SecretCipher, SecretData, SecretRepository, UpdateSecretRequest,
+    },
+    utils::{validate_pem_bundle, CertificateInfo, VALID_NAME_REGEX},
+    xds::ca::CA_TRUST_BUNDLE_SECRET,
+};
+
+use super::{error::ApiError, routes::ApiState};
+
+/// Stored secret as returned by the API. The private key is never returned.
+#[derive(Debug, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretResponse {
+    pub name: String,
+    pub version: i64,
+    /// PEM encoded certificate chain.
+    pub certificate_chain: String,
+    /// SPIFFE ID of certificates issued by the built-in CA.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(example = "spiffe://flowplane.local/team/payments")]
+    pub spiffe_id: Option<String>,
+    /// Expiry of the leaf certificate.
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateSecretBody {
+    /// Name referenced by listeners and clusters.
+    #[schema(example = "edge-cert")]
+    pub name: String,
+    /// PEM encoded certificate chain, leaf first.
+    pub certificate_chain: String,
+    /// PEM encoded private key matching the leaf certificate.
+    pub private_key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateSecretBody {
+    /// PEM encoded certificate chain, leaf first.
+    pub certificate_chain: String,
+    /// PEM encoded private key matching the leaf certificate.
+    pub private_key: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ListSecretsQuery {
+    pub limit: Option<i32>,
+    pub offset: Option<i32>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/secrets",
+    request_body = CreateSecretBody,
+    responses(
+        (status = 201, description = "Secret stored", body = SecretResponse),
+        (status = 400, description = "Invalid certificate or key"),
+        (status = 409, description = "Secret with the same name already exists"),
+        (status = 503, description = "Secret storage not configured"),
+    ),
+    tag = "secrets"
+)]
+pub async fn create_secret_handler(
+    State(state): State<ApiState>,
+    Json(payload): Json<CreateSecretBody>,
+) -> Result<(StatusCode, Json<SecretResponse>), ApiError> {
+    if !VALID_NAME_REGEX.is_match(&payload.name) || payload.name.len() > 255 {
+        return Err(ApiError::BadRequest(format!(
+            "Invalid secret name '{}': use letters, numbers, '_' and '-'",
+            payload.name
+        )));
+    }
+    if payload.name == CA_TRUST_BUNDLE_SECRET {
+        return Err(ApiError::BadRequest(format!(
+            "Secret name '{}' is reserved for the built-in CA trust bundle",
+            payload.name
+        )));
+    }
+
+    let (repository, cipher) = require_secret_storage(&state)?;
+    let info =
+        validate_key_material(&payload.name, &payload.certificate_chain, &payload.private_key)?;
+
+    let request = CreateSecretRequest {
+        name: payload.name.clone(),
+        certificate_chain: payload.certificate_chain,
+        encrypted_private_key: cipher.encrypt(&payload.private_key).map_err(ApiError::from)?,
+        spiffe_id: None,
+        expires_at: Some(info.not_after),
+    };
+
+    let created = repository.create(request).await.map_err(ApiError::from)?;
+    info!(secret_name = %created.name, "Secret created via API");
+
+    refresh_secrets(&state).await?;
+
+    Ok((StatusCode::CREATED, Json(secret_response_from_data(created))))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/secrets",
+    params(
+        ("limit" = Option<i32>, Query, description = "Maximum number of secrets to return"),
+        ("offset" = Option<i32>, Query, description = "Offset for paginated results"),
+    ),
+    responses(
+        (status = 200, description = "List of secrets", body = [SecretResponse]),
+        (status = 503, description = "Secret storage not configured"),
+    ),
+    tag = "secrets"
+)]
+pub async fn list_secrets_handler(
+    State(state): State<ApiState>,
+    Query(params): Query<ListSecretsQuery>,
+) -> Result<Json<Vec<SecretResponse>>, ApiError> {
+    let repository = require_secret_repository(&state)?;
+    let rows = repository.list(params.limit, params.offset).await.map_err(ApiError::from)?;
+    Ok(Json(rows.into_iter().map(secret_response_from_data).collect()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/secrets/{name}",
+    params(("name" = String, Path, description = "Name of the secret")),
+    responses(
+        (status = 200, description = "Secret details", body = SecretResponse),
+        (status = 404, description = "Secret not found"),
+        (status = 503, description = "Secret storage not configured"),
+    ),
+    tag = "secrets"
+)]
+pub async fn get_secret_handler(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+) -> Result<Json<SecretResponse>, ApiError> {
+    let repository = require_secret_repository(&state)?;
+    let secret = repository.get_by_name(&name).await.map_err(ApiError::from)?;
+    Ok(Json(secret_response_from_data(secret)))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/v1/secrets/{name}",
+    request_body = UpdateSecretBody,
+    params(("name" = String, Path, description = "Name of the secret")),
+    responses(
+        (status = 200, description = "Secret rotated", body = SecretResponse),
+        (status = 400, description = "Invalid certificate or key, or secret managed by the built-in CA"),
+        (status = 404, description = "Secret not found"),
+        (status = 503, description = "Secret storage not configured"),
+    ),
+    tag = "secrets"
+)]
+pub async fn update_secret_handler(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+    Json(payload): Json<UpdateSecretBody>,
+) -> Result<Json<SecretResponse>, ApiError> {
+    let (repository, cipher) = require_secret_storage(&state)?;
+    let current = repository.get_by_name(&name).await.map_err(ApiError::from)?;
+    if current.spiffe_id.is_some() {
+        return Err(ApiError::BadRequest(format!(
+            "Secret '{}' is issued and renewed by the built-in CA; use /api/v1/ca/certificates",
+            name
+        )));
+    }
+    let info = validate_key_material(&name, &payload.certificate_chain, &payload.private_key)?;
+
+    let request = UpdateSecretRequest {
+        certificate_chain: payload.certificate_chain,
+        encrypted_private_key: cipher.encrypt(&payload.private_key).map_err(ApiError::from)?,
+        expires_at: Some(info.not_after),
+    };
+
+    let updated = repository.update(&name, request).await.map_err(ApiError::from)?;
+    info!(secret_name = %name, version = updated.version, "Secret rotated via API");
+
+    refresh_secrets(&state).await?;
+
+    Ok(Json(secret_response_from_data(updated)))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/v1/secrets/{name}",
+    params(("name" = String, Path, description = "Name of the secret")),
+    responses(
+        (status = 204, description = "Secret deleted"),
+        (status = 404, description = "Secret not found"),
+        (status = 503, description = "Secret storage not configured"),
+    ),
+    tag = "secrets"
+)]
+pub async fn delete_secret_handler(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    let repository = require_secret_repository(&state)?;
+    repository.delete_by_name(&name).await.map_err(ApiError::from)?;
+    info!(secret_name = %name, "Secret deleted via API");
+
+    refresh_secrets(&state).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub(crate) fn require_secret_repository(state: &ApiState) -> Result<SecretRepository, ApiError> {
+    state
+        .xds_state
+        .secret_repository
+        .as_ref()
+        .cloned()
+        .ok_or_else(|| ApiError::service_unavailable("Secret repository not configured"))
+}
+
+/// Writes need the cipher as well, since private keys are only stored encrypted.
+pub(crate) fn require_secret_storage(
+    state: &ApiState,
+) -> Result<(SecretRepository, SecretCipher), ApiError> {
+    let repository = require_secret_repository(state)?;
+    let cipher = state.xds_state.config.secret_cipher.clone().ok_or_else(|| {
+        ApiError::service_unavailable(
+            "Secret encryption is not configured; set FLOWPLANE_SECRET_ENCRYPTION_KEY",
+        )
+    })?;
+    Ok((repository, cipher))
+}
+
+fn validate_key_material(
+    name: &str,
+    certificate_chain: &str,
+    private_key: &str,
+) -> Result<CertificateInfo, ApiError> {
+    validate_pem_bundle(
+        certificate_chain.as_bytes(),
+        private_key.as_bytes(),
+        &PathBuf::from(format!("secret '{}'", name)),
+    )
+    .map_err(|err| ApiError::BadRequest(err.to_string()))
+}
+
+pub(crate) async fn refresh_secrets(state: &ApiState) -> Result<(), ApiError> {
+    state.xds_state.refresh_secrets_from_repository().await.map_err(|err| {
+        error!(error = %err, "Failed to refresh xDS caches after secret change");
+        ApiError::from(err)
+    })
+}
+
+pub(crate) fn secret_response_from_data(data: SecretData) -> SecretResponse {
+    SecretResponse {
+        name: data.name,
+        version: data.version,
+        certificate_chain: data.certificate_chain,
+        spiffe_id: data.spiffe_id,
+        expires_at: data.expires_at,
+        created_at: data.created_at,
+        updated_at: data.updated_at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SimpleXdsConfig;
+    use crate::storage::{create_pool, DatabaseConfig};
+    use crate::xds::{resources::SECRET_TYPE_URL, XdsState};
+    use std::sync::Arc;
+
+    async fn setup_state(cipher: Option<SecretCipher>) -> ApiState {
+        let pool = create_pool(&DatabaseConfig {
+            url: "sqlite://:memory:".to_string(),
+            auto_migrate: false,
+            ..Default::default()
+        })
+        .await
+        .expect("pool");
+
+        sqlx::query(
+            r#"
+            CREATE TABLE secrets (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL UNIQUE,
+                certificate_chain TEXT NOT NULL,
+                encrypted_private_key TEXT NOT NULL,
+                spiffe_id TEXT,
+                expires_at DATETIME,
+                version INTEGER NOT NULL DEFAULT 1,
+                created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+            )
+        "#,
+        )
+        .execute(pool.as_sqlite().unwrap())
+        .await
+        .expect("create table");
+
+        let config = SimpleXdsConfig { secret_cipher: cipher, ..Default::default() };
+        ApiState { xds_state: Arc::new(XdsState::with_database(config, pool)) }
+    }
+
+    fn key_material() -> (String, String) {
+        let certified = rcgen::generate_simple_self_signed(vec!["edge.local".into()]).unwrap();
+        (certified.cert.pem(), certified.key_pair.serialize_pem())
+    }
+
+    #[tokio::test]
+    async fn secrets_are_stored_encrypted_and_rotated() {
+        let state = setup_state(Some(SecretCipher::new(&[3u8; 32]).unwrap())).await;
+        let (cert, key) = key_material();
+
+        let (status, Json(created)) = create_secret_handler(
+            State(state.clone()),
+            Json(CreateSecretBody {
+                name: "edge-cert".into(),
+                certificate_chain: cert,
+                private_key: key.clone(),
+            }),
+        )
+        .await
+        .expect("create secret");
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(created.version, 1);
+        assert!(created.expires_at.is_some());
+
+        let repository = state.xds_state.secret_repository.clone().unwrap();
+        let stored = repository.get_by_name("edge-cert").await.unwrap();
+        assert!(!stored.encrypted_private_key.contains("PRIVATE KEY"));
+        assert_eq!(state.xds_state.cached_resources(SECRET_TYPE_URL).len(), 1);
+        let first_version = state.xds_state.get_type_version_number(SECRET_TYPE_URL);
+
+        let (cert, key) = key_material();
+        let Json(rotated) = update_secret_handler(
+            State(state.clone()),
+            Path("edge-cert".into()),
+            Json(UpdateSecretBody { certificate_chain: cert, private_key: key }),
+        )
+        .await
+        .expect("rotate secret");
+        assert_eq!(rotated.version, 2);
+        assert!(state.xds_state.get_type_version_number(SECRET_TYPE_URL) > first_version);
+
+        let status = delete_secret_handler(State(state.clone()), Path("edge-cert".into()))
+            .await
+            .expect("delete secret");
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert!(state.xds_state.cached_resources(SECRET_TYPE_URL).is_empty());
+    }
+
+    #[tokio::test]
+    async fn mismatched_key_and_missing_cipher_are_rejected() {
+        let state = setup_state(Some(SecretCipher::new(&[3u8; 32]).unwrap())).await;
+        let (cert, _) = key_material();
+        let (_, other_key) = key_material();
+
+        let mismatched = create_secret_handler(
+            State(state),
+            Json(CreateSecretBody {
+                name: "edge-cert".into(),
+                certificate_chain: cert.clone(),
+                private_key: other_key.clone(),
+            }),
+        )
+        .await;
+        assert!(matches!(mismatched, Err(ApiError::BadRequest(_))));
+
+        let state = setup_state(None).await;
+        let unconfigured = create_secret_handler(
+            State(state),
+            Json(CreateSecretBody {
+                name: "edge-cert".into(),
+                certificate_chain: cert,
+                private_key: other_key,
+            }),
+        )
+        .await;
+        assert!(matches!(unconfigured, Err(ApiError::ServiceUnavailable(_))));
+    }
+}
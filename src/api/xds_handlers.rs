@@ -0,0 +1,251 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use envoy_types::pb::envoy::config::core::v3::Node;
+use envoy_types::pb::google::protobuf::{value::Kind, Struct, Value};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use crate::xds::snapshot::{config_dump, snapshot_type_urls};
+use crate::xds::{NodeStatus, RolloutStatus};
+
+use super::{error::ApiError, routes::ApiState};
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/xds/nodes",
+    responses(
+        (status = 200, description = "Envoy nodes with an open ADS stream", body = [NodeStatus]),
+    ),
+    tag = "xds"
+)]
+pub async fn list_nodes_handler(State(state): State<ApiState>) -> Json<Vec<NodeStatus>> {
+    Json(state.xds_state.node_registry.list())
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/xds/nodes/{id}",
+    params(("id" = String, Path, description = "Envoy node ID (URL-encoded)")),
+    responses(
+        (status = 200, description = "Connected node details", body = NodeStatus),
+        (status = 404, description = "Node is not connected"),
+    ),
+    tag = "xds"
+)]
+pub async fn get_node_handler(
+    State(state): State<ApiState>,
+    Path(id): Path<String>,
+) -> Result<Json<NodeStatus>, ApiError> {
+    state
+        .xds_state
+        .node_registry
+        .get(&id)
+        .map(Json)
+        .ok_or_else(|| ApiError::NotFound(format!("Node '{}' is not connected", id)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/xds/rollouts",
+    responses(
+        (status = 200, description = "Active canary rollouts", body = [RolloutStatus]),
+    ),
+    tag = "xds"
+)]
+pub async fn list_rollouts_handler(State(state): State<ApiState>) -> Json<Vec<RolloutStatus>> {
+    Json(state.xds_state.rollouts())
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/xds/rollouts/{resource_type}/promote",
+    params(("resource_type" = String, Path, description = "Resource type: cluster, route, listener, endpoint, secret or runtime")),
+    responses(
+        (status = 200, description = "Rollout promoted to every node", body = RolloutStatus),
+        (status = 404, description = "No active rollout for the resource type"),
+    ),
+    tag = "xds"
+)]
+pub async fn promote_rollout_handler(
+    State(state): State<ApiState>,
+    Path(resource_type): Path<String>,
+) -> Result<Json<RolloutStatus>, ApiError> {
+    state
+        .xds_state
+        .promote_rollout(&resource_type)
+        .map(Json)
+        .ok_or_else(|| no_rollout(&resource_type))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/v1/xds/rollouts/{resource_type}/abort",
+    params(("resource_type" = String, Path, description = "Resource type: cluster, route, listener, endpoint, secret or runtime")),
+    responses(
+        (status = 200, description = "Rollout aborted; every node is served the stable snapshot", body = RolloutStatus),
+        (status = 404, description = "No active rollout for the resource type"),
+    ),
+    tag = "xds"
+)]
+pub async fn abort_rollout_handler(
+    State(state): State<ApiState>,
+    Path(resource_type): Path<String>,
+) -> Result<Json<RolloutStatus>, ApiError> {
+    state
+        .xds_state
+        .abort_rollout(&resource_type)
+        .map(Json)
+        .ok_or_else(|| no_rollout(&resource_type))
+}
+
+#[derive(Debug, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotQuery {
+    /// Comma-separated resource types: cluster, endpoint, listener, route or
+    /// secret. Every type when omitted.
+    #[serde(default, rename = "type")]
+    #[param(rename = "type")]
+    pub resource_type: Option<String>,
+    /// ID of a connected node; the dump holds what that node is served.
+    #[serde(default)]
+    pub node: Option<String>,
+    /// Team whose listeners the dump is scoped to, as for a node with `team`
+    /// metadata.
+    #[serde(default)]
+    pub team: Option<String>,
+    /// With `team`, also include the default gateway.
+    #[serde(default)]
+    pub include_default: Option<bool>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/v1/xds/snapshot",
+    params(SnapshotQuery),
+    responses(
+        (status = 200, description = "Served resources as an Envoy `envoy.admin.v3.ConfigDump`"),
+        (status = 400, description = "Unknown resource type or conflicting scope"),
+        (status = 404, description = "Node is not connected"),
+    ),
+    tag = "xds"
+)]
+pub async fn get_snapshot_handler(
+    State(state): State<ApiState>,
+    Query(query): Query<SnapshotQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let type_urls = snapshot_type_urls(query.resource_type.as_deref())?;
+
+    let node = match (query.node, query.team) {
+        (Some(_), Some(_)) => {
+            return Err(ApiError::BadRequest("Specify either node or team, not both".into()))
+        }
+        (Some(id), None) => Some(
+            state
+                .xds_state
+                .node_registry
+                .get(&id)
+                .map(|status| status.node)
+                .ok_or_else(|| ApiError::NotFound(format!("Node '{}' is not connected", id)))?,
+        ),
+        (None, Some(team)) => Some(team_node(team, query.include_default.unwrap_or(false))),
+        (None, None) => None,
+    };
+
+    Ok(Json(config_dump(&state.xds_state, &type_urls, node.as_ref()).await?))
+}
+
+/// Node carrying the metadata of a team-scoped bootstrap.
+fn team_node(team: String, include_default: bool) -> Node {
+    let fields = [
+        ("team".to_string(), Value { kind: Some(Kind::StringValue(team.clone())) }),
+        ("include_default".to_string(), Value { kind: Some(Kind::BoolValue(include_default)) }),
+    ];
+    Node {
+        id: format!("team={}/snapshot", team),
+        metadata: Some(Struct { fields: fields.into_iter().collect() }),
+        ..Default::default()
+    }
+}
+
+fn no_rollout(resource_type: &str) -> ApiError {
+    ApiError::NotFound(format!("No active rollout for resource type '{}'", resource_type))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xds::resources::LISTENER_TYPE_URL;
+    use crate::{config::SimpleXdsConfig, xds::XdsState};
+    use std::sync::Arc;
+
+    fn state() -> ApiState {
+        ApiState { xds_state: Arc::new(XdsState::new(SimpleXdsConfig::default())) }
+    }
+
+    #[tokio::test]
+    async fn lists_and_fetches_connected_nodes() {
+        let state = state();
+        let node = Node { id: "edge/envoy-1".into(), cluster: "edge".into(), ..Default::default() };
+        state.xds_state.node_registry.connect(&node, false);
+
+        let Json(nodes) = list_nodes_handler(State(state.clone())).await;
+        assert_eq!(nodes.len(), 1);
+
+        let Json(node) =
+            get_node_handler(State(state.clone()), Path("edge/envoy-1".into())).await.unwrap();
+        assert_eq!(node.cluster, "edge");
+
+        let missing = get_node_handler(State(state), Path("unknown".into())).await;
+        assert!(matches!(missing, Err(ApiError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn promoting_unknown_rollout_is_not_found() {
+        let state = state();
+
+        let Json(rollouts) = list_rollouts_handler(State(state.clone())).await;
+        assert!(rollouts.is_empty());
+
+        let promoted = promote_rollout_handler(State(state.clone()), Path("route".into())).await;
+        assert!(matches!(promoted, Err(ApiError::NotFound(_))));
+        let aborted = abort_rollout_handler(State(state), Path("route".into())).await;
+        assert!(matches!(aborted, Err(ApiError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn snapshot_renders_served_listeners_as_envoy_json() {
+        let state = state();
+        let listeners =
+            crate::xds::resources::listeners_from_config(&state.xds_state.config).unwrap();
+        state.xds_state.apply_built_resources(LISTENER_TYPE_URL, listeners);
+        let node = Node { id: "edge/envoy-1".into(), ..Default::default() };
+        state.xds_state.node_registry.connect(&node, false);
+
+        let query = SnapshotQuery {
+            resource_type: Some("listener".into()),
+            node: Some("edge/envoy-1".into()),
+            ..Default::default()
+        };
+
+        let Json(dump) = get_snapshot_handler(State(state.clone()), Query(query)).await.unwrap();
+        let configs = dump["configs"].as_array().unwrap();
+        assert_eq!(configs.len(), 1);
+        let listener = &configs[0]["dynamic_listeners"][0]["active_state"]["listener"];
+        assert_eq!(listener["@type"], "type.googleapis.com/envoy.config.listener.v3.Listener");
+        assert_eq!(
+            listener["filter_chains"][0]["filters"][0]["typed_config"]["@type"],
+            "type.googleapis.com/envoy.extensions.filters.network.http_connection_manager.v3.HttpConnectionManager"
+        );
+
+        let query = SnapshotQuery { node: Some("unknown".into()), ..Default::default() };
+        let missing = get_snapshot_handler(State(state.clone()), Query(query)).await;
+        assert!(matches!(missing, Err(ApiError::NotFound(_))));
+
+        let query = SnapshotQuery { resource_type: Some("runtime".into()), ..Default::default() };
+        let unknown = get_snapshot_handler(State(state), Query(query)).await;
+        assert!(matches!(unknown, Err(ApiError::BadRequest(_))));
+    }
+}
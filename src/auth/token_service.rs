@@ -92,6 +92,12 @@ impl TokenService {
                 "listeners:read".into(),
                 "listeners:write".into(),
                 "gateways:import".into(),
+                "xds:read".into(),
+                "xds:write".into(),
+                "secrets:read".into(),
+                "secrets:write".into(),
+                "runtime:read".into(),
+                "runtime:write".into(),
             ],
             created_by: Some("system".into()),
         };
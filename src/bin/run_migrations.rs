@@ -19,7 +19,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         ..Default::default()
     };
 
-    let pool = create_pool(&db_config).await?;
+    let Some(pool) = create_pool(&db_config).await?.as_sqlite().cloned() else {
+        return Err("run_migrations only supports SQLite databases".into());
+    };
     info!("✅ Connected to database: {}", db_config.url);
 
     // Read and execute migration files in order
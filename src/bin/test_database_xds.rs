@@ -8,7 +8,7 @@
 
 use flowplane::{
     config::{DatabaseConfig, SimpleXdsConfig, XdsResourceConfig},
-    storage::{create_pool, ClusterRepository, CreateClusterRequest},
+    storage::{create_pool, ChangeNotifier, ClusterRepository, CreateClusterRequest},
     xds::start_database_xds_server_with_config,
 };
 use tokio::time::{timeout, Duration};
@@ -36,7 +36,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Test 2: Repository Operations
     info!("📋 Test 2: Testing repository operations...");
-    let repo = ClusterRepository::new(pool.clone());
+    let repo = ClusterRepository::new(pool.clone(), ChangeNotifier::new());
 
     // Test creating a cluster with unique name
     let cluster_name = format!("test_cluster_db_{}", Uuid::new_v4().simple());
@@ -71,6 +71,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             listener_port: 10001,
         },
         tls: None,
+        change_poll_interval_ms: None,
+        update_debounce_ms: None,
+        nack_policy: Default::default(),
+        canary: None,
+        secret_cipher: None,
+        ca: None,
+        coordination: None,
     };
 
     // Test 4: Start Database-Enabled XDS Server (with timeout)
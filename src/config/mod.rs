@@ -3,6 +3,7 @@
 //! This module provides configuration management for the Flowplane control plane,
 //! integrating both simple and comprehensive configuration approaches.
 
+use crate::storage::SecretCipher;
 use crate::Result;
 
 pub mod tls;
@@ -28,6 +29,130 @@ pub struct SimpleXdsConfig {
     pub port: u16,
     pub resources: XdsResourceConfig,
     pub tls: Option<XdsTlsConfig>,
+    /// Interval for the fallback poller that picks up writes made outside the
+    /// control plane. Disabled when `None`.
+    pub change_poll_interval_ms: Option<u64>,
+    /// Window during which cache updates are coalesced into a single push.
+    /// Updates are pushed as soon as they are built when `None`.
+    pub update_debounce_ms: Option<u64>,
+    /// What to do when an Envoy node rejects a pushed configuration.
+    pub nack_policy: NackPolicy,
+    /// Staged rollout of new snapshots to canary nodes. Disabled when `None`.
+    pub canary: Option<CanaryConfig>,
+    /// Cipher for private keys of SDS secrets. Secrets cannot be stored or
+    /// served when `None`.
+    pub secret_cipher: Option<SecretCipher>,
+    /// Built-in certificate authority issuing SPIFFE certificates over SDS.
+    /// Disabled when `None`; requires `secret_cipher`.
+    pub ca: Option<CaConfig>,
+    /// Lease-based coordination between replicas sharing one database.
+    /// Every process acts as leader when `None`.
+    pub coordination: Option<CoordinationConfig>,
+}
+
+/// Settings for running several control plane replicas against one database.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoordinationConfig {
+    /// Identity written to the leader lease, unique per replica.
+    pub replica_id: String,
+    /// How long a leader lease stays valid without renewal. Leaders renew
+    /// every third of the TTL; replica clocks must agree within it.
+    pub lease_ttl_secs: u64,
+}
+
+/// Default lifetime of the leader lease.
+pub const DEFAULT_LEASE_TTL_SECS: u64 = 15;
+
+/// Settings for the built-in certificate authority.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaConfig {
+    /// SPIFFE trust domain, e.g. `flowplane.local`.
+    pub trust_domain: String,
+    /// Lifetime of issued leaf certificates. Leaves are re-issued once less
+    /// than a third of their lifetime remains.
+    pub leaf_ttl_secs: u64,
+}
+
+/// Default lifetime of certificates issued by the built-in CA.
+pub const DEFAULT_CA_LEAF_TTL_SECS: u64 = 24 * 60 * 60;
+
+/// Whether `trust_domain` is a valid SPIFFE trust domain name.
+fn is_valid_trust_domain(trust_domain: &str) -> bool {
+    !trust_domain.is_empty()
+        && trust_domain.len() <= 255
+        && trust_domain
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '.' | '-' | '_'))
+}
+
+/// Canary rollout settings for the xDS server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CanaryConfig {
+    /// Node metadata selector identifying canary nodes.
+    pub selector: NodeSelector,
+    /// How long a new snapshot must run on canaries without a NACK before it
+    /// is promoted to the rest of the fleet.
+    pub bake_time_secs: u64,
+}
+
+/// Default bake time for canary rollouts.
+pub const DEFAULT_CANARY_BAKE_SECS: u64 = 300;
+
+/// `key=value` match against an Envoy node's metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeSelector {
+    pub key: String,
+    pub value: String,
+}
+
+impl std::str::FromStr for NodeSelector {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value.split_once('=') {
+            Some((key, expected)) if !key.trim().is_empty() => {
+                Ok(NodeSelector { key: key.trim().to_string(), value: expected.trim().to_string() })
+            }
+            _ => Err(format!("Invalid node selector '{}': expected key=value", value)),
+        }
+    }
+}
+
+/// Reaction to an Envoy NACK, beyond logging and auditing it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NackPolicy {
+    /// Keep serving the current configuration.
+    #[default]
+    Log,
+    /// Serve the rejecting node the last-known-good snapshot until a newer
+    /// version is published.
+    Pin,
+    /// Restore the rejected resources in the repository from the
+    /// last-known-good snapshot.
+    Revert,
+}
+
+impl std::str::FromStr for NackPolicy {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "log" => Ok(NackPolicy::Log),
+            "pin" => Ok(NackPolicy::Pin),
+            "revert" => Ok(NackPolicy::Revert),
+            other => Err(format!("unknown NACK policy '{}' (expected log, pin or revert)", other)),
+        }
+    }
+}
+
+impl NackPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NackPolicy::Log => "log",
+            NackPolicy::Pin => "pin",
+            NackPolicy::Revert => "revert",
+        }
+    }
 }
 
 /// TLS configuration for the xDS server
@@ -78,6 +203,13 @@ impl Default for SimpleXdsConfig {
             port: 18000,
             resources: XdsResourceConfig::default(),
             tls: None,
+            change_poll_interval_ms: None,
+            update_debounce_ms: None,
+            nack_policy: NackPolicy::default(),
+            canary: None,
+            secret_cipher: None,
+            ca: None,
+            coordination: None,
         }
     }
 }
@@ -139,6 +271,135 @@ impl Config {
             return Err(crate::Error::config("Listener port cannot be 0".to_string()));
         }
 
+        let change_poll_interval_ms = match std::env::var("FLOWPLANE_XDS_CHANGE_POLL_INTERVAL_MS") {
+            Ok(value) if !value.trim().is_empty() => {
+                let interval: u64 = value.trim().parse().map_err(|e| {
+                    crate::Error::config(format!("Invalid change poll interval '{}': {}", value, e))
+                })?;
+                if interval == 0 {
+                    return Err(crate::Error::config(
+                        "Change poll interval cannot be 0".to_string(),
+                    ));
+                }
+                Some(interval)
+            }
+            _ => None,
+        };
+
+        let update_debounce_ms = match std::env::var("FLOWPLANE_XDS_UPDATE_DEBOUNCE_MS") {
+            Ok(value) if !value.trim().is_empty() => {
+                let window: u64 = value.trim().parse().map_err(|e| {
+                    crate::Error::config(format!(
+                        "Invalid update debounce window '{}': {}",
+                        value, e
+                    ))
+                })?;
+                // A window of 0 pushes every update as soon as it is built.
+                (window > 0).then_some(window)
+            }
+            _ => None,
+        };
+
+        let nack_policy = match std::env::var("FLOWPLANE_XDS_NACK_POLICY") {
+            Ok(value) if !value.trim().is_empty() => {
+                value.parse::<NackPolicy>().map_err(crate::Error::config)?
+            }
+            _ => NackPolicy::default(),
+        };
+
+        let canary = match std::env::var("FLOWPLANE_XDS_CANARY_SELECTOR") {
+            Ok(value) if !value.trim().is_empty() => {
+                let selector = value.parse::<NodeSelector>().map_err(crate::Error::config)?;
+                let bake_time_secs = match std::env::var("FLOWPLANE_XDS_CANARY_BAKE_SECONDS") {
+                    Ok(value) if !value.trim().is_empty() => value.trim().parse().map_err(|e| {
+                        crate::Error::config(format!("Invalid canary bake time '{}': {}", value, e))
+                    })?,
+                    _ => DEFAULT_CANARY_BAKE_SECS,
+                };
+                Some(CanaryConfig { selector, bake_time_secs })
+            }
+            _ => None,
+        };
+
+        let secret_cipher = match std::env::var("FLOWPLANE_SECRET_ENCRYPTION_KEY") {
+            Ok(value) if !value.trim().is_empty() => Some(SecretCipher::from_base64(&value)?),
+            _ => None,
+        };
+
+        let ca = match std::env::var("FLOWPLANE_CA_TRUST_DOMAIN") {
+            Ok(value) if !value.trim().is_empty() => {
+                let trust_domain = value.trim().to_string();
+                if !is_valid_trust_domain(&trust_domain) {
+                    return Err(crate::Error::config(format!(
+                        "Invalid CA trust domain '{}': use lowercase letters, digits, '.', '-' and '_'",
+                        trust_domain
+                    )));
+                }
+                if secret_cipher.is_none() {
+                    return Err(crate::Error::config(
+                        "FLOWPLANE_CA_TRUST_DOMAIN requires FLOWPLANE_SECRET_ENCRYPTION_KEY"
+                            .to_string(),
+                    ));
+                }
+                let leaf_ttl_secs = match std::env::var("FLOWPLANE_CA_LEAF_TTL_HOURS") {
+                    Ok(value) if !value.trim().is_empty() => {
+                        let hours: u64 = value.trim().parse().map_err(|e| {
+                            crate::Error::config(format!(
+                                "Invalid CA leaf certificate TTL '{}': {}",
+                                value, e
+                            ))
+                        })?;
+                        if hours == 0 {
+                            return Err(crate::Error::config(
+                                "CA leaf certificate TTL cannot be 0".to_string(),
+                            ));
+                        }
+                        hours.saturating_mul(60 * 60)
+                    }
+                    _ => DEFAULT_CA_LEAF_TTL_SECS,
+                };
+                Some(CaConfig { trust_domain, leaf_ttl_secs })
+            }
+            _ => None,
+        };
+
+        let coordination = match std::env::var("FLOWPLANE_HA_ENABLED") {
+            Ok(value) if !value.trim().is_empty() => {
+                let enabled: bool = value.trim().parse().map_err(|_| {
+                    crate::Error::config("FLOWPLANE_HA_ENABLED must be a boolean value".to_string())
+                })?;
+                if enabled {
+                    let replica_id = std::env::var("FLOWPLANE_HA_REPLICA_ID")
+                        .or_else(|_| std::env::var("HOSTNAME"))
+                        .ok()
+                        .map(|id| id.trim().to_string())
+                        .filter(|id| !id.is_empty())
+                        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+                    let lease_ttl_secs = match std::env::var("FLOWPLANE_HA_LEASE_TTL_SECONDS") {
+                        Ok(value) if !value.trim().is_empty() => {
+                            let ttl: u64 = value.trim().parse().map_err(|e| {
+                                crate::Error::config(format!(
+                                    "Invalid leader lease TTL '{}': {}",
+                                    value, e
+                                ))
+                            })?;
+                            if ttl == 0 {
+                                return Err(crate::Error::config(
+                                    "Leader lease TTL cannot be 0".to_string(),
+                                ));
+                            }
+                            ttl
+                        }
+                        _ => DEFAULT_LEASE_TTL_SECS,
+                    };
+                    Some(CoordinationConfig { replica_id, lease_ttl_secs })
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+
         // API server configuration
         let api_port_str =
             std::env::var("FLOWPLANE_API_PORT").unwrap_or_else(|_| "8080".to_string());
@@ -166,6 +427,13 @@ impl Config {
                     listener_port,
                 },
                 tls: load_xds_tls_config_from_env()?,
+                change_poll_interval_ms,
+                update_debounce_ms,
+                nack_policy,
+                canary,
+                secret_cipher,
+                ca,
+                coordination,
             },
             api: ApiServerConfig {
                 bind_address: api_bind_address,
@@ -309,11 +577,13 @@ mod tests {
         env::remove_var("FLOWPLANE_API_TLS_CERT_PATH");
         env::remove_var("FLOWPLANE_API_TLS_KEY_PATH");
         env::remove_var("FLOWPLANE_API_TLS_CHAIN_PATH");
+        env::remove_var("FLOWPLANE_XDS_CHANGE_POLL_INTERVAL_MS");
 
         let config = Config::from_env().unwrap();
         assert_eq!(config.xds.port, 18000);
         assert_eq!(config.xds.bind_address, "0.0.0.0");
         assert!(config.xds.tls.is_none());
+        assert!(config.xds.change_poll_interval_ms.is_none());
         assert!(config.api.tls.is_none());
 
         // Restore original environment
@@ -389,4 +659,150 @@ mod tests {
         env::remove_var("FLOWPLANE_API_TLS_KEY_PATH");
         env::remove_var("FLOWPLANE_API_TLS_CHAIN_PATH");
     }
+
+    #[test]
+    fn test_change_poll_interval_from_env() {
+        let _guard = ENV_MUTEX.lock().unwrap();
+
+        env::set_var("FLOWPLANE_XDS_CHANGE_POLL_INTERVAL_MS", "2000");
+        let config = Config::from_env().unwrap();
+        assert_eq!(config.xds.change_poll_interval_ms, Some(2000));
+
+        env::set_var("FLOWPLANE_XDS_CHANGE_POLL_INTERVAL_MS", "0");
+        assert!(Config::from_env().is_err());
+
+        env::remove_var("FLOWPLANE_XDS_CHANGE_POLL_INTERVAL_MS");
+    }
+
+    #[test]
+    fn test_update_debounce_from_env() {
+        let _guard = ENV_MUTEX.lock().unwrap();
+
+        env::set_var("FLOWPLANE_XDS_UPDATE_DEBOUNCE_MS", "250");
+        assert_eq!(Config::from_env().unwrap().xds.update_debounce_ms, Some(250));
+
+        env::set_var("FLOWPLANE_XDS_UPDATE_DEBOUNCE_MS", "0");
+        assert_eq!(Config::from_env().unwrap().xds.update_debounce_ms, None);
+
+        env::set_var("FLOWPLANE_XDS_UPDATE_DEBOUNCE_MS", "soon");
+        assert!(Config::from_env().is_err());
+
+        env::remove_var("FLOWPLANE_XDS_UPDATE_DEBOUNCE_MS");
+    }
+
+    #[test]
+    fn test_coordination_from_env() {
+        let _guard = ENV_MUTEX.lock().unwrap();
+
+        env::remove_var("FLOWPLANE_HA_ENABLED");
+        assert_eq!(Config::from_env().unwrap().xds.coordination, None);
+
+        env::set_var("FLOWPLANE_HA_ENABLED", "true");
+        env::set_var("FLOWPLANE_HA_REPLICA_ID", "replica-a");
+        env::set_var("FLOWPLANE_HA_LEASE_TTL_SECONDS", "30");
+        assert_eq!(
+            Config::from_env().unwrap().xds.coordination,
+            Some(CoordinationConfig { replica_id: "replica-a".to_string(), lease_ttl_secs: 30 })
+        );
+
+        env::set_var("FLOWPLANE_HA_LEASE_TTL_SECONDS", "0");
+        assert!(Config::from_env().is_err());
+
+        env::set_var("FLOWPLANE_HA_ENABLED", "sometimes");
+        assert!(Config::from_env().is_err());
+
+        env::remove_var("FLOWPLANE_HA_ENABLED");
+        env::remove_var("FLOWPLANE_HA_REPLICA_ID");
+        env::remove_var("FLOWPLANE_HA_LEASE_TTL_SECONDS");
+    }
+
+    #[test]
+    fn test_nack_policy_from_env() {
+        let _guard = ENV_MUTEX.lock().unwrap();
+
+        env::remove_var("FLOWPLANE_XDS_NACK_POLICY");
+        assert_eq!(Config::from_env().unwrap().xds.nack_policy, NackPolicy::Log);
+
+        env::set_var("FLOWPLANE_XDS_NACK_POLICY", "Revert");
+        assert_eq!(Config::from_env().unwrap().xds.nack_policy, NackPolicy::Revert);
+
+        env::set_var("FLOWPLANE_XDS_NACK_POLICY", "rollback");
+        assert!(Config::from_env().is_err());
+
+        env::remove_var("FLOWPLANE_XDS_NACK_POLICY");
+    }
+
+    #[test]
+    fn test_canary_from_env() {
+        let _guard = ENV_MUTEX.lock().unwrap();
+
+        env::remove_var("FLOWPLANE_XDS_CANARY_SELECTOR");
+        env::remove_var("FLOWPLANE_XDS_CANARY_BAKE_SECONDS");
+        assert!(Config::from_env().unwrap().xds.canary.is_none());
+
+        env::set_var("FLOWPLANE_XDS_CANARY_SELECTOR", "canary=true");
+        let canary = Config::from_env().unwrap().xds.canary.expect("canary enabled");
+        assert_eq!(canary.selector, NodeSelector { key: "canary".into(), value: "true".into() });
+        assert_eq!(canary.bake_time_secs, DEFAULT_CANARY_BAKE_SECS);
+
+        env::set_var("FLOWPLANE_XDS_CANARY_BAKE_SECONDS", "60");
+        assert_eq!(Config::from_env().unwrap().xds.canary.unwrap().bake_time_secs, 60);
+
+        env::set_var("FLOWPLANE_XDS_CANARY_SELECTOR", "canary");
+        assert!(Config::from_env().is_err());
+
+        env::remove_var("FLOWPLANE_XDS_CANARY_SELECTOR");
+        env::remove_var("FLOWPLANE_XDS_CANARY_BAKE_SECONDS");
+    }
+
+    #[test]
+    fn test_secret_cipher_from_env() {
+        let _guard = ENV_MUTEX.lock().unwrap();
+
+        env::remove_var("FLOWPLANE_SECRET_ENCRYPTION_KEY");
+        assert!(Config::from_env().unwrap().xds.secret_cipher.is_none());
+
+        // 32 bytes of 0x01, base64 encoded
+        env::set_var(
+            "FLOWPLANE_SECRET_ENCRYPTION_KEY",
+            "AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE=",
+        );
+        assert!(Config::from_env().unwrap().xds.secret_cipher.is_some());
+
+        env::set_var("FLOWPLANE_SECRET_ENCRYPTION_KEY", "dG9vLXNob3J0");
+        assert!(Config::from_env().is_err());
+
+        env::remove_var("FLOWPLANE_SECRET_ENCRYPTION_KEY");
+    }
+
+    #[test]
+    fn test_ca_from_env() {
+        let _guard = ENV_MUTEX.lock().unwrap();
+
+        env::remove_var("FLOWPLANE_CA_TRUST_DOMAIN");
+        env::remove_var("FLOWPLANE_CA_LEAF_TTL_HOURS");
+        env::remove_var("FLOWPLANE_SECRET_ENCRYPTION_KEY");
+        assert!(Config::from_env().unwrap().xds.ca.is_none());
+
+        env::set_var("FLOWPLANE_CA_TRUST_DOMAIN", "flowplane.local");
+        assert!(Config::from_env().is_err(), "CA requires a secret encryption key");
+
+        env::set_var(
+            "FLOWPLANE_SECRET_ENCRYPTION_KEY",
+            "AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE=",
+        );
+        let ca = Config::from_env().unwrap().xds.ca.expect("CA enabled");
+        assert_eq!(ca.trust_domain, "flowplane.local");
+        assert_eq!(ca.leaf_ttl_secs, DEFAULT_CA_LEAF_TTL_SECS);
+
+        env::set_var("FLOWPLANE_CA_LEAF_TTL_HOURS", "6");
+        assert_eq!(Config::from_env().unwrap().xds.ca.unwrap().leaf_ttl_secs, 6 * 60 * 60);
+
+        env::set_var("FLOWPLANE_CA_TRUST_DOMAIN", "Flowplane/Local");
+        assert!(Config::from_env().is_err());
+
+        env::remove_var("FLOWPLANE_CA_TRUST_DOMAIN");
+        env::remove_var("FLOWPLANE_CA_LEAF_TTL_HOURS");
+        env::remove_var("FLOWPLANE_SECRET_ENCRYPTION_KEY");
+    }
 }
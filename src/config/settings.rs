@@ -63,11 +63,9 @@ impl AppConfig {
         }
 
         // Validate database URL format
-        if !self.database.url.starts_with("postgresql://")
-            && !self.database.url.starts_with("sqlite://")
-        {
+        if !self.database.is_postgresql() && !self.database.is_sqlite() {
             return Err(FlowplaneError::validation(
-                "Database URL must start with 'postgresql://' or 'sqlite://'",
+                "Database URL must start with 'postgresql://', 'postgres://' or 'sqlite://'",
             ));
         }
 
@@ -198,7 +196,7 @@ impl DatabaseConfig {
 
     /// Check if this is a PostgreSQL configuration
     pub fn is_postgresql(&self) -> bool {
-        self.url.starts_with("postgresql://")
+        self.url.starts_with("postgresql://") || self.url.starts_with("postgres://")
     }
 
     /// Create DatabaseConfig from environment variables
@@ -0,0 +1,235 @@
+//! # Replica Coordination
+//!
+//! Several control plane replicas can share one database. Each replica
+//! serves xDS from its own caches. Snapshot versions are claimed in
+//! `configuration_versions`, so every replica reports the same version for
+//! the same content. Background jobs that write to the database run only on
+//! the replica holding the leader lease in `control_plane_leases`: default
+//! gateway seeding, token cleanup, canary promotion and certificate renewal.
+//!
+//! Without a [`CoordinationConfig`] the process is its own leader.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::watch;
+use tracing::{info, warn};
+
+use crate::auth::cleanup_service::CleanupService;
+use crate::config::CoordinationConfig;
+use crate::errors::Result;
+use crate::openapi::defaults::ensure_default_gateway_resources;
+use crate::storage::{AuditLogRepository, DbPool, LeaseRepository};
+use crate::xds::XdsState;
+
+/// Name of the lease row owned by the leader.
+pub const LEADER_LEASE: &str = "leader";
+
+const TOKEN_CLEANUP_INTERVAL: Duration = Duration::from_secs(300);
+
+#[derive(Debug, Clone)]
+struct Lease {
+    repository: LeaseRepository,
+    replica_id: String,
+    ttl: Duration,
+}
+
+/// Leadership of this replica, backed by the leader lease when coordination
+/// is enabled.
+#[derive(Debug, Clone)]
+pub struct LeaderElection {
+    lease: Option<Lease>,
+    leader: Arc<watch::Sender<bool>>,
+}
+
+impl LeaderElection {
+    /// A replica running alone, which always leads.
+    pub fn standalone() -> Self {
+        Self { lease: None, leader: Arc::new(watch::Sender::new(true)) }
+    }
+
+    /// A replica competing for the leader lease in the shared database. It
+    /// follows until [`LeaderElection::try_acquire`] succeeds.
+    pub fn with_lease(pool: DbPool, config: &CoordinationConfig) -> Self {
+        Self {
+            lease: Some(Lease {
+                repository: LeaseRepository::new(pool),
+                replica_id: config.replica_id.clone(),
+                ttl: Duration::from_secs(config.lease_ttl_secs),
+            }),
+            leader: Arc::new(watch::Sender::new(false)),
+        }
+    }
+
+    pub fn is_coordinated(&self) -> bool {
+        self.lease.is_some()
+    }
+
+    pub fn is_leader(&self) -> bool {
+        *self.leader.borrow()
+    }
+
+    pub fn replica_id(&self) -> Option<&str> {
+        self.lease.as_ref().map(|lease| lease.replica_id.as_str())
+    }
+
+    /// Receiver notified whenever this replica gains or loses leadership.
+    pub fn subscribe(&self) -> watch::Receiver<bool> {
+        self.leader.subscribe()
+    }
+
+    /// Take or renew the leader lease. A replica that cannot reach the
+    /// database steps down, since another replica may take the lease over
+    /// once it expires.
+    pub async fn try_acquire(&self) -> bool {
+        let Some(lease) = &self.lease else {
+            return true;
+        };
+
+        let leading =
+            match lease.repository.try_acquire(LEADER_LEASE, &lease.replica_id, lease.ttl).await {
+                Ok(leading) => leading,
+                Err(error) => {
+                    warn!(%error, replica_id = %lease.replica_id, "Failed to renew leader lease");
+                    false
+                }
+            };
+
+        self.leader.send_if_modified(|current| {
+            if *current == leading {
+                return false;
+            }
+            *current = leading;
+            if leading {
+                info!(replica_id = %lease.replica_id, "Acquired leader lease");
+            } else {
+                warn!(replica_id = %lease.replica_id, "Lost leader lease");
+            }
+            true
+        });
+        leading
+    }
+
+    /// Release the lease on shutdown so another replica can lead without
+    /// waiting for it to expire.
+    pub async fn release(&self) {
+        let Some(lease) = &self.lease else {
+            return;
+        };
+        if !self.is_leader() {
+            return;
+        }
+        self.leader.send_replace(false);
+        if let Err(error) = lease.repository.release(LEADER_LEASE, &lease.replica_id).await {
+            warn!(%error, "Failed to release leader lease");
+        }
+    }
+
+    /// Renew the lease every third of its TTL.
+    fn spawn_renewal(&self) {
+        let Some(lease) = &self.lease else {
+            return;
+        };
+        let election = self.clone();
+        let period = (lease.ttl / 3).max(Duration::from_secs(1));
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(period);
+            loop {
+                ticker.tick().await;
+                election.try_acquire().await;
+            }
+        });
+    }
+}
+
+/// Run the first election, seed the default gateway if this replica leads,
+/// and start the jobs owned by the leader. Seeding failures are returned
+/// only from the initial attempt; a replica taking over later logs them.
+pub async fn start(state: Arc<XdsState>) -> Result<()> {
+    let election = state.leader.clone();
+    if election.try_acquire().await {
+        ensure_default_gateway_resources(&state).await?;
+    }
+    election.spawn_renewal();
+
+    let Some(repository) = &state.cluster_repository else {
+        return Ok(());
+    };
+    let pool = repository.pool().clone();
+    let cleanup = CleanupService::with_sqlx(pool.clone(), Arc::new(AuditLogRepository::new(pool)));
+
+    let mut leadership = election.subscribe();
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(TOKEN_CLEANUP_INTERVAL);
+        loop {
+            tokio::select! {
+                changed = leadership.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                    if *leadership.borrow_and_update() {
+                        if let Err(error) = ensure_default_gateway_resources(&state).await {
+                            warn!(%error, "Failed to seed default gateway resources");
+                        }
+                    }
+                }
+                _ = ticker.tick() => {
+                    if election.is_leader() {
+                        if let Err(error) = cleanup.run_once().await {
+                            warn!(%error, "Failed to clean up expired personal access tokens");
+                        }
+                    }
+                }
+            }
+        }
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn pool() -> DbPool {
+        let pool: DbPool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap()
+            .into();
+        crate::storage::run_migrations(&pool).await.unwrap();
+        pool
+    }
+
+    fn config(replica_id: &str) -> CoordinationConfig {
+        CoordinationConfig { replica_id: replica_id.to_string(), lease_ttl_secs: 30 }
+    }
+
+    #[tokio::test]
+    async fn one_replica_leads_until_it_releases_the_lease() {
+        let pool = pool().await;
+        let first = LeaderElection::with_lease(pool.clone(), &config("replica-a"));
+        let second = LeaderElection::with_lease(pool, &config("replica-b"));
+        let mut leadership = second.subscribe();
+
+        assert!(!first.is_leader());
+        assert!(first.try_acquire().await);
+        assert!(!second.try_acquire().await);
+        assert!(first.is_leader() && !second.is_leader());
+
+        first.release().await;
+        assert!(!first.is_leader());
+        assert!(second.try_acquire().await);
+        assert!(leadership.has_changed().unwrap());
+        assert!(*leadership.borrow_and_update());
+    }
+
+    #[tokio::test]
+    async fn standalone_replica_always_leads() {
+        let election = LeaderElection::standalone();
+        assert!(election.is_leader());
+        assert!(election.try_acquire().await);
+        assert!(!election.is_coordinated());
+    }
+}
@@ -26,6 +26,7 @@ pub mod api;
 pub mod auth;
 pub mod cli;
 pub mod config;
+pub mod coordination;
 pub mod errors;
 pub mod observability;
 pub mod openapi;
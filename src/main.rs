@@ -3,8 +3,8 @@ use std::sync::Arc;
 use flowplane::{
     api::start_api_server,
     config::{ApiServerConfig, DatabaseConfig, ObservabilityConfig, SimpleXdsConfig},
+    coordination,
     observability::init_observability,
-    openapi::defaults::ensure_default_gateway_resources,
     storage::create_pool,
     xds::{start_database_xds_server_with_state, XdsState},
     Config, Result, APP_NAME, VERSION,
@@ -26,7 +26,7 @@ async fn main() -> Result<()> {
     install_rustls_provider();
 
     let observability_config = ObservabilityConfig::from_env();
-    let _health_checker = init_observability(&observability_config).await?;
+    let health_checker = init_observability(&observability_config).await?;
 
     info!(
         app_name = APP_NAME,
@@ -54,9 +54,18 @@ async fn main() -> Result<()> {
     let simple_xds_config: SimpleXdsConfig = config.xds.clone();
     let api_config: ApiServerConfig = config.api.clone();
 
-    let state = Arc::new(XdsState::with_database(simple_xds_config.clone(), pool));
+    let state = Arc::new(
+        XdsState::with_database(simple_xds_config.clone(), pool)
+            .with_health_checker(health_checker),
+    );
 
-    ensure_default_gateway_resources(&state).await?;
+    // Seeds the default gateway when this replica leads and starts leader-only jobs
+    coordination::start(state.clone()).await?;
+    info!(
+        leader = state.leader.is_leader(),
+        replica_id = state.leader.replica_id().unwrap_or("standalone"),
+        "Replica coordination started"
+    );
 
     let xds_state = state.clone();
     let xds_task = async move {
@@ -72,9 +81,12 @@ async fn main() -> Result<()> {
 
     if let Err(e) = try_join!(xds_task, api_task) {
         error!("Control plane services terminated with error: {}", e);
+        state.leader.release().await;
         std::process::exit(1);
     }
 
+    state.leader.release().await;
+
     info!("Control plane shutdown completed");
     Ok(())
 }
@@ -3,6 +3,7 @@
 //! Provides health checking capabilities for the control plane components.
 
 use crate::errors::Result;
+use crate::storage::pool::with_pool;
 use async_trait::async_trait;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -211,6 +212,14 @@ impl HealthChecker {
     }
 }
 
+impl std::fmt::Debug for HealthChecker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HealthChecker")
+            .field("instance_id", &self.instance_id)
+            .finish_non_exhaustive()
+    }
+}
+
 impl Default for HealthChecker {
     fn default() -> Self {
         Self::new()
@@ -234,7 +243,11 @@ impl HealthProvider for DatabaseHealthProvider {
     async fn health_check(&self) -> Result<HealthCheck> {
         let start = std::time::Instant::now();
 
-        match sqlx::query("SELECT 1").fetch_one(&self.db_pool).await {
+        match with_pool!(&self.db_pool, |pool| sqlx::query("SELECT 1")
+            .execute(pool)
+            .await
+            .map(|_| ()))
+        {
             Ok(_) => {
                 let duration = start.elapsed();
                 Ok(HealthCheck::healthy("database".to_string())
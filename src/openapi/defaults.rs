@@ -3,8 +3,8 @@ use std::sync::Arc;
 use crate::auth::token_service::TokenService;
 use crate::errors::Error;
 use crate::storage::{
-    repository_simple::AuditLogRepository, CreateClusterRequest, CreateListenerRequest,
-    CreateRouteRepositoryRequest,
+    repository_simple::AuditLogRepository, Change, ChangeKind, CreateClusterRequest,
+    CreateListenerRequest, CreateRouteRepositoryRequest,
 };
 use crate::xds::XdsState;
 use crate::xds::{
@@ -59,9 +59,23 @@ pub async fn ensure_default_gateway_resources(state: &XdsState) -> Result<(), Er
     if !cluster_repo.exists_by_name(DEFAULT_GATEWAY_CLUSTER).await? {
         let cluster_spec = ClusterSpec {
             connect_timeout_seconds: Some(5),
-            endpoints: vec![EndpointSpec::Address { host: "127.0.0.1".to_string(), port: 65535 }],
+            endpoints: vec![EndpointSpec::address("127.0.0.1", 65535)],
             use_tls: Some(false),
+            use_eds: None,
             tls_server_name: None,
+            client_certificate_secret: None,
+            validation_secret: None,
+            trusted_ca_file: None,
+            subject_alt_names: Vec::new(),
+            client_certificate_file: None,
+            client_private_key_file: None,
+            tls_min_version: None,
+            tls_max_version: None,
+            alpn_protocols: Vec::new(),
+            protocol: None,
+            http2: None,
+            idle_timeout_seconds: None,
+            max_requests_per_connection: None,
             dns_lookup_family: None,
             lb_policy: None,
             least_request: None,
@@ -70,6 +84,8 @@ pub async fn ensure_default_gateway_resources(state: &XdsState) -> Result<(), Er
             circuit_breakers: None,
             health_checks: Vec::new(),
             outlier_detection: None,
+            zone_aware_routing: None,
+            locality_weighted_lb: None,
         };
 
         let cluster_config = cluster_spec.to_value()?;
@@ -194,9 +210,13 @@ pub async fn ensure_default_gateway_resources(state: &XdsState) -> Result<(), Er
         info!("Created default gateway listener");
     }
 
-    state.refresh_clusters_from_repository().await?;
-    state.refresh_routes_from_repository().await?;
-    state.refresh_listeners_from_repository().await?;
+    state
+        .refresh_changes(&[
+            Change::row(ChangeKind::Clusters, DEFAULT_GATEWAY_CLUSTER),
+            Change::row(ChangeKind::Routes, DEFAULT_GATEWAY_ROUTES),
+            Change::row(ChangeKind::Listeners, DEFAULT_GATEWAY_LISTENER),
+        ])
+        .await?;
 
     Ok(())
 }
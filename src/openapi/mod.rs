@@ -278,14 +278,30 @@ fn cluster_from_server(
     })?;
 
     let use_tls = matches!(url.scheme(), "https" | "grpcs");
+    let protocol =
+        matches!(url.scheme(), "grpc" | "grpcs").then_some(crate::xds::UpstreamProtocol::Http2);
 
     let cluster_name = sanitize_name(&format!("{}-{}", prefix, host));
 
     let spec = crate::xds::ClusterSpec {
         connect_timeout_seconds: Some(5),
-        endpoints: vec![crate::xds::EndpointSpec::Address { host: host.to_string(), port }],
+        endpoints: vec![crate::xds::EndpointSpec::address(host, port)],
         use_tls: Some(use_tls),
+        use_eds: None,
         tls_server_name: if use_tls { Some(host.to_string()) } else { None },
+        client_certificate_secret: None,
+        validation_secret: None,
+        trusted_ca_file: None,
+        subject_alt_names: Vec::new(),
+        client_certificate_file: None,
+        client_private_key_file: None,
+        tls_min_version: None,
+        tls_max_version: None,
+        alpn_protocols: Vec::new(),
+        protocol,
+        http2: None,
+        idle_timeout_seconds: None,
+        max_requests_per_connection: None,
         dns_lookup_family: None,
         lb_policy: None,
         least_request: None,
@@ -294,6 +310,8 @@ fn cluster_from_server(
         circuit_breakers: None,
         health_checks: Vec::new(),
         outlier_detection: None,
+        zone_aware_routing: None,
+        locality_weighted_lb: None,
     };
 
     let mut configuration =
@@ -1,157 +1,234 @@
-use std::fs;
-use std::path::PathBuf;
+//! Envoy bootstrap generation.
+//!
+//! Bootstraps are dynamic: Envoy fetches listeners, clusters and runtime over
+//! ADS from the xDS server, and the node metadata selects which listeners it
+//! receives (see `scope_from_discovery` in the database ADS service). Nothing
+//! is written to disk; the API serves the bootstrap on request.
 
-use crate::errors::{Error, Result};
-use crate::openapi::defaults::DEFAULT_GATEWAY_PORT;
-use crate::storage::{
-    ApiDefinitionData, ApiDefinitionRepository, ApiRouteData, UpdateBootstrapMetadataRequest,
-};
+use serde_json::{json, Value};
 
-const BOOTSTRAP_DIR: &str = "data/bootstrap";
+use crate::config::SimpleXdsConfig;
+use crate::errors::Result;
+use crate::storage::{ApiDefinitionData, ApiDefinitionRepository, UpdateBootstrapMetadataRequest};
+use crate::xds::runtime::RUNTIME_LAYER_NAME;
 
-/// Compute a bootstrap artifact URI for the given API definition identifier.
-pub fn compute_bootstrap_uri(definition_id: &str) -> String {
-    format!("/bootstrap/api-definitions/{}.yaml", definition_id)
-}
+/// Name of the static cluster pointing at the xDS server.
+pub const XDS_CLUSTER_NAME: &str = "xds_cluster";
+
+const DEFAULT_ADMIN_ADDRESS: &str = "127.0.0.1";
+const DEFAULT_ADMIN_PORT: u16 = 9901;
 
-/// Materialise a bootstrap configuration on disk and return the relative URI.
-fn write_bootstrap_file(definition: &ApiDefinitionData, routes: &[ApiRouteData]) -> Result<String> {
-    fs::create_dir_all(BOOTSTRAP_DIR).map_err(Error::from)?;
-    let filename = format!("{}.yaml", definition.id);
-    let path = PathBuf::from(BOOTSTRAP_DIR).join(&filename);
-    let contents = render_bootstrap_yaml(definition, routes);
-    fs::write(&path, contents).map_err(Error::from)?;
-    Ok(path.to_string_lossy().to_string())
+/// Certificate files Envoy loads when the xDS server uses TLS. These are
+/// paths on the Envoy host, not on the control plane.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BootstrapTlsFiles {
+    pub ca_path: String,
+    pub cert_path: String,
+    pub key_path: String,
 }
 
-fn render_bootstrap_yaml(definition: &ApiDefinitionData, routes: &[ApiRouteData]) -> String {
-    let listener_name = format!("{}-listener", definition.id);
-    let route_config_name = format!("{}-routes", definition.id);
-
-    let mut routes_yaml = String::new();
-    let mut clusters = Vec::new();
-
-    for route in routes {
-        routes_yaml.push_str("                        - match:\n");
-        match route.match_type.as_str() {
-            "prefix" => {
-                routes_yaml.push_str(&format!(
-                    "                            prefix: \"{}\"\n",
-                    route.match_value
-                ));
-            }
-            "path" => {
-                routes_yaml.push_str(&format!(
-                    "                            path: \"{}\"\n",
-                    route.match_value
-                ));
-            }
-            other => {
-                routes_yaml.push_str(&format!(
-                    "                            safe_regex: \"{}:{}\"\n",
-                    other, route.match_value
-                ));
-            }
+impl Default for BootstrapTlsFiles {
+    fn default() -> Self {
+        Self {
+            ca_path: "/etc/envoy/certs/xds-ca.pem".to_string(),
+            cert_path: "/etc/envoy/certs/xds-client.pem".to_string(),
+            key_path: "/etc/envoy/certs/xds-client.key".to_string(),
         }
+    }
+}
 
-        let (cluster_name, host, port) = extract_primary_target(&route.upstream_targets);
+/// Listeners a node receives, written to the node metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BootstrapScope {
+    /// Every listener.
+    All,
+    /// Listeners owned by `team`, plus the default gateway when
+    /// `include_default` is set.
+    Team { team: String, include_default: bool },
+    /// Only the named listeners.
+    Allowlist { team: Option<String>, listeners: Vec<String> },
+}
 
-        if !clusters.iter().any(|(name, _, _)| name == &cluster_name) {
-            clusters.push((cluster_name.clone(), host.clone(), port));
+impl BootstrapScope {
+    fn team(&self) -> Option<&str> {
+        match self {
+            BootstrapScope::All => None,
+            BootstrapScope::Team { team, .. } => Some(team),
+            BootstrapScope::Allowlist { team, .. } => team.as_deref(),
         }
+    }
 
-        routes_yaml.push_str("                          route:\n");
-        routes_yaml.push_str(&format!("                            cluster: {}\n", cluster_name));
-        if let Some(timeout) = route.timeout_seconds {
-            routes_yaml.push_str(&format!("                            timeout: {}s\n", timeout));
+    fn metadata(&self) -> Value {
+        match self {
+            BootstrapScope::All => json!({}),
+            BootstrapScope::Team { team, include_default } => json!({
+                "team": team,
+                "include_default": include_default,
+            }),
+            BootstrapScope::Allowlist { team, listeners } => {
+                let mut metadata = json!({ "listener_allowlist": listeners });
+                if let Some(team) = team {
+                    metadata["team"] = json!(team);
+                }
+                metadata
+            }
         }
-        if let Some(prefix) = route.rewrite_prefix.as_ref() {
-            routes_yaml
-                .push_str(&format!("                            prefix_rewrite: \"{}\"\n", prefix));
+    }
+}
+
+/// Inputs for [`render_bootstrap`].
+#[derive(Debug, Clone)]
+pub struct BootstrapOptions {
+    pub node_id: String,
+    pub scope: BootstrapScope,
+    /// Address Envoy dials for ADS. Defaults to the xDS bind address, with
+    /// unspecified addresses replaced by loopback.
+    pub xds_address: Option<String>,
+    pub admin_port: u16,
+    pub tls_files: BootstrapTlsFiles,
+}
+
+impl BootstrapOptions {
+    pub fn new(scope: BootstrapScope) -> Self {
+        Self {
+            node_id: default_node_id(scope.team()),
+            scope,
+            xds_address: None,
+            admin_port: DEFAULT_ADMIN_PORT,
+            tls_files: BootstrapTlsFiles::default(),
         }
-        routes_yaml.push('\n');
     }
+}
 
-    let mut yaml = String::new();
-    yaml.push_str("admin:\n");
-    yaml.push_str("  access_log_path: /tmp/envoy_admin.log\n");
-    yaml.push_str("  address:\n");
-    yaml.push_str("    socket_address:\n");
-    yaml.push_str("      address: 127.0.0.1\n");
-    yaml.push_str("      port_value: 9901\n");
-    yaml.push_str("static_resources:\n");
-    yaml.push_str("  listeners:\n");
-    yaml.push_str(&format!("    - name: {}\n", listener_name));
-    yaml.push_str("      address:\n");
-    yaml.push_str("        socket_address:\n");
-    yaml.push_str("          address: 0.0.0.0\n");
-    yaml.push_str(&format!("          port_value: {}\n", DEFAULT_GATEWAY_PORT));
-    yaml.push_str("      filter_chains:\n");
-    yaml.push_str("        - filters:\n");
-    yaml.push_str("            - name: envoy.filters.network.http_connection_manager\n");
-    yaml.push_str("              typed_config:\n");
-    yaml.push_str("                \"@type\": type.googleapis.com/envoy.extensions.filters.network.http_connection_manager.v3.HttpConnectionManager\n");
-    yaml.push_str("                stat_prefix: ingress_http\n");
-    yaml.push_str("                route_config:\n");
-    yaml.push_str(&format!("                  name: {}\n", route_config_name));
-    yaml.push_str("                  virtual_hosts:\n");
-    yaml.push_str(&format!("                    - name: {}\n", definition.domain));
-    yaml.push_str(&format!(
-        "                      domains:\n                        - \"{}\"\n",
-        definition.domain
-    ));
-    yaml.push_str("                      routes:\n");
-    yaml.push_str(&routes_yaml);
-
-    yaml.push_str("  clusters:\n");
-    for (cluster_name, host, port) in clusters {
-        yaml.push_str(&format!("    - name: {}\n", cluster_name));
-        yaml.push_str("      connect_timeout: 5s\n");
-        yaml.push_str("      type: STRICT_DNS\n");
-        yaml.push_str("      load_assignment:\n");
-        yaml.push_str(&format!("        cluster_name: {}\n", cluster_name));
-        yaml.push_str("        endpoints:\n");
-        yaml.push_str("          - lb_endpoints:\n");
-        yaml.push_str("              - endpoint:\n");
-        yaml.push_str("                  address:\n");
-        yaml.push_str("                    socket_address:\n");
-        yaml.push_str(&format!("                      address: {}\n", host));
-        yaml.push_str(&format!("                      port_value: {}\n", port));
+/// Node ID for a new data plane: `team={team}/dp-{uuid}`, or `dp-{uuid}`
+/// without a team.
+pub fn default_node_id(team: Option<&str>) -> String {
+    let suffix = format!("dp-{}", uuid::Uuid::new_v4());
+    match team {
+        Some(team) => format!("team={}/{}", team, suffix),
+        None => suffix,
     }
+}
 
-    yaml
+/// URI of the bootstrap served for an API definition.
+pub fn compute_bootstrap_uri(definition_id: &str) -> String {
+    format!("/api/v1/api-definitions/{}/bootstrap", definition_id)
 }
 
-fn extract_primary_target(targets: &serde_json::Value) -> (String, String, u16) {
-    let name = targets
-        .get("targets")
-        .and_then(|value| value.get(0))
-        .and_then(|value| value.get("name"))
-        .and_then(|value| value.as_str())
-        .unwrap_or("primary-upstream")
-        .to_string();
-
-    let endpoint = targets
-        .get("targets")
-        .and_then(|value| value.get(0))
-        .and_then(|value| value.get("endpoint"))
-        .and_then(|value| value.as_str())
-        .unwrap_or("localhost:8080");
-
-    let mut parts = endpoint.split(':');
-    let host = parts.next().unwrap_or("localhost").to_string();
-    let port = parts.next().and_then(|p| p.parse::<u16>().ok()).unwrap_or(80);
-
-    (name, host, port)
+/// Render an ADS bootstrap connecting Envoy to the xDS server described by
+/// `config`. The xDS cluster uses TLS when `config.tls` is set and presents
+/// a client certificate when the server verifies clients.
+///
+/// When the xDS address is a hostname, Envoy sends it as SNI and requires it
+/// as a DNS subject alternative name of the server certificate. For an IP
+/// address Envoy only checks that the certificate chains to the trusted CA.
+pub fn render_bootstrap(config: &SimpleXdsConfig, options: &BootstrapOptions) -> Value {
+    let xds_address =
+        options.xds_address.clone().unwrap_or_else(|| match config.bind_address.as_str() {
+            "0.0.0.0" | "::" | "[::]" | "" => "127.0.0.1".to_string(),
+            address => address.to_string(),
+        });
+
+    let mut xds_cluster = json!({
+        "name": XDS_CLUSTER_NAME,
+        "type": "STRICT_DNS",
+        "connect_timeout": "1s",
+        "typed_extension_protocol_options": {
+            "envoy.extensions.upstreams.http.v3.HttpProtocolOptions": {
+                "@type": "type.googleapis.com/envoy.extensions.upstreams.http.v3.HttpProtocolOptions",
+                "explicit_http_config": { "http2_protocol_options": {} }
+            }
+        },
+        "load_assignment": {
+            "cluster_name": XDS_CLUSTER_NAME,
+            "endpoints": [{
+                "lb_endpoints": [{
+                    "endpoint": {
+                        "address": {
+                            "socket_address": { "address": &xds_address, "port_value": config.port }
+                        }
+                    }
+                }]
+            }]
+        }
+    });
+
+    if let Some(tls) = &config.tls {
+        let files = &options.tls_files;
+        let mut validation_context = json!({ "trusted_ca": { "filename": files.ca_path } });
+        let server_name = dns_name(&xds_address);
+        if let Some(name) = server_name {
+            validation_context["match_typed_subject_alt_names"] =
+                json!([{ "san_type": "DNS", "matcher": { "exact": name } }]);
+        }
+        let mut common_tls_context = json!({ "validation_context": validation_context });
+        if tls.require_client_cert || tls.client_ca_path.is_some() {
+            common_tls_context["tls_certificates"] = json!([{
+                "certificate_chain": { "filename": files.cert_path },
+                "private_key": { "filename": files.key_path }
+            }]);
+        }
+        let mut upstream_tls_context = json!({
+            "@type": "type.googleapis.com/envoy.extensions.transport_sockets.tls.v3.UpstreamTlsContext",
+            "common_tls_context": common_tls_context
+        });
+        if let Some(name) = server_name {
+            upstream_tls_context["sni"] = json!(name);
+        }
+        xds_cluster["transport_socket"] = json!({
+            "name": "envoy.transport_sockets.tls",
+            "typed_config": upstream_tls_context
+        });
+    }
+
+    json!({
+        "node": {
+            "id": options.node_id,
+            "cluster": options.scope.team().unwrap_or("flowplane"),
+            "metadata": options.scope.metadata()
+        },
+        "admin": {
+            "address": {
+                "socket_address": { "address": DEFAULT_ADMIN_ADDRESS, "port_value": options.admin_port }
+            }
+        },
+        "dynamic_resources": {
+            "ads_config": {
+                "api_type": "GRPC",
+                "transport_api_version": "V3",
+                "grpc_services": [{ "envoy_grpc": { "cluster_name": XDS_CLUSTER_NAME } }]
+            },
+            "cds_config": { "ads": {}, "resource_api_version": "V3" },
+            "lds_config": { "ads": {}, "resource_api_version": "V3" }
+        },
+        "layered_runtime": {
+            "layers": [
+                {
+                    "name": RUNTIME_LAYER_NAME,
+                    "rtds_layer": {
+                        "name": RUNTIME_LAYER_NAME,
+                        "rtds_config": { "ads": {}, "resource_api_version": "V3" }
+                    }
+                },
+                { "name": "admin_layer", "admin_layer": {} }
+            ]
+        },
+        "static_resources": { "clusters": [xds_cluster] }
+    })
+}
+
+/// `address` when it is a hostname rather than an IP literal.
+fn dns_name(address: &str) -> Option<&str> {
+    let unbracketed = address.trim_start_matches('[').trim_end_matches(']');
+    unbracketed.parse::<std::net::IpAddr>().is_err().then_some(address)
 }
 
-/// Persist bootstrap metadata and return the updated API definition alongside the URI.
+/// Bump the definition's bootstrap revision and record its bootstrap URI,
+/// returning the updated definition alongside the URI.
 pub async fn persist_bootstrap_metadata(
     repository: &ApiDefinitionRepository,
     definition: &ApiDefinitionData,
-    routes: &[ApiRouteData],
 ) -> Result<(ApiDefinitionData, String)> {
-    let _file_path = write_bootstrap_file(definition, routes)?;
     let uri = compute_bootstrap_uri(&definition.id);
     let updated = repository
         .update_bootstrap_metadata(UpdateBootstrapMetadataRequest {
@@ -162,3 +239,100 @@ pub async fn persist_bootstrap_metadata(
         .await?;
     Ok((updated, uri))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::XdsTlsConfig;
+
+    #[test]
+    fn team_bootstrap_uses_ads_and_scopes_the_node() {
+        let config = SimpleXdsConfig { port: 18003, ..SimpleXdsConfig::default() };
+        let options = BootstrapOptions::new(BootstrapScope::Team {
+            team: "payments".into(),
+            include_default: true,
+        });
+        let bootstrap = render_bootstrap(&config, &options);
+
+        assert!(bootstrap["node"]["id"].as_str().unwrap().starts_with("team=payments/dp-"));
+        assert_eq!(
+            bootstrap["node"]["metadata"],
+            json!({ "team": "payments", "include_default": true })
+        );
+        assert_eq!(
+            bootstrap["dynamic_resources"]["ads_config"]["grpc_services"][0]["envoy_grpc"]
+                ["cluster_name"],
+            XDS_CLUSTER_NAME
+        );
+        assert_eq!(bootstrap["admin"]["address"]["socket_address"]["port_value"], 9901);
+
+        let cluster = &bootstrap["static_resources"]["clusters"][0];
+        let address = &cluster["load_assignment"]["endpoints"][0]["lb_endpoints"][0]["endpoint"]
+            ["address"]["socket_address"];
+        assert_eq!(address["address"], "127.0.0.1");
+        assert_eq!(address["port_value"], 18003);
+        assert!(cluster.get("transport_socket").is_none());
+    }
+
+    #[test]
+    fn tls_server_adds_transport_socket_with_client_certificate() {
+        let config = SimpleXdsConfig {
+            tls: Some(XdsTlsConfig {
+                cert_path: "/srv/xds.pem".into(),
+                key_path: "/srv/xds.key".into(),
+                client_ca_path: Some("/srv/clients.pem".into()),
+                require_client_cert: true,
+            }),
+            ..SimpleXdsConfig::default()
+        };
+        let mut options = BootstrapOptions::new(BootstrapScope::Allowlist {
+            team: None,
+            listeners: vec!["edge".into()],
+        });
+        options.xds_address = Some("flowplane.internal".into());
+        let bootstrap = render_bootstrap(&config, &options);
+
+        assert_eq!(bootstrap["node"]["metadata"], json!({ "listener_allowlist": ["edge"] }));
+        let cluster = &bootstrap["static_resources"]["clusters"][0];
+        let context = &cluster["transport_socket"]["typed_config"]["common_tls_context"];
+        assert_eq!(
+            context["validation_context"]["trusted_ca"]["filename"],
+            "/etc/envoy/certs/xds-ca.pem"
+        );
+        assert_eq!(
+            context["tls_certificates"][0]["certificate_chain"]["filename"],
+            "/etc/envoy/certs/xds-client.pem"
+        );
+        assert_eq!(
+            cluster["load_assignment"]["endpoints"][0]["lb_endpoints"][0]["endpoint"]["address"]
+                ["socket_address"]["address"],
+            "flowplane.internal"
+        );
+        assert_eq!(cluster["transport_socket"]["typed_config"]["sni"], "flowplane.internal");
+        assert_eq!(
+            context["validation_context"]["match_typed_subject_alt_names"],
+            json!([{ "san_type": "DNS", "matcher": { "exact": "flowplane.internal" } }])
+        );
+    }
+
+    #[test]
+    fn tls_to_an_ip_address_omits_sni_and_san_matcher() {
+        let config = SimpleXdsConfig {
+            tls: Some(XdsTlsConfig {
+                cert_path: "/srv/xds.pem".into(),
+                key_path: "/srv/xds.key".into(),
+                client_ca_path: None,
+                require_client_cert: false,
+            }),
+            ..SimpleXdsConfig::default()
+        };
+        let bootstrap = render_bootstrap(&config, &BootstrapOptions::new(BootstrapScope::All));
+
+        let typed_config =
+            &bootstrap["static_resources"]["clusters"][0]["transport_socket"]["typed_config"];
+        assert!(typed_config.get("sni").is_none());
+        let validation = &typed_config["common_tls_context"]["validation_context"];
+        assert!(validation.get("match_typed_subject_alt_names").is_none());
+        assert!(typed_config["common_tls_context"].get("tls_certificates").is_none());
+    }
+}
@@ -9,7 +9,7 @@ use super::{
 };
 use crate::errors::{Error, Result};
 use crate::storage::{
-    ApiDefinitionData, ApiDefinitionRepository, ApiRouteData, AuditLogRepository,
+    ApiDefinitionData, ApiDefinitionRepository, ApiRouteData, AuditLogRepository, ChangeKind,
     CreateApiDefinitionRequest, CreateApiRouteRequest,
 };
 use crate::xds::XdsState;
@@ -161,7 +161,7 @@ impl PlatformApiMaterializer {
         }
 
         if spec.listener_isolation {
-            if let Err(err) = self
+            match self
                 .materialize_isolated_listener(
                     &definition,
                     &created_routes,
@@ -169,16 +169,19 @@ impl PlatformApiMaterializer {
                 )
                 .await
             {
-                // Compensating delete to avoid partial writes
-                let _ = self.repository.delete_definition(&definition.id).await;
-                return Err(err);
+                Ok(listener_name) => {
+                    self.state.refresh_row(ChangeKind::Listeners, &listener_name).await?;
+                }
+                Err(err) => {
+                    // Compensating delete to avoid partial writes
+                    let _ = self.repository.delete_definition(&definition.id).await;
+                    return Err(err);
+                }
             }
-            self.state.refresh_listeners_from_repository().await?;
         }
 
         let (definition, bootstrap_uri) =
-            bootstrap::persist_bootstrap_metadata(&self.repository, &definition, &created_routes)
-                .await?;
+            bootstrap::persist_bootstrap_metadata(&self.repository, &definition).await?;
 
         audit::record_create_event(
             &self.audit_repo,
@@ -188,10 +191,7 @@ impl PlatformApiMaterializer {
         )
         .await?;
 
-        if spec.listener_isolation {
-            self.state.refresh_listeners_from_repository().await?;
-        }
-        self.refresh_caches().await?;
+        self.refresh_caches(&definition.id).await?;
 
         Ok(CreateDefinitionOutcome { definition, routes: created_routes, bootstrap_uri })
     }
@@ -216,12 +216,8 @@ impl PlatformApiMaterializer {
 
         let route = self.repository.create_route(spec.into_request(definition_id, order)).await?;
 
-        let mut all_routes = existing_routes;
-        all_routes.push(route.clone());
-
         let (definition, bootstrap_uri) =
-            bootstrap::persist_bootstrap_metadata(&self.repository, &definition, &all_routes)
-                .await?;
+            bootstrap::persist_bootstrap_metadata(&self.repository, &definition).await?;
 
         audit::record_route_appended_event(
             &self.audit_repo,
@@ -232,23 +228,23 @@ impl PlatformApiMaterializer {
         )
         .await?;
 
-        self.refresh_caches().await?;
+        self.refresh_caches(&definition.id).await?;
 
         Ok(AppendRouteOutcome { definition, route, bootstrap_uri })
     }
 
-    async fn refresh_caches(&self) -> Result<()> {
-        self.state.refresh_routes_from_repository().await?;
-        self.state.refresh_platform_api_resources().await?;
-        Ok(())
+    /// Re-encode only the resources built from `definition_id`.
+    async fn refresh_caches(&self, definition_id: &str) -> Result<()> {
+        self.state.refresh_row(ChangeKind::ApiDefinitions, definition_id).await
     }
 
+    /// Create the dedicated listener of an isolated definition, returning its name.
     async fn materialize_isolated_listener(
         &self,
         definition: &ApiDefinitionData,
         routes: &[ApiRouteData],
         listener: Option<&ListenerInput>,
-    ) -> Result<()> {
+    ) -> Result<String> {
         use crate::platform_api::filter_overrides::typed_per_filter_config;
         use crate::storage::CreateListenerRequest;
         use crate::xds::listener::ListenerConfig as XListenerConfig;
@@ -347,7 +343,7 @@ impl PlatformApiMaterializer {
         if !listener_repo.exists_by_name(&listener_name).await.unwrap_or(false) {
             let _created_listener = listener_repo
                 .create(CreateListenerRequest {
-                    name: listener_name,
+                    name: listener_name.clone(),
                     address: params.bind_address.clone(),
                     port: Some(params.port as i64),
                     protocol: Some(params.protocol.clone()),
@@ -356,7 +352,7 @@ impl PlatformApiMaterializer {
                 .await?;
         }
 
-        Ok(())
+        Ok(listener_name)
     }
 }
 
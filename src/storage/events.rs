@@ -0,0 +1,206 @@
+//! # Change Events
+//!
+//! In-process change bus that configuration repositories publish to once a
+//! write has committed. Events name the row that changed, so the xDS layer
+//! re-encodes only the affected resources instead of polling the database.
+//!
+//! On PostgreSQL every event is also sent with `pg_notify` so that other
+//! control-plane replicas sharing the database receive it through `LISTEN`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use tokio::sync::broadcast;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::storage::DbPool;
+
+/// PostgreSQL channel used to fan change events out to other replicas.
+pub const CHANGE_NOTIFY_CHANNEL: &str = "flowplane_config_changes";
+
+const CHANGE_BUS_CAPACITY: usize = 256;
+
+/// Configuration table touched by a committed write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChangeKind {
+    Clusters,
+    Routes,
+    Listeners,
+    ApiDefinitions,
+    Secrets,
+    Runtime,
+}
+
+impl ChangeKind {
+    /// Every change kind, used when a full refresh is required.
+    pub const ALL: [ChangeKind; 6] = [
+        ChangeKind::Clusters,
+        ChangeKind::Routes,
+        ChangeKind::Listeners,
+        ChangeKind::ApiDefinitions,
+        ChangeKind::Secrets,
+        ChangeKind::Runtime,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeKind::Clusters => "clusters",
+            ChangeKind::Routes => "routes",
+            ChangeKind::Listeners => "listeners",
+            ChangeKind::ApiDefinitions => "api_definitions",
+            ChangeKind::Secrets => "secrets",
+            ChangeKind::Runtime => "runtime",
+        }
+    }
+}
+
+/// A committed write to a configuration table.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Change {
+    pub kind: ChangeKind,
+    /// Row that changed: the name of a cluster, route, listener, secret or
+    /// runtime key, or the ID of an API definition. `None` when the whole
+    /// table must be reloaded.
+    pub key: Option<String>,
+}
+
+impl Change {
+    /// A write to a single row of `kind`.
+    pub fn row(kind: ChangeKind, key: impl Into<String>) -> Self {
+        Self { kind, key: Some(key.into()) }
+    }
+
+    /// A change requiring every row of `kind` to be reloaded.
+    pub fn table(kind: ChangeKind) -> Self {
+        Self { kind, key: None }
+    }
+
+    /// Reload of every table, used when events may have been missed.
+    pub fn all() -> impl Iterator<Item = Change> {
+        ChangeKind::ALL.into_iter().map(Change::table)
+    }
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ChangeKind {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        match value {
+            "clusters" => Ok(ChangeKind::Clusters),
+            "routes" => Ok(ChangeKind::Routes),
+            "listeners" => Ok(ChangeKind::Listeners),
+            "api_definitions" => Ok(ChangeKind::ApiDefinitions),
+            "secrets" => Ok(ChangeKind::Secrets),
+            "runtime" => Ok(ChangeKind::Runtime),
+            other => Err(format!("unknown change kind '{}'", other)),
+        }
+    }
+}
+
+/// Cloneable handle to the change bus shared by repositories and the xDS layer.
+#[derive(Debug, Clone)]
+pub struct ChangeNotifier {
+    origin: Uuid,
+    tx: broadcast::Sender<Change>,
+}
+
+impl Default for ChangeNotifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ChangeNotifier {
+    pub fn new() -> Self {
+        let (tx, _) = broadcast::channel(CHANGE_BUS_CAPACITY);
+        Self { origin: Uuid::new_v4(), tx }
+    }
+
+    /// Subscribe to change events published after this call.
+    pub fn subscribe(&self) -> broadcast::Receiver<Change> {
+        self.tx.subscribe()
+    }
+
+    /// Deliver an event to local subscribers only.
+    pub fn notify(&self, change: Change) {
+        // No subscribers simply means nothing is serving xDS from this process.
+        let _ = self.tx.send(change);
+    }
+
+    /// Deliver an event locally and, on PostgreSQL, to other replicas.
+    pub async fn publish(&self, pool: &DbPool, change: Change) {
+        self.notify(change.clone());
+
+        if let DbPool::Postgres(pool) = pool {
+            let result = sqlx::query("SELECT pg_notify($1, $2)")
+                .bind(CHANGE_NOTIFY_CHANNEL)
+                .bind(self.encode(&change))
+                .execute(pool)
+                .await;
+            if let Err(error) = result {
+                warn!(%error, kind = %change.kind, "Failed to send change notification to other replicas");
+            }
+        }
+    }
+
+    /// Decode a `NOTIFY` payload, ignoring events this process published itself.
+    /// Payloads without a row key reload the whole table.
+    pub fn decode_remote(&self, payload: &str) -> Option<Change> {
+        let (kind, rest) = payload.split_once(':')?;
+        let (origin, key) = match rest.split_once(':') {
+            Some((origin, key)) => (origin, Some(key.to_string())),
+            None => (rest, None),
+        };
+        if origin == self.origin.to_string() {
+            return None;
+        }
+        Some(Change { kind: kind.parse().ok()?, key })
+    }
+
+    fn encode(&self, change: &Change) -> String {
+        match &change.key {
+            Some(key) => format!("{}:{}:{}", change.kind, self.origin, key),
+            None => format!("{}:{}", change.kind, self.origin),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn notify_reaches_every_subscriber() {
+        let notifier = ChangeNotifier::new();
+        let mut first = notifier.subscribe();
+        let mut second = notifier.clone().subscribe();
+
+        notifier.notify(Change::row(ChangeKind::Routes, "edge"));
+
+        assert_eq!(first.recv().await.unwrap(), Change::row(ChangeKind::Routes, "edge"));
+        assert_eq!(second.recv().await.unwrap(), Change::row(ChangeKind::Routes, "edge"));
+    }
+
+    #[test]
+    fn decode_remote_skips_own_payloads() {
+        let local = ChangeNotifier::new();
+        let remote = ChangeNotifier::new();
+        let change = Change::row(ChangeKind::Clusters, "svc:a");
+
+        assert_eq!(local.decode_remote(&local.encode(&change)), None);
+        assert_eq!(local.decode_remote(&remote.encode(&change)), Some(change));
+        assert_eq!(
+            local.decode_remote(&format!("clusters:{}", remote.origin)),
+            Some(Change::table(ChangeKind::Clusters))
+        );
+        assert_eq!(local.decode_remote("bogus"), None);
+        assert_eq!(local.decode_remote("widgets:other"), None);
+    }
+}
@@ -3,10 +3,14 @@
 //! This module handles database schema evolution using embedded SQL migrations.
 //! Migrations are embedded in the binary for production deployment and executed automatically
 //! on application startup when auto_migrate is enabled.
+//!
+//! SQLite migrations live in `migrations/`; PostgreSQL migrations live in
+//! `migrations/postgres/` and mirror the SQLite versions one-to-one.
 
 use crate::errors::{FlowplaneError, Result};
+use crate::storage::pool::{with_pool, DatabaseBackend, DbPool};
 use serde::{Deserialize, Serialize};
-use sqlx::{Pool, Row, Sqlite};
+use sqlx::Row;
 use tracing::{error, info, warn};
 
 /// Migration information structure
@@ -19,8 +23,16 @@ pub struct MigrationInfo {
     pub checksum: Vec<u8>,
 }
 
-/// Get migrations directory path
-fn get_migrations_dir() -> std::path::PathBuf {
+/// Get migrations directory path for the given backend
+fn get_migrations_dir(backend: DatabaseBackend) -> std::path::PathBuf {
+    let base = get_base_migrations_dir();
+    match backend {
+        DatabaseBackend::Sqlite => base,
+        DatabaseBackend::Postgres => base.join("postgres"),
+    }
+}
+
+fn get_base_migrations_dir() -> std::path::PathBuf {
     // Try to find migrations directory relative to current working directory
     let cwd = std::env::current_dir().unwrap_or_else(|_| std::path::PathBuf::from("."));
     let migrations_dir = cwd.join("migrations");
@@ -38,8 +50,8 @@ fn get_migrations_dir() -> std::path::PathBuf {
 }
 
 /// Load migration files from filesystem
-fn load_migrations() -> Result<Vec<(String, String)>> {
-    let migrations_dir = get_migrations_dir();
+fn load_migrations(backend: DatabaseBackend) -> Result<Vec<(String, String)>> {
+    let migrations_dir = get_migrations_dir(backend);
 
     if !migrations_dir.exists() {
         return Err(FlowplaneError::validation(format!(
@@ -98,14 +110,14 @@ fn load_migrations() -> Result<Vec<(String, String)>> {
 }
 
 /// Run all pending database migrations
-pub async fn run_migrations(pool: &Pool<Sqlite>) -> Result<()> {
-    info!("Starting database migration process");
+pub async fn run_migrations(pool: &DbPool) -> Result<()> {
+    info!(backend = pool.backend().as_str(), "Starting database migration process");
 
     // Create migration tracking table if it doesn't exist
     create_migration_table(pool).await?;
 
     // Load migration files from filesystem
-    let migrations = load_migrations()?;
+    let migrations = load_migrations(pool.backend())?;
 
     // Get applied migrations
     let applied = get_applied_migration_versions(pool).await?;
@@ -122,46 +134,47 @@ pub async fn run_migrations(pool: &Pool<Sqlite>) -> Result<()> {
 
         info!(version = version, "Running migration: {}", filename);
         let start_time = std::time::Instant::now();
+        let checksum = calculate_checksum(sql);
 
-        // Execute migration in a transaction
-        let mut tx = pool.begin().await.map_err(|e| {
-            FlowplaneError::database(e, "Failed to start migration transaction".to_string())
-        })?;
-
-        // Run the migration SQL
-        sqlx::query(sql).execute(&mut *tx).await.map_err(|e| {
-            error!(error = %e, migration = filename, "Migration failed");
-            FlowplaneError::database(e, format!("Migration failed: {}", filename))
-        })?;
+        with_pool!(pool, |pool| {
+            // Execute migration in a transaction
+            let mut tx = pool.begin().await.map_err(|e| {
+                FlowplaneError::database(e, "Failed to start migration transaction".to_string())
+            })?;
 
-        // Record migration
-        let execution_time = start_time.elapsed().as_millis() as i64;
-        let checksum = calculate_checksum(sql);
-        let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+            // Run the migration SQL (may contain several statements)
+            sqlx::raw_sql(sql).execute(&mut *tx).await.map_err(|e| {
+                error!(error = %e, migration = filename, "Migration failed");
+                FlowplaneError::database(e, format!("Migration failed: {}", filename))
+            })?;
 
-        sqlx::query(
-            "INSERT INTO _flowplane_migrations (version, description, checksum, execution_time, installed_on) VALUES ($1, $2, $3, $4, $5)"
-        )
-        .bind(version)
-        .bind(filename)
-        .bind(&checksum)
-        .bind(execution_time)
-        .bind(&now)
-        .execute(&mut *tx)
-        .await
-        .map_err(|e| {
-            error!(error = %e, migration = filename, "Failed to record migration");
-            FlowplaneError::database(e, format!("Failed to record migration: {}", filename))
-        })?;
+            // Record migration
+            let execution_time = start_time.elapsed().as_millis() as i64;
+
+            sqlx::query(
+                "INSERT INTO _flowplane_migrations (version, description, checksum, execution_time, installed_on) VALUES ($1, $2, $3, $4, $5)"
+            )
+            .bind(version)
+            .bind(filename)
+            .bind(&checksum)
+            .bind(execution_time)
+            .bind(chrono::Utc::now())
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| {
+                error!(error = %e, migration = filename, "Failed to record migration");
+                FlowplaneError::database(e, format!("Failed to record migration: {}", filename))
+            })?;
 
-        tx.commit().await.map_err(|e| {
-            FlowplaneError::database(e, "Failed to commit migration transaction".to_string())
-        })?;
+            tx.commit().await.map_err(|e| {
+                FlowplaneError::database(e, "Failed to commit migration transaction".to_string())
+            })?;
+        });
 
         migrations_run += 1;
         info!(
             version = version,
-            execution_time_ms = execution_time,
+            execution_time_ms = start_time.elapsed().as_millis() as i64,
             "Migration completed: {}",
             filename
         );
@@ -177,9 +190,10 @@ pub async fn run_migrations(pool: &Pool<Sqlite>) -> Result<()> {
 }
 
 /// Create the migration tracking table
-async fn create_migration_table(pool: &Pool<Sqlite>) -> Result<()> {
-    sqlx::query(
-        r#"
+async fn create_migration_table(pool: &DbPool) -> Result<()> {
+    let ddl = match pool.backend() {
+        DatabaseBackend::Sqlite => {
+            r#"
         CREATE TABLE IF NOT EXISTS _flowplane_migrations (
             version BIGINT PRIMARY KEY,
             description TEXT NOT NULL,
@@ -187,28 +201,47 @@ async fn create_migration_table(pool: &Pool<Sqlite>) -> Result<()> {
             execution_time BIGINT NOT NULL,
             installed_on DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
         )
-    "#,
-    )
-    .execute(pool)
-    .await
-    .map_err(|e| {
+    "#
+        }
+        DatabaseBackend::Postgres => {
+            r#"
+        CREATE TABLE IF NOT EXISTS _flowplane_migrations (
+            version BIGINT PRIMARY KEY,
+            description TEXT NOT NULL,
+            checksum BYTEA NOT NULL,
+            execution_time BIGINT NOT NULL,
+            installed_on TIMESTAMPTZ NOT NULL DEFAULT CURRENT_TIMESTAMP
+        )
+    "#
+        }
+    };
+
+    with_pool!(pool, |pool| sqlx::query(ddl).execute(pool).await.map(|_| ())).map_err(|e| {
         FlowplaneError::database(e, "Failed to create migration tracking table".to_string())
     })?;
 
     Ok(())
 }
 
+/// Whether a database error reports that the migration tracking table is missing
+fn is_missing_migration_table(db_err: &dyn sqlx::error::DatabaseError) -> bool {
+    let message = db_err.message();
+    message.contains("no such table: _flowplane_migrations")
+        || message.contains("relation \"_flowplane_migrations\" does not exist")
+}
+
 /// Get list of applied migration versions
-async fn get_applied_migration_versions(pool: &Pool<Sqlite>) -> Result<Vec<i64>> {
-    let rows = sqlx::query("SELECT version FROM _flowplane_migrations ORDER BY version")
-        .fetch_all(pool)
-        .await;
+async fn get_applied_migration_versions(pool: &DbPool) -> Result<Vec<i64>> {
+    let rows = with_pool!(pool, |pool| {
+        sqlx::query("SELECT version FROM _flowplane_migrations ORDER BY version")
+            .fetch_all(pool)
+            .await
+            .map(|rows| rows.into_iter().map(|row| row.get::<i64, _>("version")).collect())
+    });
 
     match rows {
-        Ok(rows) => Ok(rows.into_iter().map(|row| row.get::<i64, _>("version")).collect()),
-        Err(sqlx::Error::Database(db_err))
-            if db_err.message().contains("no such table: _flowplane_migrations") =>
-        {
+        Ok(versions) => Ok(versions),
+        Err(sqlx::Error::Database(db_err)) if is_missing_migration_table(db_err.as_ref()) => {
             Ok(Vec::new())
         }
         Err(e) => Err(FlowplaneError::database(e, "Failed to get applied migrations".to_string())),
@@ -237,10 +270,10 @@ fn calculate_checksum(content: &str) -> Vec<u8> {
 }
 
 /// Validate that all migrations are applied correctly
-pub async fn validate_migrations(pool: &Pool<Sqlite>) -> Result<bool> {
+pub async fn validate_migrations(pool: &DbPool) -> Result<bool> {
     info!("Validating migration integrity");
 
-    let migrations = load_migrations()?;
+    let migrations = load_migrations(pool.backend())?;
     let applied_versions = get_applied_migration_versions(pool).await?;
     let expected_versions: Vec<i64> = migrations
         .iter()
@@ -268,34 +301,33 @@ pub async fn validate_migrations(pool: &Pool<Sqlite>) -> Result<bool> {
 }
 
 /// Get the current migration version (highest applied)
-pub async fn get_migration_version(pool: &Pool<Sqlite>) -> Result<i64> {
+pub async fn get_migration_version(pool: &DbPool) -> Result<i64> {
     let applied = get_applied_migration_versions(pool).await?;
     Ok(applied.into_iter().max().unwrap_or(0))
 }
 
 /// List all applied migrations
-pub async fn list_applied_migrations(pool: &Pool<Sqlite>) -> Result<Vec<MigrationInfo>> {
-    let rows = sqlx::query("SELECT version, description, checksum, execution_time, installed_on FROM _flowplane_migrations ORDER BY version")
-        .fetch_all(pool)
-        .await;
+pub async fn list_applied_migrations(pool: &DbPool) -> Result<Vec<MigrationInfo>> {
+    let rows = with_pool!(pool, |pool| {
+        sqlx::query("SELECT version, description, checksum, execution_time, installed_on FROM _flowplane_migrations ORDER BY version")
+            .fetch_all(pool)
+            .await
+            .map(|rows| {
+                rows.into_iter()
+                    .map(|row| MigrationInfo {
+                        version: row.get("version"),
+                        description: row.get("description"),
+                        installed_on: row.get("installed_on"),
+                        execution_time: row.get("execution_time"),
+                        checksum: row.get("checksum"),
+                    })
+                    .collect()
+            })
+    });
 
     match rows {
-        Ok(rows) => {
-            let migrations = rows
-                .into_iter()
-                .map(|row| MigrationInfo {
-                    version: row.get("version"),
-                    description: row.get("description"),
-                    installed_on: row.get("installed_on"),
-                    execution_time: row.get("execution_time"),
-                    checksum: row.get("checksum"),
-                })
-                .collect();
-            Ok(migrations)
-        }
-        Err(sqlx::Error::Database(db_err))
-            if db_err.message().contains("no such table: _flowplane_migrations") =>
-        {
+        Ok(migrations) => Ok(migrations),
+        Err(sqlx::Error::Database(db_err)) if is_missing_migration_table(db_err.as_ref()) => {
             Ok(Vec::new())
         }
         Err(e) => Err(FlowplaneError::database(e, "Failed to list applied migrations".to_string())),
@@ -308,7 +340,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_run_migrations() {
-        let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await.unwrap();
+        let pool: DbPool =
+            sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await.unwrap().into();
 
         // Run migrations
         let result = run_migrations(&pool).await;
@@ -316,7 +349,7 @@ mod tests {
 
         // Check that all migrations were applied
         let applied = get_applied_migration_versions(&pool).await.unwrap();
-        let migrations = load_migrations().unwrap();
+        let migrations = load_migrations(DatabaseBackend::Sqlite).unwrap();
         assert_eq!(applied.len(), migrations.len());
 
         // Running again should be idempotent
@@ -326,7 +359,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_validate_migrations() {
-        let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await.unwrap();
+        let pool: DbPool =
+            sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await.unwrap().into();
 
         // Run migrations first
         run_migrations(&pool).await.unwrap();
@@ -339,7 +373,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_migration_version() {
-        let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await.unwrap();
+        let pool: DbPool =
+            sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await.unwrap().into();
 
         // Before migrations
         let version = get_migration_version(&pool).await.unwrap();
@@ -353,7 +388,8 @@ mod tests {
 
     #[tokio::test]
     async fn test_list_applied_migrations() {
-        let pool = sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await.unwrap();
+        let pool: DbPool =
+            sqlx::sqlite::SqlitePool::connect("sqlite://:memory:").await.unwrap().into();
 
         // Before migrations
         let migrations = list_applied_migrations(&pool).await.unwrap();
@@ -362,10 +398,23 @@ mod tests {
         // After migrations
         run_migrations(&pool).await.unwrap();
         let applied_migrations = list_applied_migrations(&pool).await.unwrap();
-        let loaded_migrations = load_migrations().unwrap();
+        let loaded_migrations = load_migrations(DatabaseBackend::Sqlite).unwrap();
         assert_eq!(applied_migrations.len(), loaded_migrations.len());
     }
 
+    #[test]
+    fn test_postgres_migrations_mirror_sqlite_versions() {
+        let versions = |backend| {
+            load_migrations(backend)
+                .unwrap()
+                .iter()
+                .map(|(filename, _)| extract_version_from_filename(filename).unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(versions(DatabaseBackend::Sqlite), versions(DatabaseBackend::Postgres));
+    }
+
     #[test]
     fn test_extract_version_from_filename() {
         assert_eq!(
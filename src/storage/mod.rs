@@ -3,24 +3,31 @@
 //! This module provides database connectivity and persistence layer for the
 //! Flowplane control plane configuration data.
 
+pub mod encryption;
+pub mod events;
 pub mod migrations;
 pub mod pool;
 pub mod repository_simple;
 
 pub use crate::config::DatabaseConfig;
 
+pub use encryption::SecretCipher;
+pub use events::{Change, ChangeKind, ChangeNotifier};
 pub use migrations::{
     get_migration_version, list_applied_migrations, run_migrations as run_db_migrations,
     validate_migrations, MigrationInfo,
 };
-pub use pool::{create_pool, get_pool_stats, DbPool, PoolStats};
+pub use pool::{create_pool, get_pool_stats, DatabaseBackend, DbPool, PoolStats};
 pub use repository_simple::{
     ApiDefinitionData, ApiDefinitionRepository, ApiRouteData, AuditEvent, AuditLogRepository,
-    ClusterData, ClusterRepository, CreateApiDefinitionRequest, CreateApiRouteRequest,
-    CreateClusterRequest, CreateListenerRequest,
-    CreateRouteRequest as CreateRouteRepositoryRequest, ListenerData, ListenerRepository,
-    RouteData, RouteRepository, UpdateBootstrapMetadataRequest, UpdateClusterRequest,
-    UpdateListenerRequest, UpdateRouteRequest as UpdateRouteRepositoryRequest,
+    CertificateAuthorityData, CertificateAuthorityRepository, ClusterData, ClusterRepository,
+    ConfigurationVersionData, ConfigurationVersionRepository, CreateApiDefinitionRequest,
+    CreateApiRouteRequest, CreateCertificateAuthorityRequest, CreateClusterRequest,
+    CreateListenerRequest, CreateRouteRequest as CreateRouteRepositoryRequest, CreateSecretRequest,
+    LeaseRepository, ListenerData, ListenerRepository, RolloutData, RolloutRepository, RouteData,
+    RouteRepository, RuntimeKeyData, RuntimeRepository, SecretData, SecretRepository,
+    SetRuntimeKeyRequest, UpdateBootstrapMetadataRequest, UpdateClusterRequest,
+    UpdateListenerRequest, UpdateRouteRequest as UpdateRouteRepositoryRequest, UpdateSecretRequest,
 };
 
 use crate::errors::{FlowplaneError, Result};
@@ -32,10 +39,11 @@ pub async fn run_migrations(pool: &DbPool) -> Result<()> {
 
 /// Check database connectivity
 pub async fn check_connection(pool: &DbPool) -> Result<()> {
-    sqlx::query("SELECT 1").fetch_one(pool).await.map_err(|e| FlowplaneError::Database {
-        source: e,
-        context: "Database connectivity check failed".to_string(),
-    })?;
+    pool::with_pool!(pool, |pool| sqlx::query("SELECT 1").execute(pool).await.map(|_| ()))
+        .map_err(|e| FlowplaneError::Database {
+            source: e,
+            context: "Database connectivity check failed".to_string(),
+        })?;
 
     Ok(())
 }
@@ -1,71 +1,152 @@
 //! # Database Connection Pool Management
 //!
 //! Provides database connection pool creation and management utilities.
+//! The pool is backend-agnostic: SQLite is used for single-node deployments and
+//! PostgreSQL when several control-plane replicas share one durable store.
 
 use crate::config::DatabaseConfig;
 use crate::errors::{FlowplaneError, Result};
 use sqlx::{
-    sqlite::{SqliteConnectOptions, SqliteJournalMode},
-    Pool, Sqlite,
+    postgres::{PgPool, PgPoolOptions},
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePool, SqlitePoolOptions},
 };
 use std::{str::FromStr, time::Duration};
 
-/// Type alias for the database connection pool
-pub type DbPool = Pool<Sqlite>;
+/// Database connection pool for the configured backend
+#[derive(Debug, Clone)]
+pub enum DbPool {
+    Sqlite(SqlitePool),
+    Postgres(PgPool),
+}
+
+/// Supported database backends
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatabaseBackend {
+    Sqlite,
+    Postgres,
+}
+
+impl DatabaseBackend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DatabaseBackend::Sqlite => "sqlite",
+            DatabaseBackend::Postgres => "postgresql",
+        }
+    }
+}
+
+/// Run an expression against the concrete pool behind a [`DbPool`].
+///
+/// The expression is expanded once per backend, so queries must only use SQL
+/// understood by both SQLite and PostgreSQL (e.g. `$1` placeholders).
+macro_rules! with_pool {
+    ($pool:expr, |$conn:ident| $body:expr) => {
+        match $pool {
+            $crate::storage::DbPool::Sqlite($conn) => $body,
+            $crate::storage::DbPool::Postgres($conn) => $body,
+        }
+    };
+}
+
+pub(crate) use with_pool;
+
+impl DbPool {
+    /// Backend serving this pool
+    pub fn backend(&self) -> DatabaseBackend {
+        match self {
+            DbPool::Sqlite(_) => DatabaseBackend::Sqlite,
+            DbPool::Postgres(_) => DatabaseBackend::Postgres,
+        }
+    }
+
+    /// Access the SQLite pool, if this is a SQLite backend
+    pub fn as_sqlite(&self) -> Option<&SqlitePool> {
+        match self {
+            DbPool::Sqlite(pool) => Some(pool),
+            DbPool::Postgres(_) => None,
+        }
+    }
+
+    /// Access the PostgreSQL pool, if this is a PostgreSQL backend
+    pub fn as_postgres(&self) -> Option<&PgPool> {
+        match self {
+            DbPool::Postgres(pool) => Some(pool),
+            DbPool::Sqlite(_) => None,
+        }
+    }
+
+    /// Number of connections currently held by the pool
+    pub fn size(&self) -> u32 {
+        with_pool!(self, |pool| pool.size())
+    }
+
+    /// Number of idle connections in the pool
+    pub fn num_idle(&self) -> usize {
+        with_pool!(self, |pool| pool.num_idle())
+    }
+
+    /// Close all connections in the pool
+    pub async fn close(&self) {
+        with_pool!(self, |pool| pool.close().await)
+    }
+
+    /// Opaque marker that changes whenever the configuration has been written to.
+    ///
+    /// SQLite reports `PRAGMA data_version`. PostgreSQL reports the row count
+    /// and latest `updated_at` of each configuration table, so writes to other
+    /// tables or databases on the same server leave it unchanged, and it can
+    /// be read on a hot standby. Used by background watchers to detect changes
+    /// made by other connections or replicas.
+    pub async fn data_version(&self) -> std::result::Result<String, sqlx::Error> {
+        match self {
+            DbPool::Sqlite(pool) => sqlx::query_scalar::<_, i64>("PRAGMA data_version;")
+                .fetch_one(pool)
+                .await
+                .map(|version| version.to_string()),
+            DbPool::Postgres(pool) => {
+                sqlx::query_scalar::<_, String>(POSTGRES_DATA_VERSION).fetch_one(pool).await
+            }
+        }
+    }
+}
+
+impl From<SqlitePool> for DbPool {
+    fn from(pool: SqlitePool) -> Self {
+        DbPool::Sqlite(pool)
+    }
+}
+
+impl From<PgPool> for DbPool {
+    fn from(pool: PgPool) -> Self {
+        DbPool::Postgres(pool)
+    }
+}
+
+/// Row count and latest write of every table the xDS resources are built from.
+const POSTGRES_DATA_VERSION: &str = "SELECT concat_ws(';', \
+    (SELECT count(*) || '@' || COALESCE(max(updated_at)::text, '') FROM clusters), \
+    (SELECT count(*) || '@' || COALESCE(max(updated_at)::text, '') FROM routes), \
+    (SELECT count(*) || '@' || COALESCE(max(updated_at)::text, '') FROM listeners), \
+    (SELECT count(*) || '@' || COALESCE(max(updated_at)::text, '') FROM api_definitions), \
+    (SELECT count(*) || '@' || COALESCE(max(updated_at)::text, '') FROM api_routes), \
+    (SELECT count(*) || '@' || COALESCE(max(updated_at)::text, '') FROM secrets), \
+    (SELECT count(*) || '@' || COALESCE(max(updated_at)::text, '') FROM runtime_keys))";
 
 const SQLITE_BUSY_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// Create a database connection pool with the specified configuration
-pub async fn create_pool(config: &DatabaseConfig) -> Result<Pool<Sqlite>> {
+pub async fn create_pool(config: &DatabaseConfig) -> Result<DbPool> {
     // Validate configuration
     validate_config(config)?;
 
-    let pool_options = sqlx::sqlite::SqlitePoolOptions::new()
-        .max_connections(config.max_connections)
-        .min_connections(config.min_connections)
-        .acquire_timeout(config.connect_timeout())
-        .test_before_acquire(true); // Test connections before use
-
-    let pool_options = if let Some(idle_timeout) = config.idle_timeout() {
-        pool_options.idle_timeout(idle_timeout)
-    } else {
-        pool_options
-    };
-
     let pool = if config.is_sqlite() {
-        let connect_options = SqliteConnectOptions::from_str(&config.url)
-            .map_err(|e| FlowplaneError::Database {
-                source: e,
-                context: format!("Invalid SQLite connection string: {}", sanitize_url(&config.url)),
-            })?
-            .create_if_missing(true)
-            .busy_timeout(SQLITE_BUSY_TIMEOUT)
-            .journal_mode(SqliteJournalMode::Wal);
-
-        pool_options.connect_with(connect_options).await.map_err(|e| {
-            tracing::error!(
-                error = %e,
-                url = %config.url,
-                busy_timeout_ms = SQLITE_BUSY_TIMEOUT.as_millis(),
-                "Failed to create SQLite database pool"
-            );
-            FlowplaneError::Database {
-                source: e,
-                context: format!("Failed to connect to database: {}", sanitize_url(&config.url)),
-            }
-        })?
+        create_sqlite_pool(config).await?
     } else {
-        pool_options.connect(&config.url).await.map_err(|e| {
-            tracing::error!(error = %e, url = %config.url, "Failed to create database pool");
-            FlowplaneError::Database {
-                source: e,
-                context: format!("Failed to connect to database: {}", sanitize_url(&config.url)),
-            }
-        })?
+        create_postgres_pool(config).await?
     };
 
     tracing::info!(
-        database_type = if config.is_sqlite() { "sqlite" } else { "postgresql" },
+        database_type = pool.backend().as_str(),
         max_connections = config.max_connections,
         min_connections = config.min_connections,
         connect_timeout_ms = config.connect_timeout().as_millis(),
@@ -82,6 +163,72 @@ pub async fn create_pool(config: &DatabaseConfig) -> Result<Pool<Sqlite>> {
     Ok(pool)
 }
 
+async fn create_sqlite_pool(config: &DatabaseConfig) -> Result<DbPool> {
+    let pool_options = SqlitePoolOptions::new()
+        .max_connections(config.max_connections)
+        .min_connections(config.min_connections)
+        .acquire_timeout(config.connect_timeout())
+        .test_before_acquire(true); // Test connections before use
+
+    let pool_options = if let Some(idle_timeout) = config.idle_timeout() {
+        pool_options.idle_timeout(idle_timeout)
+    } else {
+        pool_options
+    };
+
+    let connect_options = SqliteConnectOptions::from_str(&config.url)
+        .map_err(|e| FlowplaneError::Database {
+            source: e,
+            context: format!("Invalid SQLite connection string: {}", sanitize_url(&config.url)),
+        })?
+        .create_if_missing(true)
+        .busy_timeout(SQLITE_BUSY_TIMEOUT)
+        .journal_mode(SqliteJournalMode::Wal);
+
+    let pool = pool_options.connect_with(connect_options).await.map_err(|e| {
+        tracing::error!(
+            error = %e,
+            url = %sanitize_url(&config.url),
+            busy_timeout_ms = SQLITE_BUSY_TIMEOUT.as_millis(),
+            "Failed to create SQLite database pool"
+        );
+        FlowplaneError::Database {
+            source: e,
+            context: format!("Failed to connect to database: {}", sanitize_url(&config.url)),
+        }
+    })?;
+
+    Ok(DbPool::Sqlite(pool))
+}
+
+async fn create_postgres_pool(config: &DatabaseConfig) -> Result<DbPool> {
+    let pool_options = PgPoolOptions::new()
+        .max_connections(config.max_connections)
+        .min_connections(config.min_connections)
+        .acquire_timeout(config.connect_timeout())
+        .test_before_acquire(true);
+
+    let pool_options = if let Some(idle_timeout) = config.idle_timeout() {
+        pool_options.idle_timeout(idle_timeout)
+    } else {
+        pool_options
+    };
+
+    let pool = pool_options.connect(&config.url).await.map_err(|e| {
+        tracing::error!(
+            error = %e,
+            url = %sanitize_url(&config.url),
+            "Failed to create PostgreSQL database pool"
+        );
+        FlowplaneError::Database {
+            source: e,
+            context: format!("Failed to connect to database: {}", sanitize_url(&config.url)),
+        }
+    })?;
+
+    Ok(DbPool::Postgres(pool))
+}
+
 /// Validate database configuration
 fn validate_config(config: &DatabaseConfig) -> Result<()> {
     if config.max_connections == 0 {
@@ -99,9 +246,9 @@ fn validate_config(config: &DatabaseConfig) -> Result<()> {
     }
 
     // Validate URL format
-    if !config.url.starts_with("sqlite://") && !config.url.starts_with("postgresql://") {
+    if !config.is_sqlite() && !config.is_postgresql() {
         return Err(FlowplaneError::validation(
-            "database URL must start with 'sqlite://' or 'postgresql://'",
+            "database URL must start with 'sqlite://', 'postgresql://' or 'postgres://'",
         ));
     }
 
@@ -128,7 +275,7 @@ fn sanitize_url(url: &str) -> String {
 }
 
 /// Get pool statistics for monitoring
-pub fn get_pool_stats(pool: &Pool<Sqlite>) -> PoolStats {
+pub fn get_pool_stats(pool: &DbPool) -> PoolStats {
     PoolStats { size: pool.size(), idle: pool.num_idle() }
 }
 
@@ -207,6 +354,14 @@ mod tests {
         assert!(validate_config(&config).is_err());
     }
 
+    #[test]
+    fn test_validate_config_accepts_postgres_schemes() {
+        for url in ["postgresql://localhost/flowplane", "postgres://localhost/flowplane"] {
+            let config = DatabaseConfig { url: url.to_string(), ..Default::default() };
+            assert!(validate_config(&config).is_ok(), "{} should be accepted", url);
+        }
+    }
+
     #[test]
     fn test_sanitize_url() {
         assert_eq!(
@@ -243,6 +398,7 @@ mod tests {
         };
 
         let pool = create_pool(&config).await.unwrap();
+        assert_eq!(pool.backend(), DatabaseBackend::Sqlite);
         let stats = get_pool_stats(&pool);
         assert!(stats.is_healthy());
     }
@@ -7,10 +7,10 @@ use crate::auth::models::{
     NewPersonalAccessToken, PersonalAccessToken, TokenStatus, UpdatePersonalAccessToken,
 };
 use crate::errors::{FlowplaneError, Result};
-use crate::storage::DbPool;
+use crate::storage::{pool::with_pool, Change, ChangeKind, ChangeNotifier, DbPool};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use sqlx::{FromRow, Sqlite};
+use sqlx::FromRow;
 use std::str::FromStr;
 use uuid::Uuid;
 
@@ -287,11 +287,14 @@ pub struct UpdateBootstrapMetadataRequest {
 #[derive(Debug, Clone)]
 pub struct ApiDefinitionRepository {
     pool: DbPool,
+    notifier: ChangeNotifier,
 }
 
 impl ApiDefinitionRepository {
-    pub fn new(pool: DbPool) -> Self {
-        Self { pool }
+    /// Create a repository that publishes committed writes to `notifier`, the
+    /// change bus shared with the xDS cache
+    pub fn new(pool: DbPool, notifier: ChangeNotifier) -> Self {
+        Self { pool, notifier }
     }
 
     pub fn pool(&self) -> DbPool {
@@ -327,56 +330,67 @@ impl ApiDefinitionRepository {
 
         let now = chrono::Utc::now();
 
-        sqlx::query::<Sqlite>(
-            "INSERT INTO api_definitions (
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                "INSERT INTO api_definitions (
                 id, team, domain, listener_isolation, tls_config, metadata, bootstrap_uri,
                 bootstrap_revision, version, created_at, updated_at
             ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
-        )
-        .bind(&id)
-        .bind(&request.team)
-        .bind(&request.domain)
-        .bind(listener_isolation)
-        .bind(tls_config)
-        .bind(metadata)
-        .bind(Option::<String>::None)
-        .bind(0_i64)
-        .bind(1_i64)
-        .bind(now)
-        .bind(now)
-        .execute(&self.pool)
-        .await
+            )
+            .bind(&id)
+            .bind(&request.team)
+            .bind(&request.domain)
+            .bind(listener_isolation)
+            .bind(tls_config)
+            .bind(metadata)
+            .bind(Option::<String>::None)
+            .bind(0_i64)
+            .bind(1_i64)
+            .bind(now)
+            .bind(now)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .map_err(|e| FlowplaneError::Database {
             source: e,
             context: "Failed to insert API definition".to_string(),
         })?;
 
+        self.notifier.publish(&self.pool, Change::row(ChangeKind::ApiDefinitions, &id)).await;
         self.get_definition(&id).await
     }
 
     /// Delete an API definition by ID (cascades to routes)
     pub async fn delete_definition(&self, id: &str) -> Result<()> {
-        sqlx::query("DELETE FROM api_definitions WHERE id = $1")
-            .bind(id)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| FlowplaneError::Database {
-                source: e,
-                context: "Failed to delete API definition".to_string(),
-            })?;
+        with_pool!(&self.pool, |pool| {
+            sqlx::query("DELETE FROM api_definitions WHERE id = $1")
+                .bind(id)
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+        })
+        .map_err(|e| FlowplaneError::Database {
+            source: e,
+            context: "Failed to delete API definition".to_string(),
+        })?;
+
+        self.notifier.publish(&self.pool, Change::row(ChangeKind::ApiDefinitions, id)).await;
         Ok(())
     }
 
     /// Fetch an API definition by identifier
     pub async fn get_definition(&self, id: &str) -> Result<ApiDefinitionData> {
-        let row = sqlx::query_as::<Sqlite, ApiDefinitionRow>(
-            "SELECT id, team, domain, listener_isolation, tls_config, metadata, bootstrap_uri,
+        let row = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, ApiDefinitionRow>(
+                "SELECT id, team, domain, listener_isolation, tls_config, metadata, bootstrap_uri,
                     bootstrap_revision, version, created_at, updated_at
              FROM api_definitions WHERE id = $1",
-        )
-        .bind(id)
-        .fetch_optional(&self.pool)
-        .await
+            )
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+        })
         .map_err(|e| FlowplaneError::Database {
             source: e,
             context: format!("Failed to load API definition '{}'", id),
@@ -392,15 +406,17 @@ impl ApiDefinitionRepository {
         team: &str,
         domain: &str,
     ) -> Result<Option<ApiDefinitionData>> {
-        let row = sqlx::query_as::<Sqlite, ApiDefinitionRow>(
-            "SELECT id, team, domain, listener_isolation, tls_config, metadata, bootstrap_uri,
+        let row = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, ApiDefinitionRow>(
+                "SELECT id, team, domain, listener_isolation, tls_config, metadata, bootstrap_uri,
                     bootstrap_revision, version, created_at, updated_at
              FROM api_definitions WHERE team = $1 AND domain = $2 LIMIT 1",
-        )
-        .bind(team)
-        .bind(domain)
-        .fetch_optional(&self.pool)
-        .await
+            )
+            .bind(team)
+            .bind(domain)
+            .fetch_optional(pool)
+            .await
+        })
         .map_err(|e| FlowplaneError::Database {
             source: e,
             context: "Failed to query API definition by team/domain".to_string(),
@@ -411,14 +427,16 @@ impl ApiDefinitionRepository {
 
     /// Fetch an API definition by domain regardless of owning team
     pub async fn find_by_domain(&self, domain: &str) -> Result<Option<ApiDefinitionData>> {
-        let row = sqlx::query_as::<Sqlite, ApiDefinitionRow>(
-            "SELECT id, team, domain, listener_isolation, tls_config, metadata, bootstrap_uri,
+        let row = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, ApiDefinitionRow>(
+                "SELECT id, team, domain, listener_isolation, tls_config, metadata, bootstrap_uri,
                     bootstrap_revision, version, created_at, updated_at
              FROM api_definitions WHERE domain = $1 LIMIT 1",
-        )
-        .bind(domain)
-        .fetch_optional(&self.pool)
-        .await
+            )
+            .bind(domain)
+            .fetch_optional(pool)
+            .await
+        })
         .map_err(|e| FlowplaneError::Database {
             source: e,
             context: "Failed to query API definition by domain".to_string(),
@@ -436,8 +454,9 @@ impl ApiDefinitionRepository {
 
         let now = chrono::Utc::now();
 
-        sqlx::query::<Sqlite>(
-            "INSERT INTO api_routes (
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                "INSERT INTO api_routes (
                 id,
                 api_definition_id,
                 match_type,
@@ -456,43 +475,53 @@ impl ApiDefinitionRepository {
             ) VALUES (
                 $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15
             )",
-        )
-        .bind(&id)
-        .bind(&request.api_definition_id)
-        .bind(&request.match_type)
-        .bind(&request.match_value)
-        .bind(case_sensitive)
-        .bind(&request.rewrite_prefix)
-        .bind(&request.rewrite_regex)
-        .bind(&request.rewrite_substitution)
-        .bind(upstream_json)
-        .bind(request.timeout_seconds)
-        .bind(overrides_json)
-        .bind(&request.deployment_note)
-        .bind(request.route_order)
-        .bind(now)
-        .bind(now)
-        .execute(&self.pool)
-        .await
+            )
+            .bind(&id)
+            .bind(&request.api_definition_id)
+            .bind(&request.match_type)
+            .bind(&request.match_value)
+            .bind(case_sensitive)
+            .bind(&request.rewrite_prefix)
+            .bind(&request.rewrite_regex)
+            .bind(&request.rewrite_substitution)
+            .bind(upstream_json)
+            .bind(request.timeout_seconds)
+            .bind(overrides_json)
+            .bind(&request.deployment_note)
+            .bind(request.route_order)
+            .bind(now)
+            .bind(now)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .map_err(|e| FlowplaneError::Database {
             source: e,
             context: "Failed to insert API route".to_string(),
         })?;
 
+        self.notifier
+            .publish(
+                &self.pool,
+                Change::row(ChangeKind::ApiDefinitions, &request.api_definition_id),
+            )
+            .await;
         self.get_route(&id).await
     }
 
     /// Retrieve a route by identifier
     pub async fn get_route(&self, id: &str) -> Result<ApiRouteData> {
-        let row = sqlx::query_as::<Sqlite, ApiRouteRow>(
+        let row = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, ApiRouteRow>(
             "SELECT id, api_definition_id, match_type, match_value, case_sensitive, rewrite_prefix,
                     rewrite_regex, rewrite_substitution, upstream_targets, timeout_seconds,
                     override_config, deployment_note, route_order, created_at, updated_at
              FROM api_routes WHERE id = $1",
         )
         .bind(id)
-        .fetch_optional(&self.pool)
+        .fetch_optional(pool)
         .await
+        })
         .map_err(|e| FlowplaneError::Database {
             source: e,
             context: format!("Failed to load API route '{}'", id),
@@ -504,7 +533,8 @@ impl ApiDefinitionRepository {
 
     /// List routes for a given definition ordered by insertion order
     pub async fn list_routes(&self, api_definition_id: &str) -> Result<Vec<ApiRouteData>> {
-        let rows = sqlx::query_as::<Sqlite, ApiRouteRow>(
+        let rows = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, ApiRouteRow>(
             "SELECT id, api_definition_id, match_type, match_value, case_sensitive, rewrite_prefix,
                     rewrite_regex, rewrite_substitution, upstream_targets, timeout_seconds,
                     override_config, deployment_note, route_order, created_at, updated_at
@@ -512,8 +542,9 @@ impl ApiDefinitionRepository {
              ORDER BY route_order ASC, created_at ASC",
         )
         .bind(api_definition_id)
-        .fetch_all(&self.pool)
+        .fetch_all(pool)
         .await
+        })
         .map_err(|e| FlowplaneError::Database {
             source: e,
             context: "Failed to list API routes".to_string(),
@@ -529,36 +560,44 @@ impl ApiDefinitionRepository {
     ) -> Result<ApiDefinitionData> {
         let now = chrono::Utc::now();
 
-        sqlx::query(
-            "UPDATE api_definitions
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                "UPDATE api_definitions
              SET bootstrap_uri = $2,
                  bootstrap_revision = $3,
                  updated_at = $4,
                  version = version + 1
              WHERE id = $1",
-        )
-        .bind(&request.definition_id)
-        .bind(&request.bootstrap_uri)
-        .bind(request.bootstrap_revision)
-        .bind(now)
-        .execute(&self.pool)
-        .await
+            )
+            .bind(&request.definition_id)
+            .bind(&request.bootstrap_uri)
+            .bind(request.bootstrap_revision)
+            .bind(now)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .map_err(|e| FlowplaneError::Database {
             source: e,
             context: "Failed to update bootstrap metadata".to_string(),
         })?;
 
+        self.notifier
+            .publish(&self.pool, Change::row(ChangeKind::ApiDefinitions, &request.definition_id))
+            .await;
         self.get_definition(&request.definition_id).await
     }
 
     pub async fn list_definitions(&self) -> Result<Vec<ApiDefinitionData>> {
-        let rows = sqlx::query_as::<Sqlite, ApiDefinitionRow>(
-            "SELECT id, team, domain, listener_isolation, tls_config, metadata, bootstrap_uri,
+        let rows = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, ApiDefinitionRow>(
+                "SELECT id, team, domain, listener_isolation, tls_config, metadata, bootstrap_uri,
                     bootstrap_revision, version, created_at, updated_at
              FROM api_definitions",
-        )
-        .fetch_all(&self.pool)
-        .await
+            )
+            .fetch_all(pool)
+            .await
+        })
         .map_err(|e| FlowplaneError::Database {
             source: e,
             context: "Failed to list API definitions".to_string(),
@@ -568,14 +607,16 @@ impl ApiDefinitionRepository {
     }
 
     pub async fn list_all_routes(&self) -> Result<Vec<ApiRouteData>> {
-        let rows = sqlx::query_as::<Sqlite, ApiRouteRow>(
+        let rows = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, ApiRouteRow>(
             "SELECT id, api_definition_id, match_type, match_value, case_sensitive, rewrite_prefix,
                     rewrite_regex, rewrite_substitution, upstream_targets, timeout_seconds,
                     override_config, deployment_note, route_order, created_at, updated_at
              FROM api_routes ORDER BY api_definition_id, route_order",
         )
-        .fetch_all(&self.pool)
+        .fetch_all(pool)
         .await
+        })
         .map_err(|e| FlowplaneError::Database {
             source: e,
             context: "Failed to list API routes".to_string(),
@@ -640,12 +681,15 @@ pub struct UpdateListenerRequest {
 #[derive(Debug, Clone)]
 pub struct ClusterRepository {
     pool: DbPool,
+    notifier: ChangeNotifier,
 }
 
 impl ClusterRepository {
     /// Create a new cluster repository
-    pub fn new(pool: DbPool) -> Self {
-        Self { pool }
+    /// Create a repository that publishes committed writes to `notifier`, the
+    /// change bus shared with the xDS cache
+    pub fn new(pool: DbPool, notifier: ChangeNotifier) -> Self {
+        Self { pool, notifier }
     }
 
     /// Create a new cluster
@@ -657,17 +701,20 @@ impl ClusterRepository {
         let now = chrono::Utc::now();
 
         // Use parameterized query with positional parameters (works with both SQLite and PostgreSQL)
-        let result = sqlx::query(
-            "INSERT INTO clusters (id, name, service_name, configuration, version, created_at, updated_at) VALUES ($1, $2, $3, $4, 1, $5, $6)"
-        )
-        .bind(&id)
-        .bind(&request.name)
-        .bind(&request.service_name)
-        .bind(&configuration_json)
-        .bind(now)
-        .bind(now)
-        .execute(&self.pool)
-        .await
+        let rows_affected = with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                "INSERT INTO clusters (id, name, service_name, configuration, version, created_at, updated_at) VALUES ($1, $2, $3, $4, 1, $5, $6)"
+            )
+            .bind(&id)
+            .bind(&request.name)
+            .bind(&request.service_name)
+            .bind(&configuration_json)
+            .bind(now)
+            .bind(now)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .map_err(|e| {
             tracing::error!(error = %e, cluster_name = %request.name, "Failed to create cluster");
             FlowplaneError::Database {
@@ -676,7 +723,7 @@ impl ClusterRepository {
             }
         })?;
 
-        if result.rows_affected() == 0 {
+        if rows_affected == 0 {
             return Err(FlowplaneError::validation("Failed to create cluster"));
         }
 
@@ -687,18 +734,22 @@ impl ClusterRepository {
             "Created new cluster"
         );
 
+        self.notifier.publish(&self.pool, Change::row(ChangeKind::Clusters, &request.name)).await;
+
         // Return the created cluster
         self.get_by_id(&id).await
     }
 
     /// Get cluster by ID
     pub async fn get_by_id(&self, id: &str) -> Result<ClusterData> {
-        let row = sqlx::query_as::<Sqlite, ClusterRow>(
-            "SELECT id, name, service_name, configuration, version, created_at, updated_at FROM clusters WHERE id = $1"
-        )
-        .bind(id)
-        .fetch_optional(&self.pool)
-        .await
+        let row = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, ClusterRow>(
+                "SELECT id, name, service_name, configuration, version, created_at, updated_at FROM clusters WHERE id = $1"
+            )
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+        })
         .map_err(|e| {
             tracing::error!(error = %e, cluster_id = %id, "Failed to get cluster by ID");
             FlowplaneError::Database {
@@ -715,12 +766,14 @@ impl ClusterRepository {
 
     /// Get cluster by name
     pub async fn get_by_name(&self, name: &str) -> Result<ClusterData> {
-        let row = sqlx::query_as::<Sqlite, ClusterRow>(
-            "SELECT id, name, service_name, configuration, version, created_at, updated_at FROM clusters WHERE name = $1 ORDER BY version DESC LIMIT 1"
-        )
-        .bind(name)
-        .fetch_optional(&self.pool)
-        .await
+        let row = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, ClusterRow>(
+                "SELECT id, name, service_name, configuration, version, created_at, updated_at FROM clusters WHERE name = $1 ORDER BY version DESC LIMIT 1"
+            )
+            .bind(name)
+            .fetch_optional(pool)
+            .await
+        })
         .map_err(|e| {
             tracing::error!(error = %e, cluster_name = %name, "Failed to get cluster by name");
             FlowplaneError::Database {
@@ -742,13 +795,15 @@ impl ClusterRepository {
         let limit = limit.unwrap_or(100).min(1000); // Max 1000 results
         let offset = offset.unwrap_or(0);
 
-        let rows = sqlx::query_as::<Sqlite, ClusterRow>(
-            "SELECT id, name, service_name, configuration, version, created_at, updated_at FROM clusters ORDER BY created_at DESC LIMIT $1 OFFSET $2"
-        )
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(&self.pool)
-        .await
+        let rows = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, ClusterRow>(
+                "SELECT id, name, service_name, configuration, version, created_at, updated_at FROM clusters ORDER BY created_at DESC LIMIT $1 OFFSET $2"
+            )
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(pool)
+            .await
+        })
         .map_err(|e| {
             tracing::error!(error = %e, "Failed to list clusters");
             FlowplaneError::Database {
@@ -777,16 +832,19 @@ impl ClusterRepository {
         let now = chrono::Utc::now();
         let new_version = current.version + 1;
 
-        let result = sqlx::query(
-            "UPDATE clusters SET service_name = $1, configuration = $2, version = $3, updated_at = $4 WHERE id = $5"
-        )
-        .bind(&new_service_name)
-        .bind(&new_configuration)
-        .bind(new_version)
-        .bind(now)
-        .bind(id)
-        .execute(&self.pool)
-        .await
+        let rows_affected = with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                "UPDATE clusters SET service_name = $1, configuration = $2, version = $3, updated_at = $4 WHERE id = $5"
+            )
+            .bind(&new_service_name)
+            .bind(&new_configuration)
+            .bind(new_version)
+            .bind(now)
+            .bind(id)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .map_err(|e| {
             tracing::error!(error = %e, cluster_id = %id, "Failed to update cluster");
             FlowplaneError::Database {
@@ -795,7 +853,7 @@ impl ClusterRepository {
             }
         })?;
 
-        if result.rows_affected() == 0 {
+        if rows_affected == 0 {
             return Err(FlowplaneError::not_found(format!("Cluster with ID '{}' not found", id)));
         }
 
@@ -806,6 +864,8 @@ impl ClusterRepository {
             "Updated cluster"
         );
 
+        self.notifier.publish(&self.pool, Change::row(ChangeKind::Clusters, &current.name)).await;
+
         // Return the updated cluster
         self.get_by_id(id).await
     }
@@ -815,19 +875,22 @@ impl ClusterRepository {
         // Check if cluster exists first
         let cluster = self.get_by_id(id).await?;
 
-        let result = sqlx::query("DELETE FROM clusters WHERE id = $1")
-            .bind(id)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| {
-                tracing::error!(error = %e, cluster_id = %id, "Failed to delete cluster");
-                FlowplaneError::Database {
-                    source: e,
-                    context: format!("Failed to delete cluster with ID '{}'", id),
-                }
-            })?;
+        let rows_affected = with_pool!(&self.pool, |pool| {
+            sqlx::query("DELETE FROM clusters WHERE id = $1")
+                .bind(id)
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+        })
+        .map_err(|e| {
+            tracing::error!(error = %e, cluster_id = %id, "Failed to delete cluster");
+            FlowplaneError::Database {
+                source: e,
+                context: format!("Failed to delete cluster with ID '{}'", id),
+            }
+        })?;
 
-        if result.rows_affected() == 0 {
+        if rows_affected == 0 {
             return Err(FlowplaneError::not_found(format!("Cluster with ID '{}' not found", id)));
         }
 
@@ -837,54 +900,61 @@ impl ClusterRepository {
             "Deleted cluster"
         );
 
+        self.notifier.publish(&self.pool, Change::row(ChangeKind::Clusters, &cluster.name)).await;
         Ok(())
     }
 
     /// Check if cluster exists by name
     pub async fn exists_by_name(&self, name: &str) -> Result<bool> {
-        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM clusters WHERE name = $1")
-            .bind(name)
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|e| {
-                tracing::error!(error = %e, cluster_name = %name, "Failed to check cluster existence");
-                FlowplaneError::Database {
-                    source: e,
-                    context: format!("Failed to check existence of cluster '{}'", name),
-                }
-            })?;
+        let count: i64 = with_pool!(&self.pool, |pool| {
+            sqlx::query_scalar("SELECT COUNT(*) FROM clusters WHERE name = $1")
+                .bind(name)
+                .fetch_one(pool)
+                .await
+        })
+        .map_err(|e| {
+            tracing::error!(error = %e, cluster_name = %name, "Failed to check cluster existence");
+            FlowplaneError::Database {
+                source: e,
+                context: format!("Failed to check existence of cluster '{}'", name),
+            }
+        })?;
 
         Ok(count > 0)
     }
 
     pub async fn delete_by_name(&self, name: &str) -> Result<()> {
-        sqlx::query("DELETE FROM clusters WHERE name = $1")
-            .bind(name)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| {
-                tracing::error!(error = %e, cluster_name = %name, "Failed to delete cluster by name");
-                FlowplaneError::Database {
-                    source: e,
-                    context: format!("Failed to delete cluster '{}'", name),
-                }
-            })?;
+        with_pool!(&self.pool, |pool| {
+            sqlx::query("DELETE FROM clusters WHERE name = $1")
+                .bind(name)
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+        })
+        .map_err(|e| {
+            tracing::error!(error = %e, cluster_name = %name, "Failed to delete cluster by name");
+            FlowplaneError::Database {
+                source: e,
+                context: format!("Failed to delete cluster '{}'", name),
+            }
+        })?;
 
+        self.notifier.publish(&self.pool, Change::row(ChangeKind::Clusters, name)).await;
         Ok(())
     }
 
     /// Get cluster count
     pub async fn count(&self) -> Result<i64> {
-        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM clusters")
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|e| {
-                tracing::error!(error = %e, "Failed to get cluster count");
-                FlowplaneError::Database {
-                    source: e,
-                    context: "Failed to get cluster count".to_string(),
-                }
-            })?;
+        let count: i64 = with_pool!(&self.pool, |pool| {
+            sqlx::query_scalar("SELECT COUNT(*) FROM clusters").fetch_one(pool).await
+        })
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to get cluster count");
+            FlowplaneError::Database {
+                source: e,
+                context: "Failed to get cluster count".to_string(),
+            }
+        })?;
 
         Ok(count)
     }
@@ -899,11 +969,14 @@ impl ClusterRepository {
 #[derive(Debug, Clone)]
 pub struct ListenerRepository {
     pool: DbPool,
+    notifier: ChangeNotifier,
 }
 
 impl ListenerRepository {
-    pub fn new(pool: DbPool) -> Self {
-        Self { pool }
+    /// Create a repository that publishes committed writes to `notifier`, the
+    /// change bus shared with the xDS cache
+    pub fn new(pool: DbPool, notifier: ChangeNotifier) -> Self {
+        Self { pool, notifier }
     }
 
     pub async fn create(&self, request: CreateListenerRequest) -> Result<ListenerData> {
@@ -914,19 +987,22 @@ impl ListenerRepository {
         let now = chrono::Utc::now();
         let protocol = request.protocol.unwrap_or_else(|| "HTTP".to_string());
 
-        let result = sqlx::query(
-            "INSERT INTO listeners (id, name, address, port, protocol, configuration, version, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6, 1, $7, $8)"
-        )
-        .bind(&id)
-        .bind(&request.name)
-        .bind(&request.address)
-        .bind(request.port)
-        .bind(&protocol)
-        .bind(&configuration_json)
-        .bind(now)
-        .bind(now)
-        .execute(&self.pool)
-        .await
+        let rows_affected = with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                "INSERT INTO listeners (id, name, address, port, protocol, configuration, version, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6, 1, $7, $8)"
+            )
+            .bind(&id)
+            .bind(&request.name)
+            .bind(&request.address)
+            .bind(request.port)
+            .bind(&protocol)
+            .bind(&configuration_json)
+            .bind(now)
+            .bind(now)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .map_err(|e| {
             tracing::error!(error = %e, listener_name = %request.name, "Failed to create listener");
             FlowplaneError::Database {
@@ -935,24 +1011,27 @@ impl ListenerRepository {
             }
         })?;
 
-        if result.rows_affected() == 0 {
+        if rows_affected == 0 {
             return Err(FlowplaneError::validation("Failed to create listener"));
         }
 
         tracing::info!(listener_id = %id, listener_name = %request.name, "Created new listener");
 
+        self.notifier.publish(&self.pool, Change::row(ChangeKind::Listeners, &request.name)).await;
         self.get_by_id(&id).await
     }
 
     // removed create_tx variant to avoid cross-transaction complexity; use create()
 
     pub async fn get_by_id(&self, id: &str) -> Result<ListenerData> {
-        let row = sqlx::query_as::<Sqlite, ListenerRow>(
-            "SELECT id, name, address, port, protocol, configuration, version, created_at, updated_at FROM listeners WHERE id = $1"
-        )
-        .bind(id)
-        .fetch_optional(&self.pool)
-        .await
+        let row = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, ListenerRow>(
+                "SELECT id, name, address, port, protocol, configuration, version, created_at, updated_at FROM listeners WHERE id = $1"
+            )
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+        })
         .map_err(|e| {
             tracing::error!(error = %e, listener_id = %id, "Failed to get listener by ID");
             FlowplaneError::Database {
@@ -968,12 +1047,14 @@ impl ListenerRepository {
     }
 
     pub async fn get_by_name(&self, name: &str) -> Result<ListenerData> {
-        let row = sqlx::query_as::<Sqlite, ListenerRow>(
-            "SELECT id, name, address, port, protocol, configuration, version, created_at, updated_at FROM listeners WHERE name = $1 ORDER BY version DESC LIMIT 1"
-        )
-        .bind(name)
-        .fetch_optional(&self.pool)
-        .await
+        let row = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, ListenerRow>(
+                "SELECT id, name, address, port, protocol, configuration, version, created_at, updated_at FROM listeners WHERE name = $1 ORDER BY version DESC LIMIT 1"
+            )
+            .bind(name)
+            .fetch_optional(pool)
+            .await
+        })
         .map_err(|e| {
             tracing::error!(error = %e, listener_name = %name, "Failed to get listener by name");
             FlowplaneError::Database {
@@ -994,13 +1075,15 @@ impl ListenerRepository {
         let limit = limit.unwrap_or(100).min(1000);
         let offset = offset.unwrap_or(0);
 
-        let rows = sqlx::query_as::<Sqlite, ListenerRow>(
-            "SELECT id, name, address, port, protocol, configuration, version, created_at, updated_at FROM listeners ORDER BY created_at DESC LIMIT $1 OFFSET $2"
-        )
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(&self.pool)
-        .await
+        let rows = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, ListenerRow>(
+                "SELECT id, name, address, port, protocol, configuration, version, created_at, updated_at FROM listeners ORDER BY created_at DESC LIMIT $1 OFFSET $2"
+            )
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(pool)
+            .await
+        })
         .map_err(|e| {
             tracing::error!(error = %e, "Failed to list listeners");
             FlowplaneError::Database {
@@ -1037,18 +1120,21 @@ impl ListenerRepository {
         let now = chrono::Utc::now();
         let new_version = current.version + 1;
 
-        let result = sqlx::query(
-            "UPDATE listeners SET address = $1, port = $2, protocol = $3, configuration = $4, version = $5, updated_at = $6 WHERE id = $7"
-        )
-        .bind(&new_address)
-        .bind(new_port)
-        .bind(&new_protocol)
-        .bind(&new_configuration)
-        .bind(new_version)
-        .bind(now)
-        .bind(id)
-        .execute(&self.pool)
-        .await
+        let rows_affected = with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                "UPDATE listeners SET address = $1, port = $2, protocol = $3, configuration = $4, version = $5, updated_at = $6 WHERE id = $7"
+            )
+            .bind(&new_address)
+            .bind(new_port)
+            .bind(&new_protocol)
+            .bind(&new_configuration)
+            .bind(new_version)
+            .bind(now)
+            .bind(id)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .map_err(|e| {
             tracing::error!(error = %e, listener_id = %id, "Failed to update listener");
             FlowplaneError::Database {
@@ -1057,45 +1143,53 @@ impl ListenerRepository {
             }
         })?;
 
-        if result.rows_affected() == 0 {
+        if rows_affected == 0 {
             return Err(FlowplaneError::not_found(format!("Listener with ID '{}' not found", id)));
         }
 
         tracing::info!(listener_id = %id, listener_name = %current_name, new_version = new_version, "Updated listener");
 
+        self.notifier.publish(&self.pool, Change::row(ChangeKind::Listeners, &current_name)).await;
         self.get_by_id(id).await
     }
 
     pub async fn delete(&self, id: &str) -> Result<()> {
         let listener = self.get_by_id(id).await?;
 
-        let result = sqlx::query("DELETE FROM listeners WHERE id = $1")
-            .bind(id)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| {
-                tracing::error!(error = %e, listener_id = %id, "Failed to delete listener");
-                FlowplaneError::Database {
-                    source: e,
-                    context: format!("Failed to delete listener with ID '{}'", id),
-                }
-            })?;
+        let rows_affected = with_pool!(&self.pool, |pool| {
+            sqlx::query("DELETE FROM listeners WHERE id = $1")
+                .bind(id)
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+        })
+        .map_err(|e| {
+            tracing::error!(error = %e, listener_id = %id, "Failed to delete listener");
+            FlowplaneError::Database {
+                source: e,
+                context: format!("Failed to delete listener with ID '{}'", id),
+            }
+        })?;
 
-        if result.rows_affected() == 0 {
+        if rows_affected == 0 {
             return Err(FlowplaneError::not_found(format!("Listener with ID '{}' not found", id)));
         }
 
         tracing::info!(listener_id = %id, listener_name = %listener.name, "Deleted listener");
 
+        self.notifier.publish(&self.pool, Change::row(ChangeKind::Listeners, &listener.name)).await;
         Ok(())
     }
 
     pub async fn delete_by_name(&self, name: &str) -> Result<()> {
-        sqlx::query("DELETE FROM listeners WHERE name = $1")
-            .bind(name)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| {
+        with_pool!(&self.pool, |pool| {
+            sqlx::query("DELETE FROM listeners WHERE name = $1")
+                .bind(name)
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+        })
+        .map_err(|e| {
             tracing::error!(error = %e, listener_name = %name, "Failed to delete listener by name");
             FlowplaneError::Database {
                 source: e,
@@ -1103,14 +1197,17 @@ impl ListenerRepository {
             }
         })?;
 
+        self.notifier.publish(&self.pool, Change::row(ChangeKind::Listeners, name)).await;
         Ok(())
     }
 
     pub async fn exists_by_name(&self, name: &str) -> Result<bool> {
-        let count = sqlx::query_scalar::<Sqlite, i64>("SELECT COUNT(*) FROM listeners WHERE name = $1")
-            .bind(name)
-            .fetch_one(&self.pool)
-            .await
+        let count = with_pool!(&self.pool, |pool| {
+            sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM listeners WHERE name = $1")
+                .bind(name)
+                .fetch_one(pool)
+                .await
+        })
             .map_err(|e| {
                 tracing::error!(error = %e, listener_name = %name, "Failed to check listener existence");
                 FlowplaneError::Database {
@@ -1123,16 +1220,16 @@ impl ListenerRepository {
     }
 
     pub async fn count(&self) -> Result<i64> {
-        let count = sqlx::query_scalar::<Sqlite, i64>("SELECT COUNT(*) FROM listeners")
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|e| {
-                tracing::error!(error = %e, "Failed to get listener count");
-                FlowplaneError::Database {
-                    source: e,
-                    context: "Failed to get listener count".to_string(),
-                }
-            })?;
+        let count = with_pool!(&self.pool, |pool| {
+            sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM listeners").fetch_one(pool).await
+        })
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to get listener count");
+            FlowplaneError::Database {
+                source: e,
+                context: "Failed to get listener count".to_string(),
+            }
+        })?;
 
         Ok(count)
     }
@@ -1146,11 +1243,14 @@ impl ListenerRepository {
 #[derive(Debug, Clone)]
 pub struct RouteRepository {
     pool: DbPool,
+    notifier: ChangeNotifier,
 }
 
 impl RouteRepository {
-    pub fn new(pool: DbPool) -> Self {
-        Self { pool }
+    /// Create a repository that publishes committed writes to `notifier`, the
+    /// change bus shared with the xDS cache
+    pub fn new(pool: DbPool, notifier: ChangeNotifier) -> Self {
+        Self { pool, notifier }
     }
 
     pub async fn create(&self, request: CreateRouteRequest) -> Result<RouteData> {
@@ -1160,18 +1260,21 @@ impl RouteRepository {
         })?;
         let now = chrono::Utc::now();
 
-        let result = sqlx::query(
-            "INSERT INTO routes (id, name, path_prefix, cluster_name, configuration, version, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, 1, $6, $7)"
-        )
-        .bind(&id)
-        .bind(&request.name)
-        .bind(&request.path_prefix)
-        .bind(&request.cluster_name)
-        .bind(&configuration_json)
-        .bind(now)
-        .bind(now)
-        .execute(&self.pool)
-        .await
+        let rows_affected = with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                "INSERT INTO routes (id, name, path_prefix, cluster_name, configuration, version, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, 1, $6, $7)"
+            )
+            .bind(&id)
+            .bind(&request.name)
+            .bind(&request.path_prefix)
+            .bind(&request.cluster_name)
+            .bind(&configuration_json)
+            .bind(now)
+            .bind(now)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .map_err(|e| {
             tracing::error!(error = %e, route_name = %request.name, "Failed to create route");
             FlowplaneError::Database {
@@ -1180,22 +1283,25 @@ impl RouteRepository {
             }
         })?;
 
-        if result.rows_affected() == 0 {
+        if rows_affected == 0 {
             return Err(FlowplaneError::validation("Failed to create route"));
         }
 
         tracing::info!(route_id = %id, route_name = %request.name, "Created new route");
 
+        self.notifier.publish(&self.pool, Change::row(ChangeKind::Routes, &request.name)).await;
         self.get_by_id(&id).await
     }
 
     pub async fn get_by_id(&self, id: &str) -> Result<RouteData> {
-        let row = sqlx::query_as::<Sqlite, RouteRow>(
-            "SELECT id, name, path_prefix, cluster_name, configuration, version, created_at, updated_at FROM routes WHERE id = $1"
-        )
-        .bind(id)
-        .fetch_optional(&self.pool)
-        .await
+        let row = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, RouteRow>(
+                "SELECT id, name, path_prefix, cluster_name, configuration, version, created_at, updated_at FROM routes WHERE id = $1"
+            )
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+        })
         .map_err(|e| {
             tracing::error!(error = %e, route_id = %id, "Failed to get route by ID");
             FlowplaneError::Database {
@@ -1211,12 +1317,14 @@ impl RouteRepository {
     }
 
     pub async fn get_by_name(&self, name: &str) -> Result<RouteData> {
-        let row = sqlx::query_as::<Sqlite, RouteRow>(
-            "SELECT id, name, path_prefix, cluster_name, configuration, version, created_at, updated_at FROM routes WHERE name = $1 ORDER BY version DESC LIMIT 1"
-        )
-        .bind(name)
-        .fetch_optional(&self.pool)
-        .await
+        let row = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, RouteRow>(
+                "SELECT id, name, path_prefix, cluster_name, configuration, version, created_at, updated_at FROM routes WHERE name = $1 ORDER BY version DESC LIMIT 1"
+            )
+            .bind(name)
+            .fetch_optional(pool)
+            .await
+        })
         .map_err(|e| {
             tracing::error!(error = %e, route_name = %name, "Failed to get route by name");
             FlowplaneError::Database {
@@ -1232,12 +1340,12 @@ impl RouteRepository {
     }
 
     pub async fn exists_by_name(&self, name: &str) -> Result<bool> {
-        let count = sqlx::query_scalar::<Sqlite, i64>(
-            "SELECT COUNT(*) FROM routes WHERE name = $1",
-        )
-        .bind(name)
-        .fetch_one(&self.pool)
-        .await
+        let count = with_pool!(&self.pool, |pool| {
+            sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM routes WHERE name = $1")
+                .bind(name)
+                .fetch_one(pool)
+                .await
+        })
         .map_err(|e| {
             tracing::error!(error = %e, route_name = %name, "Failed to check route existence");
             FlowplaneError::Database {
@@ -1253,13 +1361,15 @@ impl RouteRepository {
         let limit = limit.unwrap_or(100).min(1000);
         let offset = offset.unwrap_or(0);
 
-        let rows = sqlx::query_as::<Sqlite, RouteRow>(
-            "SELECT id, name, path_prefix, cluster_name, configuration, version, created_at, updated_at FROM routes ORDER BY created_at DESC LIMIT $1 OFFSET $2"
-        )
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(&self.pool)
-        .await
+        let rows = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, RouteRow>(
+                "SELECT id, name, path_prefix, cluster_name, configuration, version, created_at, updated_at FROM routes ORDER BY created_at DESC LIMIT $1 OFFSET $2"
+            )
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(pool)
+            .await
+        })
         .map_err(|e| {
             tracing::error!(error = %e, "Failed to list routes");
             FlowplaneError::Database {
@@ -1287,76 +1397,1014 @@ impl RouteRepository {
         let now = chrono::Utc::now();
         let new_version = current.version + 1;
 
-        let result = sqlx::query(
-            "UPDATE routes SET path_prefix = $1, cluster_name = $2, configuration = $3, version = $4, updated_at = $5 WHERE id = $6"
-        )
-        .bind(&new_path_prefix)
-        .bind(&new_cluster_name)
-        .bind(&new_configuration)
-        .bind(new_version)
-        .bind(now)
-        .bind(id)
-        .execute(&self.pool)
-        .await
-        .map_err(|e| {
-            tracing::error!(error = %e, route_id = %id, "Failed to update route");
+        let rows_affected = with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                "UPDATE routes SET path_prefix = $1, cluster_name = $2, configuration = $3, version = $4, updated_at = $5 WHERE id = $6"
+            )
+            .bind(&new_path_prefix)
+            .bind(&new_cluster_name)
+            .bind(&new_configuration)
+            .bind(new_version)
+            .bind(now)
+            .bind(id)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
+        .map_err(|e| {
+            tracing::error!(error = %e, route_id = %id, "Failed to update route");
             FlowplaneError::Database {
                 source: e,
                 context: format!("Failed to update route with ID '{}'", id),
             }
         })?;
 
-        if result.rows_affected() == 0 {
-            return Err(FlowplaneError::not_found(format!("Route with ID '{}' not found", id)));
-        }
+        if rows_affected == 0 {
+            return Err(FlowplaneError::not_found(format!("Route with ID '{}' not found", id)));
+        }
+
+        tracing::info!(route_id = %id, route_name = %current.name, new_version = new_version, "Updated route");
+
+        self.notifier.publish(&self.pool, Change::row(ChangeKind::Routes, &current.name)).await;
+        self.get_by_id(id).await
+    }
+
+    pub async fn delete(&self, id: &str) -> Result<()> {
+        let route = self.get_by_id(id).await?;
+
+        let rows_affected = with_pool!(&self.pool, |pool| {
+            sqlx::query("DELETE FROM routes WHERE id = $1")
+                .bind(id)
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+        })
+        .map_err(|e| {
+            tracing::error!(error = %e, route_id = %id, "Failed to delete route");
+            FlowplaneError::Database {
+                source: e,
+                context: format!("Failed to delete route with ID '{}'", id),
+            }
+        })?;
+
+        if rows_affected == 0 {
+            return Err(FlowplaneError::not_found(format!("Route with ID '{}' not found", id)));
+        }
+
+        tracing::info!(route_id = %id, route_name = %route.name, "Deleted route");
+
+        self.notifier.publish(&self.pool, Change::row(ChangeKind::Routes, &route.name)).await;
+        Ok(())
+    }
+
+    pub async fn delete_by_name(&self, name: &str) -> Result<()> {
+        with_pool!(&self.pool, |pool| {
+            sqlx::query("DELETE FROM routes WHERE name = $1")
+                .bind(name)
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+        })
+        .map_err(|e| {
+            tracing::error!(error = %e, route_name = %name, "Failed to delete route by name");
+            FlowplaneError::Database {
+                source: e,
+                context: format!("Failed to delete route '{}'", name),
+            }
+        })?;
+
+        self.notifier.publish(&self.pool, Change::row(ChangeKind::Routes, name)).await;
+        Ok(())
+    }
+
+    pub fn pool(&self) -> &DbPool {
+        &self.pool
+    }
+}
+
+/// Database row structure for SDS secrets
+#[derive(Debug, Clone, FromRow)]
+struct SecretRow {
+    pub id: String,
+    pub name: String,
+    pub certificate_chain: String,
+    pub encrypted_private_key: String,
+    pub spiffe_id: Option<String>,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub version: i64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// TLS certificate secret served over SDS. The private key stays encrypted
+/// with the control plane's [`SecretCipher`](crate::storage::SecretCipher).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecretData {
+    pub id: String,
+    pub name: String,
+    pub certificate_chain: String,
+    pub encrypted_private_key: String,
+    /// SPIFFE ID of certificates issued by the built-in CA.
+    pub spiffe_id: Option<String>,
+    /// Expiry of the leaf certificate, when known.
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub version: i64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<SecretRow> for SecretData {
+    fn from(row: SecretRow) -> Self {
+        Self {
+            id: row.id,
+            name: row.name,
+            certificate_chain: row.certificate_chain,
+            encrypted_private_key: row.encrypted_private_key,
+            spiffe_id: row.spiffe_id,
+            expires_at: row.expires_at,
+            version: row.version,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+/// Create secret request
+#[derive(Debug, Clone)]
+pub struct CreateSecretRequest {
+    pub name: String,
+    pub certificate_chain: String,
+    pub encrypted_private_key: String,
+    pub spiffe_id: Option<String>,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Replace the certificate and key of an existing secret
+#[derive(Debug, Clone)]
+pub struct UpdateSecretRequest {
+    pub certificate_chain: String,
+    pub encrypted_private_key: String,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Repository for SDS secrets
+#[derive(Debug, Clone)]
+pub struct SecretRepository {
+    pool: DbPool,
+    notifier: ChangeNotifier,
+}
+
+impl SecretRepository {
+    /// Create a repository that publishes committed writes to `notifier`, the
+    /// change bus shared with the xDS cache
+    pub fn new(pool: DbPool, notifier: ChangeNotifier) -> Self {
+        Self { pool, notifier }
+    }
+
+    /// Create a new secret
+    pub async fn create(&self, request: CreateSecretRequest) -> Result<SecretData> {
+        let id = Uuid::new_v4().to_string();
+        let now = chrono::Utc::now();
+
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                "INSERT INTO secrets (id, name, certificate_chain, encrypted_private_key, spiffe_id, expires_at, version, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6, 1, $7, $8)"
+            )
+            .bind(&id)
+            .bind(&request.name)
+            .bind(&request.certificate_chain)
+            .bind(&request.encrypted_private_key)
+            .bind(&request.spiffe_id)
+            .bind(request.expires_at)
+            .bind(now)
+            .bind(now)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
+        .map_err(|e| {
+            tracing::error!(error = %e, secret_name = %request.name, "Failed to create secret");
+            FlowplaneError::Database {
+                source: e,
+                context: format!("Failed to create secret '{}'", request.name),
+            }
+        })?;
+
+        tracing::info!(secret_id = %id, secret_name = %request.name, "Created new secret");
+
+        self.notifier.publish(&self.pool, Change::row(ChangeKind::Secrets, &request.name)).await;
+
+        self.get_by_name(&request.name).await
+    }
+
+    /// Get secret by name
+    pub async fn get_by_name(&self, name: &str) -> Result<SecretData> {
+        let row = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, SecretRow>(
+                "SELECT id, name, certificate_chain, encrypted_private_key, spiffe_id, expires_at, version, created_at, updated_at FROM secrets WHERE name = $1"
+            )
+            .bind(name)
+            .fetch_optional(pool)
+            .await
+        })
+        .map_err(|e| {
+            tracing::error!(error = %e, secret_name = %name, "Failed to get secret by name");
+            FlowplaneError::Database {
+                source: e,
+                context: format!("Failed to get secret with name '{}'", name),
+            }
+        })?;
+
+        match row {
+            Some(row) => Ok(SecretData::from(row)),
+            None => {
+                Err(FlowplaneError::not_found(format!("Secret with name '{}' not found", name)))
+            }
+        }
+    }
+
+    /// List secrets ordered by name
+    pub async fn list(&self, limit: Option<i32>, offset: Option<i32>) -> Result<Vec<SecretData>> {
+        let limit = limit.unwrap_or(100).min(1000);
+        let offset = offset.unwrap_or(0);
+
+        let rows = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, SecretRow>(
+                "SELECT id, name, certificate_chain, encrypted_private_key, spiffe_id, expires_at, version, created_at, updated_at FROM secrets ORDER BY name LIMIT $1 OFFSET $2"
+            )
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(pool)
+            .await
+        })
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to list secrets");
+            FlowplaneError::Database { source: e, context: "Failed to list secrets".to_string() }
+        })?;
+
+        Ok(rows.into_iter().map(SecretData::from).collect())
+    }
+
+    /// Rotate a secret by replacing its certificate chain and key
+    pub async fn update(&self, name: &str, request: UpdateSecretRequest) -> Result<SecretData> {
+        let current = self.get_by_name(name).await?;
+        let now = chrono::Utc::now();
+        let new_version = current.version + 1;
+
+        let rows_affected = with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                "UPDATE secrets SET certificate_chain = $1, encrypted_private_key = $2, expires_at = $3, version = $4, updated_at = $5 WHERE id = $6"
+            )
+            .bind(&request.certificate_chain)
+            .bind(&request.encrypted_private_key)
+            .bind(request.expires_at)
+            .bind(new_version)
+            .bind(now)
+            .bind(&current.id)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
+        .map_err(|e| {
+            tracing::error!(error = %e, secret_name = %name, "Failed to update secret");
+            FlowplaneError::Database {
+                source: e,
+                context: format!("Failed to update secret '{}'", name),
+            }
+        })?;
+
+        if rows_affected == 0 {
+            return Err(FlowplaneError::not_found(format!(
+                "Secret with name '{}' not found",
+                name
+            )));
+        }
+
+        tracing::info!(secret_name = %name, new_version = new_version, "Rotated secret");
+
+        self.notifier.publish(&self.pool, Change::row(ChangeKind::Secrets, name)).await;
+
+        self.get_by_name(name).await
+    }
+
+    /// Certificates issued by the built-in CA that expire before `cutoff`
+    pub async fn list_issued_expiring_before(
+        &self,
+        cutoff: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<SecretData>> {
+        let rows = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, SecretRow>(
+                "SELECT id, name, certificate_chain, encrypted_private_key, spiffe_id, expires_at, version, created_at, updated_at FROM secrets WHERE spiffe_id IS NOT NULL AND expires_at < $1 ORDER BY expires_at"
+            )
+            .bind(cutoff)
+            .fetch_all(pool)
+            .await
+        })
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to list expiring certificates");
+            FlowplaneError::Database {
+                source: e,
+                context: "Failed to list expiring certificates".to_string(),
+            }
+        })?;
+
+        Ok(rows.into_iter().map(SecretData::from).collect())
+    }
+
+    /// Delete secret by name
+    pub async fn delete_by_name(&self, name: &str) -> Result<()> {
+        let rows_affected = with_pool!(&self.pool, |pool| {
+            sqlx::query("DELETE FROM secrets WHERE name = $1")
+                .bind(name)
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+        })
+        .map_err(|e| {
+            tracing::error!(error = %e, secret_name = %name, "Failed to delete secret");
+            FlowplaneError::Database {
+                source: e,
+                context: format!("Failed to delete secret '{}'", name),
+            }
+        })?;
+
+        if rows_affected == 0 {
+            return Err(FlowplaneError::not_found(format!(
+                "Secret with name '{}' not found",
+                name
+            )));
+        }
+
+        tracing::info!(secret_name = %name, "Deleted secret");
+
+        self.notifier.publish(&self.pool, Change::row(ChangeKind::Secrets, name)).await;
+        Ok(())
+    }
+}
+
+/// Database row structure for runtime keys
+#[derive(Debug, Clone, FromRow)]
+struct RuntimeKeyRow {
+    pub id: String,
+    pub key: String,
+    pub value: String,
+    pub version: i64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Runtime key served in the RTDS runtime layer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuntimeKeyData {
+    pub id: String,
+    pub key: String,
+    pub value: String, // JSON serialized
+    pub version: i64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<RuntimeKeyRow> for RuntimeKeyData {
+    fn from(row: RuntimeKeyRow) -> Self {
+        Self {
+            id: row.id,
+            key: row.key,
+            value: row.value,
+            version: row.version,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+/// Create or replace a runtime key
+#[derive(Debug, Clone)]
+pub struct SetRuntimeKeyRequest {
+    pub key: String,
+    pub value: String,
+}
+
+/// Repository for runtime keys
+#[derive(Debug, Clone)]
+pub struct RuntimeRepository {
+    pool: DbPool,
+    notifier: ChangeNotifier,
+}
+
+impl RuntimeRepository {
+    /// Create a repository that publishes committed writes to `notifier`, the
+    /// change bus shared with the xDS cache
+    pub fn new(pool: DbPool, notifier: ChangeNotifier) -> Self {
+        Self { pool, notifier }
+    }
+
+    /// Set a runtime key, creating it or replacing its value
+    pub async fn set(&self, request: SetRuntimeKeyRequest) -> Result<RuntimeKeyData> {
+        let now = chrono::Utc::now();
+
+        let rows_affected = match self.find_by_key(&request.key).await? {
+            Some(current) => with_pool!(&self.pool, |pool| {
+                sqlx::query(
+                    "UPDATE runtime_keys SET value = $1, version = $2, updated_at = $3 WHERE id = $4",
+                )
+                .bind(&request.value)
+                .bind(current.version + 1)
+                .bind(now)
+                .bind(&current.id)
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+            }),
+            None => with_pool!(&self.pool, |pool| {
+                sqlx::query(
+                    "INSERT INTO runtime_keys (id, key, value, version, created_at, updated_at) VALUES ($1, $2, $3, 1, $4, $5)"
+                )
+                .bind(Uuid::new_v4().to_string())
+                .bind(&request.key)
+                .bind(&request.value)
+                .bind(now)
+                .bind(now)
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+            }),
+        }
+        .map_err(|e| {
+            tracing::error!(error = %e, runtime_key = %request.key, "Failed to set runtime key");
+            FlowplaneError::Database {
+                source: e,
+                context: format!("Failed to set runtime key '{}'", request.key),
+            }
+        })?;
+
+        if rows_affected == 0 {
+            return Err(FlowplaneError::not_found(format!(
+                "Runtime key '{}' not found",
+                request.key
+            )));
+        }
+
+        tracing::info!(runtime_key = %request.key, "Set runtime key");
+
+        self.notifier.publish(&self.pool, Change::row(ChangeKind::Runtime, &request.key)).await;
+
+        self.get_by_key(&request.key).await
+    }
+
+    /// Get runtime key, if it is set
+    pub async fn find_by_key(&self, key: &str) -> Result<Option<RuntimeKeyData>> {
+        let row = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, RuntimeKeyRow>(
+                "SELECT id, key, value, version, created_at, updated_at FROM runtime_keys WHERE key = $1"
+            )
+            .bind(key)
+            .fetch_optional(pool)
+            .await
+        })
+        .map_err(|e| {
+            tracing::error!(error = %e, runtime_key = %key, "Failed to get runtime key");
+            FlowplaneError::Database {
+                source: e,
+                context: format!("Failed to get runtime key '{}'", key),
+            }
+        })?;
+
+        Ok(row.map(RuntimeKeyData::from))
+    }
+
+    /// Get runtime key
+    pub async fn get_by_key(&self, key: &str) -> Result<RuntimeKeyData> {
+        self.find_by_key(key)
+            .await?
+            .ok_or_else(|| FlowplaneError::not_found(format!("Runtime key '{}' not found", key)))
+    }
+
+    /// List runtime keys ordered by key
+    pub async fn list(
+        &self,
+        limit: Option<i32>,
+        offset: Option<i32>,
+    ) -> Result<Vec<RuntimeKeyData>> {
+        let limit = limit.unwrap_or(100).min(1000);
+        let offset = offset.unwrap_or(0);
+
+        let rows = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, RuntimeKeyRow>(
+                "SELECT id, key, value, version, created_at, updated_at FROM runtime_keys ORDER BY key LIMIT $1 OFFSET $2"
+            )
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(pool)
+            .await
+        })
+        .map_err(|e| {
+            tracing::error!(error = %e, "Failed to list runtime keys");
+            FlowplaneError::Database {
+                source: e,
+                context: "Failed to list runtime keys".to_string(),
+            }
+        })?;
+
+        Ok(rows.into_iter().map(RuntimeKeyData::from).collect())
+    }
+
+    /// Delete runtime key
+    pub async fn delete_by_key(&self, key: &str) -> Result<()> {
+        let rows_affected = with_pool!(&self.pool, |pool| {
+            sqlx::query("DELETE FROM runtime_keys WHERE key = $1")
+                .bind(key)
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+        })
+        .map_err(|e| {
+            tracing::error!(error = %e, runtime_key = %key, "Failed to delete runtime key");
+            FlowplaneError::Database {
+                source: e,
+                context: format!("Failed to delete runtime key '{}'", key),
+            }
+        })?;
+
+        if rows_affected == 0 {
+            return Err(FlowplaneError::not_found(format!("Runtime key '{}' not found", key)));
+        }
+
+        tracing::info!(runtime_key = %key, "Deleted runtime key");
+
+        self.notifier.publish(&self.pool, Change::row(ChangeKind::Runtime, key)).await;
+        Ok(())
+    }
+}
+
+/// Database row structure for the built-in certificate authority
+#[derive(Debug, Clone, FromRow)]
+struct CertificateAuthorityRow {
+    pub id: String,
+    pub trust_domain: String,
+    pub certificate: String,
+    pub encrypted_private_key: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// CA certificate and encrypted signing key for one trust domain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CertificateAuthorityData {
+    pub id: String,
+    pub trust_domain: String,
+    pub certificate: String,
+    pub encrypted_private_key: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<CertificateAuthorityRow> for CertificateAuthorityData {
+    fn from(row: CertificateAuthorityRow) -> Self {
+        Self {
+            id: row.id,
+            trust_domain: row.trust_domain,
+            certificate: row.certificate,
+            encrypted_private_key: row.encrypted_private_key,
+            expires_at: row.expires_at,
+            created_at: row.created_at,
+        }
+    }
+}
+
+/// Create certificate authority request
+#[derive(Debug, Clone)]
+pub struct CreateCertificateAuthorityRequest {
+    pub trust_domain: String,
+    pub certificate: String,
+    pub encrypted_private_key: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Repository for the built-in certificate authority
+#[derive(Debug, Clone)]
+pub struct CertificateAuthorityRepository {
+    pool: DbPool,
+}
+
+impl CertificateAuthorityRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Get the CA of a trust domain, if one was created
+    pub async fn find_by_trust_domain(
+        &self,
+        trust_domain: &str,
+    ) -> Result<Option<CertificateAuthorityData>> {
+        let row = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, CertificateAuthorityRow>(
+                "SELECT id, trust_domain, certificate, encrypted_private_key, expires_at, created_at FROM certificate_authorities WHERE trust_domain = $1"
+            )
+            .bind(trust_domain)
+            .fetch_optional(pool)
+            .await
+        })
+        .map_err(|e| {
+            tracing::error!(error = %e, trust_domain = %trust_domain, "Failed to get certificate authority");
+            FlowplaneError::Database {
+                source: e,
+                context: format!("Failed to get certificate authority for '{}'", trust_domain),
+            }
+        })?;
+
+        Ok(row.map(CertificateAuthorityData::from))
+    }
+
+    /// Store a CA unless the trust domain already has one, returning the
+    /// stored CA. Replicas starting together therefore agree on one CA.
+    pub async fn create_if_absent(
+        &self,
+        request: CreateCertificateAuthorityRequest,
+    ) -> Result<CertificateAuthorityData> {
+        let id = Uuid::new_v4().to_string();
+        let now = chrono::Utc::now();
+
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                "INSERT INTO certificate_authorities (id, trust_domain, certificate, encrypted_private_key, expires_at, created_at) VALUES ($1, $2, $3, $4, $5, $6) ON CONFLICT (trust_domain) DO NOTHING"
+            )
+            .bind(&id)
+            .bind(&request.trust_domain)
+            .bind(&request.certificate)
+            .bind(&request.encrypted_private_key)
+            .bind(request.expires_at)
+            .bind(now)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
+        .map_err(|e| {
+            tracing::error!(error = %e, trust_domain = %request.trust_domain, "Failed to create certificate authority");
+            FlowplaneError::Database {
+                source: e,
+                context: format!(
+                    "Failed to create certificate authority for '{}'",
+                    request.trust_domain
+                ),
+            }
+        })?;
+
+        self.find_by_trust_domain(&request.trust_domain).await?.ok_or_else(|| {
+            FlowplaneError::internal(format!(
+                "Certificate authority for '{}' disappeared after creation",
+                request.trust_domain
+            ))
+        })
+    }
+}
+
+/// Database row structure for configuration versions
+#[derive(Debug, Clone, FromRow)]
+struct ConfigurationVersionRow {
+    pub resource_type: String,
+    pub current_version: i64,
+    pub content_hash: Option<String>,
+}
+
+/// Persisted xDS version for a single resource type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigurationVersionData {
+    pub resource_type: String,
+    pub current_version: u64,
+    pub content_hash: Option<String>,
+}
+
+impl From<ConfigurationVersionRow> for ConfigurationVersionData {
+    fn from(row: ConfigurationVersionRow) -> Self {
+        Self {
+            resource_type: row.resource_type,
+            current_version: row.current_version.max(0) as u64,
+            content_hash: row.content_hash,
+        }
+    }
+}
+
+/// Repository for per-type xDS versions stored in `configuration_versions`
+#[derive(Debug, Clone)]
+pub struct ConfigurationVersionRepository {
+    pool: DbPool,
+}
+
+impl ConfigurationVersionRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// List the stored version for every resource type
+    pub async fn list(&self) -> Result<Vec<ConfigurationVersionData>> {
+        let rows = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, ConfigurationVersionRow>(
+                "SELECT resource_type, current_version, content_hash FROM configuration_versions",
+            )
+            .fetch_all(pool)
+            .await
+        })
+        .map_err(|e| FlowplaneError::Database {
+            source: e,
+            context: "Failed to list configuration versions".to_string(),
+        })?;
+
+        Ok(rows.into_iter().map(ConfigurationVersionData::from).collect())
+    }
+
+    /// Record a new version for a resource type. Older versions never overwrite newer ones.
+    pub async fn record(
+        &self,
+        resource_type: &str,
+        version: u64,
+        content_hash: &str,
+    ) -> Result<()> {
+        let now = chrono::Utc::now();
+
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                "INSERT INTO configuration_versions (resource_type, current_version, content_hash, last_updated)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (resource_type) DO UPDATE
+             SET current_version = excluded.current_version,
+                 content_hash = excluded.content_hash,
+                 last_updated = excluded.last_updated
+             WHERE configuration_versions.current_version < excluded.current_version",
+            )
+            .bind(resource_type)
+            .bind(version as i64)
+            .bind(content_hash)
+            .bind(now)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
+        .map_err(|e| FlowplaneError::Database {
+            source: e,
+            context: format!("Failed to record configuration version for '{}'", resource_type),
+        })?;
+
+        Ok(())
+    }
+
+    /// Claim the version of a snapshot with `content_hash`. The stored version
+    /// is bumped only when the stored hash differs, so replicas building the
+    /// same snapshot agree on its version. Types without a row start at
+    /// `first_version`.
+    pub async fn claim(
+        &self,
+        resource_type: &str,
+        content_hash: &str,
+        first_version: u64,
+    ) -> Result<u64> {
+        const ATTEMPTS: usize = 3;
+
+        for _ in 0..ATTEMPTS {
+            let now = chrono::Utc::now();
+            with_pool!(&self.pool, |pool| {
+                sqlx::query(
+                    "INSERT INTO configuration_versions (resource_type, current_version, content_hash, last_updated)
+                 VALUES ($1, $2, $3, $4)
+                 ON CONFLICT (resource_type) DO UPDATE
+                 SET current_version = configuration_versions.current_version + 1,
+                     content_hash = excluded.content_hash,
+                     last_updated = excluded.last_updated
+                 WHERE configuration_versions.content_hash IS NULL
+                    OR configuration_versions.content_hash <> excluded.content_hash",
+                )
+                .bind(resource_type)
+                .bind(first_version as i64)
+                .bind(content_hash)
+                .bind(now)
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+            })
+            .map_err(|e| FlowplaneError::Database {
+                source: e,
+                context: format!("Failed to claim configuration version for '{}'", resource_type),
+            })?;
+
+            let row = with_pool!(&self.pool, |pool| {
+                sqlx::query_as::<_, ConfigurationVersionRow>(
+                    "SELECT resource_type, current_version, content_hash FROM configuration_versions WHERE resource_type = $1",
+                )
+                .bind(resource_type)
+                .fetch_optional(pool)
+                .await
+            })
+            .map_err(|e| FlowplaneError::Database {
+                source: e,
+                context: format!("Failed to read configuration version for '{}'", resource_type),
+            })?;
+
+            // Another replica may have claimed different content in between.
+            if let Some(row) = row.filter(|row| row.content_hash.as_deref() == Some(content_hash)) {
+                return Ok(ConfigurationVersionData::from(row).current_version);
+            }
+        }
+
+        Err(FlowplaneError::internal(format!(
+            "Configuration version for '{}' kept changing while claiming it; replicas disagree on its content",
+            resource_type
+        )))
+    }
+}
+
+/// Repository for leases held by control plane replicas in `control_plane_leases`
+#[derive(Debug, Clone)]
+pub struct LeaseRepository {
+    pool: DbPool,
+}
+
+impl LeaseRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Take or renew lease `name` for `holder` until `ttl` from now. Succeeds
+    /// when the lease is free, expired or already held by `holder`.
+    pub async fn try_acquire(
+        &self,
+        name: &str,
+        holder: &str,
+        ttl: std::time::Duration,
+    ) -> Result<bool> {
+        let now = chrono::Utc::now();
+        let expires_at = now
+            + chrono::Duration::from_std(ttl).map_err(|e| {
+                FlowplaneError::validation(format!("Invalid lease TTL for '{}': {}", name, e))
+            })?;
+
+        let rows_affected = with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                "INSERT INTO control_plane_leases (name, holder, expires_at, updated_at)
+             VALUES ($1, $2, $3, $4)
+             ON CONFLICT (name) DO UPDATE
+             SET holder = excluded.holder,
+                 expires_at = excluded.expires_at,
+                 updated_at = excluded.updated_at
+             WHERE control_plane_leases.holder = excluded.holder
+                OR control_plane_leases.expires_at < excluded.updated_at",
+            )
+            .bind(name)
+            .bind(holder)
+            .bind(expires_at)
+            .bind(now)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
+        .map_err(|e| FlowplaneError::Database {
+            source: e,
+            context: format!("Failed to acquire lease '{}'", name),
+        })?;
+
+        Ok(rows_affected > 0)
+    }
 
-        tracing::info!(route_id = %id, route_name = %current.name, new_version = new_version, "Updated route");
+    /// Give up lease `name` if `holder` still owns it.
+    pub async fn release(&self, name: &str, holder: &str) -> Result<()> {
+        with_pool!(&self.pool, |pool| {
+            sqlx::query("DELETE FROM control_plane_leases WHERE name = $1 AND holder = $2")
+                .bind(name)
+                .bind(holder)
+                .execute(pool)
+                .await
+                .map(|done| done.rows_affected())
+        })
+        .map_err(|e| FlowplaneError::Database {
+            source: e,
+            context: format!("Failed to release lease '{}'", name),
+        })?;
 
-        self.get_by_id(id).await
+        Ok(())
     }
+}
 
-    pub async fn delete(&self, id: &str) -> Result<()> {
-        let route = self.get_by_id(id).await?;
+/// Database row structure for shared canary rollout state
+#[derive(Debug, Clone, FromRow)]
+struct RolloutRow {
+    pub resource_type: String,
+    pub candidate_version: i64,
+    pub phase: String,
+    pub nack_count: i64,
+}
 
-        let result = sqlx::query("DELETE FROM routes WHERE id = $1")
-            .bind(id)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| {
-                tracing::error!(error = %e, route_id = %id, "Failed to delete route");
-                FlowplaneError::Database {
-                    source: e,
-                    context: format!("Failed to delete route with ID '{}'", id),
-                }
-            })?;
+/// Canary rollout state shared between replicas for one resource type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RolloutData {
+    pub resource_type: String,
+    pub candidate_version: u64,
+    /// `baking`, `promoted` or `aborted`.
+    pub phase: String,
+    pub nack_count: u64,
+}
 
-        if result.rows_affected() == 0 {
-            return Err(FlowplaneError::not_found(format!("Route with ID '{}' not found", id)));
+impl From<RolloutRow> for RolloutData {
+    fn from(row: RolloutRow) -> Self {
+        Self {
+            resource_type: row.resource_type,
+            candidate_version: row.candidate_version.max(0) as u64,
+            phase: row.phase,
+            nack_count: row.nack_count.max(0) as u64,
         }
+    }
+}
 
-        tracing::info!(route_id = %id, route_name = %route.name, "Deleted route");
+/// Repository for canary rollout decisions stored in `xds_rollouts`
+#[derive(Debug, Clone)]
+pub struct RolloutRepository {
+    pool: DbPool,
+}
 
-        Ok(())
+impl RolloutRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
     }
 
-    pub async fn delete_by_name(&self, name: &str) -> Result<()> {
-        sqlx::query("DELETE FROM routes WHERE name = $1")
-            .bind(name)
-            .execute(&self.pool)
+    /// List the shared state of every resource type that had a rollout
+    pub async fn list(&self) -> Result<Vec<RolloutData>> {
+        let rows = with_pool!(&self.pool, |pool| {
+            sqlx::query_as::<_, RolloutRow>(
+                "SELECT resource_type, candidate_version, phase, nack_count FROM xds_rollouts",
+            )
+            .fetch_all(pool)
             .await
-            .map_err(|e| {
-                tracing::error!(error = %e, route_name = %name, "Failed to delete route by name");
-                FlowplaneError::Database {
-                    source: e,
-                    context: format!("Failed to delete route '{}'", name),
-                }
-            })?;
+        })
+        .map_err(|e| FlowplaneError::Database {
+            source: e,
+            context: "Failed to list rollouts".to_string(),
+        })?;
+
+        Ok(rows.into_iter().map(RolloutData::from).collect())
+    }
+
+    /// Record that the candidate `candidate_version` was promoted or aborted
+    pub async fn record_phase(
+        &self,
+        resource_type: &str,
+        candidate_version: u64,
+        phase: &str,
+    ) -> Result<()> {
+        let now = chrono::Utc::now();
+
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                "INSERT INTO xds_rollouts (resource_type, candidate_version, phase, nack_count, updated_at)
+             VALUES ($1, $2, $3, 0, $4)
+             ON CONFLICT (resource_type) DO UPDATE
+             SET candidate_version = excluded.candidate_version,
+                 phase = excluded.phase,
+                 nack_count = CASE WHEN xds_rollouts.candidate_version = excluded.candidate_version
+                                   THEN xds_rollouts.nack_count ELSE 0 END,
+                 updated_at = excluded.updated_at",
+            )
+            .bind(resource_type)
+            .bind(candidate_version as i64)
+            .bind(phase)
+            .bind(now)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
+        .map_err(|e| FlowplaneError::Database {
+            source: e,
+            context: format!("Failed to record rollout phase for '{}'", resource_type),
+        })?;
 
         Ok(())
     }
 
-    pub fn pool(&self) -> &DbPool {
-        &self.pool
+    /// Count a NACK against the candidate `candidate_version`
+    pub async fn record_nack(&self, resource_type: &str, candidate_version: u64) -> Result<()> {
+        let now = chrono::Utc::now();
+
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                "INSERT INTO xds_rollouts (resource_type, candidate_version, phase, nack_count, updated_at)
+             VALUES ($1, $2, 'baking', 1, $3)
+             ON CONFLICT (resource_type) DO UPDATE
+             SET nack_count = CASE WHEN xds_rollouts.candidate_version = excluded.candidate_version
+                                   THEN xds_rollouts.nack_count + 1 ELSE 1 END,
+                 phase = CASE WHEN xds_rollouts.candidate_version = excluded.candidate_version
+                              THEN xds_rollouts.phase ELSE 'baking' END,
+                 candidate_version = excluded.candidate_version,
+                 updated_at = excluded.updated_at",
+            )
+            .bind(resource_type)
+            .bind(candidate_version as i64)
+            .bind(now)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
+        .map_err(|e| FlowplaneError::Database {
+            source: e,
+            context: format!("Failed to record rollout NACK for '{}'", resource_type),
+        })?;
+
+        Ok(())
     }
 }
 
@@ -1373,6 +2421,7 @@ mod tests {
             ..Default::default()
         };
         let pool = create_pool(&config).await.unwrap();
+        let sqlite = pool.as_sqlite().unwrap();
 
         // Run basic schema creation for testing
         sqlx::query(
@@ -1389,7 +2438,7 @@ mod tests {
             )
         "#,
         )
-        .execute(&pool)
+        .execute(sqlite)
         .await
         .unwrap();
 
@@ -1408,7 +2457,7 @@ mod tests {
             )
         "#,
         )
-        .execute(&pool)
+        .execute(sqlite)
         .await
         .unwrap();
 
@@ -1428,7 +2477,7 @@ mod tests {
             )
         "#,
         )
-        .execute(&pool)
+        .execute(sqlite)
         .await
         .unwrap();
 
@@ -1438,7 +2487,7 @@ mod tests {
     #[tokio::test]
     async fn test_cluster_crud_operations() {
         let pool = create_test_pool().await;
-        let repo = ClusterRepository::new(pool);
+        let repo = ClusterRepository::new(pool, ChangeNotifier::new());
 
         // Create a test cluster
         let create_request = CreateClusterRequest {
@@ -1499,10 +2548,176 @@ mod tests {
         assert_eq!(count_after_delete, 0);
     }
 
+    #[tokio::test]
+    async fn test_cluster_writes_publish_change_events() {
+        let pool = create_test_pool().await;
+        let notifier = ChangeNotifier::new();
+        let mut events = notifier.subscribe();
+        let repo = ClusterRepository::new(pool, notifier);
+
+        let created = repo
+            .create(CreateClusterRequest {
+                name: "notify_cluster".to_string(),
+                service_name: "notify_service".to_string(),
+                configuration: serde_json::json!({}),
+            })
+            .await
+            .unwrap();
+        let change = Change::row(ChangeKind::Clusters, "notify_cluster");
+        assert_eq!(events.try_recv().unwrap(), change);
+
+        // Reads do not publish
+        repo.list(None, None).await.unwrap();
+        assert!(events.try_recv().is_err());
+
+        repo.delete(&created.id).await.unwrap();
+        assert_eq!(events.try_recv().unwrap(), change);
+    }
+
+    #[tokio::test]
+    async fn test_secret_rotation_bumps_version_and_publishes() {
+        let pool = create_test_pool().await;
+        sqlx::query(
+            r#"
+            CREATE TABLE secrets (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL UNIQUE,
+                certificate_chain TEXT NOT NULL,
+                encrypted_private_key TEXT NOT NULL,
+                spiffe_id TEXT,
+                expires_at DATETIME,
+                version INTEGER NOT NULL DEFAULT 1,
+                created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+            )
+        "#,
+        )
+        .execute(pool.as_sqlite().unwrap())
+        .await
+        .unwrap();
+        let notifier = ChangeNotifier::new();
+        let mut events = notifier.subscribe();
+        let repo = SecretRepository::new(pool, notifier);
+
+        repo.create(CreateSecretRequest {
+            name: "edge-cert".to_string(),
+            certificate_chain: "cert-1".to_string(),
+            encrypted_private_key: "v1:key-1".to_string(),
+            spiffe_id: None,
+            expires_at: None,
+        })
+        .await
+        .unwrap();
+        assert_eq!(events.try_recv().unwrap(), Change::row(ChangeKind::Secrets, "edge-cert"));
+
+        let rotated = repo
+            .update(
+                "edge-cert",
+                UpdateSecretRequest {
+                    certificate_chain: "cert-2".to_string(),
+                    encrypted_private_key: "v1:key-2".to_string(),
+                    expires_at: None,
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(rotated.version, 2);
+        assert_eq!(rotated.certificate_chain, "cert-2");
+        assert_eq!(events.try_recv().unwrap(), Change::row(ChangeKind::Secrets, "edge-cert"));
+
+        repo.delete_by_name("edge-cert").await.unwrap();
+        assert!(repo.get_by_name("edge-cert").await.is_err());
+        assert!(repo.delete_by_name("edge-cert").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_configuration_versions_only_move_forward() {
+        let pool = create_test_pool().await;
+        sqlx::query(
+            r#"
+            CREATE TABLE configuration_versions (
+                id INTEGER PRIMARY KEY,
+                resource_type TEXT NOT NULL UNIQUE,
+                current_version INTEGER NOT NULL DEFAULT 1,
+                last_updated DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                content_hash TEXT
+            )
+        "#,
+        )
+        .execute(pool.as_sqlite().unwrap())
+        .await
+        .unwrap();
+        let repo = ConfigurationVersionRepository::new(pool);
+
+        repo.record("cluster", 5, "hash-5").await.unwrap();
+        repo.record("cluster", 3, "hash-3").await.unwrap();
+
+        let versions = repo.list().await.unwrap();
+        assert_eq!(versions.len(), 1);
+        assert_eq!(versions[0].resource_type, "cluster");
+        assert_eq!(versions[0].current_version, 5);
+        assert_eq!(versions[0].content_hash.as_deref(), Some("hash-5"));
+    }
+
+    async fn create_migrated_pool() -> DbPool {
+        let pool: DbPool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap()
+            .into();
+        crate::storage::run_migrations(&pool).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_configuration_version_claims_follow_content() {
+        let repo = ConfigurationVersionRepository::new(create_migrated_pool().await);
+
+        assert_eq!(repo.claim("secret", "hash-a", 2).await.unwrap(), 2);
+        assert_eq!(repo.claim("secret", "hash-a", 2).await.unwrap(), 2);
+        assert_eq!(repo.claim("secret", "hash-b", 2).await.unwrap(), 3);
+        assert_eq!(repo.claim("secret", "hash-a", 2).await.unwrap(), 4);
+        // Seeded rows start without a hash, so the first claim bumps them.
+        assert_eq!(repo.claim("cluster", "hash-c", 2).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_lease_has_a_single_holder() {
+        let repo = LeaseRepository::new(create_migrated_pool().await);
+        let ttl = std::time::Duration::from_secs(30);
+
+        assert!(repo.try_acquire("leader", "replica-a", ttl).await.unwrap());
+        assert!(!repo.try_acquire("leader", "replica-b", ttl).await.unwrap());
+        assert!(repo.try_acquire("leader", "replica-a", ttl).await.unwrap());
+
+        repo.release("leader", "replica-a").await.unwrap();
+        assert!(repo.try_acquire("leader", "replica-b", std::time::Duration::ZERO).await.unwrap());
+
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        assert!(repo.try_acquire("leader", "replica-a", ttl).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_rollout_nacks_reset_with_new_candidates() {
+        let repo = RolloutRepository::new(create_migrated_pool().await);
+
+        repo.record_nack("cluster", 4).await.unwrap();
+        repo.record_nack("cluster", 4).await.unwrap();
+        repo.record_phase("cluster", 4, "aborted").await.unwrap();
+        let rollouts = repo.list().await.unwrap();
+        assert_eq!((rollouts[0].phase.as_str(), rollouts[0].nack_count), ("aborted", 2));
+
+        repo.record_nack("cluster", 5).await.unwrap();
+        let rollouts = repo.list().await.unwrap();
+        assert_eq!(rollouts[0].candidate_version, 5);
+        assert_eq!((rollouts[0].phase.as_str(), rollouts[0].nack_count), ("baking", 1));
+    }
+
     #[tokio::test]
     async fn test_cluster_not_found() {
         let pool = create_test_pool().await;
-        let repo = ClusterRepository::new(pool);
+        let repo = ClusterRepository::new(pool, ChangeNotifier::new());
 
         let result = repo.get_by_id("nonexistent-id").await;
         assert!(result.is_err());
@@ -1514,7 +2729,7 @@ mod tests {
     #[tokio::test]
     async fn test_route_crud_operations() {
         let pool = create_test_pool().await;
-        let repo = RouteRepository::new(pool.clone());
+        let repo = RouteRepository::new(pool.clone(), ChangeNotifier::new());
 
         let create_request = CreateRouteRequest {
             name: "test_route".to_string(),
@@ -1599,7 +2814,7 @@ mod tests {
     #[tokio::test]
     async fn test_listener_crud_operations() {
         let pool = create_test_pool().await;
-        let repo = ListenerRepository::new(pool);
+        let repo = ListenerRepository::new(pool, ChangeNotifier::new());
 
         let create_request = CreateListenerRequest {
             name: "test-listener".to_string(),
@@ -1656,7 +2871,7 @@ mod tests {
     }
 }
 
-/// Audit event descriptor for authentication activity logging.
+/// Audit event descriptor for authentication, Platform API and xDS activity logging.
 #[derive(Debug, Clone)]
 pub struct AuditEvent {
     pub action: String,
@@ -1679,6 +2894,16 @@ impl AuditEvent {
             metadata,
         }
     }
+
+    /// Event about xDS delivery to `node_id` for resources of `type_url`.
+    pub fn xds(action: &str, node_id: &str, type_url: &str, metadata: serde_json::Value) -> Self {
+        Self {
+            action: action.to_string(),
+            resource_id: Some(node_id.to_string()),
+            resource_name: Some(type_url.to_string()),
+            metadata,
+        }
+    }
 }
 
 /// Repository for audit log interactions (scaffold for auth events).
@@ -1699,18 +2924,21 @@ impl AuditLogRepository {
         })?;
         let resource_name = event.resource_name.unwrap_or_else(|| event.action.clone());
 
-        sqlx::query(
-            "INSERT INTO audit_log (resource_type, resource_id, resource_name, action, old_configuration, new_configuration, user_id, client_ip, user_agent, created_at) \
-             VALUES ($1, $2, $3, $4, NULL, $5, NULL, NULL, NULL, $6)"
-        )
-        .bind(resource_type)
-        .bind(event.resource_id.as_deref())
-        .bind(&resource_name)
-        .bind(event.action.as_str())
-        .bind(metadata_json)
-        .bind(now)
-        .execute(&self.pool)
-        .await
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                "INSERT INTO audit_log (resource_type, resource_id, resource_name, action, old_configuration, new_configuration, user_id, client_ip, user_agent, created_at) \
+                 VALUES ($1, $2, $3, $4, NULL, $5, NULL, NULL, NULL, $6)"
+            )
+            .bind(resource_type)
+            .bind(event.resource_id.as_deref())
+            .bind(&resource_name)
+            .bind(event.action.as_str())
+            .bind(metadata_json)
+            .bind(now)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .map_err(|err| FlowplaneError::Database {
             source: err,
             context: "Failed to write authentication audit event".to_string(),
@@ -1728,6 +2956,11 @@ impl AuditLogRepository {
     pub async fn record_platform_event(&self, event: AuditEvent) -> Result<()> {
         self.record_event("platform.api", event).await
     }
+
+    /// Record an xDS delivery event, such as an Envoy NACK.
+    pub async fn record_xds_event(&self, event: AuditEvent) -> Result<()> {
+        self.record_event("xds.delivery", event).await
+    }
 }
 
 #[derive(Debug, Clone, FromRow)]
@@ -1804,15 +3037,16 @@ impl SqlxTokenRepository {
     }
 
     async fn scopes_for_token(&self, id: &str) -> Result<Vec<String>> {
-        let rows: Vec<TokenScopeRow> =
+        let rows: Vec<TokenScopeRow> = with_pool!(&self.pool, |pool| {
             sqlx::query_as("SELECT scope FROM token_scopes WHERE token_id = $1 ORDER BY scope")
                 .bind(id)
-                .fetch_all(&self.pool)
+                .fetch_all(pool)
                 .await
-                .map_err(|err| FlowplaneError::Database {
-                    source: err,
-                    context: "Failed to fetch token scopes".to_string(),
-                })?;
+        })
+        .map_err(|err| FlowplaneError::Database {
+            source: err,
+            context: "Failed to fetch token scopes".to_string(),
+        })?;
 
         Ok(rows.into_iter().map(|row| row.scope).collect())
     }
@@ -1821,12 +3055,13 @@ impl SqlxTokenRepository {
 #[async_trait]
 impl TokenRepository for SqlxTokenRepository {
     async fn create_token(&self, token: NewPersonalAccessToken) -> Result<PersonalAccessToken> {
-        let mut tx = self.pool.begin().await.map_err(|err| FlowplaneError::Database {
-            source: err,
-            context: "Failed to begin transaction for token creation".to_string(),
-        })?;
+        with_pool!(&self.pool, |pool| {
+            let mut tx = pool.begin().await.map_err(|err| FlowplaneError::Database {
+                source: err,
+                context: "Failed to begin transaction for token creation".to_string(),
+            })?;
 
-        sqlx::query(
+            sqlx::query(
             "INSERT INTO personal_access_tokens (id, name, token_hash, description, status, expires_at, created_by, created_at, updated_at)              VALUES ($1, $2, $3, $4, $5, $6, $7, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)"
         )
         .bind(&token.id)
@@ -1843,8 +3078,8 @@ impl TokenRepository for SqlxTokenRepository {
             context: "Failed to insert personal access token".to_string(),
         })?;
 
-        for scope in &token.scopes {
-            sqlx::query(
+            for scope in &token.scopes {
+                sqlx::query(
                 "INSERT INTO token_scopes (id, token_id, scope, created_at) VALUES ($1, $2, $3, CURRENT_TIMESTAMP)"
             )
             .bind(Uuid::new_v4().to_string())
@@ -1856,25 +3091,28 @@ impl TokenRepository for SqlxTokenRepository {
                 source: err,
                 context: "Failed to insert token scope".to_string(),
             })?;
-        }
+            }
 
-        tx.commit().await.map_err(|err| FlowplaneError::Database {
-            source: err,
-            context: "Failed to commit token creation".to_string(),
-        })?;
+            tx.commit().await.map_err(|err| FlowplaneError::Database {
+                source: err,
+                context: "Failed to commit token creation".to_string(),
+            })?;
+        });
 
         self.get_token(&token.id).await
     }
 
     async fn list_tokens(&self, limit: i64, offset: i64) -> Result<Vec<PersonalAccessToken>> {
         let limit = limit.clamp(1, 1000);
-        let ids: Vec<String> = sqlx::query_scalar(
-            "SELECT id FROM personal_access_tokens ORDER BY created_at DESC LIMIT $1 OFFSET $2",
-        )
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(&self.pool)
-        .await
+        let ids: Vec<String> = with_pool!(&self.pool, |pool| {
+            sqlx::query_scalar(
+                "SELECT id FROM personal_access_tokens ORDER BY created_at DESC LIMIT $1 OFFSET $2",
+            )
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(pool)
+            .await
+        })
         .map_err(|err| FlowplaneError::Database {
             source: err,
             context: "Failed to list personal access tokens".to_string(),
@@ -1888,12 +3126,14 @@ impl TokenRepository for SqlxTokenRepository {
     }
 
     async fn get_token(&self, id: &str) -> Result<PersonalAccessToken> {
-        let row: PersonalAccessTokenRow = sqlx::query_as(
-            "SELECT id, name, description, token_hash, status, expires_at, last_used_at, created_by, created_at, updated_at              FROM personal_access_tokens WHERE id = $1"
-        )
-        .bind(id)
-        .fetch_optional(&self.pool)
-        .await
+        let row: PersonalAccessTokenRow = with_pool!(&self.pool, |pool| {
+            sqlx::query_as(
+                "SELECT id, name, description, token_hash, status, expires_at, last_used_at, created_by, created_at, updated_at              FROM personal_access_tokens WHERE id = $1"
+            )
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+        })
         .map_err(|err| FlowplaneError::Database {
             source: err,
             context: "Failed to fetch personal access token".to_string(),
@@ -1909,12 +3149,13 @@ impl TokenRepository for SqlxTokenRepository {
         id: &str,
         update: UpdatePersonalAccessToken,
     ) -> Result<PersonalAccessToken> {
-        let mut tx = self.pool.begin().await.map_err(|err| FlowplaneError::Database {
-            source: err,
-            context: "Failed to begin transaction for token update".to_string(),
-        })?;
+        with_pool!(&self.pool, |pool| {
+            let mut tx = pool.begin().await.map_err(|err| FlowplaneError::Database {
+                source: err,
+                context: "Failed to begin transaction for token update".to_string(),
+            })?;
 
-        let existing: PersonalAccessTokenRow = sqlx::query_as(
+            let existing: PersonalAccessTokenRow = sqlx::query_as(
             "SELECT id, name, description, token_hash, status, expires_at, last_used_at, created_by, created_at, updated_at              FROM personal_access_tokens WHERE id = $1"
         )
         .bind(id)
@@ -1926,19 +3167,19 @@ impl TokenRepository for SqlxTokenRepository {
         })?
         .ok_or_else(|| FlowplaneError::not_found(format!("Token '{}' not found", id)))?;
 
-        let base_status = TokenStatus::from_str(&existing.status).map_err(|_| {
-            FlowplaneError::validation(format!(
-                "Unknown token status '{}' for token {}",
-                existing.status, existing.id
-            ))
-        })?;
+            let base_status = TokenStatus::from_str(&existing.status).map_err(|_| {
+                FlowplaneError::validation(format!(
+                    "Unknown token status '{}' for token {}",
+                    existing.status, existing.id
+                ))
+            })?;
 
-        let name = update.name.unwrap_or(existing.name.clone());
-        let description = update.description.or(existing.description.clone());
-        let status = update.status.unwrap_or(base_status);
-        let expires_at = update.expires_at.unwrap_or(existing.expires_at);
+            let name = update.name.unwrap_or(existing.name.clone());
+            let description = update.description.or(existing.description.clone());
+            let status = update.status.unwrap_or(base_status);
+            let expires_at = update.expires_at.unwrap_or(existing.expires_at);
 
-        sqlx::query(
+            sqlx::query(
             "UPDATE personal_access_tokens SET name = $1, description = $2, status = $3, expires_at = $4, updated_at = CURRENT_TIMESTAMP WHERE id = $5"
         )
         .bind(&name)
@@ -1953,18 +3194,18 @@ impl TokenRepository for SqlxTokenRepository {
             context: "Failed to update personal access token".to_string(),
         })?;
 
-        if let Some(scopes) = update.scopes {
-            sqlx::query("DELETE FROM token_scopes WHERE token_id = $1")
-                .bind(id)
-                .execute(&mut *tx)
-                .await
-                .map_err(|err| FlowplaneError::Database {
-                    source: err,
-                    context: "Failed to delete token scopes".to_string(),
-                })?;
-
-            for scope in scopes {
-                sqlx::query(
+            if let Some(scopes) = update.scopes {
+                sqlx::query("DELETE FROM token_scopes WHERE token_id = $1")
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(|err| FlowplaneError::Database {
+                        source: err,
+                        context: "Failed to delete token scopes".to_string(),
+                    })?;
+
+                for scope in scopes {
+                    sqlx::query(
                     "INSERT INTO token_scopes (id, token_id, scope, created_at) VALUES ($1, $2, $3, CURRENT_TIMESTAMP)"
                 )
                 .bind(Uuid::new_v4().to_string())
@@ -1976,25 +3217,29 @@ impl TokenRepository for SqlxTokenRepository {
                     source: err,
                     context: "Failed to insert token scope".to_string(),
                 })?;
+                }
             }
-        }
 
-        tx.commit().await.map_err(|err| FlowplaneError::Database {
-            source: err,
-            context: "Failed to commit token update".to_string(),
-        })?;
+            tx.commit().await.map_err(|err| FlowplaneError::Database {
+                source: err,
+                context: "Failed to commit token update".to_string(),
+            })?;
+        });
 
         self.get_token(id).await
     }
 
     async fn rotate_secret(&self, id: &str, hashed_secret: String) -> Result<()> {
-        sqlx::query(
-            "UPDATE personal_access_tokens SET token_hash = $1, updated_at = CURRENT_TIMESTAMP WHERE id = $2"
-        )
-        .bind(&hashed_secret)
-        .bind(id)
-        .execute(&self.pool)
-        .await
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                "UPDATE personal_access_tokens SET token_hash = $1, updated_at = CURRENT_TIMESTAMP WHERE id = $2"
+            )
+            .bind(&hashed_secret)
+            .bind(id)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .map_err(|err| FlowplaneError::Database {
             source: err,
             context: "Failed to rotate token secret".to_string(),
@@ -2003,13 +3248,16 @@ impl TokenRepository for SqlxTokenRepository {
     }
 
     async fn update_last_used(&self, id: &str, when: chrono::DateTime<chrono::Utc>) -> Result<()> {
-        sqlx::query(
-            "UPDATE personal_access_tokens SET last_used_at = $1, updated_at = CURRENT_TIMESTAMP WHERE id = $2"
-        )
-        .bind(when)
-        .bind(id)
-        .execute(&self.pool)
-        .await
+        with_pool!(&self.pool, |pool| {
+            sqlx::query(
+                "UPDATE personal_access_tokens SET last_used_at = $1, updated_at = CURRENT_TIMESTAMP WHERE id = $2"
+            )
+            .bind(when)
+            .bind(id)
+            .execute(pool)
+            .await
+            .map(|done| done.rows_affected())
+        })
         .map_err(|err| FlowplaneError::Database {
             source: err,
             context: "Failed to update token last_used_at".to_string(),
@@ -2021,12 +3269,14 @@ impl TokenRepository for SqlxTokenRepository {
         &self,
         id: &str,
     ) -> Result<Option<(PersonalAccessToken, String)>> {
-        let row: Option<PersonalAccessTokenRow> = sqlx::query_as(
-            "SELECT id, name, description, token_hash, status, expires_at, last_used_at, created_by, created_at, updated_at              FROM personal_access_tokens WHERE id = $1"
-        )
-        .bind(id)
-        .fetch_optional(&self.pool)
-        .await
+        let row: Option<PersonalAccessTokenRow> = with_pool!(&self.pool, |pool| {
+            sqlx::query_as(
+                "SELECT id, name, description, token_hash, status, expires_at, last_used_at, created_by, created_at, updated_at              FROM personal_access_tokens WHERE id = $1"
+            )
+            .bind(id)
+            .fetch_optional(pool)
+            .await
+        })
         .map_err(|err| FlowplaneError::Database {
             source: err,
             context: "Failed to fetch personal access token".to_string(),
@@ -2043,22 +3293,24 @@ impl TokenRepository for SqlxTokenRepository {
     }
 
     async fn count_tokens(&self) -> Result<i64> {
-        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM personal_access_tokens")
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|err| FlowplaneError::Database {
-                source: err,
-                context: "Failed to count personal access tokens".to_string(),
-            })?;
+        let count: i64 = with_pool!(&self.pool, |pool| {
+            sqlx::query_scalar("SELECT COUNT(*) FROM personal_access_tokens").fetch_one(pool).await
+        })
+        .map_err(|err| FlowplaneError::Database {
+            source: err,
+            context: "Failed to count personal access tokens".to_string(),
+        })?;
         Ok(count)
     }
 
     async fn count_active_tokens(&self) -> Result<i64> {
-        let count: i64 = sqlx::query_scalar(
-            "SELECT COUNT(*) FROM personal_access_tokens WHERE status = 'active'",
-        )
-        .fetch_one(&self.pool)
-        .await
+        let count: i64 = with_pool!(&self.pool, |pool| {
+            sqlx::query_scalar(
+                "SELECT COUNT(*) FROM personal_access_tokens WHERE status = 'active'",
+            )
+            .fetch_one(pool)
+            .await
+        })
         .map_err(|err| FlowplaneError::Database {
             source: err,
             context: "Failed to count active personal access tokens".to_string(),
@@ -109,6 +109,35 @@ pub fn load_certificate_bundle(
     })
 }
 
+/// Validate PEM encoded certificate and key material held in memory, such as
+/// an uploaded SDS secret. `source` names the material in error messages.
+///
+/// Checks the same properties as [`load_certificate_bundle`]: the leaf must
+/// be currently valid and match the private key.
+pub fn validate_pem_bundle(
+    cert_pem: &[u8],
+    key_pem: &[u8],
+    source: &Path,
+) -> Result<CertificateInfo, TlsError> {
+    let leaf = CertificateDer::pem_slice_iter(cert_pem)
+        .next()
+        .ok_or_else(|| TlsError::EmptyCertificateChain { path: source.to_path_buf() })?
+        .map_err(|err| TlsError::InvalidCertificatePem {
+            path: source.to_path_buf(),
+            source: anyhow!(err),
+        })?;
+
+    let private_key = PrivateKeyDer::from_pem_slice(key_pem).map_err(|err| {
+        TlsError::InvalidPrivateKey { path: source.to_path_buf(), source: Some(anyhow!(err)) }
+    })?;
+
+    let parsed = parse_certificate_metadata(&leaf, source)?;
+    validate_certificate_dates(&parsed.info, source)?;
+    validate_key_pair(&parsed, &private_key, source)?;
+
+    Ok(parsed.info)
+}
+
 struct ParsedCertificate {
     info: CertificateInfo,
     algorithm_oid: String,
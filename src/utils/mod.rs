@@ -2,7 +2,9 @@
 
 pub mod certificates;
 
-pub use certificates::{load_certificate_bundle, CertificateBundle, CertificateInfo};
+pub use certificates::{
+    load_certificate_bundle, validate_pem_bundle, CertificateBundle, CertificateInfo,
+};
 
 #[cfg(test)]
 pub use certificates::set_mock_time;
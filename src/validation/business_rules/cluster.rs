@@ -1,4 +1,5 @@
-use crate::errors::types::{FlowplaneError, Result};
+use crate::errors::{FlowplaneError, Result};
+use crate::xds::OutlierDetectionSpec;
 
 /// Circuit breaker configuration validation
 pub fn validate_circuit_breaker_config(
@@ -9,40 +10,110 @@ pub fn validate_circuit_breaker_config(
 ) -> Result<()> {
     if let Some(max_conn) = max_connections {
         if max_conn == 0 || max_conn > 10000 {
-            return Err(FlowplaneError::validation_field(
-                "Max connections must be between 1 and 10000",
-                "max_connections",
-            ));
+            return Err(FlowplaneError::validation("Max connections must be between 1 and 10000"));
         }
     }
 
     if let Some(max_pending) = max_pending_requests {
         if max_pending == 0 || max_pending > 10000 {
-            return Err(FlowplaneError::validation_field(
+            return Err(FlowplaneError::validation(
                 "Max pending requests must be between 1 and 10000",
-                "max_pending_requests",
             ));
         }
     }
 
     if let Some(max_req) = max_requests {
         if max_req == 0 || max_req > 10000 {
-            return Err(FlowplaneError::validation_field(
-                "Max requests must be between 1 and 10000",
-                "max_requests",
-            ));
+            return Err(FlowplaneError::validation("Max requests must be between 1 and 10000"));
         }
     }
 
     if let Some(max_ret) = max_retries {
         if max_ret > 10 {
-            return Err(FlowplaneError::validation_field(
-                "Max retries must be 10 or less",
-                "max_retries",
+            return Err(FlowplaneError::validation("Max retries must be 10 or less"));
+        }
+    }
+
+    Ok(())
+}
+
+/// Retry budget validation. A budget may be set alongside `max_retries`;
+/// Envoy then enforces the budget and ignores `max_retries`.
+pub fn validate_retry_budget_config(
+    budget_percent: Option<f64>,
+    min_retry_concurrency: Option<u32>,
+) -> Result<()> {
+    if let Some(percent) = budget_percent {
+        if !(0.0..=100.0).contains(&percent) {
+            return Err(FlowplaneError::validation(
+                "Retry budget percent must be between 0 and 100",
             ));
         }
     }
 
+    if min_retry_concurrency == Some(0) {
+        return Err(FlowplaneError::validation("Minimum retry concurrency must be greater than 0"));
+    }
+
+    Ok(())
+}
+
+/// Outlier detection configuration validation
+pub fn validate_outlier_detection_config(spec: &OutlierDetectionSpec) -> Result<()> {
+    let counts = [
+        (spec.consecutive_5xx, "consecutive_5xx"),
+        (spec.consecutive_gateway_failure, "consecutive_gateway_failure"),
+        (spec.consecutive_local_origin_failure, "consecutive_local_origin_failure"),
+    ];
+    for (count, field) in counts {
+        if count == Some(0) {
+            return Err(FlowplaneError::validation(format!("{} must be greater than 0", field)));
+        }
+    }
+
+    if spec.consecutive_local_origin_failure.is_some()
+        && !spec.split_external_local_origin_errors.unwrap_or(false)
+    {
+        return Err(FlowplaneError::validation(
+            "Local origin failure detection requires split_external_local_origin_errors",
+        ));
+    }
+
+    let mut percents = vec![(spec.max_ejection_percent, "max_ejection_percent")];
+    if let Some(success_rate) = &spec.success_rate {
+        percents.push((success_rate.enforcing_percent, "success_rate.enforcing_percent"));
+
+        if success_rate.stdev_factor.is_some_and(|factor| factor <= 0.0) {
+            return Err(FlowplaneError::validation(
+                "Success rate standard deviation factor must be greater than 0",
+            ));
+        }
+        if success_rate.minimum_hosts == Some(0) || success_rate.request_volume == Some(0) {
+            return Err(FlowplaneError::validation(
+                "Success rate minimum hosts and request volume must be greater than 0",
+            ));
+        }
+    }
+    if let Some(failure_percentage) = &spec.failure_percentage {
+        percents.push((failure_percentage.threshold, "failure_percentage.threshold"));
+        percents
+            .push((failure_percentage.enforcing_percent, "failure_percentage.enforcing_percent"));
+
+        if failure_percentage.minimum_hosts == Some(0)
+            || failure_percentage.request_volume == Some(0)
+        {
+            return Err(FlowplaneError::validation(
+                "Failure percentage minimum hosts and request volume must be greater than 0",
+            ));
+        }
+    }
+
+    for (percent, field) in percents {
+        if percent.is_some_and(|value| value > 100) {
+            return Err(FlowplaneError::validation(format!("{} must be between 0 and 100", field)));
+        }
+    }
+
     Ok(())
 }
 
@@ -61,9 +132,7 @@ pub fn validate_endpoint_weights(weights: &[Option<u32>]) -> Result<()> {
                     ));
                 }
                 if *weight > 1000 {
-                    return Err(FlowplaneError::validation(
-                        "Endpoint weight must be 1000 or less",
-                    ));
+                    return Err(FlowplaneError::validation("Endpoint weight must be 1000 or less"));
                 }
                 total_weight = total_weight.saturating_add(*weight);
                 has_weighted = true;
@@ -81,9 +150,7 @@ pub fn validate_endpoint_weights(weights: &[Option<u32>]) -> Result<()> {
     }
 
     if has_weighted && total_weight > 10000 {
-        return Err(FlowplaneError::validation(
-            "Total endpoint weights exceed maximum of 10000",
-        ));
+        return Err(FlowplaneError::validation("Total endpoint weights exceed maximum of 10000"));
     }
 
     Ok(())
@@ -98,56 +165,41 @@ pub fn validate_health_check_config(
     path: &Option<String>,
 ) -> Result<()> {
     if timeout_seconds >= interval_seconds {
-        return Err(FlowplaneError::validation(
-            "Health check timeout must be less than interval",
-        ));
+        return Err(FlowplaneError::validation("Health check timeout must be less than interval"));
     }
 
     if timeout_seconds == 0 || timeout_seconds > 60 {
-        return Err(FlowplaneError::validation_field(
+        return Err(FlowplaneError::validation(
             "Health check timeout must be between 1 and 60 seconds",
-            "timeout",
         ));
     }
 
     if interval_seconds == 0 || interval_seconds > 300 {
-        return Err(FlowplaneError::validation_field(
+        return Err(FlowplaneError::validation(
             "Health check interval must be between 1 and 300 seconds",
-            "interval",
         ));
     }
 
     if healthy_threshold == 0 || healthy_threshold > 10 {
-        return Err(FlowplaneError::validation_field(
-            "Healthy threshold must be between 1 and 10",
-            "healthy_threshold",
-        ));
+        return Err(FlowplaneError::validation("Healthy threshold must be between 1 and 10"));
     }
 
     if unhealthy_threshold == 0 || unhealthy_threshold > 10 {
-        return Err(FlowplaneError::validation_field(
-            "Unhealthy threshold must be between 1 and 10",
-            "unhealthy_threshold",
-        ));
+        return Err(FlowplaneError::validation("Unhealthy threshold must be between 1 and 10"));
     }
 
     if let Some(hc_path) = path {
         if !hc_path.starts_with('/') {
-            return Err(FlowplaneError::validation_field(
-                "Health check path must start with '/'",
-                "path",
-            ));
+            return Err(FlowplaneError::validation("Health check path must start with '/'"));
         }
         if hc_path.contains("..") {
-            return Err(FlowplaneError::validation_field(
+            return Err(FlowplaneError::validation(
                 "Health check path cannot contain '..' (path traversal)",
-                "path",
             ));
         }
         if hc_path.len() > 200 {
-            return Err(FlowplaneError::validation_field(
+            return Err(FlowplaneError::validation(
                 "Health check path cannot exceed 200 characters",
-                "path",
             ));
         }
     }
@@ -160,20 +212,16 @@ pub fn validate_cluster_naming_rules(name: &str, existing_names: &[String]) -> R
     let reserved_prefixes = ["envoy.", "xds.", "internal.", "system."];
     for prefix in &reserved_prefixes {
         if name.starts_with(prefix) {
-            return Err(FlowplaneError::validation_field(
-                format!("Cluster name cannot start with reserved prefix '{}'", prefix),
-                "name",
-            ));
+            return Err(FlowplaneError::validation(format!(
+                "Cluster name cannot start with reserved prefix '{}'",
+                prefix
+            )));
         }
     }
 
-    if existing_names
-        .iter()
-        .any(|existing| existing.eq_ignore_ascii_case(name))
-    {
-        return Err(FlowplaneError::validation_field(
+    if existing_names.iter().any(|existing| existing.eq_ignore_ascii_case(name)) {
+        return Err(FlowplaneError::validation(
             "Cluster name conflicts with existing cluster (case-insensitive)",
-            "name",
         ));
     }
 
@@ -192,6 +240,63 @@ mod tests {
         assert!(validate_circuit_breaker_config(None, None, None, Some(15)).is_err());
     }
 
+    #[test]
+    fn retry_budget_validation() {
+        assert!(validate_retry_budget_config(Some(20.0), Some(3)).is_ok());
+        assert!(validate_retry_budget_config(None, None).is_ok());
+        assert!(validate_retry_budget_config(Some(120.0), None).is_err());
+        assert!(validate_retry_budget_config(None, Some(0)).is_err());
+    }
+
+    #[test]
+    fn outlier_detection_validation() {
+        use crate::xds::{FailurePercentageEjectionSpec, SuccessRateEjectionSpec};
+
+        let valid = OutlierDetectionSpec {
+            consecutive_gateway_failure: Some(3),
+            consecutive_local_origin_failure: Some(5),
+            split_external_local_origin_errors: Some(true),
+            success_rate: Some(SuccessRateEjectionSpec {
+                stdev_factor: Some(1.9),
+                ..Default::default()
+            }),
+            failure_percentage: Some(FailurePercentageEjectionSpec {
+                threshold: Some(85),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert!(validate_outlier_detection_config(&valid).is_ok());
+
+        let local_without_split = OutlierDetectionSpec {
+            consecutive_local_origin_failure: Some(5),
+            ..Default::default()
+        };
+        assert!(validate_outlier_detection_config(&local_without_split).is_err());
+
+        let zero_gateway =
+            OutlierDetectionSpec { consecutive_gateway_failure: Some(0), ..Default::default() };
+        assert!(validate_outlier_detection_config(&zero_gateway).is_err());
+
+        let bad_threshold = OutlierDetectionSpec {
+            failure_percentage: Some(FailurePercentageEjectionSpec {
+                threshold: Some(150),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert!(validate_outlier_detection_config(&bad_threshold).is_err());
+
+        let bad_stdev = OutlierDetectionSpec {
+            success_rate: Some(SuccessRateEjectionSpec {
+                stdev_factor: Some(0.0),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert!(validate_outlier_detection_config(&bad_stdev).is_err());
+    }
+
     #[test]
     fn endpoint_weight_validation() {
         assert!(validate_endpoint_weights(&[Some(100), Some(200), Some(50)]).is_ok());
@@ -1,10 +1,12 @@
 //! Business-specific validation rules for the Platform API abstraction.
 
 pub mod api_definition;
+pub mod cluster;
 
 pub use api_definition::{
     enforce_listener_isolation_transition, validate_domain_availability, validate_route_uniqueness,
 };
+pub use cluster::{validate_outlier_detection_config, validate_retry_budget_config};
 
 #[cfg(test)]
 mod tests {
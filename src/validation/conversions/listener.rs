@@ -75,7 +75,9 @@ impl From<ValidatedTlsContextRequest> for listener::TlsContextConfig {
         Self {
             cert_chain_file: validated.cert_chain_file,
             private_key_file: validated.private_key_file,
+            certificate_secret: validated.certificate_secret,
             ca_cert_file: validated.ca_cert_file,
+            validation_secret: validated.validation_secret,
             require_client_certificate: validated.require_client_certificate,
         }
     }
@@ -158,7 +160,9 @@ impl From<listener::TlsContextConfig> for ValidatedTlsContextRequest {
         Self {
             cert_chain_file: tls_context_config.cert_chain_file,
             private_key_file: tls_context_config.private_key_file,
+            certificate_secret: tls_context_config.certificate_secret,
             ca_cert_file: tls_context_config.ca_cert_file,
+            validation_secret: tls_context_config.validation_secret,
             require_client_certificate: tls_context_config.require_client_certificate,
         }
     }
@@ -1,14 +1,12 @@
 //! # Type Conversions
 //!
 //! Resource-specific conversion helpers between validated requests and internal
-//! xDS configuration structures. Organized by cluster, route, and listener
+//! xDS configuration structures. Organized by route and listener
 //! responsibilities to keep each concern focused while preserving the original
 //! public surface.
 
-pub mod cluster;
 pub mod listener;
 pub mod route;
 
-pub use cluster::*;
 pub use listener::*;
 pub use route::*;
@@ -108,9 +108,15 @@ pub struct ValidatedTlsContextRequest {
     #[validate(length(max = 500))]
     pub private_key_file: Option<String>,
 
+    #[validate(length(min = 1, max = 255))]
+    pub certificate_secret: Option<String>,
+
     #[validate(length(max = 500))]
     pub ca_cert_file: Option<String>,
 
+    #[validate(length(min = 1, max = 255))]
+    pub validation_secret: Option<String>,
+
     pub require_client_certificate: Option<bool>,
 }
 
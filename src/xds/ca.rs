@@ -0,0 +1,313 @@
+//! Built-in certificate authority.
+//!
+//! The control plane keeps one CA per SPIFFE trust domain. Its signing key is
+//! encrypted with the secret cipher and stored next to the CA certificate, so
+//! every replica signs with the same key. Leaf certificates carry a SPIFFE ID
+//! derived from team and node ID, are stored as SDS secrets named after that
+//! ID, and are re-issued before they expire. The CA certificate itself is
+//! served as the [`CA_TRUST_BUNDLE_SECRET`] validation context.
+
+use std::fmt;
+
+use chrono::{DateTime, Duration, Utc};
+use rcgen::{
+    BasicConstraints, CertificateParams, DistinguishedName, DnType, ExtendedKeyUsagePurpose, IsCa,
+    KeyPair, KeyUsagePurpose, SanType,
+};
+
+use crate::errors::{Error, Result};
+
+/// SDS secret name of the validation context trusting the built-in CA.
+pub const CA_TRUST_BUNDLE_SECRET: &str = "flowplane-ca";
+
+/// Lifetime of a newly created CA certificate.
+const CA_VALIDITY_DAYS: i64 = 3650;
+
+/// How far `not_before` is backdated to tolerate clock skew between hosts.
+const CLOCK_SKEW_SECS: i64 = 300;
+
+/// Workload a leaf certificate is issued for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CertificateSubject {
+    pub team: String,
+    /// Envoy node ID; the certificate covers the whole team when unset.
+    pub node_id: Option<String>,
+}
+
+impl CertificateSubject {
+    /// SPIFFE ID of the subject: `spiffe://<trust-domain>/team/<team>` with a
+    /// `/node/<node-id>` suffix for node certificates.
+    pub fn spiffe_id(&self, trust_domain: &str) -> Result<String> {
+        validate_path_segment("team", &self.team)?;
+        let mut id = format!("spiffe://{}/team/{}", trust_domain, self.team);
+        if let Some(node_id) = &self.node_id {
+            validate_path_segment("node ID", node_id)?;
+            id.push_str("/node/");
+            id.push_str(node_id);
+        }
+        Ok(id)
+    }
+}
+
+/// SPIFFE path segments are limited to letters, digits, `.`, `-` and `_`.
+fn validate_path_segment(label: &str, value: &str) -> Result<()> {
+    let valid = !value.is_empty()
+        && value != "."
+        && value != ".."
+        && value.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'));
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::validation(format!(
+            "Invalid {} '{}' for a SPIFFE ID: use letters, digits, '.', '-' and '_'",
+            label, value
+        )))
+    }
+}
+
+/// Leaf certificate and key signed by the built-in CA.
+#[derive(Debug, Clone)]
+pub struct IssuedCertificate {
+    pub spiffe_id: String,
+    pub certificate_pem: String,
+    pub private_key_pem: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Signing key and certificate of the built-in CA.
+pub struct CertificateAuthority {
+    trust_domain: String,
+    certificate_pem: String,
+    expires_at: DateTime<Utc>,
+    key: KeyPair,
+    /// Certificate rebuilt from the stored key; only its subject and key
+    /// identifier are used when signing, so it matches the stored certificate.
+    issuer: rcgen::Certificate,
+}
+
+impl fmt::Debug for CertificateAuthority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CertificateAuthority")
+            .field("trust_domain", &self.trust_domain)
+            .field("expires_at", &self.expires_at)
+            .field("key", &"<redacted>")
+            .finish()
+    }
+}
+
+impl CertificateAuthority {
+    /// Create a CA with a fresh signing key.
+    pub fn generate(trust_domain: &str, now: DateTime<Utc>) -> Result<Self> {
+        let key = KeyPair::generate().map_err(|e| ca_error("generate CA key", e))?;
+        let expires_at = now + Duration::days(CA_VALIDITY_DAYS);
+
+        let mut params = ca_params(trust_domain);
+        params.not_before = to_offset_date_time(now - Duration::seconds(CLOCK_SKEW_SECS))?;
+        params.not_after = to_offset_date_time(expires_at)?;
+        let certificate =
+            params.self_signed(&key).map_err(|e| ca_error("sign CA certificate", e))?;
+
+        Self::assemble(trust_domain, certificate.pem(), expires_at, key)
+    }
+
+    /// Load a stored CA from its certificate and decrypted private key.
+    pub fn from_pem(
+        trust_domain: &str,
+        certificate_pem: String,
+        private_key_pem: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Self> {
+        let key = KeyPair::from_pem(private_key_pem).map_err(|e| ca_error("load CA key", e))?;
+        Self::assemble(trust_domain, certificate_pem, expires_at, key)
+    }
+
+    fn assemble(
+        trust_domain: &str,
+        certificate_pem: String,
+        expires_at: DateTime<Utc>,
+        key: KeyPair,
+    ) -> Result<Self> {
+        let issuer =
+            ca_params(trust_domain).self_signed(&key).map_err(|e| ca_error("load CA", e))?;
+        Ok(Self {
+            trust_domain: trust_domain.to_string(),
+            certificate_pem,
+            expires_at,
+            key,
+            issuer,
+        })
+    }
+
+    pub fn trust_domain(&self) -> &str {
+        &self.trust_domain
+    }
+
+    /// PEM encoded CA certificate, i.e. the trust bundle.
+    pub fn certificate_pem(&self) -> &str {
+        &self.certificate_pem
+    }
+
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        self.expires_at
+    }
+
+    /// PEM encoded signing key, to be encrypted before it is stored.
+    pub fn private_key_pem(&self) -> String {
+        self.key.serialize_pem()
+    }
+
+    /// Whether `spiffe_id` belongs to this CA's trust domain.
+    pub fn owns(&self, spiffe_id: &str) -> bool {
+        spiffe_id
+            .strip_prefix("spiffe://")
+            .and_then(|rest| rest.strip_prefix(self.trust_domain.as_str()))
+            .is_some_and(|path| path.starts_with('/'))
+    }
+
+    /// Issue a leaf certificate for `spiffe_id`, valid for client and server
+    /// authentication for `ttl_secs`. Never outlives the CA.
+    pub fn issue(
+        &self,
+        spiffe_id: &str,
+        ttl_secs: u64,
+        now: DateTime<Utc>,
+    ) -> Result<IssuedCertificate> {
+        if !self.owns(spiffe_id) {
+            return Err(Error::validation(format!(
+                "SPIFFE ID '{}' is outside trust domain '{}'",
+                spiffe_id, self.trust_domain
+            )));
+        }
+
+        let ttl = Duration::seconds(ttl_secs.min(i64::MAX as u64 / 1000) as i64);
+        let expires_at =
+            now.checked_add_signed(ttl).unwrap_or(self.expires_at).min(self.expires_at);
+
+        let mut params = CertificateParams::default();
+        let mut distinguished_name = DistinguishedName::new();
+        let common_name = spiffe_id.rsplit('/').next().unwrap_or(spiffe_id);
+        distinguished_name.push(DnType::CommonName, common_name);
+        params.distinguished_name = distinguished_name;
+        params.subject_alt_names =
+            vec![SanType::URI(spiffe_id.try_into().map_err(|e| ca_error("encode SPIFFE ID", e))?)];
+        params.is_ca = IsCa::ExplicitNoCa;
+        params.key_usages =
+            vec![KeyUsagePurpose::DigitalSignature, KeyUsagePurpose::KeyEncipherment];
+        params.extended_key_usages =
+            vec![ExtendedKeyUsagePurpose::ServerAuth, ExtendedKeyUsagePurpose::ClientAuth];
+        params.use_authority_key_identifier_extension = true;
+        params.not_before = to_offset_date_time(now - Duration::seconds(CLOCK_SKEW_SECS))?;
+        params.not_after = to_offset_date_time(expires_at)?;
+
+        let key = KeyPair::generate().map_err(|e| ca_error("generate leaf key", e))?;
+        let certificate = params
+            .signed_by(&key, &self.issuer, &self.key)
+            .map_err(|e| ca_error("sign leaf certificate", e))?;
+
+        Ok(IssuedCertificate {
+            spiffe_id: spiffe_id.to_string(),
+            certificate_pem: certificate.pem(),
+            private_key_pem: key.serialize_pem(),
+            expires_at,
+        })
+    }
+}
+
+/// Deterministic CA parameters; the validity period is set by the caller.
+fn ca_params(trust_domain: &str) -> CertificateParams {
+    let mut params = CertificateParams::default();
+    let mut distinguished_name = DistinguishedName::new();
+    distinguished_name.push(DnType::OrganizationName, "Flowplane");
+    distinguished_name.push(DnType::CommonName, format!("Flowplane CA {}", trust_domain));
+    params.distinguished_name = distinguished_name;
+    params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    params.key_usages = vec![
+        KeyUsagePurpose::KeyCertSign,
+        KeyUsagePurpose::CrlSign,
+        KeyUsagePurpose::DigitalSignature,
+    ];
+    if let Ok(uri) = format!("spiffe://{}", trust_domain).try_into() {
+        params.subject_alt_names = vec![SanType::URI(uri)];
+    }
+    params
+}
+
+fn to_offset_date_time(moment: DateTime<Utc>) -> Result<time::OffsetDateTime> {
+    time::OffsetDateTime::from_unix_timestamp(moment.timestamp())
+        .map_err(|e| Error::internal(format!("Certificate validity out of range: {}", e)))
+}
+
+fn ca_error(action: &str, error: impl fmt::Display) -> Error {
+    Error::internal(format!("Failed to {}: {}", action, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::validate_pem_bundle;
+    use rustls::pki_types::{pem::PemObject, CertificateDer};
+    use std::path::Path;
+
+    fn subject(node_id: Option<&str>) -> CertificateSubject {
+        CertificateSubject { team: "payments".into(), node_id: node_id.map(str::to_string) }
+    }
+
+    #[test]
+    fn spiffe_ids_are_derived_from_team_and_node() {
+        assert_eq!(
+            subject(None).spiffe_id("flowplane.local").unwrap(),
+            "spiffe://flowplane.local/team/payments"
+        );
+        assert_eq!(
+            subject(Some("envoy-1")).spiffe_id("flowplane.local").unwrap(),
+            "spiffe://flowplane.local/team/payments/node/envoy-1"
+        );
+        assert!(subject(Some("edge/envoy-1")).spiffe_id("flowplane.local").is_err());
+        assert!(CertificateSubject { team: "..".into(), node_id: None }
+            .spiffe_id("flowplane.local")
+            .is_err());
+    }
+
+    #[test]
+    fn issued_leaves_carry_spiffe_id_and_ttl() {
+        let now = Utc::now();
+        let ca = CertificateAuthority::generate("flowplane.local", now).unwrap();
+        let spiffe_id = subject(Some("envoy-1")).spiffe_id("flowplane.local").unwrap();
+
+        let issued = ca.issue(&spiffe_id, 3600, now).unwrap();
+        assert_eq!(issued.expires_at, now + Duration::seconds(3600));
+
+        let info = validate_pem_bundle(
+            issued.certificate_pem.as_bytes(),
+            issued.private_key_pem.as_bytes(),
+            Path::new("issued"),
+        )
+        .expect("leaf certificate matches its key");
+        assert!(info.issuer.contains("Flowplane CA flowplane.local"));
+        assert_eq!(info.not_after.timestamp(), issued.expires_at.timestamp());
+
+        let der = CertificateDer::from_pem_slice(issued.certificate_pem.as_bytes()).unwrap();
+        let uri = spiffe_id.as_bytes();
+        assert!(der.windows(uri.len()).any(|window| window == uri), "URI SAN carries SPIFFE ID");
+
+        assert!(ca.issue("spiffe://other.domain/team/payments", 3600, now).is_err());
+    }
+
+    #[test]
+    fn reloaded_ca_keeps_signing_with_the_stored_key() {
+        let now = Utc::now();
+        let generated = CertificateAuthority::generate("flowplane.local", now).unwrap();
+        let reloaded = CertificateAuthority::from_pem(
+            "flowplane.local",
+            generated.certificate_pem().to_string(),
+            &generated.private_key_pem(),
+            generated.expires_at(),
+        )
+        .unwrap();
+
+        assert_eq!(reloaded.certificate_pem(), generated.certificate_pem());
+        let issued = reloaded.issue("spiffe://flowplane.local/team/payments", 60, now).unwrap();
+        assert!(issued.certificate_pem.starts_with("-----BEGIN CERTIFICATE-----"));
+        assert!(format!("{:?}", reloaded).contains("redacted"));
+    }
+}
@@ -1,10 +1,14 @@
-use std::net::IpAddr;
+use std::{collections::BTreeMap, net::IpAddr};
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use utoipa::ToSchema;
 
 use crate::errors::Error;
+use crate::validation::business_rules::{
+    cluster::validate_endpoint_weights, validate_outlier_detection_config,
+    validate_retry_budget_config,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, ToSchema)]
 #[serde(rename_all = "camelCase")]
@@ -20,10 +24,84 @@ pub struct ClusterSpec {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub use_tls: Option<bool>,
 
+    #[serde(default, alias = "use_eds")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub use_eds: Option<bool>,
+
     #[serde(default, alias = "tls_server_name")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tls_server_name: Option<String>,
 
+    /// Name of an SDS secret presented as the client certificate for upstream
+    /// TLS. Setting it enables TLS.
+    #[serde(default, alias = "client_certificate_secret")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_certificate_secret: Option<String>,
+
+    /// Name of an SDS validation context secret, such as the built-in CA
+    /// trust bundle, used to verify the upstream certificate. Setting it
+    /// enables TLS.
+    #[serde(default, alias = "validation_secret")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub validation_secret: Option<String>,
+
+    /// Path on the Envoy host to a PEM bundle of CAs trusted to sign the
+    /// upstream certificate. Setting it enables TLS.
+    #[serde(default, alias = "trusted_ca_file")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trusted_ca_file: Option<String>,
+
+    /// Subject alternative names accepted on the upstream certificate; any
+    /// one of them must match. Requires a trusted CA.
+    #[serde(default, alias = "subject_alt_names")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub subject_alt_names: Vec<SubjectAltNameSpec>,
+
+    /// Path on the Envoy host to the client certificate chain presented for
+    /// upstream TLS. Set together with `client_private_key_file`, instead of
+    /// `client_certificate_secret`.
+    #[serde(default, alias = "client_certificate_file")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_certificate_file: Option<String>,
+
+    /// Path on the Envoy host to the private key of `client_certificate_file`.
+    #[serde(default, alias = "client_private_key_file")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_private_key_file: Option<String>,
+
+    #[serde(default, alias = "tls_min_version")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls_min_version: Option<TlsVersionSpec>,
+
+    #[serde(default, alias = "tls_max_version")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls_max_version: Option<TlsVersionSpec>,
+
+    /// ALPN protocols offered to the upstream, in order of preference.
+    #[serde(default, alias = "alpn_protocols")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub alpn_protocols: Vec<String>,
+
+    /// HTTP protocol spoken to the upstream (default: `http1`).
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<UpstreamProtocol>,
+
+    /// HTTP/2 settings, applied when `protocol` is `http2` or `auto`.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http2: Option<Http2OptionsSpec>,
+
+    /// Close upstream connections that carry no requests for this long.
+    #[serde(default, alias = "idle_timeout_seconds")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub idle_timeout_seconds: Option<u64>,
+
+    /// Close upstream connections after serving this many requests.
+    #[serde(default, alias = "max_requests_per_connection")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_requests_per_connection: Option<u32>,
+
     #[serde(default, alias = "dns_lookup_family")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub dns_lookup_family: Option<String>,
@@ -55,6 +133,18 @@ pub struct ClusterSpec {
     #[serde(default, alias = "outlier_detection")]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub outlier_detection: Option<OutlierDetectionSpec>,
+
+    /// Prefer endpoints in the Envoy node's own zone. Cannot be combined
+    /// with `locality_weighted_lb`.
+    #[serde(default, alias = "zone_aware_routing")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zone_aware_routing: Option<ZoneAwareRoutingSpec>,
+
+    /// Split traffic across localities by weight, where a locality weighs
+    /// the sum of its endpoint weights.
+    #[serde(default, alias = "locality_weighted_lb")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locality_weighted_lb: Option<bool>,
 }
 
 impl ClusterSpec {
@@ -87,28 +177,333 @@ impl ClusterSpec {
         self.use_tls.unwrap_or(false)
     }
 
+    /// Whether any upstream TLS setting beyond SNI is present. Any of them
+    /// enables TLS.
+    pub fn has_tls_settings(&self) -> bool {
+        self.client_certificate_secret.is_some()
+            || self.validation_secret.is_some()
+            || self.trusted_ca_file.is_some()
+            || !self.subject_alt_names.is_empty()
+            || self.client_certificate_file.is_some()
+            || self.client_private_key_file.is_some()
+            || self.tls_min_version.is_some()
+            || self.tls_max_version.is_some()
+            || !self.alpn_protocols.is_empty()
+    }
+
+    /// Whether upstream HTTP protocol options differ from Envoy's HTTP/1.1
+    /// defaults.
+    pub fn has_http_protocol_options(&self) -> bool {
+        self.protocol.is_some()
+            || self.http2.is_some()
+            || self.idle_timeout_seconds.is_some()
+            || self.max_requests_per_connection.is_some()
+    }
+
+    /// Whether endpoints are delivered separately over EDS instead of inline.
+    pub fn use_eds(&self) -> bool {
+        self.use_eds.unwrap_or(false)
+    }
+
+    fn ensure_eds_endpoints(&self) -> Result<(), Error> {
+        if !self.use_eds() {
+            return Ok(());
+        }
+
+        let hostname = self.endpoints.iter().find(|ep| {
+            ep.to_host_port().map(|(host, _)| host.parse::<IpAddr>().is_err()).unwrap_or(false)
+        });
+
+        if let Some(endpoint) = hostname {
+            return Err(Error::validation(format!(
+                "EDS clusters require IP address endpoints, got '{}'",
+                endpoint
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn ensure_tls_settings(&self) -> Result<(), Error> {
+        if self.client_certificate_file.is_some() != self.client_private_key_file.is_some() {
+            return Err(Error::validation(
+                "clientCertificateFile and clientPrivateKeyFile must be set together",
+            ));
+        }
+
+        if self.client_certificate_file.is_some() && self.client_certificate_secret.is_some() {
+            return Err(Error::validation(
+                "Set either clientCertificateSecret or clientCertificateFile, not both",
+            ));
+        }
+
+        if self.trusted_ca_file.is_some() && self.validation_secret.is_some() {
+            return Err(Error::validation(
+                "Set either validationSecret or trustedCaFile, not both",
+            ));
+        }
+
+        if !self.subject_alt_names.is_empty()
+            && self.trusted_ca_file.is_none()
+            && self.validation_secret.is_none()
+        {
+            return Err(Error::validation(
+                "subjectAltNames require a trusted CA (trustedCaFile or validationSecret)",
+            ));
+        }
+
+        if self.subject_alt_names.iter().any(|san| san.value.trim().is_empty()) {
+            return Err(Error::validation("Subject alternative names must not be empty"));
+        }
+
+        if let (Some(min), Some(max)) = (self.tls_min_version, self.tls_max_version) {
+            if min > max {
+                return Err(Error::validation(format!(
+                    "tlsMinVersion {} is greater than tlsMaxVersion {}",
+                    min, max
+                )));
+            }
+        }
+
+        if self.alpn_protocols.iter().any(|protocol| protocol.trim().is_empty()) {
+            return Err(Error::validation("ALPN protocols must not be empty"));
+        }
+
+        Ok(())
+    }
+
+    /// Whether localities receive traffic in proportion to their weight.
+    pub fn locality_weighted_lb(&self) -> bool {
+        self.locality_weighted_lb.unwrap_or(false)
+    }
+
+    fn ensure_endpoint_placement(&self) -> Result<(), Error> {
+        let weights: Vec<Option<u32>> = self.endpoints.iter().map(EndpointSpec::weight).collect();
+        validate_endpoint_weights(&weights)?;
+
+        let mut priorities: Vec<u32> =
+            self.endpoints.iter().map(|ep| ep.priority().unwrap_or(0)).collect();
+        priorities.sort_unstable();
+        priorities.dedup();
+        if let Some((index, _)) =
+            priorities.iter().enumerate().find(|(index, priority)| **priority != *index as u32)
+        {
+            return Err(Error::validation(format!(
+                "Endpoint priorities must be contiguous from 0, missing priority {}",
+                index
+            )));
+        }
+
+        if self.zone_aware_routing.is_some() && self.locality_weighted_lb() {
+            return Err(Error::validation(
+                "Set either zoneAwareRouting or localityWeightedLb, not both",
+            ));
+        }
+
+        if let Some(percent) =
+            self.zone_aware_routing.as_ref().and_then(|zone| zone.routing_enabled_percent)
+        {
+            if !(0.0..=100.0).contains(&percent) {
+                return Err(Error::validation(format!(
+                    "zoneAwareRouting.routingEnabledPercent must be between 0 and 100, got {}",
+                    percent
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn ensure_http_protocol_options(&self) -> Result<(), Error> {
+        let protocol = self.protocol.unwrap_or(UpstreamProtocol::Http1);
+
+        if self.http2.is_some() && protocol == UpstreamProtocol::Http1 {
+            return Err(Error::validation("http2 options require protocol http2 or auto"));
+        }
+
+        if protocol == UpstreamProtocol::Auto {
+            let tls_port = self
+                .endpoints
+                .iter()
+                .any(|ep| ep.to_host_port().map(|(_, port)| port == 443).unwrap_or(false));
+            if !(self.use_tls() || self.has_tls_settings() || tls_port) {
+                return Err(Error::validation(
+                    "protocol auto negotiates over ALPN and requires upstream TLS",
+                ));
+            }
+        }
+
+        if self.http2.as_ref().and_then(|http2| http2.max_concurrent_streams) == Some(0) {
+            return Err(Error::validation("http2.maxConcurrentStreams must be greater than zero"));
+        }
+
+        if let Some(keepalive) = self.http2.as_ref().and_then(|http2| http2.keepalive.as_ref()) {
+            if keepalive.timeout_seconds.unwrap_or(0) == 0 {
+                return Err(Error::validation(
+                    "http2.keepalive.timeoutSeconds is required and must be greater than zero",
+                ));
+            }
+        }
+
+        if self.max_requests_per_connection == Some(0) {
+            return Err(Error::validation("maxRequestsPerConnection must be greater than zero"));
+        }
+
+        Ok(())
+    }
+
+    fn ensure_health_checks(&self) -> Result<(), Error> {
+        for check in &self.health_checks {
+            if check.event_log_path().is_some_and(|path| path.trim().is_empty()) {
+                return Err(Error::validation("Health check event_log_path must not be empty"));
+            }
+
+            if check.no_traffic_interval_seconds() == Some(0) {
+                return Err(Error::validation(
+                    "Health check no_traffic_interval_seconds must be greater than zero",
+                ));
+            }
+
+            match check {
+                HealthCheckSpec::Http { request_headers, .. } => {
+                    if request_headers.keys().any(|name| name.trim().is_empty()) {
+                        return Err(Error::validation(
+                            "Health check request header names must not be empty",
+                        ));
+                    }
+                }
+                HealthCheckSpec::Tcp { send, receive, .. } => {
+                    if let Some(payload) =
+                        send.iter().chain(receive).find(|payload| !is_hex_payload(payload))
+                    {
+                        return Err(Error::validation(format!(
+                            "TCP health check payload '{}' is not valid hex",
+                            payload
+                        )));
+                    }
+                }
+                HealthCheckSpec::Grpc { .. } => {
+                    if self.protocol.unwrap_or(UpstreamProtocol::Http1) == UpstreamProtocol::Http1 {
+                        return Err(Error::validation(
+                            "gRPC health checks require protocol http2 or auto",
+                        ));
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn ensure_resilience(&self) -> Result<(), Error> {
+        if let Some(outlier) = &self.outlier_detection {
+            validate_outlier_detection_config(outlier)?;
+        }
+
+        let thresholds = self
+            .circuit_breakers
+            .iter()
+            .flat_map(|breakers| breakers.default.iter().chain(breakers.high.iter()));
+        for threshold in thresholds {
+            if let Some(budget) = &threshold.retry_budget {
+                validate_retry_budget_config(budget.budget_percent, budget.min_retry_concurrency)?;
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn validate_model(&self) -> Result<(), Error> {
-        self.ensure_endpoints()
+        self.ensure_endpoints()?;
+        self.ensure_eds_endpoints()?;
+        self.ensure_endpoint_placement()?;
+        self.ensure_tls_settings()?;
+        self.ensure_http_protocol_options()?;
+        self.ensure_health_checks()?;
+        self.ensure_resilience()
     }
 }
 
+/// Whether `payload` is non-empty hex with an even number of digits.
+fn is_hex_payload(payload: &str) -> bool {
+    !payload.is_empty()
+        && payload.len().is_multiple_of(2)
+        && payload.chars().all(|c| c.is_ascii_hexdigit())
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(untagged)]
 pub enum EndpointSpec {
     String(String),
-    Address { host: String, port: u16 },
+    Address {
+        host: String,
+        port: u16,
+        /// Load balancing weight within the endpoint's locality (default 1).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        weight: Option<u32>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        locality: Option<LocalitySpec>,
+        /// Failover priority; 0 is highest. Lower priorities only receive
+        /// traffic once higher ones are unhealthy.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        priority: Option<u32>,
+        /// Metadata published under `envoy.lb` for subset load balancing.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[schema(value_type = Option<Object>)]
+        metadata: Option<serde_json::Map<String, Value>>,
+    },
 }
 
 impl std::fmt::Display for EndpointSpec {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             EndpointSpec::String(s) => write!(f, "{}", s),
-            EndpointSpec::Address { host, port } => write!(f, "{}:{}", host, port),
+            EndpointSpec::Address { host, port, .. } => write!(f, "{}:{}", host, port),
         }
     }
 }
 
 impl EndpointSpec {
+    /// Plain `host:port` endpoint in the default locality and priority.
+    pub fn address(host: impl Into<String>, port: u16) -> Self {
+        EndpointSpec::Address {
+            host: host.into(),
+            port,
+            weight: None,
+            locality: None,
+            priority: None,
+            metadata: None,
+        }
+    }
+
+    pub fn weight(&self) -> Option<u32> {
+        match self {
+            EndpointSpec::Address { weight, .. } => *weight,
+            EndpointSpec::String(_) => None,
+        }
+    }
+
+    pub fn locality(&self) -> Option<&LocalitySpec> {
+        match self {
+            EndpointSpec::Address { locality, .. } => locality.as_ref(),
+            EndpointSpec::String(_) => None,
+        }
+    }
+
+    pub fn priority(&self) -> Option<u32> {
+        match self {
+            EndpointSpec::Address { priority, .. } => *priority,
+            EndpointSpec::String(_) => None,
+        }
+    }
+
+    pub fn metadata(&self) -> Option<&serde_json::Map<String, Value>> {
+        match self {
+            EndpointSpec::Address { metadata, .. } => metadata.as_ref(),
+            EndpointSpec::String(_) => None,
+        }
+    }
+
     pub fn to_host_port(&self) -> Option<(String, u32)> {
         match self {
             EndpointSpec::String(value) => {
@@ -123,7 +518,7 @@ impl EndpointSpec {
                 }
                 Some((host.to_string(), port))
             }
-            EndpointSpec::Address { host, port } => {
+            EndpointSpec::Address { host, port, .. } => {
                 if host.trim().is_empty() {
                     return None;
                 }
@@ -144,6 +539,132 @@ impl EndpointSpec {
     }
 }
 
+/// HTTP protocol used towards upstream hosts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum UpstreamProtocol {
+    Http1,
+    Http2,
+    /// HTTP/2 or HTTP/1.1, whichever the upstream selects over ALPN.
+    Auto,
+}
+
+/// HTTP/2 connection settings for upstream hosts.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Http2OptionsSpec {
+    #[serde(default, alias = "max_concurrent_streams")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_streams: Option<u32>,
+    /// PING-based keepalive, detecting dead connections without waiting for
+    /// a request to fail.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keepalive: Option<Http2KeepaliveSpec>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Http2KeepaliveSpec {
+    /// Interval between PINGs; none are sent on a schedule when unset.
+    #[serde(default, alias = "interval_seconds")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval_seconds: Option<u64>,
+    /// How long to wait for a PING response before closing the connection.
+    /// Required.
+    #[serde(default, alias = "timeout_seconds")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_seconds: Option<u64>,
+}
+
+/// Region, zone and sub-zone an endpoint runs in.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalitySpec {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub zone: Option<String>,
+    #[serde(default, alias = "sub_zone", skip_serializing_if = "Option::is_none")]
+    pub sub_zone: Option<String>,
+}
+
+/// Zone-aware routing settings, mapped onto Envoy's `ZoneAwareLbConfig`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ZoneAwareRoutingSpec {
+    /// Percentage of requests routed zone-aware (default 100).
+    #[serde(default, alias = "routing_enabled_percent")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub routing_enabled_percent: Option<f64>,
+    /// Smallest upstream cluster size for which zone-aware routing applies
+    /// (default 6).
+    #[serde(default, alias = "min_cluster_size")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_cluster_size: Option<u64>,
+    /// Fail requests instead of spreading them across zones when the local
+    /// zone is in panic mode.
+    #[serde(default, alias = "fail_traffic_on_panic")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fail_traffic_on_panic: Option<bool>,
+}
+
+/// TLS protocol version bounds for upstream connections. Envoy picks the
+/// version within the bounds when unset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, ToSchema)]
+pub enum TlsVersionSpec {
+    #[serde(rename = "TLSv1_0")]
+    Tls10,
+    #[serde(rename = "TLSv1_1")]
+    Tls11,
+    #[serde(rename = "TLSv1_2")]
+    Tls12,
+    #[serde(rename = "TLSv1_3")]
+    Tls13,
+}
+
+impl std::fmt::Display for TlsVersionSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            TlsVersionSpec::Tls10 => "TLSv1_0",
+            TlsVersionSpec::Tls11 => "TLSv1_1",
+            TlsVersionSpec::Tls12 => "TLSv1_2",
+            TlsVersionSpec::Tls13 => "TLSv1_3",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Subject alternative name matched against the upstream certificate.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SubjectAltNameSpec {
+    #[serde(rename = "type")]
+    pub san_type: SubjectAltNameType,
+    pub value: String,
+    /// How `value` is compared (default: `exact`). Exact DNS values may use
+    /// a leading `*.` wildcard.
+    #[serde(default, alias = "match_type")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub match_type: Option<SubjectAltNameMatchType>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SubjectAltNameType {
+    Dns,
+    Uri,
+    Email,
+    IpAddress,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SubjectAltNameMatchType {
+    Exact,
+    Prefix,
+    Suffix,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct LeastRequestPolicy {
@@ -189,6 +710,25 @@ pub struct CircuitBreakerThresholdsSpec {
     pub max_requests: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_retries: Option<u32>,
+    /// Cap on concurrent retries relative to active requests. Takes
+    /// precedence over `max_retries` when both are set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_budget: Option<RetryBudgetSpec>,
+    /// Publish `remaining_*` gauges for these thresholds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub track_remaining: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryBudgetSpec {
+    /// Percentage of active plus pending requests that may be retries
+    /// (default 20).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget_percent: Option<f64>,
+    /// Retries always allowed regardless of the budget (default 3).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_retry_concurrency: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -217,6 +757,18 @@ pub enum HealthCheckSpec {
         #[serde(default)]
         #[serde(skip_serializing_if = "Option::is_none")]
         expected_statuses: Option<Vec<u32>>,
+        /// Headers added to every probe request.
+        #[serde(default)]
+        #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+        request_headers: BTreeMap<String, String>,
+        /// Probe interval while the cluster has carried no traffic yet.
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        no_traffic_interval_seconds: Option<u64>,
+        /// File on the Envoy host receiving health check events.
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        event_log_path: Option<String>,
     },
     Tcp {
         #[serde(default)]
@@ -231,9 +783,110 @@ pub enum HealthCheckSpec {
         #[serde(default)]
         #[serde(skip_serializing_if = "Option::is_none")]
         unhealthy_threshold: Option<u32>,
+        /// Hex-encoded payload written after connecting; the check only
+        /// verifies the connection when unset.
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        send: Option<String>,
+        /// Hex-encoded payloads that must all appear in the response.
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        receive: Vec<String>,
+        /// Probe interval while the cluster has carried no traffic yet.
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        no_traffic_interval_seconds: Option<u64>,
+        /// File on the Envoy host receiving health check events.
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        event_log_path: Option<String>,
+    },
+    /// `grpc.health.v1.Health/Check` probe. Requires an HTTP/2 upstream.
+    Grpc {
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        interval_seconds: Option<u64>,
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        timeout_seconds: Option<u64>,
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        healthy_threshold: Option<u32>,
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        unhealthy_threshold: Option<u32>,
+        /// Service name sent in the request; an empty name checks the
+        /// whole server.
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        service_name: Option<String>,
+        /// `:authority` of the probe (default: the cluster name).
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        authority: Option<String>,
+        /// Probe interval while the cluster has carried no traffic yet.
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        no_traffic_interval_seconds: Option<u64>,
+        /// File on the Envoy host receiving health check events.
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        event_log_path: Option<String>,
     },
 }
 
+impl HealthCheckSpec {
+    pub fn interval_seconds(&self) -> Option<u64> {
+        match self {
+            HealthCheckSpec::Http { interval_seconds, .. }
+            | HealthCheckSpec::Tcp { interval_seconds, .. }
+            | HealthCheckSpec::Grpc { interval_seconds, .. } => *interval_seconds,
+        }
+    }
+
+    pub fn timeout_seconds(&self) -> Option<u64> {
+        match self {
+            HealthCheckSpec::Http { timeout_seconds, .. }
+            | HealthCheckSpec::Tcp { timeout_seconds, .. }
+            | HealthCheckSpec::Grpc { timeout_seconds, .. } => *timeout_seconds,
+        }
+    }
+
+    pub fn healthy_threshold(&self) -> Option<u32> {
+        match self {
+            HealthCheckSpec::Http { healthy_threshold, .. }
+            | HealthCheckSpec::Tcp { healthy_threshold, .. }
+            | HealthCheckSpec::Grpc { healthy_threshold, .. } => *healthy_threshold,
+        }
+    }
+
+    pub fn unhealthy_threshold(&self) -> Option<u32> {
+        match self {
+            HealthCheckSpec::Http { unhealthy_threshold, .. }
+            | HealthCheckSpec::Tcp { unhealthy_threshold, .. }
+            | HealthCheckSpec::Grpc { unhealthy_threshold, .. } => *unhealthy_threshold,
+        }
+    }
+
+    pub fn no_traffic_interval_seconds(&self) -> Option<u64> {
+        match self {
+            HealthCheckSpec::Http { no_traffic_interval_seconds, .. }
+            | HealthCheckSpec::Tcp { no_traffic_interval_seconds, .. }
+            | HealthCheckSpec::Grpc { no_traffic_interval_seconds, .. } => {
+                *no_traffic_interval_seconds
+            }
+        }
+    }
+
+    pub fn event_log_path(&self) -> Option<&str> {
+        match self {
+            HealthCheckSpec::Http { event_log_path, .. }
+            | HealthCheckSpec::Tcp { event_log_path, .. }
+            | HealthCheckSpec::Grpc { event_log_path, .. } => event_log_path.as_deref(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct OutlierDetectionSpec {
@@ -245,6 +898,59 @@ pub struct OutlierDetectionSpec {
     pub base_ejection_time_seconds: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_ejection_percent: Option<u32>,
+    /// Consecutive 502/503/504 responses before ejecting a host. Setting it
+    /// enables enforcement.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consecutive_gateway_failure: Option<u32>,
+    /// Consecutive locally originated failures (connect errors, timeouts,
+    /// resets) before ejecting a host. Requires
+    /// `split_external_local_origin_errors`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consecutive_local_origin_failure: Option<u32>,
+    /// Track locally originated failures separately from upstream responses.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub split_external_local_origin_errors: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub success_rate: Option<SuccessRateEjectionSpec>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure_percentage: Option<FailurePercentageEjectionSpec>,
+}
+
+/// Ejects hosts whose success rate falls more than `stdev_factor` standard
+/// deviations below the cluster mean.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SuccessRateEjectionSpec {
+    /// Hosts with enough request volume needed before ejecting (default 5).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minimum_hosts: Option<u32>,
+    /// Requests a host needs in an interval to be considered (default 100).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_volume: Option<u32>,
+    /// Standard deviation factor, e.g. `1.9` (the default).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stdev_factor: Option<f64>,
+    /// Percentage of detected outliers actually ejected (default 100).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enforcing_percent: Option<u32>,
+}
+
+/// Ejects hosts whose failure percentage reaches `threshold`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct FailurePercentageEjectionSpec {
+    /// Failure percentage at which a host is ejected (default 85).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<u32>,
+    /// Hosts with enough request volume needed before ejecting (default 5).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minimum_hosts: Option<u32>,
+    /// Requests a host needs in an interval to be considered (default 50).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_volume: Option<u32>,
+    /// Percentage of detected outliers actually ejected (default 100).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enforcing_percent: Option<u32>,
 }
 
 impl std::fmt::Display for HealthCheckSpec {
@@ -252,6 +958,9 @@ impl std::fmt::Display for HealthCheckSpec {
         match self {
             HealthCheckSpec::Http { path, .. } => write!(f, "http {}", path),
             HealthCheckSpec::Tcp { .. } => write!(f, "tcp"),
+            HealthCheckSpec::Grpc { service_name, .. } => {
+                write!(f, "grpc {}", service_name.as_deref().unwrap_or_default())
+            }
         }
     }
 }
@@ -151,20 +151,15 @@ impl RuntimeFractionalPercentConfig {
 }
 
 /// Denominator options mirroring Envoy enum values.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum FractionalPercentDenominator {
+    #[default]
     Hundred,
     TenThousand,
     Million,
 }
 
-impl Default for FractionalPercentDenominator {
-    fn default() -> Self {
-        Self::Hundred
-    }
-}
-
 impl FractionalPercentDenominator {
     fn to_proto_value(self) -> i32 {
         match self {
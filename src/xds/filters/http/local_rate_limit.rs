@@ -102,10 +102,11 @@ impl RuntimeFractionalPercentConfig {
 }
 
 /// Denominator options mirroring Envoy enum
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, ToSchema, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum FractionalPercentDenominator {
     /// Out of 100 (percentage)
+    #[default]
     Hundred,
     /// Out of 10,000 (basis points)
     TenThousand,
@@ -113,12 +114,6 @@ pub enum FractionalPercentDenominator {
     Million,
 }
 
-impl Default for FractionalPercentDenominator {
-    fn default() -> Self {
-        Self::Hundred
-    }
-}
-
 impl FractionalPercentDenominator {
     fn to_proto_value(self) -> i32 {
         match self {
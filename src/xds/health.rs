@@ -0,0 +1,144 @@
+//! gRPC health checking and server reflection on the xDS port.
+//!
+//! `grpc.health.v1.Health` reports the server (the empty service name) and
+//! each xDS service as `SERVING` while the [`HealthChecker`] considers the
+//! control plane healthy: the database answers and the resource caches hold
+//! their initial snapshot. Reflection serves the descriptors of the health and
+//! reflection services; `envoy-types` does not publish its descriptor set, so
+//! the xDS services still need the Envoy protos on the client side.
+
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use envoy_types::pb::envoy::service::discovery::v3::aggregated_discovery_service_server;
+use envoy_types::pb::envoy::service::status::v3::client_status_discovery_service_server;
+use tonic_health::server::HealthReporter;
+use tonic_health::ServingStatus;
+use tracing::info;
+
+use crate::errors::Result;
+use crate::observability::health::{
+    DatabaseHealthProvider, HealthCheck, HealthProvider, HealthStatus,
+};
+use crate::xds::XdsState;
+
+const HEALTH_REPORT_INTERVAL: Duration = Duration::from_secs(5);
+const CACHE_COMPONENT: &str = "xds_cache";
+
+/// Service names reported by the health service; the empty name stands for
+/// the server as a whole.
+const SERVICE_NAMES: [&str; 3] = [
+    "",
+    aggregated_discovery_service_server::SERVICE_NAME,
+    client_status_discovery_service_server::SERVICE_NAME,
+];
+
+/// Reports the xDS caches as unhealthy until their initial snapshot is built.
+pub struct CacheHealthProvider {
+    state: Weak<XdsState>,
+}
+
+impl CacheHealthProvider {
+    pub fn new(state: &Arc<XdsState>) -> Self {
+        Self { state: Arc::downgrade(state) }
+    }
+}
+
+#[async_trait]
+impl HealthProvider for CacheHealthProvider {
+    async fn health_check(&self) -> Result<HealthCheck> {
+        let check = match self.state.upgrade() {
+            Some(state) if state.caches_loaded() => HealthCheck::healthy(CACHE_COMPONENT.into())
+                .with_metadata("version", state.get_version()),
+            Some(_) => {
+                HealthCheck::unhealthy(CACHE_COMPONENT.into(), "Initial xDS snapshot not built yet")
+            }
+            None => HealthCheck::unhealthy(CACHE_COMPONENT.into(), "xDS state dropped"),
+        };
+        Ok(check)
+    }
+}
+
+/// Mark the server and its xDS services as serving unconditionally. Used by
+/// the minimal server, which has no database or caches to wait for.
+pub(crate) async fn report_serving(reporter: &HealthReporter) {
+    for service in SERVICE_NAMES {
+        reporter.set_service_status(service, ServingStatus::Serving).await;
+    }
+}
+
+/// Register the database and cache providers with the state's health checker
+/// and keep the reported serving status in step with its overall status.
+pub(crate) async fn spawn_health_reporter(state: &Arc<XdsState>, reporter: HealthReporter) {
+    let checker = state.health_checker.clone();
+    if let Some(repository) = &state.cluster_repository {
+        checker
+            .register_provider(
+                "database",
+                Box::new(DatabaseHealthProvider::new(repository.pool().clone())),
+            )
+            .await;
+    }
+    checker.register_provider(CACHE_COMPONENT, Box::new(CacheHealthProvider::new(state))).await;
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(HEALTH_REPORT_INTERVAL);
+        let mut last = None;
+        loop {
+            ticker.tick().await;
+            let status = checker.overall_status().await;
+            let serving = serving_status(&status);
+            if last != Some(serving) {
+                info!(?serving, reason = status.message(), "xDS gRPC health status changed");
+                for service in SERVICE_NAMES {
+                    reporter.set_service_status(service, serving).await;
+                }
+                last = Some(serving);
+            }
+        }
+    });
+}
+
+/// Only a fully healthy control plane is reported as serving, matching
+/// [`crate::observability::HealthChecker::is_ready`].
+fn serving_status(status: &HealthStatus) -> ServingStatus {
+    if status.is_healthy() {
+        ServingStatus::Serving
+    } else {
+        ServingStatus::NotServing
+    }
+}
+
+/// Reflection builder registering the health service descriptors. The
+/// reflection service's own descriptors are added by `build_v1` and
+/// `build_v1alpha`.
+pub(crate) fn reflection_builder() -> tonic_reflection::server::Builder<'static> {
+    tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SimpleXdsConfig;
+    use crate::xds::resources::{CLUSTER_TYPE_URL, LISTENER_TYPE_URL, ROUTE_TYPE_URL};
+
+    #[tokio::test]
+    async fn cache_provider_waits_for_the_initial_snapshot() {
+        let state = Arc::new(XdsState::new(SimpleXdsConfig::default()));
+        let provider = CacheHealthProvider::new(&state);
+
+        let check = provider.health_check().await.unwrap();
+        assert_eq!(serving_status(&check.status), ServingStatus::NotServing);
+
+        for type_url in [CLUSTER_TYPE_URL, ROUTE_TYPE_URL, LISTENER_TYPE_URL] {
+            state.apply_built_resources(type_url, Vec::new());
+        }
+        let check = provider.health_check().await.unwrap();
+        assert_eq!(serving_status(&check.status), ServingStatus::Serving);
+
+        drop(state);
+        assert!(!provider.health_check().await.unwrap().status.is_healthy());
+    }
+}
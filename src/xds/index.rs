@@ -0,0 +1,219 @@
+//! Dependency index between configuration rows and the xDS resources built
+//! from them.
+//!
+//! Each cluster, route and listener row, and each API definition together
+//! with its routes, is encoded on its own and kept under its key. A change
+//! names the rows it touched, so only those rows are re-encoded, and the
+//! snapshot of every type they contribute to is reassembled from the
+//! resources already encoded for the other rows.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::storage::{Change, ChangeKind};
+use crate::xds::resources::{
+    BuiltResource, CLUSTER_TYPE_URL, ENDPOINT_TYPE_URL, LISTENER_TYPE_URL, ROUTE_TYPE_URL,
+};
+
+/// Tables whose rows are indexed, in the order their resources are applied.
+/// Platform API resources come last so they win name collisions, as they
+/// did when snapshots were rebuilt from whole tables.
+pub const INDEXED_TABLES: [ChangeKind; 4] =
+    [ChangeKind::Clusters, ChangeKind::Routes, ChangeKind::Listeners, ChangeKind::ApiDefinitions];
+
+/// Types built from indexed rows, in the order their snapshots are
+/// published: clusters before their endpoints, routes before the listeners
+/// referencing them.
+pub const INDEXED_TYPES: [&str; 4] =
+    [CLUSTER_TYPE_URL, ENDPOINT_TYPE_URL, ROUTE_TYPE_URL, LISTENER_TYPE_URL];
+
+/// Tables contributing to the snapshot of `type_url`. The first one falls
+/// back to the static configuration while it has no rows.
+pub fn tables_for_type(type_url: &str) -> &'static [ChangeKind] {
+    match type_url {
+        CLUSTER_TYPE_URL => &[ChangeKind::Clusters, ChangeKind::ApiDefinitions],
+        ENDPOINT_TYPE_URL => &[ChangeKind::Clusters],
+        ROUTE_TYPE_URL => &[ChangeKind::Routes, ChangeKind::ApiDefinitions],
+        LISTENER_TYPE_URL => &[ChangeKind::Listeners],
+        _ => &[],
+    }
+}
+
+/// Types served from the static configuration while `kind` has no rows.
+fn fallback_types(kind: ChangeKind) -> &'static [&'static str] {
+    match kind {
+        ChangeKind::Clusters => &[CLUSTER_TYPE_URL, ENDPOINT_TYPE_URL],
+        ChangeKind::Routes => &[ROUTE_TYPE_URL],
+        ChangeKind::Listeners => &[LISTENER_TYPE_URL],
+        _ => &[],
+    }
+}
+
+/// Rows to re-encode per table; `None` when the whole table must be reloaded.
+pub fn changed_rows<'a>(
+    changes: impl IntoIterator<Item = &'a Change>,
+) -> HashMap<ChangeKind, Option<HashSet<String>>> {
+    let mut rows: HashMap<ChangeKind, Option<HashSet<String>>> = HashMap::new();
+    for change in changes {
+        let keys = rows.entry(change.kind).or_insert_with(|| Some(HashSet::new()));
+        match (&change.key, keys.as_mut()) {
+            (Some(key), Some(keys)) => {
+                keys.insert(key.clone());
+            }
+            (None, _) => *keys = None,
+            (Some(_), None) => {}
+        }
+    }
+    rows
+}
+
+/// Encoded resources of every indexed row, keyed by table and row.
+#[derive(Debug, Default)]
+pub struct ResourceIndex {
+    tables: HashMap<ChangeKind, BTreeMap<String, Vec<BuiltResource>>>,
+    encoded_rows: u64,
+}
+
+impl ResourceIndex {
+    /// Whether every row of `kind` has been encoded at least once. Single
+    /// rows are only re-encoded on top of a loaded table.
+    pub fn is_loaded(&self, kind: ChangeKind) -> bool {
+        self.tables.contains_key(&kind)
+    }
+
+    /// Whether `kind` is loaded without any rows, in which case its types
+    /// are served from the static configuration.
+    pub fn is_empty(&self, kind: ChangeKind) -> bool {
+        self.tables.get(&kind).is_some_and(BTreeMap::is_empty)
+    }
+
+    /// Replace every row of `kind`, returning the types whose snapshots must
+    /// be republished. These always include the table's fallback types, so
+    /// that the first load publishes them even without rows.
+    pub fn replace_table(
+        &mut self,
+        kind: ChangeKind,
+        rows: BTreeMap<String, Vec<BuiltResource>>,
+    ) -> HashSet<String> {
+        self.encoded_rows += rows.len() as u64;
+        let mut affected: HashSet<String> =
+            fallback_types(kind).iter().map(|type_url| type_url.to_string()).collect();
+        affected.extend(rows.values().flatten().map(|built| built.type_url().to_string()));
+        if let Some(previous) = self.tables.insert(kind, rows) {
+            affected.extend(previous.values().flatten().map(|built| built.type_url().to_string()));
+        }
+        affected
+    }
+
+    /// Replace the resources of one row of a loaded table, or remove the row
+    /// when `built` is `None`. Returns the types whose snapshots must be
+    /// republished.
+    pub fn replace_row(
+        &mut self,
+        kind: ChangeKind,
+        key: &str,
+        built: Option<Vec<BuiltResource>>,
+    ) -> HashSet<String> {
+        let table = self.tables.entry(kind).or_default();
+        let was_empty = table.is_empty();
+
+        let mut affected: HashSet<String> = HashSet::new();
+        let previous = match built {
+            Some(built) => {
+                self.encoded_rows += 1;
+                affected.extend(built.iter().map(|resource| resource.type_url().to_string()));
+                table.insert(key.to_string(), built)
+            }
+            None => table.remove(key),
+        };
+        affected.extend(previous.iter().flatten().map(|built| built.type_url().to_string()));
+
+        if was_empty != table.is_empty() {
+            affected.extend(fallback_types(kind).iter().map(|type_url| type_url.to_string()));
+        }
+        affected
+    }
+
+    /// Indexed resources of `type_url`, in [`INDEXED_TABLES`] order.
+    pub fn resources(&self, type_url: &str) -> Vec<BuiltResource> {
+        INDEXED_TABLES.iter().flat_map(|kind| self.table_resources(*kind, type_url)).collect()
+    }
+
+    /// Resources of `type_url` encoded from the rows of `kind`.
+    pub fn table_resources(&self, kind: ChangeKind, type_url: &str) -> Vec<BuiltResource> {
+        self.tables
+            .get(&kind)
+            .into_iter()
+            .flat_map(BTreeMap::values)
+            .flatten()
+            .filter(|built| built.type_url() == type_url)
+            .cloned()
+            .collect()
+    }
+
+    /// Number of rows encoded into the index so far, counting every row of
+    /// a table reload.
+    pub fn encoded_rows(&self) -> u64 {
+        self.encoded_rows
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use envoy_types::pb::google::protobuf::Any;
+
+    fn built(name: &str, type_url: &str) -> BuiltResource {
+        BuiltResource {
+            name: name.to_string(),
+            resource: Any { type_url: type_url.to_string(), value: name.as_bytes().to_vec() },
+        }
+    }
+
+    #[test]
+    fn row_changes_report_only_the_types_they_touch() {
+        let mut index = ResourceIndex::default();
+        let rows = BTreeMap::from([
+            ("a".to_string(), vec![built("a", CLUSTER_TYPE_URL)]),
+            ("b".to_string(), vec![built("b", CLUSTER_TYPE_URL), built("b", ENDPOINT_TYPE_URL)]),
+        ]);
+        index.replace_table(ChangeKind::Clusters, rows);
+        index.replace_table(
+            ChangeKind::ApiDefinitions,
+            BTreeMap::from([("def".to_string(), vec![built("platform", CLUSTER_TYPE_URL)])]),
+        );
+
+        let affected =
+            index.replace_row(ChangeKind::Clusters, "a", Some(vec![built("a2", CLUSTER_TYPE_URL)]));
+        assert_eq!(affected, HashSet::from([CLUSTER_TYPE_URL.to_string()]));
+
+        let names: Vec<String> =
+            index.resources(CLUSTER_TYPE_URL).into_iter().map(|built| built.name).collect();
+        assert_eq!(names, vec!["a2", "b", "platform"]);
+
+        let affected = index.replace_row(ChangeKind::Clusters, "b", None);
+        assert_eq!(
+            affected,
+            HashSet::from([CLUSTER_TYPE_URL.to_string(), ENDPOINT_TYPE_URL.to_string()])
+        );
+        assert!(index.resources(ENDPOINT_TYPE_URL).is_empty());
+
+        // Removing the last row falls back to the static configuration.
+        index.replace_row(ChangeKind::Clusters, "a", None);
+        assert!(index.is_empty(ChangeKind::Clusters));
+    }
+
+    #[test]
+    fn table_reloads_supersede_row_changes() {
+        let changes = [
+            Change::row(ChangeKind::Clusters, "a"),
+            Change::row(ChangeKind::Clusters, "b"),
+            Change::row(ChangeKind::Routes, "r"),
+            Change::table(ChangeKind::Routes),
+            Change::row(ChangeKind::Routes, "s"),
+        ];
+        let rows = changed_rows(&changes);
+
+        assert_eq!(rows[&ChangeKind::Clusters], Some(HashSet::from(["a".into(), "b".into()])));
+        assert_eq!(rows[&ChangeKind::Routes], None);
+    }
+}
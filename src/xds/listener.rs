@@ -29,6 +29,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use crate::xds::filters::http::{build_http_filters, HttpFilterConfigEntry};
+use crate::xds::resources::sds_secret_config;
 
 /// REST API representation of a listener configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -76,7 +77,13 @@ pub enum FilterType {
 pub struct TlsContextConfig {
     pub cert_chain_file: Option<String>,
     pub private_key_file: Option<String>,
+    /// Name of an SDS secret holding the certificate chain and key. Used
+    /// instead of the files when set.
+    pub certificate_secret: Option<String>,
     pub ca_cert_file: Option<String>,
+    /// Name of an SDS validation context secret used to verify client
+    /// certificates. Used instead of `ca_cert_file` when set.
+    pub validation_secret: Option<String>,
     pub require_client_certificate: Option<bool>,
 }
 
@@ -230,30 +237,37 @@ impl FilterConfig {
 }
 
 fn build_transport_socket(cfg: &TlsContextConfig) -> Result<TransportSocket, crate::Error> {
-    let cert_chain = cfg
-        .cert_chain_file
-        .as_ref()
-        .ok_or_else(|| crate::Error::config("Listener TLS requires cert_chain_file"))?;
-    let private_key = cfg
-        .private_key_file
-        .as_ref()
-        .ok_or_else(|| crate::Error::config("Listener TLS requires private_key_file"))?;
-
-    let validation_context_type = cfg.ca_cert_file.as_ref().map(|ca_file| {
-        common_tls_context::ValidationContextType::ValidationContext(CertificateValidationContext {
-            trusted_ca: Some(data_source_from_path(ca_file)),
-            ..Default::default()
-        })
-    });
-
-    let common = CommonTlsContext {
-        tls_certificates: vec![TlsCertificate {
+    let mut common = CommonTlsContext::default();
+
+    if let Some(secret_name) = &cfg.certificate_secret {
+        common.tls_certificate_sds_secret_configs = vec![sds_secret_config(secret_name)];
+    } else {
+        let cert_chain = cfg.cert_chain_file.as_ref().ok_or_else(|| {
+            crate::Error::config("Listener TLS requires cert_chain_file or certificate_secret")
+        })?;
+        let private_key = cfg.private_key_file.as_ref().ok_or_else(|| {
+            crate::Error::config("Listener TLS requires private_key_file or certificate_secret")
+        })?;
+        common.tls_certificates = vec![TlsCertificate {
             certificate_chain: Some(data_source_from_path(cert_chain)),
             private_key: Some(data_source_from_path(private_key)),
             ..Default::default()
-        }],
-        validation_context_type,
-        ..Default::default()
+        }];
+    }
+
+    common.validation_context_type = if let Some(secret_name) = &cfg.validation_secret {
+        Some(common_tls_context::ValidationContextType::ValidationContextSdsSecretConfig(
+            sds_secret_config(secret_name),
+        ))
+    } else {
+        cfg.ca_cert_file.as_ref().map(|ca_file| {
+            common_tls_context::ValidationContextType::ValidationContext(
+                CertificateValidationContext {
+                    trusted_ca: Some(data_source_from_path(ca_file)),
+                    ..Default::default()
+                },
+            )
+        })
     };
 
     let mut downstream =
@@ -662,4 +676,54 @@ mod tests {
         assert_eq!(hcm.http_filters[0].name, "envoy.filters.http.local_ratelimit");
         assert_eq!(hcm.http_filters[1].name, ROUTER_FILTER_NAME);
     }
+
+    #[test]
+    fn tls_certificate_secret_is_referenced_over_sds() {
+        let cfg = TlsContextConfig {
+            cert_chain_file: None,
+            private_key_file: None,
+            certificate_secret: Some("edge-cert".to_string()),
+            ca_cert_file: None,
+            validation_secret: None,
+            require_client_certificate: None,
+        };
+
+        let socket = build_transport_socket(&cfg).expect("transport socket");
+        let TransportSocketConfigType::TypedConfig(any) = socket.config_type.expect("typed config");
+        let downstream = DownstreamTlsContext::decode(any.value.as_slice()).expect("decode");
+        let common = downstream.common_tls_context.expect("common context");
+
+        assert!(common.tls_certificates.is_empty());
+        assert_eq!(common.tls_certificate_sds_secret_configs.len(), 1);
+        assert_eq!(common.tls_certificate_sds_secret_configs[0].name, "edge-cert");
+        assert!(common.tls_certificate_sds_secret_configs[0].sds_config.is_some());
+
+        let missing = TlsContextConfig { certificate_secret: None, ..cfg };
+        assert!(build_transport_socket(&missing).is_err());
+    }
+
+    #[test]
+    fn validation_secret_requires_client_certificates_from_sds_trust_bundle() {
+        let cfg = TlsContextConfig {
+            cert_chain_file: None,
+            private_key_file: None,
+            certificate_secret: Some("spiffe://flowplane.local/team/edge".to_string()),
+            ca_cert_file: Some("/etc/ca.pem".to_string()),
+            validation_secret: Some("flowplane-ca".to_string()),
+            require_client_certificate: Some(true),
+        };
+
+        let socket = build_transport_socket(&cfg).expect("transport socket");
+        let TransportSocketConfigType::TypedConfig(any) = socket.config_type.expect("typed config");
+        let downstream = DownstreamTlsContext::decode(any.value.as_slice()).expect("decode");
+        assert_eq!(downstream.require_client_certificate.map(|flag| flag.value), Some(true));
+
+        let common = downstream.common_tls_context.expect("common context");
+        match common.validation_context_type {
+            Some(common_tls_context::ValidationContextType::ValidationContextSdsSecretConfig(
+                sds,
+            )) => assert_eq!(sds.name, "flowplane-ca"),
+            other => panic!("expected SDS validation context, got {:?}", other),
+        }
+    }
 }
@@ -0,0 +1,234 @@
+//! Accessors for Envoy node metadata.
+//!
+//! Shared by listener scoping, canary selection and CSDS node matchers so
+//! they all read node metadata the same way, along with the JSON conversion
+//! used to publish runtime layers and endpoint metadata, and the team
+//! recorded on gateway listeners.
+
+use envoy_types::pb::envoy::config::core::v3::{Metadata, Node};
+use envoy_types::pb::envoy::config::listener::v3::Listener;
+use envoy_types::pb::envoy::r#type::matcher::v3::{
+    string_matcher, struct_matcher::path_segment::Segment, value_matcher, NodeMatcher,
+    StringMatcher, StructMatcher, ValueMatcher,
+};
+use envoy_types::pb::google::protobuf::{value::Kind, Struct, Value};
+
+use crate::config::NodeSelector;
+
+/// Protobuf `Value` equivalent of a JSON value.
+pub(crate) fn json_to_value(value: &serde_json::Value) -> Value {
+    let kind = match value {
+        serde_json::Value::Null => Kind::NullValue(0),
+        serde_json::Value::Bool(flag) => Kind::BoolValue(*flag),
+        serde_json::Value::Number(number) => Kind::NumberValue(number.as_f64().unwrap_or_default()),
+        serde_json::Value::String(text) => Kind::StringValue(text.clone()),
+        serde_json::Value::Array(items) => {
+            Kind::ListValue(envoy_types::pb::google::protobuf::ListValue {
+                values: items.iter().map(json_to_value).collect(),
+            })
+        }
+        serde_json::Value::Object(fields) => Kind::StructValue(json_to_struct(fields)),
+    };
+    Value { kind: Some(kind) }
+}
+
+/// Protobuf `Struct` equivalent of a JSON object.
+pub(crate) fn json_to_struct(fields: &serde_json::Map<String, serde_json::Value>) -> Struct {
+    Struct {
+        fields: fields.iter().map(|(key, value)| (key.clone(), json_to_value(value))).collect(),
+    }
+}
+
+/// Listener metadata namespace holding the owning `team`.
+const FLOWPLANE_METADATA_NAMESPACE: &str = "flowplane";
+
+/// Record `team` as the owner of `listener`, so team-scoped nodes can be
+/// matched against the listener as served.
+pub(crate) fn set_listener_team(listener: &mut Listener, team: &str) {
+    let fields = serde_json::Map::from_iter([("team".to_string(), team.into())]);
+    listener
+        .metadata
+        .get_or_insert_with(Metadata::default)
+        .filter_metadata
+        .insert(FLOWPLANE_METADATA_NAMESPACE.to_string(), json_to_struct(&fields));
+}
+
+/// Team recorded on `listener` by [`set_listener_team`].
+pub(crate) fn listener_team(listener: &Listener) -> Option<&str> {
+    let metadata = listener.metadata.as_ref()?;
+    string_field(metadata.filter_metadata.get(FLOWPLANE_METADATA_NAMESPACE)?, "team")
+}
+
+/// Non-empty string value stored under `key`.
+pub(crate) fn string_field<'a>(metadata: &'a Struct, key: &str) -> Option<&'a str> {
+    match metadata.fields.get(key).and_then(|value| value.kind.as_ref()) {
+        Some(Kind::StringValue(text)) if !text.is_empty() => Some(text.as_str()),
+        _ => None,
+    }
+}
+
+/// Boolean value stored under `key`.
+pub(crate) fn bool_field(metadata: &Struct, key: &str) -> Option<bool> {
+    match metadata.fields.get(key).and_then(|value| value.kind.as_ref()) {
+        Some(Kind::BoolValue(flag)) => Some(*flag),
+        _ => None,
+    }
+}
+
+/// String entries of the list stored under `key`; other entries are ignored.
+pub(crate) fn string_list_field(metadata: &Struct, key: &str) -> Vec<String> {
+    match metadata.fields.get(key).and_then(|value| value.kind.as_ref()) {
+        Some(Kind::ListValue(list)) => list
+            .values
+            .iter()
+            .filter_map(|item| match item.kind.as_ref() {
+                Some(Kind::StringValue(text)) => Some(text.clone()),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Whether `node` carries the metadata entry described by `selector`.
+///
+/// String and boolean values are compared by their text form, so
+/// `canary=true` matches both `"true"` and `true`.
+pub(crate) fn matches_selector(node: &Node, selector: &NodeSelector) -> bool {
+    let Some(metadata) = &node.metadata else {
+        return false;
+    };
+    if let Some(text) = string_field(metadata, &selector.key) {
+        return text == selector.value;
+    }
+    bool_field(metadata, &selector.key).is_some_and(|flag| flag.to_string() == selector.value)
+}
+
+/// Whether `node` satisfies a CSDS `NodeMatcher`: its ID matcher, if any,
+/// and every metadata matcher.
+pub(crate) fn matches_node_matcher(node: &Node, matcher: &NodeMatcher) -> bool {
+    matcher.node_id.as_ref().is_none_or(|id| string_matches(id, &node.id))
+        && matcher.node_metadatas.iter().all(|metadata| struct_matches(node, metadata))
+}
+
+fn struct_matches(node: &Node, matcher: &StructMatcher) -> bool {
+    let mut value: Option<&Value> = None;
+    let mut current = node.metadata.as_ref();
+    for segment in &matcher.path {
+        let Some(Segment::Key(key)) = &segment.segment else {
+            return false;
+        };
+        value = current.and_then(|fields| fields.fields.get(key));
+        current = match value.and_then(|value| value.kind.as_ref()) {
+            Some(Kind::StructValue(inner)) => Some(inner),
+            _ => None,
+        };
+    }
+    matcher.value.as_ref().is_some_and(|expected| value_matches(expected, value))
+}
+
+/// Null, string, boolean, presence and "or" value matchers are supported;
+/// other matchers never match.
+fn value_matches(matcher: &ValueMatcher, value: Option<&Value>) -> bool {
+    let kind = value.and_then(|value| value.kind.as_ref());
+    match &matcher.match_pattern {
+        Some(value_matcher::MatchPattern::NullMatch(_)) => {
+            matches!(kind, Some(Kind::NullValue(_)))
+        }
+        Some(value_matcher::MatchPattern::StringMatch(expected)) => {
+            matches!(kind, Some(Kind::StringValue(text)) if string_matches(expected, text))
+        }
+        Some(value_matcher::MatchPattern::BoolMatch(expected)) => {
+            matches!(kind, Some(Kind::BoolValue(flag)) if flag == expected)
+        }
+        Some(value_matcher::MatchPattern::PresentMatch(expected)) => kind.is_some() == *expected,
+        Some(value_matcher::MatchPattern::OrMatch(or)) => {
+            or.value_matchers.iter().any(|matcher| value_matches(matcher, value))
+        }
+        _ => false,
+    }
+}
+
+fn string_matches(matcher: &StringMatcher, text: &str) -> bool {
+    let normalize = |value: &str| {
+        if matcher.ignore_case {
+            value.to_lowercase()
+        } else {
+            value.to_string()
+        }
+    };
+    let text = normalize(text);
+    match &matcher.match_pattern {
+        Some(string_matcher::MatchPattern::Exact(exact)) => text == normalize(exact),
+        Some(string_matcher::MatchPattern::Prefix(prefix)) => text.starts_with(&normalize(prefix)),
+        Some(string_matcher::MatchPattern::Suffix(suffix)) => text.ends_with(&normalize(suffix)),
+        Some(string_matcher::MatchPattern::Contains(part)) => text.contains(&normalize(part)),
+        Some(string_matcher::MatchPattern::SafeRegex(regex)) => {
+            regex::Regex::new(&format!("^(?:{})$", regex.regex))
+                .is_ok_and(|pattern| pattern.is_match(&text))
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use envoy_types::pb::google::protobuf::Value;
+
+    fn node_with(key: &str, kind: Kind) -> Node {
+        let mut fields = std::collections::HashMap::new();
+        fields.insert(key.to_string(), Value { kind: Some(kind) });
+        Node { metadata: Some(Struct { fields }), ..Default::default() }
+    }
+
+    #[test]
+    fn selector_matches_string_and_bool_values() {
+        let selector = NodeSelector { key: "canary".into(), value: "true".into() };
+
+        assert!(matches_selector(
+            &node_with("canary", Kind::StringValue("true".into())),
+            &selector
+        ));
+        assert!(matches_selector(&node_with("canary", Kind::BoolValue(true)), &selector));
+        assert!(!matches_selector(&node_with("canary", Kind::BoolValue(false)), &selector));
+        assert!(!matches_selector(&node_with("team", Kind::StringValue("true".into())), &selector));
+        assert!(!matches_selector(&Node::default(), &selector));
+    }
+
+    #[test]
+    fn node_matchers_check_id_and_metadata() {
+        use envoy_types::pb::envoy::r#type::matcher::v3::struct_matcher::PathSegment;
+
+        let mut node = node_with("team", Kind::StringValue("payments".into()));
+        node.id = "gateway-payments-1".into();
+
+        let id = |pattern| StringMatcher { match_pattern: Some(pattern), ignore_case: false };
+        let team = |value: &str| StructMatcher {
+            path: vec![PathSegment { segment: Some(Segment::Key("team".into())) }],
+            value: Some(ValueMatcher {
+                match_pattern: Some(value_matcher::MatchPattern::StringMatch(id(
+                    string_matcher::MatchPattern::Exact(value.into()),
+                ))),
+            }),
+        };
+
+        let matcher = NodeMatcher {
+            node_id: Some(id(string_matcher::MatchPattern::Prefix("gateway-".into()))),
+            node_metadatas: vec![team("payments")],
+        };
+        assert!(matches_node_matcher(&node, &matcher));
+        assert!(matches_node_matcher(&node, &NodeMatcher::default()));
+        assert!(!matches_node_matcher(
+            &node,
+            &NodeMatcher { node_id: None, node_metadatas: vec![team("billing")] }
+        ));
+        assert!(!matches_node_matcher(
+            &node,
+            &NodeMatcher {
+                node_id: Some(id(string_matcher::MatchPattern::Exact("gateway".into()))),
+                node_metadatas: Vec::new(),
+            }
+        ));
+    }
+}
@@ -6,13 +6,24 @@
 //! - RDS (Route Discovery Service)
 //! - LDS (Listener Discovery Service)
 
+pub mod ca;
 pub mod cluster;
 mod cluster_spec;
 pub mod filters;
+mod health;
+mod index;
 pub mod listener;
+mod metadata;
+mod publisher;
+mod references;
+mod registry;
 pub(crate) mod resources;
+mod rollback;
+mod rollout;
 pub mod route;
+pub mod runtime;
 mod services;
+pub mod snapshot;
 mod state;
 
 use crate::{config::SimpleXdsConfig, storage::DbPool, Result};
@@ -22,9 +33,15 @@ use tonic::transport::{Certificate, Identity, Server, ServerTlsConfig};
 use tracing::info;
 
 use envoy_types::pb::envoy::service::discovery::v3::aggregated_discovery_service_server::AggregatedDiscoveryServiceServer;
+use envoy_types::pb::envoy::service::status::v3::client_status_discovery_service_server::ClientStatusDiscoveryServiceServer;
 
 pub use cluster_spec::*;
-pub use services::{DatabaseAggregatedDiscoveryService, MinimalAggregatedDiscoveryService};
+pub use registry::{NackDetail, NodeRegistry, NodeStatus, RequestKind, ResourceTypeStatus};
+pub use rollback::KnownGoodSnapshot;
+pub use rollout::{RolloutPhase, RolloutStatus};
+pub use services::{
+    ClientStatusService, DatabaseAggregatedDiscoveryService, MinimalAggregatedDiscoveryService,
+};
 pub use state::XdsState;
 
 /// Start the minimal xDS gRPC server with configuration and graceful shutdown
@@ -50,12 +67,21 @@ where
     // Create ADS service implementation
     let ads_service = MinimalAggregatedDiscoveryService::new(state.clone());
 
-    // Build and start the gRPC server with ADS service only
+    let csds_service = ClientStatusService::for_config(state.clone());
+
+    let (health_reporter, health_service) = tonic_health::server::health_reporter();
+    health::report_serving(&health_reporter).await;
+
+    // Build and start the gRPC server with the ADS, CSDS, health and reflection services
     // This serves actual Envoy resources (clusters, routes, listeners, endpoints)
     let mut server_builder = configure_server_builder(Server::builder(), &state.config)?;
 
     let server = server_builder
         .add_service(AggregatedDiscoveryServiceServer::new(ads_service))
+        .add_service(ClientStatusDiscoveryServiceServer::new(csds_service))
+        .add_service(health_service)
+        .add_service(build_reflection_v1()?)
+        .add_service(build_reflection_v1alpha()?)
         .serve_with_shutdown(addr, shutdown_signal);
 
     info!("XDS server listening on {}", addr);
@@ -113,11 +139,19 @@ where
     );
 
     let ads_service = DatabaseAggregatedDiscoveryService::new(state.clone());
+    let csds_service = ClientStatusService::for_database(state.clone());
+
+    let (health_reporter, health_service) = tonic_health::server::health_reporter();
+    health::spawn_health_reporter(&state, health_reporter).await;
 
     let mut server_builder = configure_server_builder(Server::builder(), &state.config)?;
 
     let server = server_builder
         .add_service(AggregatedDiscoveryServiceServer::new(ads_service))
+        .add_service(ClientStatusDiscoveryServiceServer::new(csds_service))
+        .add_service(health_service)
+        .add_service(build_reflection_v1()?)
+        .add_service(build_reflection_v1alpha()?)
         .serve_with_shutdown(addr, shutdown_signal);
 
     info!("Database-enabled XDS server listening on {}", addr);
@@ -157,6 +191,26 @@ fn configure_server_builder(mut builder: Server, config: &SimpleXdsConfig) -> Re
     Ok(builder)
 }
 
+fn build_reflection_v1() -> Result<
+    tonic_reflection::server::v1::ServerReflectionServer<
+        impl tonic_reflection::server::v1::ServerReflection,
+    >,
+> {
+    health::reflection_builder().build_v1().map_err(|e| {
+        crate::Error::internal(format!("Failed to build gRPC reflection service: {}", e))
+    })
+}
+
+fn build_reflection_v1alpha() -> Result<
+    tonic_reflection::server::v1alpha::ServerReflectionServer<
+        impl tonic_reflection::server::v1alpha::ServerReflection,
+    >,
+> {
+    health::reflection_builder().build_v1alpha().map_err(|e| {
+        crate::Error::internal(format!("Failed to build gRPC reflection service: {}", e))
+    })
+}
+
 fn build_server_tls_config(config: &SimpleXdsConfig) -> Result<Option<ServerTlsConfig>> {
     let tls = match &config.tls {
         Some(tls) => tls,
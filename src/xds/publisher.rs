@@ -0,0 +1,208 @@
+//! Broadcast of cache updates to ADS streams.
+//!
+//! With a debounce window configured, snapshots applied within the window are
+//! staged instead of written to the resource caches. When the window closes
+//! they are written together and broadcast as a single [`ResourceUpdate`], so
+//! a write touching several resource types (such as an OpenAPI import)
+//! reaches readers and Envoy as one change instead of several intermediate
+//! states.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::broadcast;
+
+use crate::xds::resources::{
+    BuiltResource, CLUSTER_TYPE_URL, ENDPOINT_TYPE_URL, LISTENER_TYPE_URL, ROUTE_TYPE_URL,
+    RUNTIME_TYPE_URL, SECRET_TYPE_URL,
+};
+use crate::xds::state::ResourceUpdate;
+
+/// Order in which deltas of one update are pushed. Secrets and clusters come
+/// before the listeners and routes that reference them, following Envoy's
+/// make-before-break guidance (CDS, EDS, LDS, RDS).
+const PUSH_ORDER: [&str; 6] = [
+    SECRET_TYPE_URL,
+    RUNTIME_TYPE_URL,
+    CLUSTER_TYPE_URL,
+    ENDPOINT_TYPE_URL,
+    LISTENER_TYPE_URL,
+    ROUTE_TYPE_URL,
+];
+
+/// Snapshot of one resource type waiting for the debounce window to close.
+#[derive(Debug)]
+pub(crate) struct StagedSnapshot {
+    pub(crate) built: Vec<BuiltResource>,
+    /// Version claimed for the snapshot when replicas coordinate versions.
+    pub(crate) claimed_version: Option<u64>,
+}
+
+/// Writes the snapshots staged during a window, in push order, and returns
+/// the update to broadcast.
+type ApplyStaged = Arc<
+    dyn Fn(
+            Vec<(String, StagedSnapshot)>,
+        ) -> Pin<Box<dyn Future<Output = Option<ResourceUpdate>> + Send>>
+        + Send
+        + Sync,
+>;
+
+pub(crate) struct UpdatePublisher {
+    tx: broadcast::Sender<Arc<ResourceUpdate>>,
+    window: Option<Duration>,
+    staged: Arc<Mutex<HashMap<String, StagedSnapshot>>>,
+    apply: ApplyStaged,
+}
+
+impl std::fmt::Debug for UpdatePublisher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UpdatePublisher").field("window", &self.window).finish_non_exhaustive()
+    }
+}
+
+impl UpdatePublisher {
+    /// Publisher whose debounce `window` closes by passing the staged
+    /// snapshots to `apply`.
+    pub(crate) fn new<F, Fut>(window: Option<Duration>, apply: F) -> Self
+    where
+        F: Fn(Vec<(String, StagedSnapshot)>) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Option<ResourceUpdate>> + Send + 'static,
+    {
+        let (tx, _) = broadcast::channel(128);
+        Self {
+            tx,
+            window,
+            staged: Arc::new(Mutex::new(HashMap::new())),
+            apply: Arc::new(move |snapshots| Box::pin(apply(snapshots))),
+        }
+    }
+
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<Arc<ResourceUpdate>> {
+        self.tx.subscribe()
+    }
+
+    /// Broadcast `update` now.
+    pub(crate) fn publish(&self, update: ResourceUpdate) {
+        let _ = self.tx.send(Arc::new(ordered(update)));
+    }
+
+    /// Hold `snapshot` of `type_url` until the debounce window closes,
+    /// replacing a snapshot of the same type staged earlier in the window.
+    /// The first staged snapshot opens the window; when it closes, the
+    /// publisher's apply function writes every staged snapshot, in push
+    /// order, and the update it returns is broadcast.
+    ///
+    /// Hands `snapshot` back when no window is configured, for the caller to
+    /// apply now.
+    pub(crate) fn stage(&self, type_url: &str, snapshot: StagedSnapshot) -> Option<StagedSnapshot> {
+        let (Some(window), Ok(runtime)) = (self.window, tokio::runtime::Handle::try_current())
+        else {
+            return Some(snapshot);
+        };
+
+        let mut staged = self.staged.lock().expect("staged snapshot lock poisoned");
+        let opens_window = staged.is_empty();
+        staged.insert(type_url.to_string(), snapshot);
+        if opens_window {
+            let staged = self.staged.clone();
+            let tx = self.tx.clone();
+            let apply = self.apply.clone();
+            runtime.spawn(async move {
+                tokio::time::sleep(window).await;
+                let mut snapshots: Vec<(String, StagedSnapshot)> =
+                    staged.lock().expect("staged snapshot lock poisoned").drain().collect();
+                snapshots.sort_by_key(|(type_url, _)| push_position(type_url));
+                if let Some(update) = apply(snapshots).await {
+                    let _ = tx.send(Arc::new(ordered(update)));
+                }
+            });
+        }
+        None
+    }
+}
+
+pub(crate) fn push_position(type_url: &str) -> usize {
+    PUSH_ORDER.iter().position(|ordered| *ordered == type_url).unwrap_or(PUSH_ORDER.len())
+}
+
+fn ordered(mut update: ResourceUpdate) -> ResourceUpdate {
+    update.deltas.sort_by_key(|delta| push_position(&delta.type_url));
+    update
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xds::state::ResourceDelta;
+    use envoy_types::pb::google::protobuf::Any;
+
+    fn snapshot(names: &[&str]) -> StagedSnapshot {
+        StagedSnapshot {
+            built: names
+                .iter()
+                .map(|name| BuiltResource { name: name.to_string(), resource: Any::default() })
+                .collect(),
+            claimed_version: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn stages_snapshots_within_the_window_and_applies_them_in_push_order() {
+        let applied = Arc::new(Mutex::new(Vec::new()));
+        let publisher = UpdatePublisher::new(Some(Duration::from_millis(50)), {
+            let applied = applied.clone();
+            move |staged: Vec<(String, StagedSnapshot)>| {
+                let applied = applied.clone();
+                async move {
+                    let deltas = staged
+                        .iter()
+                        .map(|(type_url, _)| ResourceDelta {
+                            type_url: type_url.clone(),
+                            ..Default::default()
+                        })
+                        .collect();
+                    applied.lock().unwrap().extend(staged);
+                    Some(ResourceUpdate { version: 7, deltas })
+                }
+            }
+        });
+        let mut receiver = publisher.subscribe();
+
+        for (type_url, names) in [
+            (ROUTE_TYPE_URL, vec!["routes"]),
+            (LISTENER_TYPE_URL, vec!["gateway"]),
+            (CLUSTER_TYPE_URL, vec!["payments"]),
+            (ROUTE_TYPE_URL, vec![]),
+        ] {
+            assert!(publisher.stage(type_url, snapshot(&names)).is_none());
+        }
+        assert!(applied.lock().unwrap().is_empty(), "nothing is applied before the window closes");
+        assert!(receiver.try_recv().is_err(), "nothing is pushed before the window closes");
+
+        let update = receiver.recv().await.expect("single update");
+        let types: Vec<&str> = update.deltas.iter().map(|delta| delta.type_url.as_str()).collect();
+        assert_eq!(types, vec![CLUSTER_TYPE_URL, LISTENER_TYPE_URL, ROUTE_TYPE_URL]);
+
+        let applied = applied.lock().unwrap();
+        let types: Vec<&str> = applied.iter().map(|(type_url, _)| type_url.as_str()).collect();
+        assert_eq!(types, vec![CLUSTER_TYPE_URL, LISTENER_TYPE_URL, ROUTE_TYPE_URL]);
+        assert!(applied[2].1.built.is_empty(), "the latest route snapshot wins");
+        assert!(receiver.try_recv().is_err(), "the window produces a single update");
+    }
+
+    #[tokio::test]
+    async fn hands_snapshots_back_without_a_window() {
+        let publisher = UpdatePublisher::new(None, |_| async { None });
+        let mut receiver = publisher.subscribe();
+
+        let handed_back = publisher.stage(CLUSTER_TYPE_URL, snapshot(&["payments"]));
+        assert_eq!(handed_back.expect("snapshot handed back").built.len(), 1);
+
+        publisher.publish(ResourceUpdate { version: 2, deltas: Vec::new() });
+        assert_eq!(receiver.try_recv().expect("immediate update").version, 2);
+    }
+}
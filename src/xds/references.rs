@@ -0,0 +1,408 @@
+//! Names referenced between cached xDS resources.
+//!
+//! Node scoping starts from the listeners a node may receive and follows their
+//! references, so the node is also served only the route configurations,
+//! clusters and endpoints those listeners need.
+
+use std::collections::HashSet;
+
+use envoy_types::pb::envoy::config::core::v3::http_uri::HttpUpstreamType;
+use envoy_types::pb::envoy::config::listener::v3::{filter, FilterChain, Listener};
+use envoy_types::pb::envoy::config::route::v3::{
+    route::Action, route_action::ClusterSpecifier, RouteConfiguration,
+};
+use envoy_types::pb::envoy::extensions::filters::http::jwt_authn::v3::{
+    jwt_provider::JwksSourceSpecifier, JwtAuthentication,
+};
+use envoy_types::pb::envoy::extensions::filters::network::http_connection_manager::v3::{
+    http_connection_manager::RouteSpecifier, http_filter, HttpConnectionManager,
+};
+use envoy_types::pb::envoy::extensions::filters::network::tcp_proxy::v3::{tcp_proxy, TcpProxy};
+use envoy_types::pb::google::protobuf::Any;
+use prost::Message;
+
+use crate::errors::{Error, Result};
+use crate::xds::state::CachedResource;
+
+const HTTP_CONNECTION_MANAGER_TYPE_URL: &str =
+    "type.googleapis.com/envoy.extensions.filters.network.http_connection_manager.v3.HttpConnectionManager";
+const TCP_PROXY_TYPE_URL: &str =
+    "type.googleapis.com/envoy.extensions.filters.network.tcp_proxy.v3.TcpProxy";
+const JWT_AUTHN_TYPE_URL: &str =
+    "type.googleapis.com/envoy.extensions.filters.http.jwt_authn.v3.JwtAuthentication";
+
+/// Route configuration and cluster names referenced by a set of resources.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct References {
+    pub routes: HashSet<String>,
+    pub clusters: HashSet<String>,
+}
+
+impl References {
+    /// Collect the RDS route configurations and clusters used by `listeners`,
+    /// including clusters of inline route configurations, TCP proxies and
+    /// remote JWKS providers.
+    pub fn from_listeners(listeners: &[CachedResource]) -> Result<Self> {
+        let mut references = Self::default();
+        for cached in listeners {
+            let listener = decode::<Listener>(&cached.body, &cached.name)?;
+            for chain in listener.filter_chains.iter().chain(listener.default_filter_chain.iter()) {
+                references.add_filter_chain(chain, &cached.name)?;
+            }
+        }
+        Ok(references)
+    }
+
+    /// Add the clusters targeted by the routes of `route_configs`.
+    pub fn add_route_clusters(&mut self, route_configs: &[CachedResource]) -> Result<()> {
+        for cached in route_configs {
+            let route_config = decode::<RouteConfiguration>(&cached.body, &cached.name)?;
+            self.add_route_configuration(&route_config);
+        }
+        Ok(())
+    }
+
+    fn add_filter_chain(&mut self, chain: &FilterChain, listener: &str) -> Result<()> {
+        for network_filter in &chain.filters {
+            let Some(filter::ConfigType::TypedConfig(any)) = &network_filter.config_type else {
+                continue;
+            };
+            match any.type_url.as_str() {
+                HTTP_CONNECTION_MANAGER_TYPE_URL => {
+                    let manager = decode::<HttpConnectionManager>(any, listener)?;
+                    match &manager.route_specifier {
+                        Some(RouteSpecifier::Rds(rds)) => {
+                            self.routes.insert(rds.route_config_name.clone());
+                        }
+                        Some(RouteSpecifier::RouteConfig(route_config)) => {
+                            self.add_route_configuration(route_config);
+                        }
+                        _ => {}
+                    }
+                    for http_filter in &manager.http_filters {
+                        if let Some(http_filter::ConfigType::TypedConfig(any)) =
+                            &http_filter.config_type
+                        {
+                            if any.type_url == JWT_AUTHN_TYPE_URL {
+                                self.add_jwks_clusters(&decode::<JwtAuthentication>(
+                                    any, listener,
+                                )?);
+                            }
+                        }
+                    }
+                }
+                TCP_PROXY_TYPE_URL => {
+                    let proxy = decode::<TcpProxy>(any, listener)?;
+                    match proxy.cluster_specifier {
+                        Some(tcp_proxy::ClusterSpecifier::Cluster(cluster)) => {
+                            self.clusters.insert(cluster);
+                        }
+                        Some(tcp_proxy::ClusterSpecifier::WeightedClusters(weighted)) => {
+                            self.clusters
+                                .extend(weighted.clusters.into_iter().map(|weight| weight.name));
+                        }
+                        None => {}
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn add_route_configuration(&mut self, route_config: &RouteConfiguration) {
+        let actions = route_config
+            .virtual_hosts
+            .iter()
+            .flat_map(|virtual_host| virtual_host.routes.iter())
+            .filter_map(|route| match &route.action {
+                Some(Action::Route(action)) => Some(action),
+                _ => None,
+            });
+
+        for action in actions {
+            match &action.cluster_specifier {
+                Some(ClusterSpecifier::Cluster(cluster)) => {
+                    self.clusters.insert(cluster.clone());
+                }
+                Some(ClusterSpecifier::WeightedClusters(weighted)) => {
+                    self.clusters
+                        .extend(weighted.clusters.iter().map(|weight| weight.name.clone()));
+                }
+                _ => {}
+            }
+            self.clusters
+                .extend(action.request_mirror_policies.iter().map(|mirror| mirror.cluster.clone()));
+        }
+    }
+
+    fn add_jwks_clusters(&mut self, authentication: &JwtAuthentication) {
+        for provider in authentication.providers.values() {
+            if let Some(JwksSourceSpecifier::RemoteJwks(remote)) = &provider.jwks_source_specifier {
+                if let Some(HttpUpstreamType::Cluster(cluster)) =
+                    remote.http_uri.as_ref().and_then(|uri| uri.http_upstream_type.as_ref())
+                {
+                    self.clusters.insert(cluster.clone());
+                }
+            }
+        }
+    }
+}
+
+fn decode<M: Message + Default>(any: &Any, resource: &str) -> Result<M> {
+    M::decode(any.value.as_slice()).map_err(|e| {
+        Error::internal(format!("Failed to decode {} in '{}': {}", any.type_url, resource, e))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::xds::listener::{FilterChainConfig, FilterConfig, FilterType, ListenerConfig};
+    use crate::xds::resources::{LISTENER_TYPE_URL, ROUTE_TYPE_URL};
+    use crate::xds::route::{
+        PathMatch, RouteActionConfig, RouteConfig, RouteMatchConfig, RouteRule, VirtualHostConfig,
+    };
+    use envoy_types::pb::envoy::config::core::v3::HttpUri;
+    use envoy_types::pb::envoy::config::listener::v3::Filter;
+    use envoy_types::pb::envoy::config::route::v3::{
+        route_action::RequestMirrorPolicy, weighted_cluster::ClusterWeight, Route, RouteAction,
+        VirtualHost, WeightedCluster,
+    };
+    use envoy_types::pb::envoy::extensions::filters::http::jwt_authn::v3::{
+        JwtProvider, RemoteJwks,
+    };
+    use envoy_types::pb::envoy::extensions::filters::network::http_connection_manager::v3::{
+        HttpFilter, Rds,
+    };
+
+    fn listener(name: &str, filter_type: FilterType) -> CachedResource {
+        let config = ListenerConfig {
+            name: name.to_string(),
+            address: "0.0.0.0".to_string(),
+            port: 10000,
+            filter_chains: vec![FilterChainConfig {
+                name: None,
+                filters: vec![FilterConfig { name: "filter".to_string(), filter_type }],
+                tls_context: None,
+            }],
+        };
+        CachedResource::new(
+            name.to_string(),
+            LISTENER_TYPE_URL.to_string(),
+            1,
+            Any {
+                type_url: LISTENER_TYPE_URL.to_string(),
+                value: config.to_envoy_listener().unwrap().encode_to_vec(),
+            },
+        )
+    }
+
+    fn route_config(name: &str, cluster: &str) -> RouteConfig {
+        RouteConfig {
+            name: name.to_string(),
+            virtual_hosts: vec![VirtualHostConfig {
+                name: "default".to_string(),
+                domains: vec!["*".to_string()],
+                routes: vec![RouteRule {
+                    name: None,
+                    r#match: RouteMatchConfig {
+                        path: PathMatch::Prefix("/".to_string()),
+                        headers: None,
+                        query_parameters: None,
+                    },
+                    action: RouteActionConfig::Cluster {
+                        name: cluster.to_string(),
+                        timeout: None,
+                        prefix_rewrite: None,
+                        path_template_rewrite: None,
+                    },
+                    typed_per_filter_config: Default::default(),
+                }],
+                typed_per_filter_config: Default::default(),
+            }],
+        }
+    }
+
+    fn cached(name: &str, type_url: &str, message: &impl Message) -> CachedResource {
+        CachedResource::new(
+            name.to_string(),
+            type_url.to_string(),
+            1,
+            Any { type_url: type_url.to_string(), value: message.encode_to_vec() },
+        )
+    }
+
+    fn manager_listener(name: &str, manager: HttpConnectionManager) -> CachedResource {
+        let listener = Listener {
+            name: name.to_string(),
+            filter_chains: vec![FilterChain {
+                filters: vec![Filter {
+                    name: "envoy.filters.network.http_connection_manager".to_string(),
+                    config_type: Some(filter::ConfigType::TypedConfig(Any {
+                        type_url: HTTP_CONNECTION_MANAGER_TYPE_URL.to_string(),
+                        value: manager.encode_to_vec(),
+                    })),
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        cached(name, LISTENER_TYPE_URL, &listener)
+    }
+
+    fn action_routes(name: &str, action: RouteAction) -> CachedResource {
+        let route_config = RouteConfiguration {
+            name: name.to_string(),
+            virtual_hosts: vec![VirtualHost {
+                name: "default".to_string(),
+                domains: vec!["*".to_string()],
+                routes: vec![Route { action: Some(Action::Route(action)), ..Default::default() }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        cached(name, ROUTE_TYPE_URL, &route_config)
+    }
+
+    fn names(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn follows_listener_routes_and_clusters() {
+        let listeners = vec![
+            listener(
+                "rds-listener",
+                FilterType::HttpConnectionManager {
+                    route_config_name: Some("payments-routes".to_string()),
+                    inline_route_config: None,
+                    access_log: None,
+                    tracing: None,
+                    http_filters: Vec::new(),
+                },
+            ),
+            listener(
+                "inline-listener",
+                FilterType::HttpConnectionManager {
+                    route_config_name: None,
+                    inline_route_config: Some(route_config("inline", "inline-cluster")),
+                    access_log: None,
+                    tracing: None,
+                    http_filters: Vec::new(),
+                },
+            ),
+            listener(
+                "tcp-listener",
+                FilterType::TcpProxy { cluster: "tcp-cluster".to_string(), access_log: None },
+            ),
+        ];
+
+        let mut references = References::from_listeners(&listeners).unwrap();
+        assert_eq!(references.routes, HashSet::from(["payments-routes".to_string()]));
+        assert_eq!(
+            references.clusters,
+            HashSet::from(["inline-cluster".to_string(), "tcp-cluster".to_string()])
+        );
+
+        let routes = vec![CachedResource::new(
+            "payments-routes".to_string(),
+            ROUTE_TYPE_URL.to_string(),
+            1,
+            Any {
+                type_url: ROUTE_TYPE_URL.to_string(),
+                value: route_config("payments-routes", "payments-api")
+                    .to_envoy_route_configuration()
+                    .unwrap()
+                    .encode_to_vec(),
+            },
+        )];
+        references.add_route_clusters(&routes).unwrap();
+        assert!(references.clusters.contains("payments-api"));
+    }
+
+    #[test]
+    fn follows_weighted_clusters_of_routes() {
+        let routes = action_routes(
+            "canary-routes",
+            RouteAction {
+                cluster_specifier: Some(ClusterSpecifier::WeightedClusters(WeightedCluster {
+                    clusters: vec![
+                        ClusterWeight { name: "orders-blue".to_string(), ..Default::default() },
+                        ClusterWeight { name: "orders-green".to_string(), ..Default::default() },
+                    ],
+                    ..Default::default()
+                })),
+                ..Default::default()
+            },
+        );
+
+        let mut references = References::default();
+        references.add_route_clusters(&[routes]).unwrap();
+        assert_eq!(references.clusters, names(&["orders-blue", "orders-green"]));
+    }
+
+    #[test]
+    fn follows_request_mirror_clusters_of_routes() {
+        let routes = action_routes(
+            "mirrored-routes",
+            RouteAction {
+                cluster_specifier: Some(ClusterSpecifier::Cluster("orders".to_string())),
+                request_mirror_policies: vec![RequestMirrorPolicy {
+                    cluster: "orders-shadow".to_string(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+        );
+
+        let mut references = References::default();
+        references.add_route_clusters(&[routes]).unwrap();
+        assert_eq!(references.clusters, names(&["orders", "orders-shadow"]));
+    }
+
+    #[test]
+    fn follows_remote_jwks_clusters_of_jwt_filters() {
+        let remote = JwtProvider {
+            jwks_source_specifier: Some(JwksSourceSpecifier::RemoteJwks(RemoteJwks {
+                http_uri: Some(HttpUri {
+                    uri: "https://auth.example.com/.well-known/jwks.json".to_string(),
+                    http_upstream_type: Some(HttpUpstreamType::Cluster("jwks".to_string())),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        let local = JwtProvider {
+            jwks_source_specifier: Some(JwksSourceSpecifier::LocalJwks(Default::default())),
+            ..Default::default()
+        };
+        let authentication = JwtAuthentication {
+            providers: [("remote".to_string(), remote), ("local".to_string(), local)]
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        };
+        let manager = HttpConnectionManager {
+            route_specifier: Some(RouteSpecifier::Rds(Rds {
+                route_config_name: "secured-routes".to_string(),
+                ..Default::default()
+            })),
+            http_filters: vec![HttpFilter {
+                name: "envoy.filters.http.jwt_authn".to_string(),
+                config_type: Some(http_filter::ConfigType::TypedConfig(Any {
+                    type_url: JWT_AUTHN_TYPE_URL.to_string(),
+                    value: authentication.encode_to_vec(),
+                })),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let references =
+            References::from_listeners(&[manager_listener("secured", manager)]).unwrap();
+        assert_eq!(references.routes, names(&["secured-routes"]));
+        assert_eq!(references.clusters, names(&["jwks"]));
+    }
+}
@@ -0,0 +1,339 @@
+//! Registry of Envoy nodes connected to the ADS server.
+//!
+//! Stream loops record what each node subscribed to, which versions were sent
+//! and which were acknowledged or rejected, so operators can see whether a
+//! configuration change actually reached the data plane.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::RwLock;
+
+use chrono::{DateTime, Utc};
+use envoy_types::pb::envoy::config::core::v3::{node::UserAgentVersionType, Node};
+use envoy_types::pb::google::protobuf::{value::Kind, Struct, Value};
+use envoy_types::pb::google::rpc::Status as RpcStatus;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Delivery state for one resource type on one node.
+#[derive(Debug, Clone, Default, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceTypeStatus {
+    pub last_sent_version: Option<String>,
+    pub last_sent_nonce: Option<String>,
+    pub last_acked_version: Option<String>,
+    pub last_nack: Option<NackDetail>,
+}
+
+/// Error detail from the most recent response a node rejected.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NackDetail {
+    pub nonce: String,
+    /// Version carried by the rejected response, when the nonce is still known.
+    pub version: Option<String>,
+    pub code: i32,
+    pub message: String,
+    pub received_at: DateTime<Utc>,
+}
+
+/// How a discovery request relates to the last response sent for its type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequestKind {
+    /// Initial subscription or a request for a type not tracked for the node.
+    Subscribe,
+    /// Acknowledgement of `version`, when it could be resolved.
+    Ack { version: Option<String> },
+    /// Rejection of `version`. `repeated` is set when the same version was
+    /// already rejected by this node.
+    Nack { version: Option<String>, repeated: bool },
+}
+
+/// Connected Envoy node as seen by the ADS server.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeStatus {
+    pub node_id: String,
+    pub cluster: String,
+    #[schema(value_type = Object)]
+    pub metadata: serde_json::Value,
+    pub user_agent: Option<String>,
+    pub build_version: Option<String>,
+    /// Whether the node matches the canary selector.
+    pub canary: bool,
+    pub active_streams: usize,
+    pub connected_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    /// Delivery state keyed by subscribed type URL.
+    pub subscriptions: BTreeMap<String, ResourceTypeStatus>,
+    /// Node as sent on its most recent stream, used to scope resources for CSDS.
+    #[serde(skip)]
+    pub node: Node,
+}
+
+/// Thread-safe registry of connected nodes keyed by node ID.
+#[derive(Debug, Default)]
+pub struct NodeRegistry {
+    nodes: RwLock<HashMap<String, NodeStatus>>,
+}
+
+impl NodeRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a new stream for `node` and return its ID.
+    pub fn connect(&self, node: &Node, canary: bool) -> String {
+        let now = Utc::now();
+        let mut nodes = self.nodes.write().expect("node registry lock poisoned");
+        let entry = nodes.entry(node.id.clone()).or_insert_with(|| NodeStatus {
+            node_id: node.id.clone(),
+            cluster: String::new(),
+            metadata: serde_json::Value::Null,
+            user_agent: None,
+            build_version: None,
+            canary: false,
+            active_streams: 0,
+            connected_at: now,
+            last_seen_at: now,
+            subscriptions: BTreeMap::new(),
+            node: Node::default(),
+        });
+
+        entry.cluster = node.cluster.clone();
+        entry.metadata = node.metadata.as_ref().map(struct_to_json).unwrap_or_default();
+        entry.user_agent = Some(node.user_agent_name.clone()).filter(|name| !name.is_empty());
+        entry.build_version = build_version(node);
+        entry.canary = canary;
+        entry.node = node.clone();
+        entry.active_streams += 1;
+        entry.last_seen_at = now;
+
+        node.id.clone()
+    }
+
+    /// Drop a stream for `node_id`, forgetting the node once no streams remain.
+    pub fn disconnect(&self, node_id: &str) {
+        let mut nodes = self.nodes.write().expect("node registry lock poisoned");
+        if let Some(entry) = nodes.get_mut(node_id) {
+            entry.active_streams = entry.active_streams.saturating_sub(1);
+            if entry.active_streams == 0 {
+                nodes.remove(node_id);
+            }
+        }
+    }
+
+    /// Record a discovery request, classifying it as a subscription, ACK or NACK.
+    ///
+    /// `version_info` is only carried by SotW requests; delta ACKs are matched
+    /// against the last nonce sent for the type instead.
+    pub fn record_request(
+        &self,
+        node_id: &str,
+        type_url: &str,
+        response_nonce: &str,
+        version_info: Option<&str>,
+        error_detail: Option<&RpcStatus>,
+    ) -> RequestKind {
+        let mut nodes = self.nodes.write().expect("node registry lock poisoned");
+        let Some(entry) = nodes.get_mut(node_id) else {
+            return RequestKind::Subscribe;
+        };
+
+        entry.last_seen_at = Utc::now();
+        let status = entry.subscriptions.entry(type_url.to_string()).or_default();
+
+        if response_nonce.is_empty() {
+            return RequestKind::Subscribe;
+        }
+
+        let sent_version = if status.last_sent_nonce.as_deref() == Some(response_nonce) {
+            status.last_sent_version.clone()
+        } else {
+            None
+        };
+
+        match error_detail {
+            Some(error) => {
+                let repeated = sent_version.is_some()
+                    && status.last_nack.as_ref().and_then(|nack| nack.version.as_ref())
+                        == sent_version.as_ref();
+                status.last_nack = Some(NackDetail {
+                    nonce: response_nonce.to_string(),
+                    version: sent_version.clone(),
+                    code: error.code,
+                    message: error.message.clone(),
+                    received_at: entry.last_seen_at,
+                });
+                RequestKind::Nack { version: sent_version, repeated }
+            }
+            None => {
+                let acked = version_info.map(str::to_string).or(sent_version);
+                if acked.is_some() {
+                    status.last_acked_version = acked.clone();
+                }
+                RequestKind::Ack { version: acked }
+            }
+        }
+    }
+
+    /// Record a response sent to `node_id`.
+    pub fn record_response(&self, node_id: &str, type_url: &str, version: &str, nonce: &str) {
+        let mut nodes = self.nodes.write().expect("node registry lock poisoned");
+        if let Some(entry) = nodes.get_mut(node_id) {
+            let status = entry.subscriptions.entry(type_url.to_string()).or_default();
+            status.last_sent_version = Some(version.to_string());
+            status.last_sent_nonce = Some(nonce.to_string());
+        }
+    }
+
+    /// Snapshot of every connected node, ordered by node ID.
+    pub fn list(&self) -> Vec<NodeStatus> {
+        let nodes = self.nodes.read().expect("node registry lock poisoned");
+        let mut list: Vec<NodeStatus> = nodes.values().cloned().collect();
+        list.sort_by(|a, b| a.node_id.cmp(&b.node_id));
+        list
+    }
+
+    pub fn is_canary(&self, node_id: &str) -> bool {
+        let nodes = self.nodes.read().expect("node registry lock poisoned");
+        nodes.get(node_id).is_some_and(|entry| entry.canary)
+    }
+
+    pub fn get(&self, node_id: &str) -> Option<NodeStatus> {
+        let nodes = self.nodes.read().expect("node registry lock poisoned");
+        nodes.get(node_id).cloned()
+    }
+}
+
+fn build_version(node: &Node) -> Option<String> {
+    match node.user_agent_version_type.as_ref()? {
+        UserAgentVersionType::UserAgentVersion(version) => {
+            Some(version.clone()).filter(|version| !version.is_empty())
+        }
+        UserAgentVersionType::UserAgentBuildVersion(build) => build
+            .version
+            .as_ref()
+            .map(|v| format!("{}.{}.{}", v.major_number, v.minor_number, v.patch)),
+    }
+}
+
+fn struct_to_json(value: &Struct) -> serde_json::Value {
+    serde_json::Value::Object(
+        value.fields.iter().map(|(key, value)| (key.clone(), value_to_json(value))).collect(),
+    )
+}
+
+fn value_to_json(value: &Value) -> serde_json::Value {
+    match value.kind.as_ref() {
+        None | Some(Kind::NullValue(_)) => serde_json::Value::Null,
+        Some(Kind::NumberValue(number)) => serde_json::Number::from_f64(*number)
+            .map(serde_json::Value::Number)
+            .unwrap_or(serde_json::Value::Null),
+        Some(Kind::StringValue(text)) => serde_json::Value::String(text.clone()),
+        Some(Kind::BoolValue(flag)) => serde_json::Value::Bool(*flag),
+        Some(Kind::StructValue(inner)) => struct_to_json(inner),
+        Some(Kind::ListValue(list)) => {
+            serde_json::Value::Array(list.values.iter().map(value_to_json).collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use envoy_types::pb::envoy::config::core::v3::BuildVersion;
+    use envoy_types::pb::envoy::r#type::v3::SemanticVersion;
+
+    fn node(id: &str) -> Node {
+        let mut fields = std::collections::HashMap::new();
+        fields.insert(
+            "team".to_string(),
+            Value { kind: Some(Kind::StringValue("payments".to_string())) },
+        );
+        Node {
+            id: id.to_string(),
+            cluster: "edge".to_string(),
+            metadata: Some(Struct { fields }),
+            user_agent_name: "envoy".to_string(),
+            user_agent_version_type: Some(UserAgentVersionType::UserAgentBuildVersion(
+                BuildVersion {
+                    version: Some(SemanticVersion { major_number: 1, minor_number: 31, patch: 2 }),
+                    metadata: None,
+                },
+            )),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn connect_records_node_identity() {
+        let registry = NodeRegistry::new();
+        let node_id = registry.connect(&node("envoy-1"), false);
+
+        let status = registry.get(&node_id).expect("node registered");
+        assert_eq!(status.cluster, "edge");
+        assert_eq!(status.metadata, serde_json::json!({ "team": "payments" }));
+        assert_eq!(status.user_agent.as_deref(), Some("envoy"));
+        assert_eq!(status.build_version.as_deref(), Some("1.31.2"));
+        assert_eq!(status.active_streams, 1);
+    }
+
+    #[test]
+    fn tracks_sent_acked_and_nacked_versions() {
+        let registry = NodeRegistry::new();
+        let node_id = registry.connect(&node("envoy-1"), false);
+        let type_url = "type.googleapis.com/envoy.config.cluster.v3.Cluster";
+
+        registry.record_request(&node_id, type_url, "", Some(""), None);
+        registry.record_response(&node_id, type_url, "3", "nonce-1");
+        registry.record_request(&node_id, type_url, "nonce-1", Some("3"), None);
+
+        registry.record_response(&node_id, type_url, "4", "nonce-2");
+        let rejection = RpcStatus { code: 3, message: "bad cluster".to_string(), details: vec![] };
+        let kind =
+            registry.record_request(&node_id, type_url, "nonce-2", Some("3"), Some(&rejection));
+        assert_eq!(kind, RequestKind::Nack { version: Some("4".to_string()), repeated: false });
+
+        registry.record_response(&node_id, type_url, "4", "nonce-3");
+        let kind =
+            registry.record_request(&node_id, type_url, "nonce-3", Some("3"), Some(&rejection));
+        assert_eq!(kind, RequestKind::Nack { version: Some("4".to_string()), repeated: true });
+
+        let status = registry.get(&node_id).unwrap().subscriptions[type_url].clone();
+        assert_eq!(status.last_sent_version.as_deref(), Some("4"));
+        assert_eq!(status.last_sent_nonce.as_deref(), Some("nonce-3"));
+        assert_eq!(status.last_acked_version.as_deref(), Some("3"));
+        let nack = status.last_nack.expect("nack recorded");
+        assert_eq!(nack.nonce, "nonce-3");
+        assert_eq!(nack.version.as_deref(), Some("4"));
+        assert_eq!(nack.message, "bad cluster");
+    }
+
+    #[test]
+    fn delta_ack_resolves_version_from_nonce() {
+        let registry = NodeRegistry::new();
+        let node_id = registry.connect(&node("envoy-1"), false);
+        let type_url = "type.googleapis.com/envoy.config.listener.v3.Listener";
+
+        registry.record_response(&node_id, type_url, "7", "nonce-7");
+        let kind = registry.record_request(&node_id, type_url, "nonce-7", None, None);
+        assert_eq!(kind, RequestKind::Ack { version: Some("7".to_string()) });
+
+        let status = registry.get(&node_id).unwrap().subscriptions[type_url].clone();
+        assert_eq!(status.last_acked_version.as_deref(), Some("7"));
+    }
+
+    #[test]
+    fn node_is_forgotten_after_last_stream_disconnects() {
+        let registry = NodeRegistry::new();
+        registry.connect(&node("envoy-1"), false);
+        registry.connect(&node("envoy-1"), false);
+
+        registry.disconnect("envoy-1");
+        assert_eq!(registry.get("envoy-1").unwrap().active_streams, 1);
+
+        registry.disconnect("envoy-1");
+        assert!(registry.get("envoy-1").is_none());
+        assert!(registry.list().is_empty());
+    }
+}
@@ -0,0 +1,208 @@
+//! Last-known-good snapshots and the NACK policies built on them.
+//!
+//! A snapshot is taken from the resource cache whenever a node ACKs the
+//! currently published version of a type. When a later version is rejected,
+//! [`NackPolicy::Pin`](crate::config::NackPolicy) serves the snapshot to the
+//! rejecting node, while [`NackPolicy::Revert`](crate::config::NackPolicy)
+//! writes the repository rows captured alongside it back to the database.
+
+use std::collections::HashMap;
+
+use crate::storage::{
+    ClusterData, ListenerData, RouteData, UpdateClusterRequest, UpdateListenerRequest,
+    UpdateRouteRepositoryRequest as UpdateRouteRequest,
+};
+use crate::xds::resources::{
+    CLUSTER_TYPE_URL, ENDPOINT_TYPE_URL, LISTENER_TYPE_URL, ROUTE_TYPE_URL,
+};
+use crate::xds::state::{CachedResource, XdsState, SNAPSHOT_ROW_LIMIT};
+use crate::{Error, Result};
+
+/// Resources of one type that an Envoy node accepted.
+#[derive(Debug, Clone)]
+pub struct KnownGoodSnapshot {
+    pub version: u64,
+    pub resources: Vec<CachedResource>,
+    pub(crate) rows: Option<RepositoryRows>,
+}
+
+/// Repository rows backing a snapshot, keyed by resource name.
+#[derive(Debug, Clone)]
+pub(crate) enum RepositoryRows {
+    Clusters(HashMap<String, ClusterData>),
+    Routes(HashMap<String, RouteData>),
+    Listeners(HashMap<String, ListenerData>),
+}
+
+/// Capture the repository rows that produce `type_url`, if it is repository backed.
+pub(crate) async fn capture_rows(
+    state: &XdsState,
+    type_url: &str,
+) -> Result<Option<RepositoryRows>> {
+    let rows = match type_url {
+        CLUSTER_TYPE_URL | ENDPOINT_TYPE_URL => match &state.cluster_repository {
+            Some(repo) => RepositoryRows::Clusters(
+                repo.list(Some(SNAPSHOT_ROW_LIMIT), None)
+                    .await?
+                    .into_iter()
+                    .map(|row| (row.name.clone(), row))
+                    .collect(),
+            ),
+            None => return Ok(None),
+        },
+        ROUTE_TYPE_URL => match &state.route_repository {
+            Some(repo) => RepositoryRows::Routes(
+                repo.list(Some(SNAPSHOT_ROW_LIMIT), None)
+                    .await?
+                    .into_iter()
+                    .map(|row| (row.name.clone(), row))
+                    .collect(),
+            ),
+            None => return Ok(None),
+        },
+        LISTENER_TYPE_URL => match &state.listener_repository {
+            Some(repo) => RepositoryRows::Listeners(
+                repo.list(Some(SNAPSHOT_ROW_LIMIT), None)
+                    .await?
+                    .into_iter()
+                    .map(|row| (row.name.clone(), row))
+                    .collect(),
+            ),
+            None => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+    Ok(Some(rows))
+}
+
+/// Names of cached resources that are new or changed relative to `snapshot`.
+pub(crate) fn changed_since(
+    snapshot: &KnownGoodSnapshot,
+    current: &[CachedResource],
+) -> Vec<String> {
+    let known: HashMap<&str, &CachedResource> =
+        snapshot.resources.iter().map(|resource| (resource.name.as_str(), resource)).collect();
+
+    let mut changed: Vec<String> = current
+        .iter()
+        .filter(|resource| {
+            known.get(resource.name.as_str()).map(|good| good.body != resource.body).unwrap_or(true)
+        })
+        .map(|resource| resource.name.clone())
+        .collect();
+    changed.sort();
+    changed
+}
+
+/// Restore each named resource to its row in `rows`, deleting rows that did
+/// not exist when the snapshot was taken. Returns the names actually written.
+///
+/// Names without a repository row (e.g. Platform API resources) are skipped.
+pub(crate) async fn revert_rows(
+    state: &XdsState,
+    rows: &RepositoryRows,
+    names: &[String],
+) -> Result<Vec<String>> {
+    let mut reverted = Vec::new();
+
+    for name in names {
+        let changed = match rows {
+            RepositoryRows::Clusters(known) => {
+                let Some(repo) = &state.cluster_repository else { break };
+                let current = repo.get_by_name(name).await.ok();
+                match (current, known.get(name)) {
+                    (Some(current), Some(good))
+                        if current.configuration != good.configuration
+                            || current.service_name != good.service_name =>
+                    {
+                        repo.update(
+                            &current.id,
+                            UpdateClusterRequest {
+                                service_name: Some(good.service_name.clone()),
+                                configuration: Some(parse_configuration(&good.configuration)?),
+                            },
+                        )
+                        .await?;
+                        true
+                    }
+                    (Some(current), None) => {
+                        repo.delete(&current.id).await?;
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            RepositoryRows::Routes(known) => {
+                let Some(repo) = &state.route_repository else { break };
+                let current = repo.get_by_name(name).await.ok();
+                match (current, known.get(name)) {
+                    (Some(current), Some(good))
+                        if current.configuration != good.configuration
+                            || current.path_prefix != good.path_prefix
+                            || current.cluster_name != good.cluster_name =>
+                    {
+                        repo.update(
+                            &current.id,
+                            UpdateRouteRequest {
+                                path_prefix: Some(good.path_prefix.clone()),
+                                cluster_name: Some(good.cluster_name.clone()),
+                                configuration: Some(parse_configuration(&good.configuration)?),
+                            },
+                        )
+                        .await?;
+                        true
+                    }
+                    (Some(current), None) => {
+                        repo.delete(&current.id).await?;
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            RepositoryRows::Listeners(known) => {
+                let Some(repo) = &state.listener_repository else { break };
+                let current = repo.get_by_name(name).await.ok();
+                match (current, known.get(name)) {
+                    (Some(current), Some(good))
+                        if current.configuration != good.configuration
+                            || current.address != good.address
+                            || current.port != good.port
+                            || current.protocol != good.protocol =>
+                    {
+                        repo.update(
+                            &current.id,
+                            UpdateListenerRequest {
+                                address: Some(good.address.clone()),
+                                port: Some(good.port),
+                                protocol: Some(good.protocol.clone()),
+                                configuration: Some(parse_configuration(&good.configuration)?),
+                            },
+                        )
+                        .await?;
+                        true
+                    }
+                    (Some(current), None) => {
+                        repo.delete(&current.id).await?;
+                        true
+                    }
+                    _ => false,
+                }
+            }
+        };
+
+        if changed {
+            reverted.push(name.clone());
+        }
+    }
+
+    Ok(reverted)
+}
+
+fn parse_configuration(configuration: &str) -> Result<serde_json::Value> {
+    serde_json::from_str(configuration).map_err(|e| {
+        Error::internal(format!(
+            "Stored configuration in last-known-good snapshot is invalid: {}",
+            e
+        ))
+    })
+}
@@ -0,0 +1,139 @@
+//! Staged delivery of new snapshots to canary nodes.
+//!
+//! While a rollout is baking, nodes matching the canary selector receive the
+//! newest snapshot of a type and every other node keeps the stable snapshot
+//! that was current when the rollout started. A rollout is promoted once it
+//! has baked for the configured time without a NACK, or through the API.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::xds::rollback::KnownGoodSnapshot;
+
+/// Lifecycle of an active rollout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum RolloutPhase {
+    /// Candidate is served to canary nodes only.
+    Baking,
+    /// Candidate was withdrawn; every node is served the stable snapshot
+    /// until a newer version starts another rollout.
+    Aborted,
+}
+
+/// In-flight rollout for one resource type.
+#[derive(Debug, Clone)]
+pub(crate) struct Rollout {
+    pub stable: KnownGoodSnapshot,
+    pub candidate_version: u64,
+    pub started_at: DateTime<Utc>,
+    pub phase: RolloutPhase,
+    pub nack_count: u64,
+}
+
+impl Rollout {
+    pub fn new(stable: KnownGoodSnapshot, candidate_version: u64) -> Self {
+        Self {
+            stable,
+            candidate_version,
+            started_at: Utc::now(),
+            phase: RolloutPhase::Baking,
+            nack_count: 0,
+        }
+    }
+
+    /// Replace the candidate with a newer version, restarting the bake.
+    pub fn restart(&mut self, candidate_version: u64) {
+        self.candidate_version = candidate_version;
+        self.started_at = Utc::now();
+        self.phase = RolloutPhase::Baking;
+        self.nack_count = 0;
+    }
+
+    /// Whether the candidate has baked for `bake_time_secs` without a NACK.
+    pub fn is_baked(&self, bake_time_secs: u64, now: DateTime<Utc>) -> bool {
+        self.phase == RolloutPhase::Baking
+            && self.nack_count == 0
+            && now >= self.promote_after(bake_time_secs)
+    }
+
+    /// Whether a node is held on the stable snapshot.
+    pub fn holds(&self, canary: bool) -> bool {
+        self.phase == RolloutPhase::Aborted || !canary
+    }
+
+    pub fn promote_after(&self, bake_time_secs: u64) -> DateTime<Utc> {
+        self.started_at + Duration::seconds(bake_time_secs.min(i64::MAX as u64) as i64)
+    }
+
+    pub fn status(
+        &self,
+        type_url: &str,
+        resource_type: &str,
+        bake_time_secs: u64,
+    ) -> RolloutStatus {
+        RolloutStatus {
+            resource_type: resource_type.to_string(),
+            type_url: type_url.to_string(),
+            phase: self.phase,
+            stable_version: self.stable.version,
+            candidate_version: self.candidate_version,
+            started_at: self.started_at,
+            promote_after: (self.phase == RolloutPhase::Baking)
+                .then(|| self.promote_after(bake_time_secs)),
+            nack_count: self.nack_count,
+        }
+    }
+}
+
+/// Rollout state as reported by the API.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct RolloutStatus {
+    /// Short resource type name (`cluster`, `route`, `listener`, `endpoint`, `secret`, `runtime`).
+    pub resource_type: String,
+    pub type_url: String,
+    pub phase: RolloutPhase,
+    pub stable_version: u64,
+    pub candidate_version: u64,
+    pub started_at: DateTime<Utc>,
+    /// Earliest automatic promotion time; unset once aborted.
+    pub promote_after: Option<DateTime<Utc>>,
+    pub nack_count: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rollout() -> Rollout {
+        Rollout::new(KnownGoodSnapshot { version: 2, resources: Vec::new(), rows: None }, 3)
+    }
+
+    #[test]
+    fn bakes_only_without_nacks() {
+        let mut rollout = rollout();
+        let later = rollout.started_at + Duration::seconds(61);
+
+        assert!(!rollout.is_baked(60, rollout.started_at));
+        assert!(rollout.is_baked(60, later));
+
+        rollout.nack_count = 1;
+        assert!(!rollout.is_baked(60, later));
+    }
+
+    #[test]
+    fn abort_holds_canaries_too() {
+        let mut rollout = rollout();
+        assert!(rollout.holds(false));
+        assert!(!rollout.holds(true));
+
+        rollout.phase = RolloutPhase::Aborted;
+        assert!(rollout.holds(true));
+
+        rollout.restart(4);
+        assert_eq!(rollout.phase, RolloutPhase::Baking);
+        assert!(!rollout.holds(true));
+    }
+}
@@ -0,0 +1,181 @@
+//! Runtime layer served over RTDS.
+//!
+//! Every runtime key stored through `/api/v1/runtime` is published in a single
+//! `Runtime` resource named [`RUNTIME_LAYER_NAME`]. Envoy loads it as an RTDS
+//! layer, so runtime keys referenced by filters (for example the
+//! `runtime_key` of a CORS or local rate limit fractional percent) can be
+//! changed fleet-wide without rewriting listeners.
+
+use envoy_types::pb::envoy::service::runtime::v3::Runtime;
+use envoy_types::pb::google::protobuf::{Any, Struct};
+use prost::Message;
+use tracing::info;
+
+use crate::errors::{Error, Result};
+use crate::storage::RuntimeKeyData;
+use crate::xds::metadata::json_to_value;
+use crate::xds::resources::{BuiltResource, RUNTIME_TYPE_URL};
+
+/// Name of the RTDS layer carrying every stored runtime key.
+pub const RUNTIME_LAYER_NAME: &str = "flowplane-runtime";
+
+const MAX_KEY_LENGTH: usize = 255;
+const FRACTIONAL_PERCENT_DENOMINATORS: [&str; 3] = ["HUNDRED", "TEN_THOUSAND", "MILLION"];
+
+/// Runtime keys are dot separated segments of letters, digits, `_` and `-`.
+pub fn validate_runtime_key(key: &str) -> Result<()> {
+    let valid = !key.is_empty()
+        && key.len() <= MAX_KEY_LENGTH
+        && key.split('.').all(|segment| {
+            !segment.is_empty()
+                && segment.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-'))
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::validation(format!(
+            "Invalid runtime key '{}': use dot separated segments of letters, digits, '_' and '-'",
+            key
+        )))
+    }
+}
+
+/// Envoy accepts booleans, numbers, strings and fractional percents
+/// (`{"numerator": 50, "denominator": "HUNDRED"}`) as runtime values. Lists and
+/// nulls are rejected by Envoy, and other objects would be flattened into
+/// nested keys, so both are refused here.
+pub fn validate_runtime_value(key: &str, value: &serde_json::Value) -> Result<()> {
+    let valid = match value {
+        serde_json::Value::Bool(_)
+        | serde_json::Value::Number(_)
+        | serde_json::Value::String(_) => true,
+        serde_json::Value::Object(fields) => {
+            fields.keys().all(|field| field == "numerator" || field == "denominator")
+                && fields.get("numerator").is_some_and(|numerator| {
+                    numerator.as_u64().is_some_and(|numerator| numerator <= u32::MAX as u64)
+                })
+                && fields.get("denominator").is_none_or(|denominator| {
+                    denominator
+                        .as_str()
+                        .is_some_and(|name| FRACTIONAL_PERCENT_DENOMINATORS.contains(&name))
+                })
+        }
+        serde_json::Value::Null | serde_json::Value::Array(_) => false,
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::validation(format!(
+            "Invalid value for runtime key '{}': use a boolean, number, string or \
+             {{\"numerator\": <n>, \"denominator\": \"HUNDRED|TEN_THOUSAND|MILLION\"}}",
+            key
+        )))
+    }
+}
+
+/// Build the RTDS layer from stored runtime keys. The layer is built even when
+/// no keys are stored, since Envoy waits for every configured RTDS layer
+/// before it finishes initializing.
+pub fn runtime_layer_from_database_entries(
+    entries: Vec<RuntimeKeyData>,
+    context: &str,
+) -> Result<Vec<BuiltResource>> {
+    let mut fields = std::collections::HashMap::with_capacity(entries.len());
+
+    for entry in entries {
+        let value: serde_json::Value = serde_json::from_str(&entry.value).map_err(|e| {
+            Error::internal(format!("Failed to parse value of runtime key '{}': {}", entry.key, e))
+        })?;
+        fields.insert(entry.key, json_to_value(&value));
+    }
+
+    let runtime = Runtime { name: RUNTIME_LAYER_NAME.to_string(), layer: Some(Struct { fields }) };
+    let encoded = runtime.encode_to_vec();
+
+    info!(
+        phase = context,
+        layer = RUNTIME_LAYER_NAME,
+        key_count = runtime.layer.as_ref().map_or(0, |layer| layer.fields.len()),
+        encoded_size = encoded.len(),
+        "Built runtime layer from database entries"
+    );
+
+    Ok(vec![BuiltResource {
+        name: RUNTIME_LAYER_NAME.to_string(),
+        resource: Any { type_url: RUNTIME_TYPE_URL.to_string(), value: encoded },
+    }])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use envoy_types::pb::google::protobuf::value::Kind;
+    use serde_json::json;
+
+    fn entry(key: &str, value: serde_json::Value) -> RuntimeKeyData {
+        RuntimeKeyData {
+            id: key.to_string(),
+            key: key.to_string(),
+            value: value.to_string(),
+            version: 1,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn validates_keys_and_values() {
+        assert!(validate_runtime_key("cors.enabled").is_ok());
+        assert!(validate_runtime_key("local_rate_limit.enforce-percent").is_ok());
+        assert!(validate_runtime_key("").is_err());
+        assert!(validate_runtime_key("cors..enabled").is_err());
+        assert!(validate_runtime_key("cors/enabled").is_err());
+
+        for value in [json!(true), json!(25), json!("on"), json!({"numerator": 5})] {
+            assert!(validate_runtime_value("key", &value).is_ok(), "{} is valid", value);
+        }
+        assert!(validate_runtime_value(
+            "key",
+            &json!({"numerator": 5, "denominator": "TEN_THOUSAND"})
+        )
+        .is_ok());
+        for value in [
+            json!(null),
+            json!([1, 2]),
+            json!({"enabled": true}),
+            json!({"numerator": -1}),
+            json!({"numerator": 5, "denominator": "THOUSAND"}),
+        ] {
+            assert!(validate_runtime_value("key", &value).is_err(), "{} is invalid", value);
+        }
+    }
+
+    #[test]
+    fn builds_a_single_layer_with_every_key() {
+        let built = runtime_layer_from_database_entries(
+            vec![
+                entry("cors.enabled", json!(false)),
+                entry("rate_limit.percent", json!({"numerator": 25, "denominator": "HUNDRED"})),
+            ],
+            "test",
+        )
+        .unwrap();
+        assert_eq!(built.len(), 1);
+        assert_eq!(built[0].name, RUNTIME_LAYER_NAME);
+        assert_eq!(built[0].resource.type_url, RUNTIME_TYPE_URL);
+
+        let runtime = Runtime::decode(built[0].resource.value.as_slice()).unwrap();
+        let layer = runtime.layer.unwrap();
+        assert_eq!(layer.fields["cors.enabled"].kind, Some(Kind::BoolValue(false)));
+        match &layer.fields["rate_limit.percent"].kind {
+            Some(Kind::StructValue(percent)) => {
+                assert_eq!(percent.fields["numerator"].kind, Some(Kind::NumberValue(25.0)));
+            }
+            other => panic!("expected fractional percent struct, got {:?}", other),
+        }
+
+        let empty = runtime_layer_from_database_entries(Vec::new(), "test").unwrap();
+        assert_eq!(empty.len(), 1, "an empty layer is still served");
+    }
+}
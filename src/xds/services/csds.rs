@@ -0,0 +1,248 @@
+//! Client Status Discovery Service (CSDS).
+//!
+//! Reports, for every connected node, the delivery status of each subscribed
+//! resource type and the resources the control plane serves it. Statuses come
+//! from the ACK/NACK bookkeeping the ADS stream loops keep in the node
+//! registry.
+
+use std::pin::Pin;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use tokio::sync::mpsc;
+use tokio_stream::{wrappers::ReceiverStream, Stream, StreamExt};
+use tonic::{Request, Response, Status};
+use tracing::{info, warn};
+
+use envoy_types::pb::envoy::admin::v3::UpdateFailureState;
+use envoy_types::pb::envoy::service::status::v3::{
+    client_config::GenericXdsConfig,
+    client_status_discovery_service_server::ClientStatusDiscoveryService, ClientConfig,
+    ClientStatusRequest, ClientStatusResponse, ConfigStatus,
+};
+use envoy_types::pb::google::protobuf::{Any, Timestamp};
+
+use crate::Result;
+
+use super::super::{metadata, NodeStatus, ResourceTypeStatus, XdsState};
+use super::ResourceSource;
+
+/// CSDS implementation sharing the ADS server's state.
+#[derive(Debug, Clone)]
+pub struct ClientStatusService {
+    state: Arc<XdsState>,
+    source: ResourceSource,
+}
+
+impl ClientStatusService {
+    /// CSDS for the minimal server, serving resources from static configuration.
+    pub fn for_config(state: Arc<XdsState>) -> Self {
+        Self { state, source: ResourceSource::Config }
+    }
+
+    /// CSDS for the database-backed server, applying node scoping.
+    pub fn for_database(state: Arc<XdsState>) -> Self {
+        Self { state, source: ResourceSource::Database }
+    }
+
+    async fn client_status(&self, request: &ClientStatusRequest) -> Result<ClientStatusResponse> {
+        let mut config = Vec::new();
+        for node in self.state.node_registry.list() {
+            let matched = request.node_matchers.is_empty()
+                || request
+                    .node_matchers
+                    .iter()
+                    .any(|matcher| metadata::matches_node_matcher(&node.node, matcher));
+            if matched {
+                config.push(self.client_config(&node, request.exclude_resource_contents).await?);
+            }
+        }
+        Ok(ClientStatusResponse { config })
+    }
+
+    async fn client_config(
+        &self,
+        node: &NodeStatus,
+        exclude_contents: bool,
+    ) -> Result<ClientConfig> {
+        let mut generic_xds_configs = Vec::new();
+
+        for (type_url, status) in &node.subscriptions {
+            let config_status = config_status(status);
+            let error_state = match (&status.last_nack, config_status) {
+                (Some(nack), ConfigStatus::Error) => Some(UpdateFailureState {
+                    last_update_attempt: Some(timestamp(nack.received_at)),
+                    details: nack.message.clone(),
+                    version_info: nack.version.clone().unwrap_or_default(),
+                    ..Default::default()
+                }),
+                _ => None,
+            };
+            let entry = |name: String, body: Option<Any>| GenericXdsConfig {
+                type_url: type_url.clone(),
+                name,
+                version_info: status.last_sent_version.clone().unwrap_or_default(),
+                xds_config: body.filter(|_| !exclude_contents),
+                config_status: config_status as i32,
+                error_state: error_state.clone(),
+                ..Default::default()
+            };
+
+            let (_, resources) =
+                self.source.served_resources(&self.state, &node.node, type_url).await?;
+            if resources.is_empty() {
+                // Keep the type's status visible when nothing is served for it.
+                generic_xds_configs.push(entry(String::new(), None));
+            }
+            generic_xds_configs
+                .extend(resources.into_iter().map(|(name, body)| entry(name, Some(body))));
+        }
+
+        Ok(ClientConfig {
+            node: Some(node.node.clone()),
+            generic_xds_configs,
+            ..Default::default()
+        })
+    }
+}
+
+/// Status of a type as seen by the control plane: NOT_SENT before any
+/// response, ERROR when the last response was rejected, SYNCED once its
+/// version is acknowledged and STALE while the acknowledgement is outstanding.
+fn config_status(status: &ResourceTypeStatus) -> ConfigStatus {
+    let Some(sent_nonce) = &status.last_sent_nonce else {
+        return ConfigStatus::NotSent;
+    };
+    if status.last_nack.as_ref().is_some_and(|nack| &nack.nonce == sent_nonce) {
+        return ConfigStatus::Error;
+    }
+    if status.last_acked_version.is_some() && status.last_acked_version == status.last_sent_version
+    {
+        ConfigStatus::Synced
+    } else {
+        ConfigStatus::Stale
+    }
+}
+
+fn timestamp(at: DateTime<Utc>) -> Timestamp {
+    Timestamp { seconds: at.timestamp(), nanos: at.timestamp_subsec_nanos() as i32 }
+}
+
+fn status_from_error(error: crate::Error) -> Status {
+    warn!(%error, "Failed to build client status");
+    Status::internal(error.to_string())
+}
+
+#[tonic::async_trait]
+impl ClientStatusDiscoveryService for ClientStatusService {
+    type StreamClientStatusStream =
+        Pin<Box<dyn Stream<Item = std::result::Result<ClientStatusResponse, Status>> + Send>>;
+
+    async fn stream_client_status(
+        &self,
+        request: Request<tonic::Streaming<ClientStatusRequest>>,
+    ) -> std::result::Result<Response<Self::StreamClientStatusStream>, Status> {
+        info!("CSDS stream connection established");
+
+        let mut in_stream = request.into_inner();
+        let service = self.clone();
+        let (tx, rx) = mpsc::channel(16);
+
+        tokio::spawn(async move {
+            while let Some(request) = in_stream.next().await {
+                let response = match request {
+                    Ok(request) => service.client_status(&request).await.map_err(status_from_error),
+                    Err(status) => Err(status),
+                };
+                let failed = response.is_err();
+                if tx.send(response).await.is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
+    }
+
+    async fn fetch_client_status(
+        &self,
+        request: Request<ClientStatusRequest>,
+    ) -> std::result::Result<Response<ClientStatusResponse>, Status> {
+        self.client_status(request.get_ref()).await.map(Response::new).map_err(status_from_error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SimpleXdsConfig;
+    use crate::xds::resources::CLUSTER_TYPE_URL;
+    use envoy_types::pb::envoy::config::core::v3::Node;
+    use envoy_types::pb::envoy::r#type::matcher::v3::{string_matcher, NodeMatcher, StringMatcher};
+    use envoy_types::pb::google::rpc::Status as RpcStatus;
+
+    fn service() -> ClientStatusService {
+        ClientStatusService::for_config(Arc::new(XdsState::new(SimpleXdsConfig::default())))
+    }
+
+    fn cluster_status(config: &ClientConfig) -> (i32, String) {
+        let entry = &config.generic_xds_configs[0];
+        assert_eq!(entry.type_url, CLUSTER_TYPE_URL);
+        (entry.config_status, entry.version_info.clone())
+    }
+
+    #[tokio::test]
+    async fn reports_status_per_node_and_type() {
+        let service = service();
+        let registry = &service.state.node_registry;
+        let synced = registry.connect(&Node { id: "synced".into(), ..Default::default() }, false);
+        let rejected =
+            registry.connect(&Node { id: "rejected".into(), ..Default::default() }, false);
+        let pending = registry.connect(&Node { id: "pending".into(), ..Default::default() }, false);
+
+        for node_id in [&synced, &rejected, &pending] {
+            registry.record_request(node_id, CLUSTER_TYPE_URL, "", Some(""), None);
+        }
+        registry.record_response(&synced, CLUSTER_TYPE_URL, "2", "nonce-1");
+        registry.record_request(&synced, CLUSTER_TYPE_URL, "nonce-1", Some("2"), None);
+        registry.record_response(&rejected, CLUSTER_TYPE_URL, "2", "nonce-2");
+        let error = RpcStatus { code: 3, message: "bad cluster".into(), details: vec![] };
+        registry.record_request(&rejected, CLUSTER_TYPE_URL, "nonce-2", Some(""), Some(&error));
+
+        let response = service.client_status(&ClientStatusRequest::default()).await.unwrap();
+        let by_node = |id: &str| {
+            response
+                .config
+                .iter()
+                .find(|config| config.node.as_ref().is_some_and(|node| node.id == id))
+                .expect("node reported")
+        };
+
+        assert_eq!(cluster_status(by_node("synced")), (ConfigStatus::Synced as i32, "2".into()));
+        assert!(by_node("synced").generic_xds_configs[0].xds_config.is_some());
+        assert_eq!(cluster_status(by_node("pending")).0, ConfigStatus::NotSent as i32);
+        let rejected_entry = &by_node("rejected").generic_xds_configs[0];
+        assert_eq!(rejected_entry.config_status, ConfigStatus::Error as i32);
+        assert_eq!(rejected_entry.error_state.as_ref().unwrap().details, "bad cluster");
+
+        let filtered = service
+            .client_status(&ClientStatusRequest {
+                node_matchers: vec![NodeMatcher {
+                    node_id: Some(StringMatcher {
+                        match_pattern: Some(string_matcher::MatchPattern::Exact("pending".into())),
+                        ignore_case: false,
+                    }),
+                    node_metadatas: Vec::new(),
+                }],
+                exclude_resource_contents: true,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(filtered.config.len(), 1);
+        assert!(filtered.config[0]
+            .generic_xds_configs
+            .iter()
+            .all(|entry| entry.xds_config.is_none()));
+    }
+}
@@ -1,27 +1,40 @@
+use std::collections::HashSet;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Duration;
 
+use prost::Message;
+use sqlx::postgres::{PgListener, PgPool};
+use tokio::sync::broadcast::{
+    self,
+    error::{RecvError, TryRecvError},
+};
 use tokio_stream::Stream;
 use tonic::{Request, Response, Status};
 use tracing::{info, warn};
 
 use crate::{
-    storage::{ClusterRepository, ListenerRepository, RouteRepository},
+    storage::{events::CHANGE_NOTIFY_CHANNEL, Change, ChangeNotifier, DbPool},
     Result,
 };
+use envoy_types::pb::envoy::config::listener::v3::Listener;
 use envoy_types::pb::envoy::service::discovery::v3::{
     aggregated_discovery_service_server::AggregatedDiscoveryService, DeltaDiscoveryRequest,
     DeltaDiscoveryResponse, DiscoveryRequest, DiscoveryResponse, Resource,
 };
 
 use super::super::{
-    resources::{self, BuiltResource},
-    XdsState,
+    metadata, references::References, resources, state::CachedResource, KnownGoodSnapshot, XdsState,
 };
 
+const NOTIFY_RETRY_DELAY: Duration = Duration::from_secs(5);
+const ROLLOUT_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+const CERTIFICATE_RENEWAL_INTERVAL: Duration = Duration::from_secs(60);
+
 /// Database-enabled Aggregated Discovery Service implementation
-/// Returns resources from database when available, falls back to config-based resources
+/// Serves the resource caches built from the database, which fall back to
+/// config-based resources for tables without rows
 #[derive(Debug)]
 pub struct DatabaseAggregatedDiscoveryService {
     pub(super) state: Arc<XdsState>,
@@ -30,39 +43,52 @@ pub struct DatabaseAggregatedDiscoveryService {
 impl DatabaseAggregatedDiscoveryService {
     pub fn new(state: Arc<XdsState>) -> Self {
         if let Some(repo) = &state.cluster_repository {
-            spawn_cluster_watcher(state.clone(), repo.clone());
-        }
+            let pool = repo.pool().clone();
+            let notifier = state.change_notifier.clone();
 
-        if let Some(repo) = &state.route_repository {
-            spawn_route_watcher(state.clone(), repo.clone());
-        }
+            spawn_change_listener(state.clone(), notifier.subscribe());
+
+            if let Some(interval) = state.config.change_poll_interval_ms {
+                spawn_change_poller(
+                    notifier.clone(),
+                    pool.clone(),
+                    Duration::from_millis(interval),
+                );
+            }
+
+            if let Some(pg_pool) = pool.as_postgres() {
+                spawn_postgres_change_listener(notifier, pg_pool.clone());
+            }
+
+            if state.config.canary.is_some() {
+                spawn_rollout_promoter(state.clone());
+            }
 
-        if let Some(repo) = &state.listener_repository {
-            spawn_listener_watcher(state.clone(), repo.clone());
+            if state.config.ca.is_some() {
+                spawn_certificate_renewer(state.clone());
+            }
         }
 
         Self { state }
     }
 
-    /// Create discovery response with database-backed resources
+    /// Create discovery response from the cached resources the node may receive
     async fn create_resource_response(
         &self,
         request: &DiscoveryRequest,
     ) -> Result<DiscoveryResponse> {
-        let version = self.state.get_version();
+        self.state.ensure_caches_loaded().await;
         let nonce = uuid::Uuid::new_v4().to_string();
 
         let scope = scope_from_discovery(&request.node);
-        let built = if request.type_url == "type.googleapis.com/envoy.config.listener.v3.Listener" {
-            self.create_listener_resources_from_db_scoped(&scope).await?
-        } else {
-            self.build_resources(request.type_url.as_str()).await?
-        };
-        let resources = built.iter().map(|r| r.resource.clone()).collect();
+        let (version, mut cached) = scoped_resources(&self.state, &request.type_url, &scope)?;
+        if !resources::requests_all(&request.resource_names) {
+            cached.retain(|resource| request.resource_names.contains(&resource.name));
+        }
 
         Ok(DiscoveryResponse {
-            version_info: version.clone(),
-            resources,
+            version_info: version.to_string(),
+            resources: cached.into_iter().map(|resource| resource.body).collect(),
             canary: false,
             type_url: request.type_url.clone(),
             nonce: nonce.clone(),
@@ -71,290 +97,40 @@ impl DatabaseAggregatedDiscoveryService {
         })
     }
 
-    /// Create cluster resources from database
-    async fn create_cluster_resources_from_db(&self) -> Result<Vec<BuiltResource>> {
-        let mut built = if let Some(repo) = &self.state.cluster_repository {
-            match repo.list(Some(100), None).await {
-                Ok(cluster_data_list) => {
-                    if cluster_data_list.is_empty() {
-                        info!(
-                            "No clusters found in database, falling back to config-based cluster"
-                        );
-                        self.create_fallback_cluster_resources()?
-                    } else {
-                        info!(
-                            phase = "ads_response",
-                            cluster_count = cluster_data_list.len(),
-                            "Building cluster resources from database for ADS response"
-                        );
-                        resources::clusters_from_database_entries(
-                            cluster_data_list,
-                            "ads_response",
-                        )?
-                    }
-                }
-                Err(e) => {
-                    warn!("Failed to load clusters from database: {}, falling back to config", e);
-                    self.create_fallback_cluster_resources()?
-                }
-            }
-        } else {
-            info!("No database repository available, using config-based cluster");
-            self.create_fallback_cluster_resources()?
-        };
-
-        if let Some(api_repo) = &self.state.api_definition_repository {
-            let definitions = api_repo.list_definitions().await?;
-            let routes = api_repo.list_all_routes().await?;
-            let platform_resources =
-                resources::resources_from_api_definitions(definitions, routes)?;
-            built.extend(
-                platform_resources
-                    .into_iter()
-                    .filter(|res| res.type_url() == resources::CLUSTER_TYPE_URL),
-            );
-        }
-
-        Ok(built)
-    }
-
-    /// Create fallback cluster resources from config
-    fn create_fallback_cluster_resources(&self) -> Result<Vec<BuiltResource>> {
-        resources::clusters_from_config(&self.state.config)
-    }
-
-    async fn create_route_resources_from_db(&self) -> Result<Vec<BuiltResource>> {
-        let mut built = if let Some(repo) = &self.state.route_repository {
-            match repo.list(Some(100), None).await {
-                Ok(route_data_list) => {
-                    if route_data_list.is_empty() {
-                        info!("No routes found in database, falling back to config-based routes");
-                        self.create_fallback_route_resources()?
-                    } else {
-                        info!(
-                            phase = "ads_response",
-                            route_count = route_data_list.len(),
-                            "Building route resources from database for ADS response"
-                        );
-                        resources::routes_from_database_entries(route_data_list, "ads_response")?
-                    }
-                }
-                Err(e) => {
-                    warn!("Failed to load routes from database: {}, falling back to config", e);
-                    self.create_fallback_route_resources()?
-                }
-            }
-        } else {
-            info!("No database repository available, using config-based routes");
-            self.create_fallback_route_resources()?
-        };
-
-        // Merge Platform API virtual hosts into the default gateway routes for non-isolated APIs only
-        if let Some(api_repo) = &self.state.api_definition_repository {
-            use envoy_types::pb::envoy::config::route::v3::RouteConfiguration;
-            use prost::Message;
-
-            let definitions = api_repo.list_definitions().await?;
-            let platform_routes = api_repo.list_all_routes().await?;
-
-            if !definitions.is_empty() && !platform_routes.is_empty() {
-                let mut default_index: Option<usize> = None;
-                for (idx, res) in built.iter().enumerate() {
-                    if res.name == crate::openapi::defaults::DEFAULT_GATEWAY_ROUTES {
-                        default_index = Some(idx);
-                        break;
-                    }
-                }
-
-                if let Some(idx) = default_index {
-                    let mut default_rc = {
-                        let any = &built[idx].resource;
-                        RouteConfiguration::decode(any.value.as_slice()).map_err(|e| {
-                            crate::Error::internal(format!(
-                                "Failed to decode default gateway RouteConfiguration: {}",
-                                e
-                            ))
-                        })?
-                    };
-
-                    // Build a domain allowlist from non-isolated API definitions
-                    let allowed_domains: std::collections::HashSet<String> = definitions
-                        .iter()
-                        .filter(|d| !d.listener_isolation)
-                        .map(|d| d.domain.clone())
-                        .collect();
-
-                    let platform_resources =
-                        resources::resources_from_api_definitions(definitions, platform_routes)?;
-
-                    for res in platform_resources.into_iter() {
-                        if res.type_url() != resources::ROUTE_TYPE_URL {
-                            continue;
-                        }
-                        let mut rc = RouteConfiguration::decode(res.resource.value.as_slice())
-                            .map_err(|e| {
-                                crate::Error::internal(format!(
-                                    "Failed to decode Platform API RouteConfiguration: {}",
-                                    e
-                                ))
-                            })?;
-                        // Retain only vhosts whose domains intersect with allowed (non-isolated) domains
-                        rc.virtual_hosts
-                            .retain(|vh| vh.domains.iter().any(|d| allowed_domains.contains(d)));
-                        if rc.virtual_hosts.is_empty() {
-                            continue;
-                        }
-                        default_rc.virtual_hosts.extend(rc.virtual_hosts.into_iter());
-                    }
-
-                    // Re-encode merged default gateway route config
-                    let merged_any = envoy_types::pb::google::protobuf::Any {
-                        type_url: resources::ROUTE_TYPE_URL.to_string(),
-                        value: default_rc.encode_to_vec(),
-                    };
-
-                    built[idx] = resources::BuiltResource {
-                        name: crate::openapi::defaults::DEFAULT_GATEWAY_ROUTES.to_string(),
-                        resource: merged_any,
-                    };
-                } else {
-                    warn!(
-                        "DEFAULT_GATEWAY_ROUTES not found in repository-built routes; skipping Platform API merge"
-                    );
-                }
-            }
-        }
-
-        Ok(built)
-    }
-
-    fn create_fallback_route_resources(&self) -> Result<Vec<BuiltResource>> {
-        resources::routes_from_config(&self.state.config)
-    }
-
-    async fn create_listener_resources_from_db_scoped(
+    /// Version of `type_url` and the cached resources of it served to `node`.
+    pub(super) async fn node_resources(
         &self,
-        scope: &Scope,
-    ) -> Result<Vec<BuiltResource>> {
-        let built = if let Some(repo) = &self.state.listener_repository {
-            match repo.list(Some(100), None).await {
-                Ok(listener_data_list) => {
-                    if listener_data_list.is_empty() {
-                        info!(
-                            "No listeners found in database, falling back to config-based listener"
-                        );
-                        self.create_fallback_listener_resources()?
-                    } else {
-                        let filtered: Vec<crate::storage::repository_simple::ListenerData> =
-                            match scope {
-                                Scope::All => listener_data_list,
-                                Scope::Team { team, include_default } => {
-                                    let mut keep = Vec::new();
-                                    for entry in listener_data_list.into_iter() {
-                                        if entry.name
-                                            == crate::openapi::defaults::DEFAULT_GATEWAY_LISTENER
-                                            && *include_default
-                                        {
-                                            keep.push(entry);
-                                            continue;
-                                        }
-                                        if let Ok(value) = serde_json::from_str::<serde_json::Value>(
-                                            &entry.configuration,
-                                        ) {
-                                            if let Some(tag_team) = value
-                                                .get("flowplaneGateway")
-                                                .and_then(|v| v.get("team"))
-                                                .and_then(|v| v.as_str())
-                                            {
-                                                if tag_team == team {
-                                                    keep.push(entry);
-                                                }
-                                            }
-                                        }
-                                    }
-                                    keep
-                                }
-                                Scope::Allowlist { names } => listener_data_list
-                                    .into_iter()
-                                    .filter(|e| names.contains(&e.name))
-                                    .collect(),
-                            };
-                        info!(
-                            phase = "ads_response",
-                            listener_count = filtered.len(),
-                            "Building listener resources from database for ADS response"
-                        );
-                        resources::listeners_from_database_entries(filtered, "ads_response")?
-                    }
-                }
-                Err(e) => {
-                    warn!("Failed to load listeners from database: {}, falling back to config", e);
-                    self.create_fallback_listener_resources()?
-                }
-            }
-        } else {
-            info!("No database repository available, using config-based listener");
-            self.create_fallback_listener_resources()?
-        };
-
-        // Intentionally do not emit Platform API listeners here to avoid port conflicts
-
-        Ok(built)
-    }
-
-    fn create_fallback_listener_resources(&self) -> Result<Vec<BuiltResource>> {
-        resources::listeners_from_config(&self.state.config)
-    }
-
-    async fn build_resources(&self, type_url: &str) -> Result<Vec<BuiltResource>> {
-        match type_url {
-            "type.googleapis.com/envoy.config.cluster.v3.Cluster" => {
-                self.create_cluster_resources_from_db().await
-            }
-            "type.googleapis.com/envoy.config.route.v3.RouteConfiguration" => {
-                self.create_route_resources_from_db().await
-            }
-            "type.googleapis.com/envoy.config.listener.v3.Listener" => {
-                self.create_listener_resources_from_db_scoped(&Scope::All).await
-            }
-            "type.googleapis.com/envoy.config.endpoint.v3.ClusterLoadAssignment" => {
-                resources::endpoints_from_config(&self.state.config)
-            }
-            _ => {
-                warn!("Unknown resource type requested: {}", type_url);
-                Ok(Vec::new())
-            }
-        }
+        type_url: &str,
+        node: &envoy_types::pb::envoy::config::core::v3::Node,
+    ) -> Result<(u64, Vec<CachedResource>)> {
+        self.state.ensure_caches_loaded().await;
+        node_snapshot(&self.state, type_url, node)
     }
 
     async fn create_delta_response(
         &self,
         request: &DeltaDiscoveryRequest,
     ) -> Result<DeltaDiscoveryResponse> {
-        let version = self.state.get_version();
+        self.state.ensure_caches_loaded().await;
         let nonce = uuid::Uuid::new_v4().to_string();
 
-        // Build all available resources for this type
+        // Send every resource the node may receive
         // The stream logic will handle proper delta filtering and ACK detection
         let scope = scope_from_discovery(&request.node);
-        let built = if request.type_url == "type.googleapis.com/envoy.config.listener.v3.Listener" {
-            self.create_listener_resources_from_db_scoped(&scope).await?
-        } else {
-            self.build_resources(&request.type_url).await?
-        };
+        let (version, cached) = scoped_resources(&self.state, &request.type_url, &scope)?;
 
-        let resources: Vec<Resource> = built
+        let resources: Vec<Resource> = cached
             .into_iter()
-            .map(|r| Resource {
-                name: r.name,
-                version: version.clone(),
-                resource: Some(r.resource),
+            .map(|resource| Resource {
+                version: resource.resource_version(),
+                name: resource.name,
+                resource: Some(resource.body),
                 ..Default::default()
             })
             .collect();
 
         Ok(DeltaDiscoveryResponse {
-            system_version_info: version.clone(),
+            system_version_info: version.to_string(),
             type_url: request.type_url.clone(),
             nonce,
             resources,
@@ -364,134 +140,225 @@ impl DatabaseAggregatedDiscoveryService {
     }
 }
 
-fn spawn_cluster_watcher(state: Arc<XdsState>, repository: ClusterRepository) {
-    tokio::spawn(async move {
-        use tokio::time::{sleep, Duration};
+/// Version of `type_url` and the cached resources of that version `node` may
+/// receive under the scope its metadata asks for. Reads only the cache.
+pub(crate) fn node_snapshot(
+    state: &XdsState,
+    type_url: &str,
+    node: &envoy_types::pb::envoy::config::core::v3::Node,
+) -> Result<(u64, Vec<CachedResource>)> {
+    scoped_resources(state, type_url, &scope_from_discovery(&Some(node.clone())))
+}
 
-        if let Err(error) = state.refresh_clusters_from_repository().await {
-            warn!(%error, "Failed to initialize cluster cache from repository");
-        }
-        if let Err(error) = state.refresh_platform_api_resources().await {
-            warn!(%error, "Failed to prime Platform API resources after cluster refresh");
-        }
+/// Whether `node` asks to receive only part of the cached resources.
+pub(crate) fn node_is_scoped(node: &envoy_types::pb::envoy::config::core::v3::Node) -> bool {
+    !matches!(scope_from_discovery(&Some(node.clone())), Scope::All)
+}
 
-        let mut last_version: Option<i64> = None;
+/// Snapshot `node_id` is held on for `type_url`, narrowed to the scope of
+/// `node`. References are followed through the listeners and routes the node
+/// is held on, so it keeps the clusters its held routes point at.
+pub(crate) fn held_node_snapshot(
+    state: &XdsState,
+    node_id: &str,
+    node: &Option<envoy_types::pb::envoy::config::core::v3::Node>,
+    type_url: &str,
+) -> Result<Option<KnownGoodSnapshot>> {
+    let Some(mut snapshot) = state.held_snapshot(node_id, type_url) else {
+        return Ok(None);
+    };
+    snapshot.resources =
+        retain_in_scope(type_url, snapshot.resources, &scope_from_discovery(node), |type_url| {
+            match state.held_snapshot(node_id, type_url) {
+                Some(held) => held.resources,
+                None => state.cached_resources(type_url),
+            }
+        })?;
+    Ok(Some(snapshot))
+}
+
+/// Version of `type_url` and the cached resources of that version a node in
+/// `scope` may receive, by name.
+///
+/// Scoped nodes get their allowed listeners, then only the route
+/// configurations, clusters and endpoints those listeners reference.
+fn scoped_resources(
+    state: &XdsState,
+    type_url: &str,
+    scope: &Scope,
+) -> Result<(u64, Vec<CachedResource>)> {
+    let (version, mut cached) = state.type_snapshot(type_url);
+    cached.sort_by(|a, b| a.name.cmp(&b.name));
+    let cached =
+        retain_in_scope(type_url, cached, scope, |type_url| state.cached_resources(type_url))?;
+    Ok((version, cached))
+}
+
+/// Keep the resources of `type_url` a node in `scope` may receive, following
+/// references from the listeners and routes `resources_of` returns.
+fn retain_in_scope(
+    type_url: &str,
+    mut cached: Vec<CachedResource>,
+    scope: &Scope,
+    resources_of: impl Fn(&str) -> Vec<CachedResource>,
+) -> Result<Vec<CachedResource>> {
+    let follows_listeners = matches!(
+        type_url,
+        resources::ROUTE_TYPE_URL | resources::CLUSTER_TYPE_URL | resources::ENDPOINT_TYPE_URL
+    );
+    if matches!(scope, Scope::All)
+        || !(follows_listeners || type_url == resources::LISTENER_TYPE_URL)
+    {
+        return Ok(cached);
+    }
+    if type_url == resources::LISTENER_TYPE_URL {
+        return scope.retain_listeners(cached);
+    }
+
+    let listeners = scope.retain_listeners(resources_of(resources::LISTENER_TYPE_URL))?;
+    let mut references = References::from_listeners(&listeners)?;
+    if type_url == resources::ROUTE_TYPE_URL {
+        cached.retain(|route| references.routes.contains(&route.name));
+        return Ok(cached);
+    }
+
+    let mut routes = resources_of(resources::ROUTE_TYPE_URL);
+    routes.retain(|route| references.routes.contains(&route.name));
+    references.add_route_clusters(&routes)?;
+    cached.retain(|resource| references.clusters.contains(&resource.name));
+    Ok(cached)
+}
+
+/// Rebuild caches as repositories publish committed writes to the change bus.
+///
+/// Events that queue up while a refresh is running are coalesced so a burst of
+/// writes re-encodes each changed row once.
+fn spawn_change_listener(state: Arc<XdsState>, mut changes: broadcast::Receiver<Change>) {
+    tokio::spawn(async move {
+        state.ensure_caches_loaded().await;
 
         loop {
-            let poll_result = sqlx::query_scalar::<_, i64>("PRAGMA data_version;")
-                .fetch_one(repository.pool())
-                .await;
-
-            match poll_result {
-                Ok(version) => match last_version {
-                    Some(previous) if previous == version => {}
-                    Some(_) => {
-                        last_version = Some(version);
-                        if let Err(error) = state.refresh_clusters_from_repository().await {
-                            warn!(%error, "Failed to refresh cluster cache from repository");
-                        }
-                        if let Err(error) = state.refresh_platform_api_resources().await {
-                            warn!(%error, "Failed to refresh Platform API resources after cluster change");
-                        }
-                    }
-                    None => {
-                        last_version = Some(version);
+            let mut pending = HashSet::new();
+            match changes.recv().await {
+                Ok(change) => {
+                    pending.insert(change);
+                }
+                Err(RecvError::Lagged(skipped)) => {
+                    warn!(skipped, "Change bus lagged; refreshing all resources");
+                    pending.extend(Change::all());
+                }
+                Err(RecvError::Closed) => break,
+            }
+
+            loop {
+                match changes.try_recv() {
+                    Ok(change) => {
+                        pending.insert(change);
                     }
-                },
-                Err(e) => {
-                    warn!(error = %e, "Failed to poll SQLite data_version for changes");
+                    Err(TryRecvError::Lagged(_)) => pending.extend(Change::all()),
+                    Err(_) => break,
                 }
             }
 
-            sleep(Duration::from_millis(500)).await;
+            state.refresh_for_changes(&pending).await;
         }
     });
 }
 
-fn spawn_route_watcher(state: Arc<XdsState>, repository: RouteRepository) {
+/// Promote canary rollouts once they have baked without a NACK. Only the
+/// leader promotes; other replicas follow the decisions it records.
+fn spawn_rollout_promoter(state: Arc<XdsState>) {
     tokio::spawn(async move {
-        use tokio::time::{sleep, Duration};
-
-        if let Err(error) = state.refresh_routes_from_repository().await {
-            warn!(%error, "Failed to initialize route cache from repository");
+        let mut ticker = tokio::time::interval(ROLLOUT_CHECK_INTERVAL);
+        loop {
+            ticker.tick().await;
+            state.sync_rollouts().await;
+            if state.leader.is_leader() {
+                state.promote_baked_rollouts();
+            }
         }
-        if let Err(error) = state.refresh_platform_api_resources().await {
-            warn!(%error, "Failed to prime Platform API resources after route refresh");
+    });
+}
+
+/// Re-issue certificates from the built-in CA before they expire. Only the
+/// leader renews.
+fn spawn_certificate_renewer(state: Arc<XdsState>) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(CERTIFICATE_RENEWAL_INTERVAL);
+        loop {
+            ticker.tick().await;
+            if !state.leader.is_leader() {
+                continue;
+            }
+            if let Err(error) = state.renew_expiring_certificates().await {
+                warn!(%error, "Failed to renew certificates issued by the built-in CA");
+            }
         }
+    });
+}
 
-        let mut last_version: Option<i64> = None;
+/// Optional fallback that detects writes made outside the control plane and
+/// republishes them as a full change batch.
+fn spawn_change_poller(notifier: ChangeNotifier, pool: DbPool, interval: Duration) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        let mut last_version: Option<String> = None;
 
         loop {
-            let poll_result = sqlx::query_scalar::<_, i64>("PRAGMA data_version;")
-                .fetch_one(repository.pool())
-                .await;
-
-            match poll_result {
-                Ok(version) => match last_version {
-                    Some(previous) if previous == version => {}
-                    Some(_) => {
-                        last_version = Some(version);
-                        if let Err(error) = state.refresh_routes_from_repository().await {
-                            warn!(%error, "Failed to refresh route cache from repository");
-                        }
-                        if let Err(error) = state.refresh_platform_api_resources().await {
-                            warn!(%error, "Failed to refresh Platform API resources after route change");
-                        }
-                    }
-                    None => {
-                        last_version = Some(version);
+            ticker.tick().await;
+
+            match pool.data_version().await {
+                Ok(version) => {
+                    if last_version.as_deref().is_some_and(|previous| previous != version) {
+                        Change::all().for_each(|change| notifier.notify(change));
                     }
-                },
-                Err(e) => {
-                    warn!(error = %e, "Failed to poll SQLite data_version for route changes");
+                    last_version = Some(version);
+                }
+                Err(error) => {
+                    warn!(%error, "Failed to poll database data_version for external changes");
                 }
             }
-
-            sleep(Duration::from_millis(500)).await;
         }
     });
 }
 
-fn spawn_listener_watcher(state: Arc<XdsState>, repository: ListenerRepository) {
+/// Forward `NOTIFY` events published by other replicas onto the local change bus.
+fn spawn_postgres_change_listener(notifier: ChangeNotifier, pool: PgPool) {
     tokio::spawn(async move {
-        use tokio::time::{sleep, Duration};
-
-        if let Err(error) = state.refresh_listeners_from_repository().await {
-            warn!(%error, "Failed to initialize listener cache from repository");
-        }
-        if let Err(error) = state.refresh_platform_api_resources().await {
-            warn!(%error, "Failed to prime Platform API resources after listener refresh");
-        }
+        let mut listener = loop {
+            let connected = match PgListener::connect_with(&pool).await {
+                Ok(mut listener) => listener.listen(CHANGE_NOTIFY_CHANNEL).await.map(|_| listener),
+                Err(error) => Err(error),
+            };
+            match connected {
+                Ok(listener) => break listener,
+                Err(error) => {
+                    warn!(%error, "Failed to listen for change notifications; retrying");
+                    tokio::time::sleep(NOTIFY_RETRY_DELAY).await;
+                }
+            }
+        };
 
-        let mut last_version: Option<i64> = None;
+        info!(channel = CHANGE_NOTIFY_CHANNEL, "Listening for change notifications from replicas");
 
         loop {
-            let poll_result = sqlx::query_scalar::<_, i64>("PRAGMA data_version;")
-                .fetch_one(repository.pool())
-                .await;
-
-            match poll_result {
-                Ok(version) => match last_version {
-                    Some(previous) if previous == version => {}
-                    Some(_) => {
-                        last_version = Some(version);
-                        if let Err(error) = state.refresh_listeners_from_repository().await {
-                            warn!(%error, "Failed to refresh listener cache from repository");
-                        }
-                        if let Err(error) = state.refresh_platform_api_resources().await {
-                            warn!(%error, "Failed to refresh Platform API resources after listener change");
-                        }
+            match listener.try_recv().await {
+                Ok(Some(notification)) => {
+                    if let Some(change) = notifier.decode_remote(notification.payload()) {
+                        notifier.notify(change);
                     }
-                    None => {
-                        last_version = Some(version);
-                    }
-                },
-                Err(e) => {
-                    warn!(error = %e, "Failed to poll SQLite data_version for listener changes");
+                }
+                Ok(None) => {
+                    // The connection dropped and will be re-established on the next
+                    // call; notifications sent in between are lost.
+                    warn!("Change notification connection lost; refreshing all resources");
+                    Change::all().for_each(|change| notifier.notify(change));
+                }
+                Err(error) => {
+                    warn!(%error, "Failed to receive change notification");
+                    tokio::time::sleep(NOTIFY_RETRY_DELAY).await;
                 }
             }
-
-            sleep(Duration::from_millis(500)).await;
         }
     });
 }
@@ -555,44 +422,387 @@ enum Scope {
     Allowlist { names: Vec<String> },
 }
 
-fn scope_from_discovery(node: &Option<envoy_types::pb::envoy::config::core::v3::Node>) -> Scope {
-    if let Some(n) = node {
-        if let Some(meta) = &n.metadata {
-            let mut team: Option<String> = None;
-            let mut include_default = false;
-            let mut allow: Vec<String> = Vec::new();
-
-            if let Some(envoy_types::pb::google::protobuf::value::Kind::StringValue(s)) =
-                meta.fields.get("team").and_then(|v| v.kind.as_ref())
-            {
-                if !s.is_empty() {
-                    team = Some(s.clone());
-                }
-            }
-            if let Some(envoy_types::pb::google::protobuf::value::Kind::BoolValue(b)) =
-                meta.fields.get("include_default").and_then(|v| v.kind.as_ref())
-            {
-                include_default = *b;
-            }
-            if let Some(envoy_types::pb::google::protobuf::value::Kind::ListValue(lv)) =
-                meta.fields.get("listener_allowlist").and_then(|v| v.kind.as_ref())
-            {
-                for item in &lv.values {
-                    if let Some(envoy_types::pb::google::protobuf::value::Kind::StringValue(s)) =
-                        item.kind.as_ref()
-                    {
-                        allow.push(s.clone());
+impl Scope {
+    /// Keep the listeners a node in this scope may receive: the listeners
+    /// owned by its team (plus the default gateway when asked for), or the
+    /// ones it names.
+    fn retain_listeners(&self, mut listeners: Vec<CachedResource>) -> Result<Vec<CachedResource>> {
+        match self {
+            Scope::All => {}
+            Scope::Team { team, include_default } => {
+                let mut keep = Vec::new();
+                for cached in listeners {
+                    if cached.name == crate::openapi::defaults::DEFAULT_GATEWAY_LISTENER {
+                        if *include_default {
+                            keep.push(cached);
+                        }
+                        continue;
+                    }
+                    let listener = Listener::decode(cached.body.value.as_slice()).map_err(|e| {
+                        crate::Error::internal(format!(
+                            "Failed to decode listener '{}': {}",
+                            cached.name, e
+                        ))
+                    })?;
+                    if metadata::listener_team(&listener) == Some(team.as_str()) {
+                        keep.push(cached);
                     }
                 }
+                listeners = keep;
             }
+            Scope::Allowlist { names } => listeners.retain(|cached| names.contains(&cached.name)),
+        }
+        Ok(listeners)
+    }
+}
 
-            if !allow.is_empty() {
-                return Scope::Allowlist { names: allow };
-            }
-            if let Some(team) = team {
-                return Scope::Team { team, include_default };
-            }
+fn scope_from_discovery(node: &Option<envoy_types::pb::envoy::config::core::v3::Node>) -> Scope {
+    if let Some(meta) = node.as_ref().and_then(|n| n.metadata.as_ref()) {
+        let allow = metadata::string_list_field(meta, "listener_allowlist");
+        if !allow.is_empty() {
+            return Scope::Allowlist { names: allow };
+        }
+        if let Some(team) = metadata::string_field(meta, "team") {
+            return Scope::Team {
+                team: team.to_string(),
+                include_default: metadata::bool_field(meta, "include_default").unwrap_or(false),
+            };
         }
     }
     Scope::All
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SimpleXdsConfig;
+    use crate::xds::resources::{
+        BuiltResource, CLUSTER_TYPE_URL, ENDPOINT_TYPE_URL, LISTENER_TYPE_URL, ROUTE_TYPE_URL,
+    };
+    use envoy_types::pb::envoy::config::cluster::v3::Cluster;
+    use envoy_types::pb::envoy::config::core::v3::Node;
+    use envoy_types::pb::envoy::config::endpoint::v3::ClusterLoadAssignment;
+    use envoy_types::pb::envoy::config::listener::v3::{filter, Filter, FilterChain};
+    use envoy_types::pb::envoy::config::route::v3::{
+        route::Action, route_action::ClusterSpecifier, Route, RouteAction, RouteConfiguration,
+        VirtualHost,
+    };
+    use envoy_types::pb::envoy::extensions::filters::network::http_connection_manager::v3::{
+        http_connection_manager::RouteSpecifier, HttpConnectionManager, Rds,
+    };
+    use envoy_types::pb::envoy::service::discovery::v3::aggregated_discovery_service_client::AggregatedDiscoveryServiceClient;
+    use envoy_types::pb::envoy::service::discovery::v3::aggregated_discovery_service_server::AggregatedDiscoveryServiceServer;
+    use envoy_types::pb::google::protobuf::{value::Kind, Any, Struct, Value};
+
+    const HTTP_CONNECTION_MANAGER_TYPE_URL: &str =
+        "type.googleapis.com/envoy.extensions.filters.network.http_connection_manager.v3.HttpConnectionManager";
+
+    fn built(name: &str, type_url: &str, message: &impl Message) -> BuiltResource {
+        BuiltResource {
+            name: name.to_string(),
+            resource: Any { type_url: type_url.to_string(), value: message.encode_to_vec() },
+        }
+    }
+
+    fn team_listener(team: &str) -> BuiltResource {
+        let manager = HttpConnectionManager {
+            route_specifier: Some(RouteSpecifier::Rds(Rds {
+                route_config_name: format!("{}-routes", team),
+                ..Default::default()
+            })),
+            ..Default::default()
+        };
+        let mut listener = Listener {
+            name: format!("{}-listener", team),
+            filter_chains: vec![FilterChain {
+                filters: vec![Filter {
+                    name: "envoy.filters.network.http_connection_manager".to_string(),
+                    config_type: Some(filter::ConfigType::TypedConfig(Any {
+                        type_url: HTTP_CONNECTION_MANAGER_TYPE_URL.to_string(),
+                        value: manager.encode_to_vec(),
+                    })),
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        metadata::set_listener_team(&mut listener, team);
+        built(&listener.name, LISTENER_TYPE_URL, &listener)
+    }
+
+    fn team_routes(team: &str) -> BuiltResource {
+        let route_config = RouteConfiguration {
+            name: format!("{}-routes", team),
+            virtual_hosts: vec![VirtualHost {
+                name: team.to_string(),
+                domains: vec!["*".to_string()],
+                routes: vec![Route {
+                    action: Some(Action::Route(RouteAction {
+                        cluster_specifier: Some(ClusterSpecifier::Cluster(format!("{}-api", team))),
+                        ..Default::default()
+                    })),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        built(&route_config.name, ROUTE_TYPE_URL, &route_config)
+    }
+
+    /// A service over caches holding a listener, route configuration, cluster
+    /// and endpoints for each of the `payments` and `search` teams.
+    async fn service() -> DatabaseAggregatedDiscoveryService {
+        let state = Arc::new(XdsState::new(SimpleXdsConfig::default()));
+        state.ensure_caches_loaded().await;
+
+        let teams = ["payments", "search"];
+        state.apply_built_resources(
+            LISTENER_TYPE_URL,
+            teams.iter().map(|team| team_listener(team)).collect(),
+        );
+        state.apply_built_resources(
+            ROUTE_TYPE_URL,
+            teams.iter().map(|team| team_routes(team)).collect(),
+        );
+        let cluster_names: Vec<String> = teams.iter().map(|team| format!("{}-api", team)).collect();
+        state.apply_built_resources(
+            CLUSTER_TYPE_URL,
+            cluster_names
+                .iter()
+                .map(|name| {
+                    built(
+                        name,
+                        CLUSTER_TYPE_URL,
+                        &Cluster { name: name.clone(), ..Default::default() },
+                    )
+                })
+                .collect(),
+        );
+        state.apply_built_resources(
+            ENDPOINT_TYPE_URL,
+            cluster_names
+                .iter()
+                .map(|name| {
+                    let assignment =
+                        ClusterLoadAssignment { cluster_name: name.clone(), ..Default::default() };
+                    built(name, ENDPOINT_TYPE_URL, &assignment)
+                })
+                .collect(),
+        );
+        DatabaseAggregatedDiscoveryService { state }
+    }
+
+    fn team_node(team: &str) -> Node {
+        let fields =
+            [("team".to_string(), Value { kind: Some(Kind::StringValue(team.to_string())) })];
+        Node {
+            id: format!("{}-envoy", team),
+            metadata: Some(Struct { fields: fields.into_iter().collect() }),
+            ..Default::default()
+        }
+    }
+
+    fn served_names(
+        service: &DatabaseAggregatedDiscoveryService,
+        node: Option<Node>,
+        type_url: &str,
+    ) -> Vec<String> {
+        let scope = scope_from_discovery(&node);
+        let (_, cached) = scoped_resources(&service.state, type_url, &scope).unwrap();
+        cached.into_iter().map(|resource| resource.name).collect()
+    }
+
+    #[tokio::test]
+    async fn scoped_endpoints_follow_the_team_listeners() {
+        let service = service().await;
+
+        assert_eq!(
+            served_names(&service, Some(team_node("search")), ENDPOINT_TYPE_URL),
+            vec!["search-api"]
+        );
+        assert_eq!(
+            served_names(&service, Some(team_node("billing")), ENDPOINT_TYPE_URL),
+            Vec::<String>::new()
+        );
+    }
+
+    #[tokio::test]
+    async fn unscoped_nodes_receive_every_cached_resource() {
+        let service = service().await;
+
+        for type_url in [LISTENER_TYPE_URL, ROUTE_TYPE_URL, CLUSTER_TYPE_URL, ENDPOINT_TYPE_URL] {
+            assert_eq!(served_names(&service, None, type_url).len(), 2, "{}", type_url);
+        }
+        assert_eq!(
+            served_names(&service, Some(Node::default()), CLUSTER_TYPE_URL),
+            vec!["payments-api", "search-api"]
+        );
+    }
+
+    #[tokio::test]
+    async fn team_node_is_served_only_its_resources() {
+        let service = service().await;
+
+        for (type_url, expected) in [
+            (LISTENER_TYPE_URL, "payments-listener"),
+            (ROUTE_TYPE_URL, "payments-routes"),
+            (CLUSTER_TYPE_URL, "payments-api"),
+            (ENDPOINT_TYPE_URL, "payments-api"),
+        ] {
+            let request = DiscoveryRequest {
+                node: Some(team_node("payments")),
+                type_url: type_url.to_string(),
+                ..Default::default()
+            };
+            let response = service.create_resource_response(&request).await.unwrap();
+            assert_eq!(
+                response.version_info,
+                service.state.get_type_version_number(type_url).to_string()
+            );
+            assert_eq!(response.resources.len(), 1, "{}", type_url);
+
+            let delta = DeltaDiscoveryRequest {
+                node: Some(team_node("payments")),
+                type_url: type_url.to_string(),
+                ..Default::default()
+            };
+            let response = service.create_delta_response(&delta).await.unwrap();
+            let names: Vec<&str> =
+                response.resources.iter().map(|resource| resource.name.as_str()).collect();
+            assert_eq!(names, vec![expected]);
+        }
+    }
+
+    fn cluster(name: &str, connect_timeout_secs: i64) -> BuiltResource {
+        let cluster = Cluster {
+            name: name.to_string(),
+            connect_timeout: Some(envoy_types::pb::google::protobuf::Duration {
+                seconds: connect_timeout_secs,
+                nanos: 0,
+            }),
+            ..Default::default()
+        };
+        built(name, CLUSTER_TYPE_URL, &cluster)
+    }
+
+    /// Client of an ADS server on a local port serving `service`.
+    async fn serve(
+        service: DatabaseAggregatedDiscoveryService,
+    ) -> AggregatedDiscoveryServiceClient<tonic::transport::Channel> {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(AggregatedDiscoveryServiceServer::new(service))
+                .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener)),
+        );
+        AggregatedDiscoveryServiceClient::connect(format!("http://{}", address)).await.unwrap()
+    }
+
+    async fn next<T>(stream: &mut tonic::Streaming<T>) -> Option<T> {
+        tokio::time::timeout(Duration::from_millis(500), stream.message())
+            .await
+            .ok()
+            .map(|message| message.unwrap().expect("stream open"))
+    }
+
+    #[tokio::test]
+    async fn delta_pushes_to_a_team_node_leave_out_other_teams() {
+        let service = service().await;
+        let state = service.state.clone();
+        let mut client = serve(service).await;
+
+        let (requests, rx) = tokio::sync::mpsc::channel(4);
+        requests
+            .send(DeltaDiscoveryRequest {
+                node: Some(team_node("payments")),
+                type_url: CLUSTER_TYPE_URL.to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let mut responses = client
+            .delta_aggregated_resources(tokio_stream::wrappers::ReceiverStream::new(rx))
+            .await
+            .unwrap()
+            .into_inner();
+        let initial = next(&mut responses).await.expect("initial response");
+        assert_eq!(initial.resources.len(), 1);
+
+        state.apply_built_resources(
+            CLUSTER_TYPE_URL,
+            vec![cluster("search-api", 3), cluster("billing-api", 3)],
+        );
+        let push = next(&mut responses).await.expect("removal of the payments cluster");
+        assert!(push.resources.is_empty(), "other teams' clusters are never sent");
+        assert_eq!(push.removed_resources, vec!["payments-api"]);
+
+        state.apply_built_resources(
+            CLUSTER_TYPE_URL,
+            vec![cluster("payments-api", 5), cluster("search-api", 5), cluster("billing-api", 3)],
+        );
+        let push = next(&mut responses).await.expect("payments cluster push");
+        let names: Vec<&str> =
+            push.resources.iter().map(|resource| resource.name.as_str()).collect();
+        assert_eq!(names, vec!["payments-api"]);
+        assert!(push.removed_resources.is_empty());
+
+        state.apply_built_resources(
+            CLUSTER_TYPE_URL,
+            vec![cluster("payments-api", 5), cluster("search-api", 7)],
+        );
+        assert!(next(&mut responses).await.is_none(), "out-of-scope changes are not pushed");
+    }
+
+    #[tokio::test]
+    async fn route_changes_push_newly_referenced_clusters_to_scoped_sotw_nodes() {
+        let service = service().await;
+        let state = service.state.clone();
+        state.apply_built_resources(
+            CLUSTER_TYPE_URL,
+            vec![cluster("payments-api", 5), cluster("search-api", 5), cluster("shared-api", 5)],
+        );
+        let mut client = serve(service).await;
+
+        let (requests, rx) = tokio::sync::mpsc::channel(4);
+        requests
+            .send(DiscoveryRequest {
+                node: Some(team_node("payments")),
+                type_url: CLUSTER_TYPE_URL.to_string(),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        let mut responses = client
+            .stream_aggregated_resources(tokio_stream::wrappers::ReceiverStream::new(rx))
+            .await
+            .unwrap()
+            .into_inner();
+        let initial = next(&mut responses).await.expect("initial response");
+        assert_eq!(initial.resources.len(), 1);
+
+        let mut routes =
+            RouteConfiguration::decode(team_routes("payments").resource.value.as_slice()).unwrap();
+        routes.virtual_hosts[0].routes.push(Route {
+            action: Some(Action::Route(RouteAction {
+                cluster_specifier: Some(ClusterSpecifier::Cluster("shared-api".to_string())),
+                ..Default::default()
+            })),
+            ..Default::default()
+        });
+        state.apply_built_resources(
+            ROUTE_TYPE_URL,
+            vec![built(&routes.name, ROUTE_TYPE_URL, &routes), team_routes("search")],
+        );
+
+        let push = next(&mut responses).await.expect("cluster push after the route change");
+        assert_eq!(push.type_url, CLUSTER_TYPE_URL);
+        let names: Vec<String> = push
+            .resources
+            .iter()
+            .map(|resource| Cluster::decode(resource.value.as_slice()).unwrap().name)
+            .collect();
+        assert_eq!(names, vec!["payments-api", "shared-api"]);
+    }
+}
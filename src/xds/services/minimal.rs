@@ -15,6 +15,7 @@ use crate::Result;
 
 use super::super::{
     resources::{self, BuiltResource},
+    state::resource_version,
     XdsState,
 };
 
@@ -31,10 +32,11 @@ impl MinimalAggregatedDiscoveryService {
 
     /// Create discovery response with actual Envoy resources based on request type (legacy)
     fn create_resource_response(&self, request: &DiscoveryRequest) -> Result<DiscoveryResponse> {
-        let version = self.state.get_version();
+        let version = self.state.get_type_version(&request.type_url);
         let nonce = uuid::Uuid::new_v4().to_string();
 
-        let built = self.build_resources(request.type_url.as_str())?;
+        let mut built = self.build_resources(request.type_url.as_str())?;
+        resources::retain_requested(&mut built, &request.resource_names);
         let resources = built.into_iter().map(BuiltResource::into_any).collect();
 
         Ok(DiscoveryResponse {
@@ -50,7 +52,7 @@ impl MinimalAggregatedDiscoveryService {
 }
 
 impl MinimalAggregatedDiscoveryService {
-    fn build_resources(&self, type_url: &str) -> Result<Vec<BuiltResource>> {
+    pub(super) fn build_resources(&self, type_url: &str) -> Result<Vec<BuiltResource>> {
         match type_url {
             "type.googleapis.com/envoy.config.cluster.v3.Cluster" => {
                 resources::clusters_from_config(&self.state.config)
@@ -61,9 +63,11 @@ impl MinimalAggregatedDiscoveryService {
             "type.googleapis.com/envoy.config.listener.v3.Listener" => {
                 resources::listeners_from_config(&self.state.config)
             }
-            "type.googleapis.com/envoy.config.endpoint.v3.ClusterLoadAssignment" => {
-                resources::endpoints_from_config(&self.state.config)
-            }
+            resources::ENDPOINT_TYPE_URL => resources::endpoints_from_config(&self.state.config),
+            // Secrets are only stored in the database
+            resources::SECRET_TYPE_URL => Ok(Vec::new()),
+            // Runtime keys are only stored in the database
+            resources::RUNTIME_TYPE_URL => Ok(Vec::new()),
             _ => {
                 warn!("Unknown resource type requested: {}", type_url);
                 Ok(Vec::new())
@@ -75,7 +79,7 @@ impl MinimalAggregatedDiscoveryService {
         &self,
         request: &DeltaDiscoveryRequest,
     ) -> Result<DeltaDiscoveryResponse> {
-        let version = self.state.get_version();
+        let version = self.state.get_type_version(&request.type_url);
         let nonce = uuid::Uuid::new_v4().to_string();
 
         // Build all available resources for this type
@@ -86,7 +90,7 @@ impl MinimalAggregatedDiscoveryService {
             .into_iter()
             .map(|r| Resource {
                 name: r.name,
-                version: version.clone(),
+                version: resource_version(&r.resource),
                 resource: Some(r.resource),
                 ..Default::default()
             })
@@ -1,6 +1,63 @@
+mod csds;
 mod database;
 mod minimal;
 pub mod stream;
 
+use std::sync::Arc;
+
+use envoy_types::pb::envoy::config::core::v3::Node;
+use envoy_types::pb::google::protobuf::Any;
+
+use crate::Result;
+
+use super::XdsState;
+
+pub use csds::ClientStatusService;
 pub use database::DatabaseAggregatedDiscoveryService;
+pub(crate) use database::{held_node_snapshot, node_is_scoped, node_snapshot};
 pub use minimal::MinimalAggregatedDiscoveryService;
+
+/// Where the resources served to a node are built from.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum ResourceSource {
+    Config,
+    Database,
+}
+
+impl ResourceSource {
+    /// Resources of `type_url` served to `node` and the version they are
+    /// served at: its held snapshot while pinned or outside a rollout,
+    /// otherwise the current resources, scoped either way.
+    pub(crate) async fn served_resources(
+        self,
+        state: &Arc<XdsState>,
+        node: &Node,
+        type_url: &str,
+    ) -> Result<(u64, Vec<(String, Any)>)> {
+        if let Some(snapshot) = held_node_snapshot(state, &node.id, &Some(node.clone()), type_url)?
+        {
+            let resources =
+                snapshot.resources.into_iter().map(|cached| (cached.name, cached.body)).collect();
+            return Ok((snapshot.version, resources));
+        }
+
+        let state = state.clone();
+        match self {
+            ResourceSource::Config => {
+                let version = state.get_type_version_number(type_url);
+                let built =
+                    MinimalAggregatedDiscoveryService { state }.build_resources(type_url)?;
+                Ok((
+                    version,
+                    built.into_iter().map(|resource| (resource.name, resource.resource)).collect(),
+                ))
+            }
+            ResourceSource::Database => {
+                let (version, cached) = DatabaseAggregatedDiscoveryService { state }
+                    .node_resources(type_url, node)
+                    .await?;
+                Ok((version, cached.into_iter().map(|cached| (cached.name, cached.body)).collect()))
+            }
+        }
+    }
+}
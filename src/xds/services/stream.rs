@@ -1,4 +1,4 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
@@ -8,11 +8,19 @@ use tokio_stream::{wrappers::ReceiverStream, StreamExt};
 use tonic::Status;
 use tracing::{debug, error, info, warn};
 
-use crate::xds::state::{ResourceDelta, XdsState};
+use crate::xds::publisher::push_position;
+use crate::xds::resources::{
+    CLUSTER_TYPE_URL, ENDPOINT_TYPE_URL, LISTENER_TYPE_URL, ROUTE_TYPE_URL,
+};
+use crate::xds::services::{held_node_snapshot, node_is_scoped, node_snapshot};
+use crate::xds::state::{CachedResource, ResourceDelta, ResourceUpdate, XdsState};
+use crate::xds::{KnownGoodSnapshot, RequestKind};
+use envoy_types::pb::envoy::config::core::v3::Node;
 use envoy_types::pb::envoy::service::discovery::v3::Resource;
 use envoy_types::pb::envoy::service::discovery::v3::{
     DeltaDiscoveryRequest, DeltaDiscoveryResponse, DiscoveryRequest, DiscoveryResponse,
 };
+use envoy_types::pb::google::rpc::Status as RpcStatus;
 use uuid::Uuid;
 
 #[derive(Clone, Debug)]
@@ -21,7 +29,64 @@ struct LastDiscoverySnapshot {
     nonce: String,
 }
 
-// Removed complex delta state tracking - using PoC-style approach instead
+/// Resource names a SotW stream is subscribed to for one resource type.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Subscription {
+    wildcard: bool,
+    names: BTreeSet<String>,
+}
+
+impl Subscription {
+    /// Apply the `resource_names` of a request, returning whether the subscription changed.
+    ///
+    /// An empty list on the first request for a type is a legacy wildcard, which
+    /// later empty lists keep. `*` subscribes to every resource alongside any
+    /// explicit names. Otherwise the list replaces the subscription, so names
+    /// left out are unsubscribed.
+    fn update(&mut self, resource_names: &[String], first: bool) -> bool {
+        let previous = self.clone();
+        if resource_names.is_empty() {
+            let legacy_wildcard = first || (self.wildcard && self.names.is_empty());
+            self.wildcard = legacy_wildcard;
+            self.names.clear();
+        } else {
+            self.wildcard = resource_names.iter().any(|name| name == "*");
+            self.names =
+                resource_names.iter().filter(|name| name.as_str() != "*").cloned().collect();
+        }
+        *self != previous
+    }
+
+    fn contains(&self, name: &str) -> bool {
+        self.wildcard || self.names.contains(name)
+    }
+
+    fn is_empty(&self) -> bool {
+        !self.wildcard && self.names.is_empty()
+    }
+
+    /// Whether a published delta can change what this subscription is served.
+    /// Deltas without resources (version-only notifications) always can.
+    fn covers(&self, delta: &ResourceDelta) -> bool {
+        if self.wildcard {
+            return true;
+        }
+        if self.names.is_empty() {
+            return false;
+        }
+        (delta.added_or_updated.is_empty() && delta.removed.is_empty())
+            || delta.added_or_updated.iter().any(|cached| self.names.contains(&cached.name))
+            || delta.removed.iter().any(|name| self.names.contains(name))
+    }
+
+    /// Rewrite `request` to name exactly the subscribed resources, leaving the
+    /// list empty for wildcard subscriptions.
+    fn apply_to(&self, mut request: DiscoveryRequest) -> DiscoveryRequest {
+        request.resource_names =
+            if self.wildcard { Vec::new() } else { self.names.iter().cloned().collect() };
+        request
+    }
+}
 
 /// Run the shared ADS stream loop for both minimal and database-backed services.
 pub fn run_stream_loop<F>(
@@ -45,9 +110,23 @@ where
     let label = label.to_string();
     let last_sent = Arc::new(Mutex::new(HashMap::<String, LastDiscoverySnapshot>::new()));
     let mut update_rx = state.subscribe_updates();
-    let subscribed_types = Arc::new(Mutex::new(std::collections::HashSet::<String>::new()));
+    let push_tx = spawn_sotw_pusher(
+        state.clone(),
+        responder.clone(),
+        tx.clone(),
+        label.clone(),
+        last_sent.clone(),
+    );
 
     tokio::spawn(async move {
+        // Envoy only sends its node on the first request of a stream.
+        let mut registered_node: Option<String> = None;
+        let mut stream_node: Option<Node> = None;
+        let mut scoped_node = false;
+        // Subscriptions are updated here rather than in the per-request tasks so
+        // they follow the order requests arrive in.
+        let mut subscriptions: HashMap<String, Subscription> = HashMap::new();
+
         loop {
             tokio::select! {
                 result = in_stream.next() => {
@@ -61,12 +140,42 @@ where
                                 "Received discovery request"
                             );
 
+                            if registered_node.is_none() {
+                                registered_node = discovery_request
+                                    .node
+                                    .as_ref()
+                                    .map(|node| state_clone.connect_node(node));
+                            }
+                            let request_kind = match &registered_node {
+                                Some(node_id) => state_clone.node_registry.record_request(
+                                    node_id,
+                                    &discovery_request.type_url,
+                                    &discovery_request.response_nonce,
+                                    Some(&discovery_request.version_info),
+                                    discovery_request.error_detail.as_ref(),
+                                ),
+                                None => RequestKind::Subscribe,
+                            };
+                            if stream_node.is_none() {
+                                stream_node = discovery_request.node.clone();
+                                scoped_node = stream_node.as_ref().is_some_and(node_is_scoped);
+                            }
+
+                            let first_request = !subscriptions.contains_key(&discovery_request.type_url);
+                            let subscription_entry = subscriptions
+                                .entry(discovery_request.type_url.clone())
+                                .or_default();
+                            let subscription_changed =
+                                subscription_entry.update(&discovery_request.resource_names, first_request);
+                            let subscription = subscription_entry.clone();
+
                             let state = state_clone.clone();
+                            let registry_node = registered_node.clone();
+                            let node = stream_node.clone();
                             let responder = responder.clone();
                             let tx = tx.clone();
                             let label_clone = label.clone();
                             let tracker = last_sent.clone();
-                            let subscribed_for_task = subscribed_types.clone();
 
                             tokio::spawn(async move {
                                 let node_id = discovery_request
@@ -74,12 +183,35 @@ where
                                     .as_ref()
                                     .map(|n| n.id.clone());
 
+                                if let Some(registry_id) = &registry_node {
+                                    handle_request_kind(
+                                        &state,
+                                        registry_id,
+                                        &discovery_request.type_url,
+                                        &request_kind,
+                                        discovery_request.error_detail.as_ref(),
+                                    )
+                                    .await;
+                                }
+                                let held = match &registry_node {
+                                    Some(registry_id) => held_node_snapshot(
+                                        &state,
+                                        registry_id,
+                                        &node,
+                                        &discovery_request.type_url,
+                                    ),
+                                    None => Ok(None),
+                                };
+
                                 let tracker_guard = tracker.lock().await;
                                 let last_snapshot = tracker_guard
                                     .get(&discovery_request.type_url)
                                     .cloned();
 
-                                let current_version = state.get_version();
+                                let current_version = match &held {
+                                    Ok(Some(snapshot)) => snapshot.version.to_string(),
+                                    _ => state.get_type_version(&discovery_request.type_url),
+                                };
 
                                 let is_ack = last_snapshot
                                     .as_ref()
@@ -89,6 +221,7 @@ where
                                             && discovery_request.version_info == snapshot.version
                                             && discovery_request.error_detail.is_none()
                                             && snapshot.version == current_version
+                                            && !subscription_changed
                                     })
                                     .unwrap_or(false);
 
@@ -118,13 +251,23 @@ where
 
                                 drop(tracker_guard);
 
-                                // Track this type_url as subscribed by the client
-                                {
-                                    let mut guard = subscribed_for_task.lock().await;
-                                    guard.insert(discovery_request.type_url.clone());
-                                }
+                                let response = match held {
+                                    Ok(Some(snapshot)) => Ok(known_good_response(
+                                        &discovery_request.type_url,
+                                        &snapshot,
+                                        &subscription,
+                                    )),
+                                    Ok(None) if subscription.is_empty() => {
+                                        Ok(unsubscribed_response(&state, &discovery_request.type_url))
+                                    }
+                                    Ok(None) => {
+                                        responder(state.clone(), subscription.apply_to(discovery_request))
+                                            .await
+                                    }
+                                    Err(e) => Err(e),
+                                };
 
-                                match responder(state, discovery_request).await {
+                                match response {
                                     Ok(response) => {
                                         info!(
                                             type_url = %response.type_url,
@@ -135,6 +278,15 @@ where
                                             "Sending discovery response"
                                         );
 
+                                        if let Some(node_id) = &registry_node {
+                                            state.node_registry.record_response(
+                                                node_id,
+                                                &response.type_url,
+                                                &response.version_info,
+                                                &response.nonce,
+                                            );
+                                        }
+
                                         let version = response.version_info.clone();
                                         let nonce = response.nonce.clone();
                                         let type_url = response.type_url.clone();
@@ -171,54 +323,42 @@ where
                 update = update_rx.recv() => {
                     match update {
                         Ok(update) => {
-                            // For SOTW, push a fresh snapshot for each type this client has requested
-                            let interested: Vec<String> = {
-                                let guard = subscribed_types.lock().await;
-                                guard.iter().cloned().collect()
-                            };
-                            if interested.is_empty() { continue; }
-
-                            for delta in &update.deltas {
-                                if !interested.contains(&delta.type_url) { continue; }
-
-                                let state_for_task = state_clone.clone();
-                                let responder_for_task = responder.clone();
-                                let tx_for_task = tx.clone();
-                                let label_for_task = label.clone();
-                                let tracker_for_task = last_sent.clone();
-                                let type_url_for_task = delta.type_url.clone();
-
-                                tokio::spawn(async move {
-                                    // Build a minimal request for this type
-                                    let request = DiscoveryRequest { type_url: type_url_for_task.clone(), ..Default::default() };
-                                    match responder_for_task(state_for_task, request).await {
-                                        Ok(response) => {
-                                            info!(
-                                                type_url = %response.type_url,
-                                                version = %response.version_info,
-                                                nonce = %response.nonce,
-                                                resource_count = response.resources.len(),
-                                                stream = %label_for_task,
-                                                "Pushing SOTW update response"
-                                            );
-
-                                            let version = response.version_info.clone();
-                                            let nonce = response.nonce.clone();
-                                            let type_url = response.type_url.clone();
-                                            {
-                                                let mut guard = tracker_for_task.lock().await;
-                                                guard.insert(type_url, LastDiscoverySnapshot { version, nonce });
-                                            }
-
-                                            if tx_for_task.send(Ok(response)).await.is_err() {
-                                                error!(stream = %label_for_task, "Discovery response receiver dropped");
-                                            }
-                                        }
-                                        Err(e) => {
-                                            error!(stream = %label_for_task, error = %e, "Failed to create SOTW push response");
-                                        }
+                            // For SOTW, push a fresh snapshot for each type whose subscribed
+                            // resources the update touches
+                            let mut pushes: Vec<(String, Subscription)> = update
+                                .deltas
+                                .iter()
+                                .filter_map(|delta| {
+                                    let subscription = subscriptions.get(&delta.type_url)?;
+                                    subscription
+                                        .covers(delta)
+                                        .then(|| (delta.type_url.clone(), subscription.clone()))
+                                })
+                                .collect();
+                            if scoped_node {
+                                for type_url in scope_dependents(&update) {
+                                    let Some(subscription) = subscriptions.get(*type_url) else {
+                                        continue;
+                                    };
+                                    if !subscription.is_empty()
+                                        && !pushes.iter().any(|(pushed, _)| pushed == type_url)
+                                    {
+                                        pushes.push((type_url.to_string(), subscription.clone()));
                                     }
-                                });
+                                }
+                                pushes.sort_by_key(|(type_url, _)| push_position(type_url));
+                            }
+                            if pushes.is_empty() {
+                                continue;
+                            }
+
+                            let push = SotwPush {
+                                pushes,
+                                registry_node: registered_node.clone(),
+                                node: stream_node.clone(),
+                            };
+                            if push_tx.send(push).await.is_err() {
+                                break;
                             }
                         }
                         Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
@@ -236,11 +376,107 @@ where
                 }
             }
         }
+
+        if let Some(node_id) = registered_node {
+            state_clone.node_registry.disconnect(&node_id);
+        }
     });
 
     ReceiverStream::new(rx)
 }
 
+/// Types of one update to push to a SotW stream, in push order.
+struct SotwPush {
+    pushes: Vec<(String, Subscription)>,
+    registry_node: Option<String>,
+    node: Option<Node>,
+}
+
+/// Spawn the task that pushes updates to one SotW stream. Pushes are sent
+/// from this single task so they reach Envoy in the order updates were
+/// published; it ends when the returned sender is dropped.
+fn spawn_sotw_pusher<F>(
+    state: Arc<XdsState>,
+    responder: Arc<F>,
+    tx: mpsc::Sender<std::result::Result<DiscoveryResponse, Status>>,
+    label: String,
+    tracker: Arc<Mutex<HashMap<String, LastDiscoverySnapshot>>>,
+) -> mpsc::Sender<SotwPush>
+where
+    F: Fn(
+            Arc<XdsState>,
+            DiscoveryRequest,
+        ) -> Pin<Box<dyn Future<Output = crate::Result<DiscoveryResponse>> + Send>>
+        + Send
+        + Sync
+        + 'static,
+{
+    let (push_tx, mut push_rx) = mpsc::channel::<SotwPush>(100);
+    tokio::spawn(async move {
+        while let Some(push) = push_rx.recv().await {
+            for (type_url, subscription) in push.pushes {
+                let held = match &push.registry_node {
+                    Some(node_id) => held_node_snapshot(&state, node_id, &push.node, &type_url),
+                    None => Ok(None),
+                };
+                let response = match held {
+                    Ok(Some(snapshot)) => {
+                        Ok(known_good_response(&type_url, &snapshot, &subscription))
+                    }
+                    Err(e) => Err(e),
+                    Ok(None) => {
+                        // Build a request for the subscribed resources of this type
+                        let request = DiscoveryRequest {
+                            type_url: type_url.clone(),
+                            node: push.node.clone(),
+                            ..Default::default()
+                        };
+                        responder(state.clone(), subscription.apply_to(request)).await
+                    }
+                };
+                match response {
+                    Ok(response) => {
+                        info!(
+                            type_url = %response.type_url,
+                            version = %response.version_info,
+                            nonce = %response.nonce,
+                            resource_count = response.resources.len(),
+                            stream = %label,
+                            "Pushing SOTW update response"
+                        );
+
+                        if let Some(node_id) = &push.registry_node {
+                            state.node_registry.record_response(
+                                node_id,
+                                &response.type_url,
+                                &response.version_info,
+                                &response.nonce,
+                            );
+                        }
+
+                        let version = response.version_info.clone();
+                        let nonce = response.nonce.clone();
+                        let type_url = response.type_url.clone();
+                        {
+                            let mut guard = tracker.lock().await;
+                            guard.insert(type_url, LastDiscoverySnapshot { version, nonce });
+                        }
+
+                        if tx.send(Ok(response)).await.is_err() {
+                            error!(stream = %label, "Discovery response receiver dropped");
+                            return;
+                        }
+                    }
+                    Err(e) => {
+                        error!(stream = %label, error = %e, "Failed to create SOTW push response");
+                    }
+                }
+            }
+        }
+    });
+    push_tx
+}
+
 /// Run the delta ADS stream loop using PoC-style approach with database persistence
 pub fn run_delta_loop<F>(
     state: Arc<XdsState>,
@@ -262,9 +498,17 @@ where
     let responder = Arc::new(responder);
     let label = label.to_string();
     let mut update_rx = state.subscribe_updates();
+    let push_tx = spawn_delta_pusher(tx.clone(), label.clone());
 
     tokio::spawn(async move {
         let mut pending_types: HashSet<String> = HashSet::new();
+        // Envoy only sends its node on the first request of a stream.
+        let mut registered_node: Option<String> = None;
+        let mut stream_node: Option<Node> = None;
+        let mut scoped_node = false;
+        // Resource names last served from a held (pinned or stable) snapshot, by type.
+        let held_types = Arc::new(std::sync::Mutex::new(HashMap::<String, Vec<String>>::new()));
+        let served = Arc::new(ServedResources::default());
 
         loop {
             tokio::select! {
@@ -278,6 +522,27 @@ where
                                 "Received delta discovery request"
                             );
 
+                            if registered_node.is_none() {
+                                registered_node = delta_request
+                                    .node
+                                    .as_ref()
+                                    .map(|node| state_clone.connect_node(node));
+                            }
+                            if stream_node.is_none() {
+                                stream_node = delta_request.node.clone();
+                                scoped_node = stream_node.as_ref().is_some_and(node_is_scoped);
+                            }
+                            let request_kind = match &registered_node {
+                                Some(node_id) => state_clone.node_registry.record_request(
+                                    node_id,
+                                    &delta_request.type_url,
+                                    &delta_request.response_nonce,
+                                    None,
+                                    delta_request.error_detail.as_ref(),
+                                ),
+                                None => RequestKind::Subscribe,
+                            };
+
                             // Check if this is an ACK/NACK (has our previous nonce) or initial request
                             let is_ack_or_nack = !delta_request.response_nonce.is_empty();
 
@@ -299,6 +564,68 @@ where
                                         "[ACK] Delta request acknowledged"
                                     );
                                 }
+
+                                if let Some(node_id) = registered_node.clone() {
+                                    let state_for_task = state_clone.clone();
+                                    let tx_for_task = tx.clone();
+                                    let label_for_task = label.clone();
+                                    let held_for_task = held_types.clone();
+                                    let served_for_task = served.clone();
+                                    let node = stream_node.clone();
+
+                                    tokio::spawn(async move {
+                                        handle_request_kind(
+                                            &state_for_task,
+                                            &node_id,
+                                            &delta_request.type_url,
+                                            &request_kind,
+                                            delta_request.error_detail.as_ref(),
+                                        )
+                                        .await;
+
+                                        if !matches!(request_kind, RequestKind::Nack { repeated: false, .. }) {
+                                            return;
+                                        }
+                                        let response = held_node_snapshot(&state_for_task, &node_id, &node, &delta_request.type_url)
+                                            .and_then(|held| {
+                                                held.map(|snapshot| {
+                                                    known_good_delta_response(
+                                                        &state_for_task,
+                                                        &delta_request.type_url,
+                                                        &node,
+                                                        &snapshot,
+                                                        &held_for_task,
+                                                        &served_for_task,
+                                                    )
+                                                })
+                                                .transpose()
+                                            });
+                                        let response = match response {
+                                            Ok(Some(response)) => response,
+                                            Ok(None) => return,
+                                            Err(e) => {
+                                                error!(stream = %label_for_task, error = %e, "Failed to create delta response");
+                                                return;
+                                            }
+                                        };
+                                        served_for_task.apply(&response);
+                                        state_for_task.node_registry.record_response(
+                                            &node_id,
+                                            &response.type_url,
+                                            &response.system_version_info,
+                                            &response.nonce,
+                                        );
+                                        info!(
+                                            type_url = %response.type_url,
+                                            version = %response.system_version_info,
+                                            stream = %label_for_task,
+                                            "Serving last-known-good snapshot to pinned node"
+                                        );
+                                        if tx_for_task.send(Ok(response)).await.is_err() {
+                                            error!(stream = %label_for_task, "Delta response receiver dropped");
+                                        }
+                                    });
+                                }
                                 // For ACKs/NACKs, just continue listening
                                 continue;
                             }
@@ -316,18 +643,52 @@ where
                             let responder_for_task = responder.clone();
                             let tx_for_task = tx.clone();
                             let label_for_task = label.clone();
+                            let registry_node = registered_node.clone();
+                            let node = stream_node.clone();
+                            let held_for_task = held_types.clone();
+                            let served_for_task = served.clone();
+                            let initial_versions = delta_request.initial_resource_versions.clone();
 
                             tokio::spawn(async move {
-                                match responder_for_task(state_for_task, delta_request.clone()).await {
-                                    Ok(response) => {
+                                let held = match &registry_node {
+                                    Some(node_id) => held_node_snapshot(&state_for_task, node_id, &node, &delta_request.type_url),
+                                    None => Ok(None),
+                                };
+                                let response = match held {
+                                    Ok(Some(snapshot)) => known_good_delta_response(
+                                        &state_for_task,
+                                        &delta_request.type_url,
+                                        &node,
+                                        &snapshot,
+                                        &held_for_task,
+                                        &served_for_task,
+                                    ),
+                                    Ok(None) => responder_for_task(state_for_task.clone(), delta_request.clone()).await,
+                                    Err(e) => Err(e),
+                                };
+
+                                match response {
+                                    Ok(mut response) => {
+                                        served_for_task.replace(&response);
+                                        reconcile_initial_versions(&mut response, &initial_versions);
                                         info!(
                                             type_url = %response.type_url,
                                             nonce = %response.nonce,
                                             version = %response.system_version_info,
                                             resource_count = response.resources.len(),
+                                            removed_count = response.removed_resources.len(),
                                             stream = %label_for_task,
                                             "Sending initial delta response"
                                         );
+
+                                        if let Some(node_id) = &registry_node {
+                                            state_for_task.node_registry.record_response(
+                                                node_id,
+                                                &response.type_url,
+                                                &response.system_version_info,
+                                                &response.nonce,
+                                            );
+                                        }
                                         if tx_for_task.send(Ok(response)).await.is_err() {
                                             error!(stream = %label_for_task, "Delta response receiver dropped");
                                         }
@@ -356,33 +717,65 @@ where
                                 continue;
                             }
 
-                            for delta in &update.deltas {
-                                if !pending_types.contains(&delta.type_url) {
-                                    continue;
+                            let mut type_urls: Vec<&str> =
+                                update.deltas.iter().map(|delta| delta.type_url.as_str()).collect();
+                            if scoped_node {
+                                for type_url in scope_dependents(&update) {
+                                    if !type_urls.contains(type_url) {
+                                        type_urls.push(type_url);
+                                    }
                                 }
+                                type_urls.sort_by_key(|type_url| push_position(type_url));
+                            }
 
-                                if delta.added_or_updated.is_empty() && delta.removed.is_empty() {
+                            let mut responses = Vec::new();
+                            for type_url in type_urls {
+                                if !pending_types.contains(type_url) {
                                     continue;
                                 }
 
-                                let response = build_delta_response(update.version, delta);
-                                let tx_for_task = tx.clone();
-                                let label_for_task = label.clone();
+                                let response = match delta_push_response(
+                                    &state_clone,
+                                    registered_node.as_deref(),
+                                    &stream_node,
+                                    &update,
+                                    type_url,
+                                    &held_types,
+                                    &served,
+                                ) {
+                                    Ok(Some(response)) => response,
+                                    Ok(None) => continue,
+                                    Err(e) => {
+                                        error!(stream = %label, error = %e, type_url, "Failed to create delta push response");
+                                        continue;
+                                    }
+                                };
+                                served.apply(&response);
+                                if let Some(node_id) = &registered_node {
+                                    state_clone.node_registry.record_response(
+                                        node_id,
+                                        &response.type_url,
+                                        &response.system_version_info,
+                                        &response.nonce,
+                                    );
+                                }
 
                                 info!(
-                                    type_url = %delta.type_url,
-                                    added = delta.added_or_updated.len(),
-                                    removed = delta.removed.len(),
+                                    type_url,
+                                    added = response.resources.len(),
+                                    removed = response.removed_resources.len(),
                                     version = update.version,
                                     stream = %label,
                                     "Sending delta push update to client"
                                 );
+                                responses.push(response);
+                            }
 
-                                tokio::spawn(async move {
-                                    if tx_for_task.send(Ok(response)).await.is_err() {
-                                        error!(stream = %label_for_task, "Delta response receiver dropped");
-                                    }
-                                });
+                            if responses.is_empty() {
+                                continue;
+                            }
+                            if push_tx.send(responses).await.is_err() {
+                                break;
                             }
                         }
                         Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
@@ -405,11 +798,36 @@ where
                 }
             }
         }
+
+        if let Some(node_id) = registered_node {
+            state_clone.node_registry.disconnect(&node_id);
+        }
     });
 
     ReceiverStream::new(rx)
 }
 
+/// Spawn the task that pushes updates to one delta stream, sending the
+/// responses of each update in push order and updates in the order they were
+/// published. It ends when the returned sender is dropped.
+fn spawn_delta_pusher(
+    tx: mpsc::Sender<std::result::Result<DeltaDiscoveryResponse, Status>>,
+    label: String,
+) -> mpsc::Sender<Vec<DeltaDiscoveryResponse>> {
+    let (push_tx, mut push_rx) = mpsc::channel::<Vec<DeltaDiscoveryResponse>>(100);
+    tokio::spawn(async move {
+        while let Some(responses) = push_rx.recv().await {
+            for response in responses {
+                if tx.send(Ok(response)).await.is_err() {
+                    error!(stream = %label, "Delta response receiver dropped");
+                    return;
+                }
+            }
+        }
+    });
+    push_tx
+}
+
 // Removed complex process_delta_request function - using PoC-style direct response pattern
 
 fn build_delta_response(update_version: u64, delta: &ResourceDelta) -> DeltaDiscoveryResponse {
@@ -418,7 +836,7 @@ fn build_delta_response(update_version: u64, delta: &ResourceDelta) -> DeltaDisc
         .iter()
         .map(|cached| Resource {
             name: cached.name.clone(),
-            version: cached.version.to_string(),
+            version: cached.resource_version(),
             resource: Some(cached.body.clone()),
             ..Default::default()
         })
@@ -433,3 +851,354 @@ fn build_delta_response(update_version: u64, delta: &ResourceDelta) -> DeltaDisc
         ..Default::default()
     }
 }
+
+/// Skip resources a reconnecting client already holds at the served version,
+/// and remove the ones it holds that are no longer served.
+fn reconcile_initial_versions(
+    response: &mut DeltaDiscoveryResponse,
+    initial_versions: &HashMap<String, String>,
+) {
+    if initial_versions.is_empty() {
+        return;
+    }
+
+    let mut stale: Vec<String> = initial_versions
+        .keys()
+        .filter(|name| {
+            !response.resources.iter().any(|resource| &resource.name == *name)
+                && !response.removed_resources.contains(name)
+        })
+        .cloned()
+        .collect();
+    stale.sort();
+    response.removed_resources.extend(stale);
+    response
+        .resources
+        .retain(|resource| initial_versions.get(&resource.name) != Some(&resource.version));
+}
+
+/// Act on an ACK or first NACK for `type_url` from `node_id`.
+async fn handle_request_kind(
+    state: &XdsState,
+    node_id: &str,
+    type_url: &str,
+    kind: &RequestKind,
+    error_detail: Option<&RpcStatus>,
+) {
+    match (kind, error_detail) {
+        (RequestKind::Ack { version: Some(version) }, None) => {
+            state.record_ack(type_url, version).await;
+        }
+        (RequestKind::Nack { version, repeated: false }, Some(error)) => {
+            state.handle_nack(node_id, type_url, version.as_deref(), error).await;
+        }
+        _ => {}
+    }
+}
+
+/// SotW response carrying the subscribed part of the snapshot a held node must be served.
+fn known_good_response(
+    type_url: &str,
+    snapshot: &KnownGoodSnapshot,
+    subscription: &Subscription,
+) -> DiscoveryResponse {
+    DiscoveryResponse {
+        version_info: snapshot.version.to_string(),
+        resources: snapshot
+            .resources
+            .iter()
+            .filter(|cached| subscription.contains(&cached.name))
+            .map(|cached| cached.body.clone())
+            .collect(),
+        type_url: type_url.to_string(),
+        nonce: Uuid::new_v4().to_string(),
+        ..Default::default()
+    }
+}
+
+/// SotW response for a stream subscribed to no resources of `type_url`.
+fn unsubscribed_response(state: &XdsState, type_url: &str) -> DiscoveryResponse {
+    DiscoveryResponse {
+        version_info: state.get_type_version(type_url),
+        type_url: type_url.to_string(),
+        nonce: Uuid::new_v4().to_string(),
+        ..Default::default()
+    }
+}
+
+/// Delta response moving a held node to the snapshot it must be served,
+/// already narrowed to the node's scope.
+///
+/// Resources the node was served or may receive now that are missing from
+/// the snapshot are removed, and the held names are remembered so the node
+/// can be resynchronised once released.
+fn known_good_delta_response(
+    state: &XdsState,
+    type_url: &str,
+    node: &Option<Node>,
+    snapshot: &KnownGoodSnapshot,
+    held_types: &std::sync::Mutex<HashMap<String, Vec<String>>>,
+    served: &ServedResources,
+) -> crate::Result<DeltaDiscoveryResponse> {
+    let held: Vec<String> = snapshot.resources.iter().map(|cached| cached.name.clone()).collect();
+    let mut removed: BTreeSet<String> = served.names(type_url).into_iter().collect();
+    removed.extend(stream_resources(state, type_url, node)?.into_iter().map(|cached| cached.name));
+    let removed_resources = removed.into_iter().filter(|name| !held.contains(name)).collect();
+
+    held_types.lock().expect("held type lock poisoned").insert(type_url.to_string(), held);
+
+    Ok(DeltaDiscoveryResponse {
+        system_version_info: snapshot.version.to_string(),
+        type_url: type_url.to_string(),
+        nonce: Uuid::new_v4().to_string(),
+        resources: snapshot
+            .resources
+            .iter()
+            .map(|cached| Resource {
+                name: cached.name.clone(),
+                version: cached.resource_version(),
+                resource: Some(cached.body.clone()),
+                ..Default::default()
+            })
+            .collect(),
+        removed_resources,
+        ..Default::default()
+    })
+}
+
+/// Response pushing `update` for `type_url` to a delta stream, or `None`
+/// when it changes nothing the stream's node is served.
+///
+/// Held nodes get their held snapshot and released ones their full current
+/// state. Scoped nodes get the changed resources in their scope, resources
+/// that came into it and removals of served ones that left it.
+fn delta_push_response(
+    state: &XdsState,
+    node_id: Option<&str>,
+    node: &Option<Node>,
+    update: &ResourceUpdate,
+    type_url: &str,
+    held_types: &std::sync::Mutex<HashMap<String, Vec<String>>>,
+    served: &ServedResources,
+) -> crate::Result<Option<DeltaDiscoveryResponse>> {
+    let held = match node_id {
+        Some(node_id) => held_node_snapshot(state, node_id, node, type_url)?,
+        None => None,
+    };
+    if let Some(snapshot) = held {
+        return known_good_delta_response(state, type_url, node, &snapshot, held_types, served)
+            .map(Some);
+    }
+
+    // A node released from a held snapshot needs the full current state
+    // rather than this delta.
+    let released = held_types.lock().expect("held type lock poisoned").remove(type_url);
+    if let Some(held) = released {
+        let current = stream_resources(state, type_url, node)?;
+        return Ok(Some(full_state_delta_response(update.version, type_url, &current, &held)));
+    }
+
+    let delta = update.deltas.iter().find(|delta| delta.type_url == type_url);
+    if !node.as_ref().is_some_and(node_is_scoped) {
+        return Ok(delta
+            .filter(|delta| !delta.added_or_updated.is_empty() || !delta.removed.is_empty())
+            .map(|delta| build_delta_response(update.version, delta)));
+    }
+
+    let current = stream_resources(state, type_url, node)?;
+    let served_names = served.names(type_url);
+    let changed: HashSet<&str> = delta
+        .iter()
+        .flat_map(|delta| delta.added_or_updated.iter().map(|cached| cached.name.as_str()))
+        .collect();
+    let added_or_updated: Vec<CachedResource> = current
+        .iter()
+        .filter(|cached| {
+            changed.contains(cached.name.as_str()) || !served_names.contains(&cached.name)
+        })
+        .cloned()
+        .collect();
+    let mut removed: Vec<String> = served_names
+        .into_iter()
+        .filter(|name| !current.iter().any(|cached| &cached.name == name))
+        .collect();
+    removed.sort();
+    if added_or_updated.is_empty() && removed.is_empty() {
+        return Ok(None);
+    }
+    let delta = ResourceDelta { type_url: type_url.to_string(), added_or_updated, removed };
+    Ok(Some(build_delta_response(update.version, &delta)))
+}
+
+/// Types a scoped node is served by following references from the
+/// listeners and routes `update` changes, whose served resources it may
+/// change without touching them.
+fn scope_dependents(update: &ResourceUpdate) -> &'static [&'static str] {
+    let touches = |type_url: &str| update.deltas.iter().any(|delta| delta.type_url == type_url);
+    if touches(LISTENER_TYPE_URL) {
+        &[CLUSTER_TYPE_URL, ENDPOINT_TYPE_URL, ROUTE_TYPE_URL]
+    } else if touches(ROUTE_TYPE_URL) {
+        &[CLUSTER_TYPE_URL, ENDPOINT_TYPE_URL]
+    } else {
+        &[]
+    }
+}
+
+/// Current resources of `type_url` the stream's `node` may receive.
+fn stream_resources(
+    state: &XdsState,
+    type_url: &str,
+    node: &Option<Node>,
+) -> crate::Result<Vec<CachedResource>> {
+    match node {
+        Some(node) => Ok(node_snapshot(state, type_url, node)?.1),
+        None => Ok(state.cached_resources(type_url)),
+    }
+}
+
+/// Resource names sent to one delta stream, by type, so pushes to a scoped
+/// node can add what came into its scope and remove what left it.
+#[derive(Debug, Default)]
+struct ServedResources(std::sync::Mutex<HashMap<String, HashSet<String>>>);
+
+impl ServedResources {
+    /// Record a response carrying the full state of its type.
+    fn replace(&self, response: &DeltaDiscoveryResponse) {
+        let names = response.resources.iter().map(|resource| resource.name.clone()).collect();
+        self.0
+            .lock()
+            .expect("served resource lock poisoned")
+            .insert(response.type_url.clone(), names);
+    }
+
+    /// Record a response carrying changes to its type.
+    fn apply(&self, response: &DeltaDiscoveryResponse) {
+        let mut served = self.0.lock().expect("served resource lock poisoned");
+        let names = served.entry(response.type_url.clone()).or_default();
+        for name in &response.removed_resources {
+            names.remove(name);
+        }
+        names.extend(response.resources.iter().map(|resource| resource.name.clone()));
+    }
+
+    fn names(&self, type_url: &str) -> HashSet<String> {
+        self.0
+            .lock()
+            .expect("served resource lock poisoned")
+            .get(type_url)
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// Delta response replacing everything previously served with `current`.
+fn full_state_delta_response(
+    version: u64,
+    type_url: &str,
+    current: &[CachedResource],
+    served: &[String],
+) -> DeltaDiscoveryResponse {
+    let delta = ResourceDelta {
+        type_url: type_url.to_string(),
+        added_or_updated: current.to_vec(),
+        removed: served
+            .iter()
+            .filter(|name| !current.iter().any(|cached| &cached.name == *name))
+            .cloned()
+            .collect(),
+    };
+    build_delta_response(version, &delta)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use envoy_types::pb::google::protobuf::Any;
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn route_delta(added: &[&str], removed: &[&str]) -> ResourceDelta {
+        ResourceDelta {
+            type_url: "type.googleapis.com/envoy.config.route.v3.RouteConfiguration".to_string(),
+            added_or_updated: added
+                .iter()
+                .map(|name| CachedResource::new(name.to_string(), String::new(), 1, Any::default()))
+                .collect(),
+            removed: names(removed),
+        }
+    }
+
+    #[test]
+    fn reconnect_skips_held_versions_and_removes_missing_names() {
+        let resource = |name: &str, version: &str| Resource {
+            name: name.to_string(),
+            version: version.to_string(),
+            ..Default::default()
+        };
+        let mut response = DeltaDiscoveryResponse {
+            resources: vec![
+                resource("unchanged", "v1"),
+                resource("changed", "v2"),
+                resource("new", "v1"),
+            ],
+            ..Default::default()
+        };
+        let initial_versions = HashMap::from([
+            ("unchanged".to_string(), "v1".to_string()),
+            ("changed".to_string(), "v1".to_string()),
+            ("deleted".to_string(), "v1".to_string()),
+        ]);
+
+        reconcile_initial_versions(&mut response, &initial_versions);
+
+        let sent: Vec<&str> = response.resources.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(sent, vec!["changed", "new"]);
+        assert_eq!(response.removed_resources, vec!["deleted".to_string()]);
+    }
+
+    #[test]
+    fn empty_first_request_is_a_wildcard_until_names_are_sent() {
+        let mut subscription = Subscription::default();
+        assert!(subscription.update(&[], true));
+        assert!(subscription.contains("anything"));
+        assert!(!subscription.update(&[], false), "an ACK keeps the legacy wildcard");
+
+        assert!(subscription.update(&names(&["a"]), false));
+        assert!(!subscription.contains("b"));
+        assert!(subscription.update(&names(&["*", "a"]), false));
+        assert!(subscription.contains("b"));
+    }
+
+    #[test]
+    fn explicit_names_replace_the_subscription() {
+        let mut subscription = Subscription::default();
+        assert!(subscription.update(&names(&["a", "b"]), true));
+        assert!(!subscription.update(&names(&["b", "a"]), false));
+
+        assert!(subscription.update(&names(&["b"]), false));
+        assert!(!subscription.contains("a"), "names left out are unsubscribed");
+
+        assert!(subscription.update(&[], false));
+        assert!(subscription.is_empty(), "an empty list later unsubscribes everything");
+        assert!(!subscription.covers(&route_delta(&["b"], &[])));
+    }
+
+    #[test]
+    fn pushes_only_cover_subscribed_names() {
+        let mut subscription = Subscription::default();
+        subscription.update(&names(&["a"]), true);
+
+        assert!(subscription.covers(&route_delta(&["a", "b"], &[])));
+        assert!(subscription.covers(&route_delta(&[], &["a"])));
+        assert!(!subscription.covers(&route_delta(&["b"], &["c"])));
+        assert!(subscription.covers(&route_delta(&[], &[])), "version-only updates are pushed");
+
+        let request = subscription.apply_to(DiscoveryRequest {
+            resource_names: names(&["a", "b"]),
+            ..Default::default()
+        });
+        assert_eq!(request.resource_names, names(&["a"]));
+    }
+}
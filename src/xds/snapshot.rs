@@ -0,0 +1,405 @@
+//! Dump of the resources the control plane serves, in Envoy's JSON form.
+//!
+//! The dump is an `envoy.admin.v3.ConfigDump`, the message behind Envoy's
+//! `/config_dump` admin endpoint, so it can be diffed against a running
+//! Envoy. Protobufs are rendered through the descriptors in
+//! `proto/envoy_descriptors.bin` (regenerated by
+//! `scripts/generate-envoy-descriptors.sh`). As in Envoy, fields annotated
+//! `udpa.annotations.sensitive`, such as private keys, are redacted.
+
+use std::sync::Arc;
+
+use envoy_types::pb::envoy::admin::v3::{
+    clusters_config_dump::DynamicCluster,
+    endpoints_config_dump::DynamicEndpointConfig,
+    listeners_config_dump::{DynamicListener, DynamicListenerState},
+    routes_config_dump::DynamicRouteConfig,
+    secrets_config_dump::DynamicSecret,
+    ClustersConfigDump, ConfigDump, EndpointsConfigDump, ListenersConfigDump, RoutesConfigDump,
+    SecretsConfigDump,
+};
+use envoy_types::pb::envoy::config::core::v3::Node;
+use envoy_types::pb::google::protobuf::Any;
+use once_cell::sync::Lazy;
+use prost::Message;
+use prost_reflect::{
+    DescriptorPool, DynamicMessage, ExtensionDescriptor, FieldDescriptor, ReflectMessage,
+    SerializeOptions, Value,
+};
+
+use super::resources::{
+    CLUSTER_TYPE_URL, ENDPOINT_TYPE_URL, LISTENER_TYPE_URL, ROUTE_TYPE_URL, SECRET_TYPE_URL,
+};
+use super::services::{held_node_snapshot, node_snapshot};
+use super::XdsState;
+use crate::{Error, Result};
+
+static DESCRIPTORS: Lazy<DescriptorPool> = Lazy::new(|| {
+    DescriptorPool::decode(include_bytes!("../../proto/envoy_descriptors.bin").as_ref())
+        .expect("proto/envoy_descriptors.bin is a valid descriptor set")
+});
+
+const SENSITIVE_EXTENSION: &str = "udpa.annotations.sensitive";
+const REDACTED: &str = "[redacted]";
+const ANY_MESSAGE: &str = "google.protobuf.Any";
+const CONFIG_DUMP_MESSAGE: &str = "envoy.admin.v3.ConfigDump";
+
+/// Resource types a dump can include, keyed by the names the API accepts, in
+/// the order Envoy lists them.
+pub const SNAPSHOT_TYPES: [(&str, &str); 5] = [
+    ("cluster", CLUSTER_TYPE_URL),
+    ("endpoint", ENDPOINT_TYPE_URL),
+    ("listener", LISTENER_TYPE_URL),
+    ("route", ROUTE_TYPE_URL),
+    ("secret", SECRET_TYPE_URL),
+];
+
+/// Type URLs named by a comma-separated `filter`, in dump order. Every type
+/// when `filter` is `None` or empty.
+pub fn snapshot_type_urls(filter: Option<&str>) -> Result<Vec<&'static str>> {
+    let names: Vec<&str> = filter
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .collect();
+    if let Some(unknown) =
+        names.iter().find(|name| !SNAPSHOT_TYPES.iter().any(|(key, _)| key == *name))
+    {
+        return Err(Error::validation(format!(
+            "Unknown resource type '{}'; expected cluster, endpoint, listener, route or secret",
+            unknown
+        )));
+    }
+    Ok(SNAPSHOT_TYPES
+        .iter()
+        .filter(|(key, _)| names.is_empty() || names.contains(key))
+        .map(|(_, type_url)| *type_url)
+        .collect())
+}
+
+/// Render the resources of `type_urls` as an Envoy config dump, read from the
+/// resource caches. With a `node`, the dump holds what that node is served:
+/// its held snapshot while pinned or outside a rollout, otherwise the cache,
+/// scoped by its metadata either way. Without one it holds the full cache.
+pub async fn config_dump(
+    state: &Arc<XdsState>,
+    type_urls: &[&str],
+    node: Option<&Node>,
+) -> Result<serde_json::Value> {
+    let mut configs = Vec::new();
+    for type_url in type_urls {
+        let (version, cached) = match node {
+            Some(node) => match held_node_snapshot(state, &node.id, &Some(node.clone()), type_url)?
+            {
+                Some(held) => (held.version, held.resources),
+                None => node_snapshot(state, type_url, node)?,
+            },
+            None => state.type_snapshot(type_url),
+        };
+        let mut resources: Vec<(String, Any)> =
+            cached.into_iter().map(|cached| (cached.name, cached.body)).collect();
+        resources.sort_by(|a, b| a.0.cmp(&b.0));
+        configs.push(type_dump(type_url, version.to_string(), resources));
+    }
+
+    let dump = ConfigDump { configs };
+    to_envoy_json(CONFIG_DUMP_MESSAGE, &dump.encode_to_vec())
+}
+
+/// The `*ConfigDump` message for one resource type, packed in an `Any`.
+fn type_dump(type_url: &str, version_info: String, resources: Vec<(String, Any)>) -> Any {
+    let (name, value) = match type_url {
+        CLUSTER_TYPE_URL => (
+            "ClustersConfigDump",
+            ClustersConfigDump {
+                dynamic_active_clusters: resources
+                    .into_iter()
+                    .map(|(_, body)| DynamicCluster {
+                        version_info: version_info.clone(),
+                        cluster: Some(body),
+                        ..Default::default()
+                    })
+                    .collect(),
+                version_info,
+                ..Default::default()
+            }
+            .encode_to_vec(),
+        ),
+        ENDPOINT_TYPE_URL => (
+            "EndpointsConfigDump",
+            EndpointsConfigDump {
+                dynamic_endpoint_configs: resources
+                    .into_iter()
+                    .map(|(_, body)| DynamicEndpointConfig {
+                        version_info: version_info.clone(),
+                        endpoint_config: Some(body),
+                        ..Default::default()
+                    })
+                    .collect(),
+                ..Default::default()
+            }
+            .encode_to_vec(),
+        ),
+        LISTENER_TYPE_URL => (
+            "ListenersConfigDump",
+            ListenersConfigDump {
+                dynamic_listeners: resources
+                    .into_iter()
+                    .map(|(name, body)| DynamicListener {
+                        name,
+                        active_state: Some(DynamicListenerState {
+                            version_info: version_info.clone(),
+                            listener: Some(body),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    })
+                    .collect(),
+                version_info,
+                ..Default::default()
+            }
+            .encode_to_vec(),
+        ),
+        ROUTE_TYPE_URL => (
+            "RoutesConfigDump",
+            RoutesConfigDump {
+                dynamic_route_configs: resources
+                    .into_iter()
+                    .map(|(_, body)| DynamicRouteConfig {
+                        version_info: version_info.clone(),
+                        route_config: Some(body),
+                        ..Default::default()
+                    })
+                    .collect(),
+                ..Default::default()
+            }
+            .encode_to_vec(),
+        ),
+        _ => (
+            "SecretsConfigDump",
+            SecretsConfigDump {
+                dynamic_active_secrets: resources
+                    .into_iter()
+                    .map(|(name, body)| DynamicSecret {
+                        name,
+                        version_info: version_info.clone(),
+                        secret: Some(body),
+                        ..Default::default()
+                    })
+                    .collect(),
+                ..Default::default()
+            }
+            .encode_to_vec(),
+        ),
+    };
+    Any { type_url: format!("type.googleapis.com/envoy.admin.v3.{}", name), value }
+}
+
+/// Decode `bytes` as the message `message_name`, redact sensitive fields and
+/// serialize it as proto3 JSON with Envoy's snake_case field names.
+fn to_envoy_json(message_name: &str, bytes: &[u8]) -> Result<serde_json::Value> {
+    let descriptor = DESCRIPTORS
+        .get_message_by_name(message_name)
+        .ok_or_else(|| Error::internal(format!("No descriptor for {}", message_name)))?;
+    let mut message = DynamicMessage::decode(descriptor, bytes)
+        .map_err(|e| Error::internal(format!("Failed to decode {}: {}", message_name, e)))?;
+    if let Some(sensitive) = DESCRIPTORS.get_extension_by_name(SENSITIVE_EXTENSION) {
+        redact(&mut message, &sensitive)?;
+    }
+
+    let options = SerializeOptions::new().use_proto_field_name(true);
+    message.serialize_with_options(serde_json::value::Serializer, &options).map_err(|e| {
+        Error::internal(format!(
+            "Failed to render {} as JSON ({}); regenerate proto/envoy_descriptors.bin if a \
+             new typed config was added",
+            message_name, e
+        ))
+    })
+}
+
+/// Redact sensitive fields of `message`, descending into nested messages and
+/// the payloads of `Any` fields.
+fn redact(message: &mut DynamicMessage, sensitive: &ExtensionDescriptor) -> Result<()> {
+    if message.descriptor().full_name() == ANY_MESSAGE {
+        return redact_any(message, sensitive);
+    }
+    for (field, value) in message.fields_mut() {
+        if is_sensitive(&field, sensitive) {
+            redact_value(value);
+        } else {
+            redact_nested(value, sensitive)?;
+        }
+    }
+    Ok(())
+}
+
+fn redact_nested(value: &mut Value, sensitive: &ExtensionDescriptor) -> Result<()> {
+    match value {
+        Value::Message(message) => redact(message, sensitive),
+        Value::List(values) => values.iter_mut().try_for_each(|v| redact_nested(v, sensitive)),
+        Value::Map(entries) => entries.values_mut().try_for_each(|v| redact_nested(v, sensitive)),
+        _ => Ok(()),
+    }
+}
+
+/// Redact the payload of an `Any`, leaving payloads of types missing from the
+/// descriptor set for serialization to report.
+fn redact_any(any: &mut DynamicMessage, sensitive: &ExtensionDescriptor) -> Result<()> {
+    let type_url = any.get_field_by_name("type_url").and_then(|v| v.as_str().map(str::to_string));
+    let Some(descriptor) = type_url
+        .as_deref()
+        .and_then(|url| url.rsplit('/').next())
+        .and_then(|name| DESCRIPTORS.get_message_by_name(name))
+    else {
+        return Ok(());
+    };
+    let Some(Value::Bytes(bytes)) = any.get_field_by_name("value").map(|v| v.into_owned()) else {
+        return Ok(());
+    };
+
+    let mut payload = DynamicMessage::decode(descriptor, bytes)
+        .map_err(|e| Error::internal(format!("Failed to decode {:?}: {}", type_url, e)))?;
+    redact(&mut payload, sensitive)?;
+    any.set_field_by_name("value", Value::Bytes(payload.encode_to_vec().into()));
+    Ok(())
+}
+
+fn is_sensitive(field: &FieldDescriptor, sensitive: &ExtensionDescriptor) -> bool {
+    field.options().get_extension(sensitive).as_bool() == Some(true)
+}
+
+/// Replace every string and bytes leaf under `value`, as Envoy does.
+fn redact_value(value: &mut Value) {
+    match value {
+        Value::String(text) => *text = REDACTED.to_string(),
+        Value::Bytes(bytes) => *bytes = REDACTED.as_bytes().to_vec().into(),
+        Value::Message(message) => {
+            for (_, value) in message.fields_mut() {
+                redact_value(value);
+            }
+        }
+        Value::List(values) => values.iter_mut().for_each(redact_value),
+        Value::Map(entries) => entries.values_mut().for_each(redact_value),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SimpleXdsConfig;
+    use envoy_types::pb::envoy::config::cluster::v3::Cluster;
+    use envoy_types::pb::envoy::config::core::v3::{data_source::Specifier, DataSource};
+    use envoy_types::pb::envoy::extensions::transport_sockets::tls::v3::{
+        secret, Secret, TlsCertificate,
+    };
+    use envoy_types::pb::google::protobuf::Duration;
+
+    fn any(type_url: &str, message: &impl Message) -> Any {
+        Any { type_url: type_url.to_string(), value: message.encode_to_vec() }
+    }
+
+    #[tokio::test]
+    async fn dumps_cache_as_envoy_json_and_redacts_private_keys() {
+        let state = Arc::new(XdsState::new(SimpleXdsConfig::default()));
+        let cluster = Cluster {
+            name: "payments".into(),
+            connect_timeout: Some(Duration { seconds: 5, nanos: 0 }),
+            ..Default::default()
+        };
+        state.apply_built_resources(
+            CLUSTER_TYPE_URL,
+            vec![crate::xds::resources::BuiltResource {
+                name: "payments".into(),
+                resource: any(CLUSTER_TYPE_URL, &cluster),
+            }],
+        );
+        let secret = Secret {
+            name: "edge-cert".into(),
+            r#type: Some(secret::Type::TlsCertificate(TlsCertificate {
+                certificate_chain: Some(DataSource {
+                    specifier: Some(Specifier::InlineString("CERT".into())),
+                    ..Default::default()
+                }),
+                private_key: Some(DataSource {
+                    specifier: Some(Specifier::InlineBytes(b"KEY".to_vec())),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })),
+        };
+        state.apply_built_resources(
+            SECRET_TYPE_URL,
+            vec![crate::xds::resources::BuiltResource {
+                name: "edge-cert".into(),
+                resource: any(SECRET_TYPE_URL, &secret),
+            }],
+        );
+
+        let dump = config_dump(&state, &snapshot_type_urls(None).unwrap(), None).await.unwrap();
+        let configs = dump["configs"].as_array().unwrap();
+        assert_eq!(configs.len(), SNAPSHOT_TYPES.len());
+
+        let clusters = &configs[0];
+        assert_eq!(clusters["@type"], "type.googleapis.com/envoy.admin.v3.ClustersConfigDump");
+        let dumped = &clusters["dynamic_active_clusters"][0]["cluster"];
+        assert_eq!(dumped["@type"], CLUSTER_TYPE_URL);
+        assert_eq!(dumped["name"], "payments");
+        assert_eq!(dumped["connect_timeout"], "5s");
+
+        let tls = &configs[4]["dynamic_active_secrets"][0]["secret"]["tls_certificate"];
+        assert_eq!(tls["certificate_chain"]["inline_string"], "CERT");
+        assert_eq!(tls["private_key"]["inline_bytes"], "W3JlZGFjdGVkXQ==");
+    }
+
+    #[tokio::test]
+    async fn node_dump_scopes_the_cached_listeners_by_team() {
+        use envoy_types::pb::envoy::config::listener::v3::Listener;
+        use envoy_types::pb::google::protobuf::{value::Kind, Struct, Value as ProtoValue};
+
+        let state = Arc::new(XdsState::new(SimpleXdsConfig::default()));
+        let listener = |name: &str, team: &str| {
+            let mut listener = Listener { name: name.into(), ..Default::default() };
+            crate::xds::metadata::set_listener_team(&mut listener, team);
+            crate::xds::resources::BuiltResource {
+                name: name.into(),
+                resource: any(LISTENER_TYPE_URL, &listener),
+            }
+        };
+        state.apply_built_resources(
+            LISTENER_TYPE_URL,
+            vec![listener("payments-listener", "payments"), listener("search-listener", "search")],
+        );
+        let node = Node {
+            id: "payments-envoy".into(),
+            metadata: Some(Struct {
+                fields: [(
+                    "team".to_string(),
+                    ProtoValue { kind: Some(Kind::StringValue("payments".into())) },
+                )]
+                .into_iter()
+                .collect(),
+            }),
+            ..Default::default()
+        };
+
+        let dump = config_dump(&state, &[LISTENER_TYPE_URL], Some(&node)).await.unwrap();
+        let listeners = dump["configs"][0]["dynamic_listeners"].as_array().unwrap();
+        let names: Vec<&str> = listeners.iter().map(|l| l["name"].as_str().unwrap()).collect();
+        assert_eq!(names, vec!["payments-listener"]);
+        assert_eq!(
+            dump["configs"][0]["version_info"],
+            state.get_type_version_number(LISTENER_TYPE_URL).to_string()
+        );
+    }
+
+    #[test]
+    fn type_filter_keeps_dump_order_and_rejects_unknown_types() {
+        assert_eq!(
+            snapshot_type_urls(Some("route, cluster")).unwrap(),
+            vec![CLUSTER_TYPE_URL, ROUTE_TYPE_URL]
+        );
+        assert!(snapshot_type_urls(Some("runtime")).is_err());
+    }
+}
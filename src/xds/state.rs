@@ -1,22 +1,45 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::{Arc, RwLock};
 
+use crate::xds::ca::{
+    CertificateAuthority, CertificateSubject, IssuedCertificate, CA_TRUST_BUNDLE_SECRET,
+};
+use crate::xds::index::{
+    changed_rows, tables_for_type, ResourceIndex, INDEXED_TABLES, INDEXED_TYPES,
+};
+use crate::xds::metadata;
+use crate::xds::publisher::{StagedSnapshot, UpdatePublisher};
+use crate::xds::registry::NodeRegistry;
 use crate::xds::resources::{
-    clusters_from_config, clusters_from_database_entries, listeners_from_config,
-    listeners_from_database_entries, resources_from_api_definitions, routes_from_config,
-    routes_from_database_entries, BuiltResource, CLUSTER_TYPE_URL, LISTENER_TYPE_URL,
-    ROUTE_TYPE_URL,
+    clusters_from_config, clusters_from_database_entries, endpoints_from_config,
+    endpoints_from_database_entries, listeners_from_config, listeners_from_database_entries,
+    merge_platform_routes, resources_from_api_definitions, routes_from_config,
+    routes_from_database_entries, secrets_from_database_entries, trust_bundle_secret,
+    BuiltResource, CLUSTER_TYPE_URL, ENDPOINT_TYPE_URL, LISTENER_TYPE_URL, ROUTE_TYPE_URL,
+    RUNTIME_TYPE_URL, SECRET_TYPE_URL,
 };
+use crate::xds::rollback::{self, KnownGoodSnapshot};
+use crate::xds::rollout::{Rollout, RolloutPhase, RolloutStatus};
+use crate::xds::runtime::runtime_layer_from_database_entries;
 use crate::{
-    config::SimpleXdsConfig,
+    config::{NackPolicy, SimpleXdsConfig},
+    coordination::LeaderElection,
+    observability::HealthChecker,
     storage::{
-        ApiDefinitionRepository, ClusterRepository, DbPool, ListenerRepository, RouteRepository,
+        ApiDefinitionData, ApiDefinitionRepository, ApiRouteData, AuditEvent, AuditLogRepository,
+        CertificateAuthorityRepository, Change, ChangeKind, ChangeNotifier, ClusterData,
+        ClusterRepository, ConfigurationVersionRepository, CreateCertificateAuthorityRequest,
+        CreateSecretRequest, DbPool, ListenerRepository, RolloutRepository, RouteRepository,
+        RuntimeRepository, SecretData, SecretRepository, UpdateSecretRequest,
     },
-    Result,
+    Error, Result,
 };
+use envoy_types::pb::envoy::config::core::v3::Node;
 use envoy_types::pb::google::protobuf::Any;
-use tokio::sync::broadcast;
-use tracing::info;
+use envoy_types::pb::google::rpc::Status as RpcStatus;
+use ring::digest;
+use tokio::sync::{broadcast, OnceCell};
+use tracing::{debug, info, warn};
 
 /// Cached Envoy resource along with metadata required for delta semantics.
 #[derive(Clone, Debug)]
@@ -31,6 +54,24 @@ impl CachedResource {
     pub fn new(name: String, type_url: String, version: u64, body: Any) -> Self {
         Self { name, type_url, version, body }
     }
+
+    /// Version reported for this resource on delta streams.
+    pub fn resource_version(&self) -> String {
+        resource_version(&self.body)
+    }
+}
+
+/// Version of a resource on delta streams, derived from its body.
+///
+/// Unlike type versions it does not depend on when the cache was built, so a
+/// node reconnecting after a control-plane restart reports versions that still
+/// match every resource that did not change.
+pub fn resource_version(body: &Any) -> String {
+    let mut context = digest::Context::new(&digest::SHA256);
+    context.update(body.type_url.as_bytes());
+    context.update(&[0]);
+    context.update(&body.value);
+    context.finish().as_ref()[..8].iter().map(|byte| format!("{:02x}", byte)).collect()
 }
 
 /// Delta information for a single type URL.
@@ -48,121 +89,448 @@ pub struct ResourceUpdate {
     pub deltas: Vec<ResourceDelta>,
 }
 
+/// Version assigned to a resource type before anything has been published.
+const INITIAL_TYPE_VERSION: u64 = 1;
+
+/// Upper bound on repository rows listed when building a snapshot. Every
+/// path that lists rows for xDS uses it, so they all see the same rows.
+pub(crate) const SNAPSHOT_ROW_LIMIT: i32 = 1000;
+
+/// Version bookkeeping for a single resource type.
+#[derive(Clone, Debug)]
+struct TypeVersion {
+    version: u64,
+    content_hash: Option<String>,
+}
+
+impl Default for TypeVersion {
+    fn default() -> Self {
+        Self { version: INITIAL_TYPE_VERSION, content_hash: None }
+    }
+}
+
+/// Cached resources by type URL, then by name.
+type ResourceCaches = HashMap<String, HashMap<String, CachedResource>>;
+
+/// Handles to everything applying a snapshot writes, so snapshots staged
+/// during a debounce window can be applied once it closes.
+#[derive(Debug, Clone)]
+struct CacheWriter {
+    caches: Arc<RwLock<ResourceCaches>>,
+    versions: Arc<RwLock<HashMap<String, TypeVersion>>>,
+    rollouts: Arc<RwLock<HashMap<String, Rollout>>>,
+    version: Arc<std::sync::atomic::AtomicU64>,
+    canary: bool,
+    version_repository: Option<ConfigurationVersionRepository>,
+}
+
+impl CacheWriter {
+    /// Write `snapshots` to the caches under one hold of the cache locks, so
+    /// readers see either all of them or none. Returns the update covering
+    /// the types that changed.
+    fn apply(&self, snapshots: Vec<(String, StagedSnapshot)>) -> Option<ResourceUpdate> {
+        let mut caches = self.caches.write().expect("resource cache lock poisoned");
+        let mut versions = self.versions.write().expect("type version lock poisoned");
+
+        let mut update: Option<ResourceUpdate> = None;
+        for (type_url, snapshot) in snapshots {
+            let total_resources = snapshot.built.len();
+            let Some((version, delta)) = self.apply_locked(
+                &mut caches,
+                &mut versions,
+                &type_url,
+                snapshot.built,
+                snapshot.claimed_version,
+            ) else {
+                info!(
+                    phase = "cache_refresh",
+                    %type_url, total_resources, "Cache refresh detected no changes"
+                );
+                continue;
+            };
+            info!(
+                phase = "cache_refresh",
+                %type_url,
+                added = delta.added_or_updated.len(),
+                removed = delta.removed.len(),
+                version,
+                total_resources,
+                "Cache refresh produced delta"
+            );
+            match update.as_mut() {
+                Some(update) => {
+                    update.version = update.version.max(version);
+                    update.deltas.push(delta);
+                }
+                None => update = Some(ResourceUpdate { version, deltas: vec![delta] }),
+            }
+        }
+        update
+    }
+
+    /// Replace the cached snapshot of `type_url` with `built_resources`.
+    /// Returns the new type version and the delta, or `None` when nothing
+    /// changed.
+    fn apply_locked(
+        &self,
+        caches: &mut ResourceCaches,
+        versions: &mut HashMap<String, TypeVersion>,
+        type_url: &str,
+        built_resources: Vec<BuiltResource>,
+        claimed_version: Option<u64>,
+    ) -> Option<(u64, ResourceDelta)> {
+        let had_snapshot = caches.contains_key(type_url);
+        let cache = caches.entry(type_url.to_string()).or_default();
+        let type_version = versions.entry(type_url.to_string()).or_default();
+
+        let incoming_names: HashSet<String> =
+            built_resources.iter().map(|resource| resource.name.clone()).collect();
+
+        let removed: Vec<String> = cache
+            .keys()
+            .filter(|existing_name| !incoming_names.contains(*existing_name))
+            .cloned()
+            .collect();
+
+        let mut pending_updates: Vec<BuiltResource> = Vec::new();
+
+        for built in built_resources {
+            match cache.get(&built.name) {
+                Some(existing) if existing.body == built.resource => {}
+                _ => pending_updates.push(built),
+            }
+        }
+
+        // Another replica may have claimed a newer version for content this
+        // replica already serves; adopt it so every replica reports the same.
+        let version_moved = claimed_version.is_some_and(|version| version != type_version.version);
+
+        if pending_updates.is_empty() && removed.is_empty() && !version_moved {
+            return None;
+        }
+
+        let previous = (self.canary && had_snapshot).then(|| KnownGoodSnapshot {
+            version: type_version.version,
+            resources: sorted_resources(cache),
+            rows: None,
+        });
+
+        for name in &removed {
+            cache.remove(name);
+        }
+        for built in &pending_updates {
+            cache.insert(
+                built.name.clone(),
+                CachedResource::new(
+                    built.name.clone(),
+                    type_url.to_string(),
+                    type_version.version,
+                    built.resource.clone(),
+                ),
+            );
+        }
+
+        let content_hash =
+            snapshot_hash(cache.iter().map(|(name, cached)| (name.as_str(), &cached.body)));
+        if type_version.content_hash.as_deref() == Some(content_hash.as_str()) && !version_moved {
+            return None;
+        }
+
+        let new_version = claimed_version.unwrap_or(type_version.version + 1);
+        type_version.version = new_version;
+        type_version.content_hash = Some(content_hash);
+        self.version.fetch_max(new_version, std::sync::atomic::Ordering::Relaxed);
+
+        if let Some(stable) = previous {
+            let mut rollouts = self.rollouts.write().expect("rollout lock poisoned");
+            match rollouts.get_mut(type_url) {
+                Some(rollout) => rollout.restart(new_version),
+                None => {
+                    rollouts.insert(type_url.to_string(), Rollout::new(stable, new_version));
+                }
+            }
+            info!(type_url, version = new_version, "Started canary rollout");
+        }
+
+        let mut delta =
+            ResourceDelta { type_url: type_url.to_string(), added_or_updated: Vec::new(), removed };
+
+        for built in pending_updates {
+            if let Some(cached) = cache.get_mut(&built.name) {
+                cached.version = new_version;
+                delta.added_or_updated.push(cached.clone());
+            }
+        }
+
+        Some((new_version, delta))
+    }
+
+    async fn persist_type_version(&self, type_url: &str) {
+        let Some(repository) = &self.version_repository else {
+            return;
+        };
+
+        let (version, content_hash) = {
+            let versions = self.versions.read().expect("type version lock poisoned");
+            match versions.get(type_url) {
+                Some(TypeVersion { version, content_hash: Some(hash) }) => (*version, hash.clone()),
+                _ => return,
+            }
+        };
+
+        if let Err(error) =
+            repository.record(version_key_for_type_url(type_url), version, &content_hash).await
+        {
+            warn!(%error, type_url, version, "Failed to persist xDS version");
+        }
+    }
+}
+
 /// Shared xDS server state, providing configuration, persistence access, and
 /// cached resource snapshots for delta streaming.
 #[derive(Debug)]
 pub struct XdsState {
     pub config: SimpleXdsConfig,
+    /// Highest version published for any resource type.
     pub version: Arc<std::sync::atomic::AtomicU64>,
     pub cluster_repository: Option<ClusterRepository>,
     pub route_repository: Option<RouteRepository>,
     pub listener_repository: Option<ListenerRepository>,
     pub api_definition_repository: Option<ApiDefinitionRepository>,
-    update_tx: broadcast::Sender<Arc<ResourceUpdate>>,
-    resource_caches: RwLock<HashMap<String, HashMap<String, CachedResource>>>,
+    pub secret_repository: Option<SecretRepository>,
+    pub certificate_authority_repository: Option<CertificateAuthorityRepository>,
+    pub runtime_repository: Option<RuntimeRepository>,
+    pub version_repository: Option<ConfigurationVersionRepository>,
+    pub audit_repository: Option<AuditLogRepository>,
+    /// Rollout decisions shared with other replicas; used only when coordinated.
+    pub rollout_repository: Option<RolloutRepository>,
+    /// Change bus the repositories above publish committed writes to.
+    pub change_notifier: ChangeNotifier,
+    /// Envoy nodes with an open ADS stream.
+    pub node_registry: NodeRegistry,
+    /// Component health backing the gRPC health service on the xDS port.
+    pub health_checker: HealthChecker,
+    /// Whether this replica runs the background jobs that write to the database.
+    pub leader: LeaderElection,
+    publisher: UpdatePublisher,
+    resource_caches: Arc<RwLock<ResourceCaches>>,
+    type_versions: Arc<RwLock<HashMap<String, TypeVersion>>>,
+    versions_loaded: OnceCell<()>,
+    caches_initialized: OnceCell<()>,
+    known_good: RwLock<HashMap<String, KnownGoodSnapshot>>,
+    /// Rejected version per `(node_id, type_url)` for nodes pinned to the
+    /// last-known-good snapshot.
+    pinned_nodes: RwLock<HashMap<(String, String), u64>>,
+    rollouts: Arc<RwLock<HashMap<String, Rollout>>>,
+    certificate_authority: OnceCell<Arc<CertificateAuthority>>,
+    /// Encoded resources of every configuration row, so changes re-encode
+    /// only the rows they touch.
+    resource_index: RwLock<ResourceIndex>,
 }
 
 impl XdsState {
     pub fn new(config: SimpleXdsConfig) -> Self {
-        let (update_tx, _) = broadcast::channel(128);
+        Self::with_version_repository(config, None)
+    }
+
+    /// State whose publisher persists type versions to `version_repository`
+    /// when a debounce window closes.
+    fn with_version_repository(
+        config: SimpleXdsConfig,
+        version_repository: Option<ConfigurationVersionRepository>,
+    ) -> Self {
+        let writer = CacheWriter {
+            caches: Arc::new(RwLock::new(HashMap::new())),
+            versions: Arc::new(RwLock::new(HashMap::new())),
+            rollouts: Arc::new(RwLock::new(HashMap::new())),
+            version: Arc::new(std::sync::atomic::AtomicU64::new(INITIAL_TYPE_VERSION)),
+            canary: config.canary.is_some(),
+            version_repository: version_repository.clone(),
+        };
+        let window = config.update_debounce_ms.map(std::time::Duration::from_millis);
+        let publisher = UpdatePublisher::new(window, {
+            let writer = writer.clone();
+            move |staged: Vec<(String, StagedSnapshot)>| {
+                let writer = writer.clone();
+                async move {
+                    let persisted: Vec<String> = staged
+                        .iter()
+                        .filter(|(_, snapshot)| snapshot.claimed_version.is_none())
+                        .map(|(type_url, _)| type_url.clone())
+                        .collect();
+                    let update = writer.apply(staged)?;
+                    for delta in &update.deltas {
+                        if persisted.contains(&delta.type_url) {
+                            writer.persist_type_version(&delta.type_url).await;
+                        }
+                    }
+                    Some(update)
+                }
+            }
+        });
         Self {
             config,
-            version: Arc::new(std::sync::atomic::AtomicU64::new(1)),
+            version: writer.version,
             cluster_repository: None,
             route_repository: None,
             listener_repository: None,
             api_definition_repository: None,
-            update_tx,
-            resource_caches: RwLock::new(HashMap::new()),
+            secret_repository: None,
+            certificate_authority_repository: None,
+            runtime_repository: None,
+            version_repository,
+            audit_repository: None,
+            rollout_repository: None,
+            change_notifier: ChangeNotifier::new(),
+            node_registry: NodeRegistry::new(),
+            health_checker: HealthChecker::new(),
+            leader: LeaderElection::standalone(),
+            publisher,
+            resource_caches: writer.caches,
+            type_versions: writer.versions,
+            versions_loaded: OnceCell::new(),
+            caches_initialized: OnceCell::new(),
+            known_good: RwLock::new(HashMap::new()),
+            pinned_nodes: RwLock::new(HashMap::new()),
+            rollouts: writer.rollouts,
+            certificate_authority: OnceCell::new(),
+            resource_index: RwLock::new(ResourceIndex::default()),
         }
     }
 
     pub fn with_database(config: SimpleXdsConfig, pool: DbPool) -> Self {
-        let (update_tx, _) = broadcast::channel(128);
-        let cluster_repository = ClusterRepository::new(pool.clone());
-        let route_repository = RouteRepository::new(pool.clone());
-        let listener_repository = ListenerRepository::new(pool.clone());
-        let api_definition_repository = ApiDefinitionRepository::new(pool);
+        let change_notifier = ChangeNotifier::new();
+        let cluster_repository = ClusterRepository::new(pool.clone(), change_notifier.clone());
+        let route_repository = RouteRepository::new(pool.clone(), change_notifier.clone());
+        let listener_repository = ListenerRepository::new(pool.clone(), change_notifier.clone());
+        let api_definition_repository =
+            ApiDefinitionRepository::new(pool.clone(), change_notifier.clone());
+        let secret_repository = SecretRepository::new(pool.clone(), change_notifier.clone());
+        let certificate_authority_repository = CertificateAuthorityRepository::new(pool.clone());
+        let runtime_repository = RuntimeRepository::new(pool.clone(), change_notifier.clone());
+        let version_repository = ConfigurationVersionRepository::new(pool.clone());
+        let (leader, rollout_repository) = match &config.coordination {
+            Some(coordination) => (
+                LeaderElection::with_lease(pool.clone(), coordination),
+                Some(RolloutRepository::new(pool.clone())),
+            ),
+            None => (LeaderElection::standalone(), None),
+        };
+        let audit_repository = AuditLogRepository::new(pool);
         Self {
-            config,
-            version: Arc::new(std::sync::atomic::AtomicU64::new(1)),
             cluster_repository: Some(cluster_repository),
             route_repository: Some(route_repository),
             listener_repository: Some(listener_repository),
             api_definition_repository: Some(api_definition_repository),
-            update_tx,
-            resource_caches: RwLock::new(HashMap::new()),
+            secret_repository: Some(secret_repository),
+            certificate_authority_repository: Some(certificate_authority_repository),
+            runtime_repository: Some(runtime_repository),
+            audit_repository: Some(audit_repository),
+            rollout_repository,
+            change_notifier,
+            leader,
+            ..Self::with_version_repository(config, Some(version_repository))
         }
     }
 
+    /// Share an existing health checker, such as the one created by
+    /// [`crate::observability::init_observability`].
+    pub fn with_health_checker(mut self, health_checker: HealthChecker) -> Self {
+        self.health_checker = health_checker;
+        self
+    }
+
     pub fn get_version(&self) -> String {
-        self.version.load(std::sync::atomic::Ordering::Relaxed).to_string()
+        self.get_version_number().to_string()
     }
 
     pub fn get_version_number(&self) -> u64 {
         self.version.load(std::sync::atomic::Ordering::Relaxed)
     }
 
+    /// Version string of the snapshot currently published for `type_url`.
+    pub fn get_type_version(&self, type_url: &str) -> String {
+        self.get_type_version_number(type_url).to_string()
+    }
+
+    pub fn get_type_version_number(&self, type_url: &str) -> u64 {
+        let versions = self.type_versions.read().expect("type version lock poisoned");
+        versions.get(type_url).map(|entry| entry.version).unwrap_or(INITIAL_TYPE_VERSION)
+    }
+
     /// Apply a new snapshot of built resources for `type_url` and broadcast changes.
     /// Returns `Some(ResourceUpdate)` when a delta was published.
+    ///
+    /// Only the version of `type_url` is bumped. A snapshot whose content hash
+    /// matches the last published one (e.g. the first refresh after a restart)
+    /// is cached under the existing version without being broadcast.
+    ///
+    /// With canary rollouts enabled, replacing an existing snapshot starts (or
+    /// restarts) a rollout that holds non-canary nodes on the previous one.
     pub fn apply_built_resources(
         &self,
         type_url: &str,
         built_resources: Vec<BuiltResource>,
     ) -> Option<Arc<ResourceUpdate>> {
-        let mut caches = self.resource_caches.write().expect("resource cache lock poisoned");
-        let cache = caches.entry(type_url.to_string()).or_default();
-
-        let incoming_names: HashSet<String> =
-            built_resources.iter().map(|resource| resource.name.clone()).collect();
-
-        let removed: Vec<String> = cache
-            .keys()
-            .filter(|existing_name| !incoming_names.contains(*existing_name))
-            .cloned()
-            .collect();
-
-        let mut pending_updates: Vec<BuiltResource> = Vec::new();
-
-        for built in built_resources {
-            match cache.get(&built.name) {
-                Some(existing) if existing.body == built.resource => {}
-                _ => pending_updates.push(built),
-            }
-        }
+        self.apply_snapshot(type_url, built_resources, None)
+    }
 
-        if pending_updates.is_empty() && removed.is_empty() {
+    /// [`XdsState::apply_built_resources`] under `claimed_version` when
+    /// replicas coordinate versions, or under the next local version. With a
+    /// debounce window the snapshot is staged and `None` returned; it reaches
+    /// the caches when the window closes.
+    fn apply_snapshot(
+        &self,
+        type_url: &str,
+        built_resources: Vec<BuiltResource>,
+        claimed_version: Option<u64>,
+    ) -> Option<Arc<ResourceUpdate>> {
+        let snapshot = StagedSnapshot { built: built_resources, claimed_version };
+        let Some(snapshot) = self.publisher.stage(type_url, snapshot) else {
+            debug!(type_url, "Staged snapshot until the update window closes");
             return None;
-        }
+        };
 
-        let new_version = self.version.fetch_add(1, std::sync::atomic::Ordering::Relaxed) + 1;
+        let update = Arc::new(self.cache_writer().apply(vec![(type_url.to_string(), snapshot)])?);
+        self.publisher.publish(update.as_ref().clone());
+        Some(update)
+    }
 
-        for name in &removed {
-            cache.remove(name);
+    fn cache_writer(&self) -> CacheWriter {
+        CacheWriter {
+            caches: self.resource_caches.clone(),
+            versions: self.type_versions.clone(),
+            rollouts: self.rollouts.clone(),
+            version: self.version.clone(),
+            canary: self.config.canary.is_some(),
+            version_repository: self.version_repository.clone(),
         }
+    }
 
-        let mut delta =
-            ResourceDelta { type_url: type_url.to_string(), added_or_updated: Vec::new(), removed };
-
-        for built in pending_updates {
-            let cached = CachedResource::new(
-                built.name.clone(),
-                type_url.to_string(),
-                new_version,
-                built.resource.clone(),
-            );
-            cache.insert(built.name.clone(), cached.clone());
-            delta.added_or_updated.push(cached);
-        }
+    /// Load every cache from the repositories the first time it is called;
+    /// later calls return once that initial load has finished.
+    pub async fn ensure_caches_loaded(&self) {
+        self.caches_initialized
+            .get_or_init(|| async { self.refresh_for_changes(&Change::all().collect()).await })
+            .await;
+    }
 
-        let update = Arc::new(ResourceUpdate { version: new_version, deltas: vec![delta] });
+    /// Configuration rows encoded into the resource caches since start. A
+    /// change re-encodes only the rows it names.
+    pub fn encoded_rows(&self) -> u64 {
+        self.resource_index.read().expect("resource index lock poisoned").encoded_rows()
+    }
 
-        let _ = self.update_tx.send(update.clone());
-        Some(update)
+    /// Whether the cluster, route and listener caches hold an initial snapshot.
+    pub fn caches_loaded(&self) -> bool {
+        let caches = self.resource_caches.read().expect("resource cache lock poisoned");
+        [CLUSTER_TYPE_URL, ROUTE_TYPE_URL, LISTENER_TYPE_URL]
+            .iter()
+            .all(|type_url| caches.contains_key(*type_url))
     }
 
     pub fn subscribe_updates(&self) -> broadcast::Receiver<Arc<ResourceUpdate>> {
-        self.update_tx.subscribe()
+        self.publisher.subscribe()
     }
 
     /// Return a clone of the cached resources for the provided type URL.
@@ -171,172 +539,1015 @@ impl XdsState {
         caches.get(type_url).map(|cache| cache.values().cloned().collect()).unwrap_or_default()
     }
 
-    /// Refresh the cluster cache from the backing repository (if available).
-    pub async fn refresh_clusters_from_repository(&self) -> Result<()> {
-        let repository = match &self.cluster_repository {
-            Some(repo) => repo.clone(),
-            None => return Ok(()),
-        };
+    /// Version of `type_url` together with its cached resources, read under
+    /// the same locks so the resources are exactly those of that version.
+    pub fn type_snapshot(&self, type_url: &str) -> (u64, Vec<CachedResource>) {
+        let caches = self.resource_caches.read().expect("resource cache lock poisoned");
+        let versions = self.type_versions.read().expect("type version lock poisoned");
+        let version = versions.get(type_url).map(|entry| entry.version);
+        let resources =
+            caches.get(type_url).map(|cache| cache.values().cloned().collect()).unwrap_or_default();
+        (version.unwrap_or(INITIAL_TYPE_VERSION), resources)
+    }
 
-        let cluster_rows = repository.list(Some(1000), None).await?;
+    /// Record that a node accepted `version` of `type_url`.
+    ///
+    /// When `version` is the one currently cached, the cache becomes the
+    /// type's last-known-good snapshot. Under [`NackPolicy::Revert`] the
+    /// repository rows behind it are captured too.
+    pub async fn record_ack(&self, type_url: &str, version: &str) {
+        let Ok(version) = version.parse::<u64>() else {
+            return;
+        };
+        if self.known_good_snapshot(type_url).is_some_and(|snapshot| snapshot.version >= version) {
+            return;
+        }
+        if self.get_type_version_number(type_url) != version {
+            return;
+        }
 
-        let built = if cluster_rows.is_empty() {
-            clusters_from_config(&self.config)?
-        } else {
-            clusters_from_database_entries(cluster_rows, "cache_refresh")?
-        };
-
-        let total_resources = built.len();
-        match self.apply_built_resources(CLUSTER_TYPE_URL, built) {
-            Some(update) => {
-                for delta in &update.deltas {
-                    info!(
-                        phase = "cache_refresh",
-                        type_url = %delta.type_url,
-                        added = delta.added_or_updated.len(),
-                        removed = delta.removed.len(),
-                        version = update.version,
-                        total_resources,
-                        "Cache refresh produced delta"
-                    );
+        // Rows are read outside the cache lock, so a snapshot applied while
+        // they are captured is caught by the version check below.
+        let rows = if self.config.nack_policy == NackPolicy::Revert {
+            match rollback::capture_rows(self, type_url).await {
+                Ok(rows) => rows,
+                Err(error) => {
+                    warn!(%error, type_url, "Failed to capture repository rows for last-known-good snapshot");
+                    return;
                 }
             }
-            None => {
+        } else {
+            None
+        };
+
+        let resources = {
+            let caches = self.resource_caches.read().expect("resource cache lock poisoned");
+            let Some(cache) = caches.get(type_url) else {
+                return;
+            };
+            if self.get_type_version_number(type_url) != version {
+                return;
+            }
+            sorted_resources(cache)
+        };
+
+        let mut known_good = self.known_good.write().expect("known-good lock poisoned");
+        if known_good.get(type_url).is_some_and(|snapshot| snapshot.version >= version) {
+            return;
+        }
+        known_good.insert(type_url.to_string(), KnownGoodSnapshot { version, resources, rows });
+    }
+
+    /// Last snapshot of `type_url` that an Envoy node accepted.
+    pub fn known_good_snapshot(&self, type_url: &str) -> Option<KnownGoodSnapshot> {
+        let known_good = self.known_good.read().expect("known-good lock poisoned");
+        known_good.get(type_url).cloned()
+    }
+
+    /// React to `node_id` rejecting `version` of `type_url`: audit the Envoy
+    /// error, then apply the configured [`NackPolicy`].
+    pub async fn handle_nack(
+        &self,
+        node_id: &str,
+        type_url: &str,
+        version: Option<&str>,
+        error: &RpcStatus,
+    ) {
+        let policy = self.config.nack_policy;
+        let known_good = self.known_good_snapshot(type_url);
+
+        warn!(
+            node_id,
+            type_url,
+            version = ?version,
+            error_code = error.code,
+            error_message = %error.message,
+            policy = policy.as_str(),
+            known_good_version = ?known_good.as_ref().map(|snapshot| snapshot.version),
+            "Envoy rejected configuration"
+        );
+        self.record_audit(
+            "xds.nack",
+            node_id,
+            type_url,
+            serde_json::json!({
+                "node_id": node_id,
+                "type_url": type_url,
+                "version": version,
+                "error_code": error.code,
+                "error_message": error.message,
+                "policy": policy.as_str(),
+                "known_good_version": known_good.as_ref().map(|snapshot| snapshot.version),
+            }),
+        )
+        .await;
+
+        let baking_candidate = self
+            .rollouts
+            .write()
+            .expect("rollout lock poisoned")
+            .get_mut(type_url)
+            .filter(|rollout| rollout.phase == RolloutPhase::Baking)
+            .map(|rollout| {
+                rollout.nack_count += 1;
+                rollout.candidate_version
+            });
+        if let (Some(candidate), Some(repository)) = (baking_candidate, &self.rollout_repository) {
+            if let Err(error) =
+                repository.record_nack(version_key_for_type_url(type_url), candidate).await
+            {
+                warn!(%error, type_url, "Failed to share rollout NACK with other replicas");
+            }
+        }
+
+        let Some(known_good) = known_good else {
+            if policy != NackPolicy::Log {
+                warn!(type_url, "No last-known-good snapshot yet; NACK policy not applied");
+            }
+            return;
+        };
+
+        match policy {
+            NackPolicy::Log => {}
+            NackPolicy::Pin => {
+                let rejected = version
+                    .and_then(|version| version.parse().ok())
+                    .unwrap_or_else(|| self.get_type_version_number(type_url));
+                let mut pinned = self.pinned_nodes.write().expect("pinned node lock poisoned");
+                pinned.insert((node_id.to_string(), type_url.to_string()), rejected);
                 info!(
-                    phase = "cache_refresh",
-                    type_url = CLUSTER_TYPE_URL,
-                    total_resources,
-                    "Cache refresh detected no changes"
+                    node_id,
+                    type_url,
+                    version = known_good.version,
+                    "Pinned node to last-known-good snapshot"
                 );
             }
+            NackPolicy::Revert => {
+                let Some(rows) = &known_good.rows else {
+                    warn!(type_url, "Resource type is not repository backed; nothing to revert");
+                    return;
+                };
+                let changed =
+                    rollback::changed_since(&known_good, &self.cached_resources(type_url));
+                match rollback::revert_rows(self, rows, &changed).await {
+                    Ok(reverted) if !reverted.is_empty() => {
+                        info!(
+                            type_url,
+                            ?reverted,
+                            version = known_good.version,
+                            "Reverted rejected resources to last-known-good configuration"
+                        );
+                        self.record_audit(
+                            "xds.nack.revert",
+                            node_id,
+                            type_url,
+                            serde_json::json!({
+                                "node_id": node_id,
+                                "type_url": type_url,
+                                "rejected_version": version,
+                                "known_good_version": known_good.version,
+                                "reverted": reverted,
+                                "error_message": error.message,
+                            }),
+                        )
+                        .await;
+                    }
+                    Ok(_) => {
+                        warn!(
+                            type_url,
+                            "No repository changes found to revert for rejected configuration"
+                        );
+                    }
+                    Err(error) => {
+                        warn!(%error, type_url, "Failed to revert rejected configuration");
+                    }
+                }
+            }
         }
-        Ok(())
     }
 
-    /// Refresh the route cache from the backing repository (if available).
-    pub async fn refresh_routes_from_repository(&self) -> Result<()> {
-        let repository = match &self.route_repository {
-            Some(repo) => repo.clone(),
-            None => return Ok(()),
+    /// Snapshot to serve `node_id` for `type_url` while it is pinned.
+    ///
+    /// A pin lasts until a version newer than the rejected one is published.
+    pub fn pinned_snapshot(&self, node_id: &str, type_url: &str) -> Option<KnownGoodSnapshot> {
+        let key = (node_id.to_string(), type_url.to_string());
+        let rejected = {
+            let pinned = self.pinned_nodes.read().expect("pinned node lock poisoned");
+            *pinned.get(&key)?
         };
 
-        let route_rows = repository.list(Some(1000), None).await?;
+        if self.get_type_version_number(type_url) > rejected {
+            self.pinned_nodes.write().expect("pinned node lock poisoned").remove(&key);
+            return None;
+        }
+        self.known_good_snapshot(type_url)
+    }
+
+    /// Register a stream for `node`, classifying it against the canary selector.
+    pub fn connect_node(&self, node: &Node) -> String {
+        let canary = self
+            .config
+            .canary
+            .as_ref()
+            .is_some_and(|canary| metadata::matches_selector(node, &canary.selector));
+        self.node_registry.connect(node, canary)
+    }
 
-        let built = if route_rows.is_empty() {
-            routes_from_config(&self.config)?
-        } else {
-            routes_from_database_entries(route_rows, "cache_refresh")?
-        };
-
-        let total_resources = built.len();
-        match self.apply_built_resources(ROUTE_TYPE_URL, built) {
-            Some(update) => {
-                for delta in &update.deltas {
-                    info!(
-                        phase = "cache_refresh",
-                        type_url = %delta.type_url,
-                        added = delta.added_or_updated.len(),
-                        removed = delta.removed.len(),
-                        version = update.version,
-                        total_resources,
-                        "Route cache refresh produced delta"
-                    );
+    /// Snapshot `node_id` must be served for `type_url` instead of the current
+    /// one: its last-known-good snapshot while pinned after a NACK, or the
+    /// stable snapshot while a rollout holds it back.
+    pub fn held_snapshot(&self, node_id: &str, type_url: &str) -> Option<KnownGoodSnapshot> {
+        if let Some(snapshot) = self.pinned_snapshot(node_id, type_url) {
+            return Some(snapshot);
+        }
+        let rollouts = self.rollouts.read().expect("rollout lock poisoned");
+        let rollout = rollouts.get(type_url)?;
+        rollout.holds(self.node_registry.is_canary(node_id)).then(|| rollout.stable.clone())
+    }
+
+    /// Active rollouts, ordered by resource type.
+    pub fn rollouts(&self) -> Vec<RolloutStatus> {
+        let bake_time_secs = self.canary_bake_time_secs();
+        let rollouts = self.rollouts.read().expect("rollout lock poisoned");
+        let mut statuses: Vec<RolloutStatus> = rollouts
+            .iter()
+            .map(|(type_url, rollout)| {
+                rollout.status(type_url, version_key_for_type_url(type_url), bake_time_secs)
+            })
+            .collect();
+        statuses.sort_by(|a, b| a.resource_type.cmp(&b.resource_type));
+        statuses
+    }
+
+    /// Deliver the candidate for `resource_type` to every node.
+    pub fn promote_rollout(&self, resource_type: &str) -> Option<RolloutStatus> {
+        let status = self.promote_local_rollout(resource_type)?;
+        self.share_rollout_phase(&status, "promoted");
+        Some(status)
+    }
+
+    /// Withdraw the candidate for `resource_type`, returning canaries to the
+    /// stable snapshot.
+    pub fn abort_rollout(&self, resource_type: &str) -> Option<RolloutStatus> {
+        let status = self.abort_local_rollout(resource_type)?;
+        self.share_rollout_phase(&status, "aborted");
+        Some(status)
+    }
+
+    /// Apply the rollout decisions and NACK counts recorded by other replicas
+    /// to local rollouts of the same candidate version.
+    pub async fn sync_rollouts(&self) {
+        let Some(repository) = &self.rollout_repository else {
+            return;
+        };
+        let shared = match repository.list().await {
+            Ok(shared) => shared,
+            Err(error) => {
+                warn!(%error, "Failed to load rollouts shared by other replicas");
+                return;
+            }
+        };
+
+        for record in shared {
+            let type_url = type_url_for_version_key(&record.resource_type);
+            let baking = {
+                let mut rollouts = self.rollouts.write().expect("rollout lock poisoned");
+                let Some(rollout) = rollouts
+                    .get_mut(type_url)
+                    .filter(|rollout| rollout.candidate_version == record.candidate_version)
+                else {
+                    continue;
+                };
+                rollout.nack_count = rollout.nack_count.max(record.nack_count);
+                rollout.phase == RolloutPhase::Baking
+            };
+            match record.phase.as_str() {
+                "promoted" => {
+                    self.promote_local_rollout(&record.resource_type);
+                }
+                "aborted" if baking => {
+                    self.abort_local_rollout(&record.resource_type);
                 }
+                _ => {}
             }
-            None => {
-                info!(
-                    phase = "cache_refresh",
-                    type_url = ROUTE_TYPE_URL,
-                    total_resources,
-                    "Route cache refresh detected no changes"
-                );
+        }
+    }
+
+    /// Record a rollout decision for the other replicas.
+    fn share_rollout_phase(&self, status: &RolloutStatus, phase: &'static str) {
+        let (Some(repository), Ok(runtime)) =
+            (self.rollout_repository.clone(), tokio::runtime::Handle::try_current())
+        else {
+            return;
+        };
+        let resource_type = status.resource_type.clone();
+        let candidate_version = status.candidate_version;
+        runtime.spawn(async move {
+            if let Err(error) =
+                repository.record_phase(&resource_type, candidate_version, phase).await
+            {
+                warn!(%error, resource_type, phase, "Failed to share rollout decision");
             }
+        });
+    }
+
+    fn promote_local_rollout(&self, resource_type: &str) -> Option<RolloutStatus> {
+        let type_url = type_url_for_version_key(resource_type);
+        let rollout = self.rollouts.write().expect("rollout lock poisoned").remove(type_url)?;
+        info!(type_url, version = rollout.candidate_version, "Promoted canary rollout");
+        self.notify_type(type_url);
+        Some(rollout.status(type_url, resource_type, self.canary_bake_time_secs()))
+    }
+
+    fn abort_local_rollout(&self, resource_type: &str) -> Option<RolloutStatus> {
+        let type_url = type_url_for_version_key(resource_type);
+        let status = {
+            let mut rollouts = self.rollouts.write().expect("rollout lock poisoned");
+            let rollout = rollouts.get_mut(type_url)?;
+            rollout.phase = RolloutPhase::Aborted;
+            rollout.status(type_url, resource_type, self.canary_bake_time_secs())
+        };
+        warn!(type_url, version = status.candidate_version, "Aborted canary rollout");
+        self.notify_type(type_url);
+        Some(status)
+    }
+
+    /// Promote every rollout that has baked without a NACK.
+    pub fn promote_baked_rollouts(&self) {
+        let bake_time_secs = self.canary_bake_time_secs();
+        let now = chrono::Utc::now();
+        let baked: Vec<String> = {
+            let rollouts = self.rollouts.read().expect("rollout lock poisoned");
+            rollouts
+                .iter()
+                .filter(|(_, rollout)| rollout.is_baked(bake_time_secs, now))
+                .map(|(type_url, _)| type_url.clone())
+                .collect()
+        };
+        for type_url in baked {
+            self.promote_rollout(version_key_for_type_url(&type_url));
         }
+    }
 
-        Ok(())
+    fn canary_bake_time_secs(&self) -> u64 {
+        self.config.canary.as_ref().map(|canary| canary.bake_time_secs).unwrap_or_default()
     }
 
-    pub async fn refresh_platform_api_resources(&self) -> Result<()> {
-        let repository = match &self.api_definition_repository {
-            Some(repo) => repo.clone(),
-            None => return Ok(()),
+    /// Wake every stream subscribed to `type_url` so held nodes are re-evaluated.
+    fn notify_type(&self, type_url: &str) {
+        let update = ResourceUpdate {
+            version: self.get_type_version_number(type_url),
+            deltas: vec![ResourceDelta { type_url: type_url.to_string(), ..Default::default() }],
+        };
+        self.publisher.publish(update);
+    }
+
+    async fn record_audit(
+        &self,
+        action: &str,
+        node_id: &str,
+        type_url: &str,
+        metadata: serde_json::Value,
+    ) {
+        let Some(repository) = &self.audit_repository else {
+            return;
         };
+        let event = AuditEvent::xds(action, node_id, type_url, metadata);
+        if let Err(error) = repository.record_xds_event(event).await {
+            warn!(%error, action, "Failed to record xDS audit event");
+        }
+    }
 
-        let definitions = repository.list_definitions().await?;
-        let routes = repository.list_all_routes().await?;
+    /// Seed per-type versions from `configuration_versions` so they keep
+    /// increasing across restarts. Runs once, before the first snapshot is applied.
+    async fn ensure_versions_loaded(&self) {
+        self.versions_loaded
+            .get_or_init(|| async {
+                let Some(repository) = &self.version_repository else {
+                    return;
+                };
 
-        if definitions.is_empty() {
-            return Ok(());
+                match repository.list().await {
+                    Ok(rows) => {
+                        let mut versions =
+                            self.type_versions.write().expect("type version lock poisoned");
+                        for row in rows {
+                            let entry = versions
+                                .entry(type_url_for_version_key(&row.resource_type).to_string())
+                                .or_default();
+                            if row.current_version >= entry.version {
+                                entry.version = row.current_version;
+                                entry.content_hash = row.content_hash;
+                            }
+                            self.version.fetch_max(
+                                row.current_version,
+                                std::sync::atomic::Ordering::Relaxed,
+                            );
+                        }
+                    }
+                    Err(error) => {
+                        warn!(%error, "Failed to load persisted xDS versions; starting from defaults");
+                    }
+                }
+            })
+            .await;
+    }
+
+    async fn persist_type_version(&self, type_url: &str) {
+        self.cache_writer().persist_type_version(type_url).await;
+    }
+
+    /// Version of `built` claimed in `configuration_versions` when replicas
+    /// coordinate, so that all of them publish it under the same version.
+    async fn claim_version(&self, type_url: &str, built: &[BuiltResource]) -> Result<Option<u64>> {
+        let Some(repository) = &self.version_repository else {
+            return Ok(None);
+        };
+        if !self.leader.is_coordinated() {
+            return Ok(None);
         }
 
-        let built = resources_from_api_definitions(definitions, routes)?;
-        if built.is_empty() {
-            return Ok(());
+        let content_hash = snapshot_hash(
+            built.iter().map(|resource| (resource.name.as_str(), &resource.resource)),
+        );
+        repository
+            .claim(version_key_for_type_url(type_url), &content_hash, INITIAL_TYPE_VERSION + 1)
+            .await
+            .map(Some)
+    }
+
+    /// Apply a snapshot for `type_url`, log the outcome and persist the new version.
+    async fn publish_snapshot(&self, type_url: &str, built: Vec<BuiltResource>) {
+        self.ensure_versions_loaded().await;
+
+        let claimed_version = match self.claim_version(type_url, &built).await {
+            Ok(version) => version,
+            Err(error) => {
+                warn!(%error, type_url, "Failed to claim xDS version; keeping the current snapshot");
+                return;
+            }
+        };
+
+        let applied = self.apply_snapshot(type_url, built, claimed_version);
+        if applied.is_some() && claimed_version.is_none() {
+            self.persist_type_version(type_url).await;
         }
+    }
 
-        let route_resources: Vec<_> =
-            built.iter().filter(|res| res.type_url() == ROUTE_TYPE_URL).cloned().collect();
-        if !route_resources.is_empty() {
-            self.apply_built_resources(ROUTE_TYPE_URL, route_resources);
+    /// Rebuild the caches affected by a batch of change events.
+    ///
+    /// Only the cluster, route and listener rows and the API definitions
+    /// named by the events are re-encoded; secrets and runtime are rebuilt
+    /// as whole types.
+    pub async fn refresh_for_changes(&self, changes: &HashSet<Change>) {
+        if changes.iter().any(|change| change.kind == ChangeKind::Secrets) {
+            if let Err(error) = self.refresh_secrets_from_repository().await {
+                warn!(%error, "Failed to refresh secret cache from repository");
+            }
+        }
+        if changes.iter().any(|change| change.kind == ChangeKind::Runtime) {
+            if let Err(error) = self.refresh_runtime_from_repository().await {
+                warn!(%error, "Failed to refresh runtime cache from repository");
+            }
         }
 
-        let listener_resources: Vec<_> =
-            built.iter().filter(|res| res.type_url() == LISTENER_TYPE_URL).cloned().collect();
-        if !listener_resources.is_empty() {
-            self.apply_built_resources(LISTENER_TYPE_URL, listener_resources);
+        let mut affected = HashSet::new();
+        for (kind, keys) in changed_rows(changes) {
+            if !INDEXED_TABLES.contains(&kind) {
+                continue;
+            }
+            match self.reindex(kind, keys.as_ref()).await {
+                Ok(types) => affected.extend(types),
+                Err(error) => warn!(%error, table = %kind, "Failed to re-encode changed resources"),
+            }
         }
+        self.publish_indexed(affected).await;
+    }
 
-        let cluster_resources: Vec<_> =
-            built.iter().filter(|res| res.type_url() == CLUSTER_TYPE_URL).cloned().collect();
-        if !cluster_resources.is_empty() {
-            self.apply_built_resources(CLUSTER_TYPE_URL, cluster_resources);
+    /// Re-encode the rows named by `changes` after a write and republish the
+    /// types they affect. Unlike [`XdsState::refresh_for_changes`], the first
+    /// failure is returned.
+    pub async fn refresh_changes(&self, changes: &[Change]) -> Result<()> {
+        let rows = changed_rows(changes);
+        if rows.contains_key(&ChangeKind::Secrets) {
+            self.refresh_secrets_from_repository().await?;
+        }
+        if rows.contains_key(&ChangeKind::Runtime) {
+            self.refresh_runtime_from_repository().await?;
         }
 
+        let mut affected = HashSet::new();
+        for (kind, keys) in rows {
+            if INDEXED_TABLES.contains(&kind) {
+                affected.extend(self.reindex(kind, keys.as_ref()).await?);
+            }
+        }
+        self.publish_indexed(affected).await;
         Ok(())
     }
 
+    /// Re-encode a single row after a write: a cluster, route, listener,
+    /// secret or runtime key by name, or an API definition by ID.
+    pub async fn refresh_row(&self, kind: ChangeKind, key: &str) -> Result<()> {
+        self.refresh_changes(&[Change::row(kind, key)]).await
+    }
+
+    /// Refresh the cluster cache from the backing repository (if available).
+    pub async fn refresh_clusters_from_repository(&self) -> Result<()> {
+        self.refresh_table(ChangeKind::Clusters).await
+    }
+
+    /// Refresh the route cache from the backing repository (if available).
+    pub async fn refresh_routes_from_repository(&self) -> Result<()> {
+        self.refresh_table(ChangeKind::Routes).await
+    }
+
     /// Refresh the listener cache from the backing repository (if available).
     pub async fn refresh_listeners_from_repository(&self) -> Result<()> {
-        let repository = match &self.listener_repository {
-            Some(repo) => repo.clone(),
-            None => return Ok(()),
+        self.refresh_table(ChangeKind::Listeners).await
+    }
+
+    /// Refresh the SDS secret cache from the backing repository (if available).
+    ///
+    /// Secrets are published as their own type, so rotating one pushes the
+    /// new certificate without touching the listeners and clusters using it.
+    pub async fn refresh_secrets_from_repository(&self) -> Result<()> {
+        let built = self.secret_resources("cache_refresh").await?;
+        self.publish_snapshot(SECRET_TYPE_URL, built).await;
+        Ok(())
+    }
+
+    /// Build SDS secrets from the repository, decrypting their private keys.
+    /// Empty without a repository or secret cipher. Includes the built-in CA
+    /// trust bundle when the CA is enabled.
+    pub async fn secret_resources(&self, context: &str) -> Result<Vec<BuiltResource>> {
+        let (Some(repository), Some(cipher)) =
+            (&self.secret_repository, &self.config.secret_cipher)
+        else {
+            return Ok(Vec::new());
+        };
+
+        let rows = repository.list(Some(SNAPSHOT_ROW_LIMIT), None).await?;
+        let mut built = secrets_from_database_entries(rows, cipher, context)?;
+        if let Some(ca) = self.certificate_authority().await? {
+            built.push(trust_bundle_secret(
+                CA_TRUST_BUNDLE_SECRET,
+                ca.certificate_pem().to_string(),
+            ));
+        }
+        Ok(built)
+    }
+
+    /// Refresh the RTDS runtime layer from the backing repository (if available).
+    pub async fn refresh_runtime_from_repository(&self) -> Result<()> {
+        if self.runtime_repository.is_none() {
+            return Ok(());
+        }
+        let built = self.runtime_resources("cache_refresh").await?;
+        self.publish_snapshot(RUNTIME_TYPE_URL, built).await;
+        Ok(())
+    }
+
+    /// Build the RTDS runtime layer from the repository. Empty without a
+    /// repository.
+    pub async fn runtime_resources(&self, context: &str) -> Result<Vec<BuiltResource>> {
+        let Some(repository) = &self.runtime_repository else {
+            return Ok(Vec::new());
         };
 
-        let listener_rows = repository.list(Some(1000), None).await?;
+        let rows = repository.list(Some(SNAPSHOT_ROW_LIMIT), None).await?;
+        runtime_layer_from_database_entries(rows, context)
+    }
 
-        let built = if listener_rows.is_empty() {
-            listeners_from_config(&self.config)?
-        } else {
-            listeners_from_database_entries(listener_rows, "cache_refresh")?
-        };
-
-        let total_resources = built.len();
-        match self.apply_built_resources(LISTENER_TYPE_URL, built) {
-            Some(update) => {
-                for delta in &update.deltas {
-                    info!(
-                        phase = "cache_refresh",
-                        type_url = %delta.type_url,
-                        added = delta.added_or_updated.len(),
-                        removed = delta.removed.len(),
-                        version = update.version,
-                        total_resources,
-                        "Listener cache refresh produced delta"
-                    );
+    /// Built-in CA, loaded from the repository or created on first use.
+    /// `None` unless the CA is configured and a database is available.
+    pub async fn certificate_authority(&self) -> Result<Option<Arc<CertificateAuthority>>> {
+        let (Some(ca_config), Some(cipher), Some(repository)) =
+            (&self.config.ca, &self.config.secret_cipher, &self.certificate_authority_repository)
+        else {
+            return Ok(None);
+        };
+
+        let ca = self
+            .certificate_authority
+            .get_or_try_init(|| async {
+                let trust_domain = ca_config.trust_domain.as_str();
+                let stored = match repository.find_by_trust_domain(trust_domain).await? {
+                    Some(stored) => stored,
+                    None => {
+                        let generated =
+                            CertificateAuthority::generate(trust_domain, chrono::Utc::now())?;
+                        let request = CreateCertificateAuthorityRequest {
+                            trust_domain: trust_domain.to_string(),
+                            certificate: generated.certificate_pem().to_string(),
+                            encrypted_private_key: cipher.encrypt(&generated.private_key_pem())?,
+                            expires_at: generated.expires_at(),
+                        };
+                        // Another replica may have won the race; sign with whichever CA was stored.
+                        let stored = repository.create_if_absent(request).await?;
+                        info!(trust_domain, expires_at = %stored.expires_at, "Created built-in CA");
+                        stored
+                    }
+                };
+
+                let private_key = cipher.decrypt(&stored.encrypted_private_key)?;
+                CertificateAuthority::from_pem(
+                    trust_domain,
+                    stored.certificate,
+                    &private_key,
+                    stored.expires_at,
+                )
+                .map(Arc::new)
+            })
+            .await?;
+
+        Ok(Some(ca.clone()))
+    }
+
+    /// Issue (or re-issue) the leaf certificate of `subject`, stored as the
+    /// SDS secret named after its SPIFFE ID.
+    pub async fn issue_certificate(&self, subject: &CertificateSubject) -> Result<SecretData> {
+        let ca = self
+            .certificate_authority()
+            .await?
+            .ok_or_else(|| Error::config("The built-in CA is not enabled"))?;
+        let ca_config = self.config.ca.as_ref().expect("CA is configured when loaded");
+
+        let spiffe_id = subject.spiffe_id(ca.trust_domain())?;
+        let issued = ca.issue(&spiffe_id, ca_config.leaf_ttl_secs, chrono::Utc::now())?;
+        self.store_issued_certificate(issued).await
+    }
+
+    /// Re-issue CA certificates with less than a third of their lifetime
+    /// left. Returns the number of renewed certificates.
+    ///
+    /// A certificate that fails to renew is logged and skipped so the others
+    /// are still renewed; the pass then returns an error counting failures.
+    pub async fn renew_expiring_certificates(&self) -> Result<usize> {
+        let (Some(ca), Some(ca_config), Some(repository)) =
+            (self.certificate_authority().await?, &self.config.ca, &self.secret_repository)
+        else {
+            return Ok(0);
+        };
+
+        let now = chrono::Utc::now();
+        let window = chrono::Duration::seconds((ca_config.leaf_ttl_secs / 3) as i64);
+        let expiring = repository.list_issued_expiring_before(now + window).await?;
+
+        let mut renewed = 0;
+        let mut failed = 0;
+        for secret in expiring {
+            let Some(spiffe_id) = secret.spiffe_id.as_deref() else {
+                continue;
+            };
+            if !ca.owns(spiffe_id) {
+                warn!(
+                    secret_name = %secret.name,
+                    trust_domain = ca.trust_domain(),
+                    "Skipping renewal of certificate from another trust domain"
+                );
+                continue;
+            }
+
+            let stored = match ca.issue(spiffe_id, ca_config.leaf_ttl_secs, now) {
+                Ok(issued) => self.store_issued_certificate(issued).await,
+                Err(error) => Err(error),
+            };
+            match stored {
+                Ok(_) => renewed += 1,
+                Err(error) => {
+                    warn!(%error, secret_name = %secret.name, "Failed to renew certificate");
+                    failed += 1;
                 }
             }
-            None => {
-                info!(
-                    phase = "cache_refresh",
-                    type_url = LISTENER_TYPE_URL,
-                    total_resources,
-                    "Listener cache refresh detected no changes"
-                );
+        }
+
+        if renewed > 0 {
+            info!(renewed, "Renewed certificates issued by the built-in CA");
+        }
+        if failed > 0 {
+            return Err(Error::internal(format!(
+                "Failed to renew {} of {} expiring certificates",
+                failed,
+                renewed + failed
+            )));
+        }
+        Ok(renewed)
+    }
+
+    async fn store_issued_certificate(&self, issued: IssuedCertificate) -> Result<SecretData> {
+        let (Some(repository), Some(cipher)) =
+            (&self.secret_repository, &self.config.secret_cipher)
+        else {
+            return Err(Error::config("Secret storage is not configured"));
+        };
+        let encrypted_private_key = cipher.encrypt(&issued.private_key_pem)?;
+
+        match repository.get_by_name(&issued.spiffe_id).await {
+            Ok(_) => {
+                repository
+                    .update(
+                        &issued.spiffe_id,
+                        UpdateSecretRequest {
+                            certificate_chain: issued.certificate_pem,
+                            encrypted_private_key,
+                            expires_at: Some(issued.expires_at),
+                        },
+                    )
+                    .await
             }
+            Err(Error::NotFound(_)) => {
+                repository
+                    .create(CreateSecretRequest {
+                        name: issued.spiffe_id.clone(),
+                        certificate_chain: issued.certificate_pem,
+                        encrypted_private_key,
+                        spiffe_id: Some(issued.spiffe_id),
+                        expires_at: Some(issued.expires_at),
+                    })
+                    .await
+            }
+            Err(error) => Err(error),
         }
+    }
 
+    /// Refresh every cache that carries Platform API resources.
+    pub async fn refresh_platform_api_resources(&self) -> Result<()> {
+        self.refresh_table(ChangeKind::ApiDefinitions).await
+    }
+
+    /// Re-encode every row of `kind` and republish the types it affects.
+    async fn refresh_table(&self, kind: ChangeKind) -> Result<()> {
+        let affected = self.reindex(kind, None).await?;
+        self.publish_indexed(affected).await;
         Ok(())
     }
+
+    /// Re-encode the rows of `kind` named by `keys`, or every row when `keys`
+    /// is `None` or the table has not been loaded yet, and return the types
+    /// whose snapshots changed. A no-op without a repository for `kind`.
+    async fn reindex(
+        &self,
+        kind: ChangeKind,
+        keys: Option<&HashSet<String>>,
+    ) -> Result<HashSet<String>> {
+        let loaded =
+            self.resource_index.read().expect("resource index lock poisoned").is_loaded(kind);
+        let Some(keys) = keys.filter(|_| loaded) else {
+            let Some(rows) = self.encode_table(kind).await? else {
+                return Ok(HashSet::new());
+            };
+            let mut index = self.resource_index.write().expect("resource index lock poisoned");
+            return Ok(index.replace_table(kind, rows));
+        };
+
+        let mut affected = HashSet::new();
+        for key in keys {
+            let built = self.encode_row(kind, key).await?;
+            let mut index = self.resource_index.write().expect("resource index lock poisoned");
+            affected.extend(index.replace_row(kind, key, built));
+        }
+        Ok(affected)
+    }
+
+    /// Encode every row of `kind`, keyed like [`XdsState::encode_row`].
+    /// `None` without a repository for `kind`.
+    async fn encode_table(
+        &self,
+        kind: ChangeKind,
+    ) -> Result<Option<BTreeMap<String, Vec<BuiltResource>>>> {
+        let mut rows = BTreeMap::new();
+        match kind {
+            ChangeKind::Clusters => {
+                let Some(repository) = &self.cluster_repository else {
+                    return Ok(None);
+                };
+                for row in repository.list(Some(SNAPSHOT_ROW_LIMIT), None).await? {
+                    rows.insert(row.name.clone(), encode_cluster(row)?);
+                }
+            }
+            ChangeKind::Routes => {
+                let Some(repository) = &self.route_repository else {
+                    return Ok(None);
+                };
+                for row in repository.list(Some(SNAPSHOT_ROW_LIMIT), None).await? {
+                    rows.insert(
+                        row.name.clone(),
+                        routes_from_database_entries(vec![row], "cache_refresh")?,
+                    );
+                }
+            }
+            ChangeKind::Listeners => {
+                let Some(repository) = &self.listener_repository else {
+                    return Ok(None);
+                };
+                for row in repository.list(Some(SNAPSHOT_ROW_LIMIT), None).await? {
+                    rows.insert(
+                        row.name.clone(),
+                        listeners_from_database_entries(vec![row], "cache_refresh")?,
+                    );
+                }
+            }
+            ChangeKind::ApiDefinitions => {
+                let Some(repository) = &self.api_definition_repository else {
+                    return Ok(None);
+                };
+                let mut routes_by_definition: HashMap<String, Vec<ApiRouteData>> = HashMap::new();
+                for route in repository.list_all_routes().await? {
+                    routes_by_definition
+                        .entry(route.api_definition_id.clone())
+                        .or_default()
+                        .push(route);
+                }
+                for definition in repository.list_definitions().await? {
+                    let routes = routes_by_definition.remove(&definition.id).unwrap_or_default();
+                    let id = definition.id.clone();
+                    rows.insert(id, encode_api_definition(definition, routes)?);
+                }
+            }
+            ChangeKind::Secrets | ChangeKind::Runtime => return Ok(None),
+        }
+        Ok(Some(rows))
+    }
+
+    /// Encode the row of `kind` under `key`: the name of a cluster, route or
+    /// listener, or the ID of an API definition. `None` once the row is gone.
+    async fn encode_row(&self, kind: ChangeKind, key: &str) -> Result<Option<Vec<BuiltResource>>> {
+        let built = match kind {
+            ChangeKind::Clusters => match &self.cluster_repository {
+                Some(repository) => found(repository.get_by_name(key).await)?.map(encode_cluster),
+                None => None,
+            },
+            ChangeKind::Routes => match &self.route_repository {
+                Some(repository) => found(repository.get_by_name(key).await)?
+                    .map(|row| routes_from_database_entries(vec![row], "cache_refresh")),
+                None => None,
+            },
+            ChangeKind::Listeners => match &self.listener_repository {
+                Some(repository) => found(repository.get_by_name(key).await)?
+                    .map(|row| listeners_from_database_entries(vec![row], "cache_refresh")),
+                None => None,
+            },
+            ChangeKind::ApiDefinitions => match &self.api_definition_repository {
+                Some(repository) => match found(repository.get_definition(key).await)? {
+                    Some(definition) => {
+                        let routes = repository.list_routes(key).await?;
+                        Some(encode_api_definition(definition, routes))
+                    }
+                    None => None,
+                },
+                None => None,
+            },
+            ChangeKind::Secrets | ChangeKind::Runtime => None,
+        };
+        built.transpose()
+    }
+
+    /// Republish the snapshots of `type_urls` from the resource index,
+    /// loading any contributing table that has not been indexed yet.
+    async fn publish_indexed(&self, mut type_urls: HashSet<String>) {
+        for type_url in INDEXED_TYPES {
+            if !type_urls.contains(type_url) {
+                continue;
+            }
+            for kind in tables_for_type(type_url) {
+                let loaded = self
+                    .resource_index
+                    .read()
+                    .expect("resource index lock poisoned")
+                    .is_loaded(*kind);
+                if loaded {
+                    continue;
+                }
+                match self.reindex(*kind, None).await {
+                    Ok(types) => type_urls.extend(types),
+                    Err(error) => {
+                        warn!(%error, table = %kind, "Failed to load resources; publishing without them")
+                    }
+                }
+            }
+        }
+
+        for type_url in INDEXED_TYPES {
+            if !type_urls.contains(type_url) {
+                continue;
+            }
+            match self.indexed_snapshot(type_url) {
+                Ok(built) => self.publish_snapshot(type_url, built).await,
+                Err(error) => warn!(%error, type_url, "Failed to assemble snapshot"),
+            }
+        }
+    }
+
+    /// Snapshot of `type_url` assembled from the resource index, using the
+    /// static configuration for tables without rows. Platform API route
+    /// configurations are merged into the default gateway routes.
+    fn indexed_snapshot(&self, type_url: &str) -> Result<Vec<BuiltResource>> {
+        let index = self.resource_index.read().expect("resource index lock poisoned");
+        let mut built = match tables_for_type(type_url).first() {
+            Some(kind) if index.is_empty(*kind) => match type_url {
+                CLUSTER_TYPE_URL => clusters_from_config(&self.config)?,
+                ENDPOINT_TYPE_URL => endpoints_from_config(&self.config)?,
+                ROUTE_TYPE_URL => routes_from_config(&self.config)?,
+                LISTENER_TYPE_URL => listeners_from_config(&self.config)?,
+                _ => Vec::new(),
+            },
+            _ => Vec::new(),
+        };
+        if type_url == ROUTE_TYPE_URL {
+            built.extend(index.table_resources(ChangeKind::Routes, type_url));
+            merge_platform_routes(
+                &mut built,
+                index.table_resources(ChangeKind::ApiDefinitions, type_url),
+            )?;
+        } else {
+            built.extend(index.resources(type_url));
+        }
+        Ok(built)
+    }
+}
+
+/// Resources an API definition contributes: its clusters, plus its route
+/// configuration unless it is isolated, in which case its listener row
+/// carries the routes inline. Platform listeners are not served, so they
+/// cannot take ports from the gateway listeners.
+fn encode_api_definition(
+    definition: ApiDefinitionData,
+    routes: Vec<ApiRouteData>,
+) -> Result<Vec<BuiltResource>> {
+    let isolated = definition.listener_isolation;
+    let mut built = resources_from_api_definitions(vec![definition], routes)?;
+    built.retain(|resource| match resource.type_url() {
+        CLUSTER_TYPE_URL => true,
+        ROUTE_TYPE_URL => !isolated,
+        _ => false,
+    });
+    Ok(built)
+}
+
+/// Cluster and, for EDS clusters, endpoint resources of a cluster row.
+fn encode_cluster(row: ClusterData) -> Result<Vec<BuiltResource>> {
+    let mut built = clusters_from_database_entries(vec![row.clone()], "cache_refresh")?;
+    built.extend(endpoints_from_database_entries(vec![row], "cache_refresh")?);
+    Ok(built)
+}
+
+/// `None` for rows deleted since their change was published.
+fn found<T>(result: Result<T>) -> Result<Option<T>> {
+    match result {
+        Ok(row) => Ok(Some(row)),
+        Err(Error::NotFound(_)) => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+/// Digest of a snapshot given as `(name, body)` pairs. Later pairs replace
+/// earlier ones with the same name, as when they are applied to a cache.
+fn snapshot_hash<'a>(resources: impl IntoIterator<Item = (&'a str, &'a Any)>) -> String {
+    let snapshot: std::collections::BTreeMap<&str, &Any> = resources.into_iter().collect();
+
+    let mut context = digest::Context::new(&digest::SHA256);
+    for (name, body) in snapshot {
+        context.update(name.as_bytes());
+        context.update(&[0]);
+        context.update(body.type_url.as_bytes());
+        context.update(&[0]);
+        context.update(&(body.value.len() as u64).to_be_bytes());
+        context.update(&body.value);
+    }
+
+    context.finish().as_ref().iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn sorted_resources(cache: &HashMap<String, CachedResource>) -> Vec<CachedResource> {
+    let mut resources: Vec<CachedResource> = cache.values().cloned().collect();
+    resources.sort_by(|a, b| a.name.cmp(&b.name));
+    resources
+}
+
+/// Key used for a type URL in `configuration_versions`.
+fn version_key_for_type_url(type_url: &str) -> &str {
+    match type_url {
+        CLUSTER_TYPE_URL => "cluster",
+        ROUTE_TYPE_URL => "route",
+        LISTENER_TYPE_URL => "listener",
+        ENDPOINT_TYPE_URL => "endpoint",
+        SECRET_TYPE_URL => "secret",
+        RUNTIME_TYPE_URL => "runtime",
+        other => other,
+    }
+}
+
+fn type_url_for_version_key(key: &str) -> &str {
+    match key {
+        "cluster" => CLUSTER_TYPE_URL,
+        "route" => ROUTE_TYPE_URL,
+        "listener" => LISTENER_TYPE_URL,
+        "endpoint" => ENDPOINT_TYPE_URL,
+        "secret" => SECRET_TYPE_URL,
+        "runtime" => RUNTIME_TYPE_URL,
+        other => other,
+    }
 }
 
 #[cfg(test)]
@@ -408,6 +1619,74 @@ mod tests {
         assert_eq!(update.deltas[0].removed, vec!["cluster-1".to_string()]);
     }
 
+    #[tokio::test]
+    async fn debounced_snapshots_reach_the_caches_when_the_window_closes() {
+        let config =
+            SimpleXdsConfig { update_debounce_ms: Some(50), ..build_state().config.clone() };
+        let state = XdsState::new(config);
+        let mut receiver = state.subscribe_updates();
+
+        assert!(state
+            .apply_built_resources(CLUSTER_TYPE_URL, vec![fake_resource("cluster-1", b"payload")])
+            .is_none());
+        let listener = BuiltResource {
+            name: "listener-1".to_string(),
+            resource: Any { type_url: LISTENER_TYPE_URL.to_string(), value: b"listener".to_vec() },
+        };
+        assert!(state.apply_built_resources(LISTENER_TYPE_URL, vec![listener]).is_none());
+
+        assert!(state.cached_resources(CLUSTER_TYPE_URL).is_empty());
+        assert!(state.cached_resources(LISTENER_TYPE_URL).is_empty());
+        assert_eq!(state.get_type_version_number(CLUSTER_TYPE_URL), INITIAL_TYPE_VERSION);
+
+        let update = receiver.recv().await.expect("coalesced update");
+        let types: Vec<&str> = update.deltas.iter().map(|delta| delta.type_url.as_str()).collect();
+        assert_eq!(types, vec![CLUSTER_TYPE_URL, LISTENER_TYPE_URL]);
+        assert_eq!(state.cached_resources(CLUSTER_TYPE_URL).len(), 1);
+        assert_eq!(state.cached_resources(LISTENER_TYPE_URL).len(), 1);
+        assert_eq!(state.get_type_version_number(CLUSTER_TYPE_URL), INITIAL_TYPE_VERSION + 1);
+    }
+
+    #[test]
+    fn resource_versions_follow_content_only() {
+        let cached = |version: u64, payload: &[u8]| {
+            CachedResource::new(
+                "cluster-1".to_string(),
+                CLUSTER_TYPE_URL.to_string(),
+                version,
+                fake_resource("cluster-1", payload).resource,
+            )
+        };
+
+        assert_eq!(cached(2, b"a").resource_version(), cached(7, b"a").resource_version());
+        assert_ne!(cached(2, b"a").resource_version(), cached(2, b"b").resource_version());
+    }
+
+    #[test]
+    fn type_snapshot_pairs_the_version_with_its_resources() {
+        let state = build_state();
+
+        // Every apply changes the payload, so the cached resource carries the
+        // type version it was stored under.
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                for round in 0..200u32 {
+                    let payload = round.to_be_bytes();
+                    let _ = state.apply_built_resources(
+                        CLUSTER_TYPE_URL,
+                        vec![fake_resource("cluster-1", &payload)],
+                    );
+                }
+            });
+            for _ in 0..200 {
+                let (version, resources) = state.type_snapshot(CLUSTER_TYPE_URL);
+                for resource in resources {
+                    assert_eq!(resource.version, version);
+                }
+            }
+        });
+    }
+
     #[tokio::test]
     async fn broadcast_updates_to_multiple_subscribers() {
         let state = build_state();
@@ -430,4 +1709,430 @@ mod tests {
         let state = build_state();
         assert!(state.refresh_listeners_from_repository().await.is_ok());
     }
+
+    #[test]
+    fn type_versions_advance_independently() {
+        let state = build_state();
+
+        state.apply_built_resources(CLUSTER_TYPE_URL, vec![fake_resource("cluster-1", b"a")]);
+        state.apply_built_resources(CLUSTER_TYPE_URL, vec![fake_resource("cluster-1", b"b")]);
+        assert_eq!(state.get_type_version_number(CLUSTER_TYPE_URL), 3);
+        assert_eq!(state.get_type_version_number(ROUTE_TYPE_URL), 1);
+
+        let route = BuiltResource {
+            name: "route-1".to_string(),
+            resource: Any { type_url: ROUTE_TYPE_URL.to_string(), value: b"r".to_vec() },
+        };
+        let update = state.apply_built_resources(ROUTE_TYPE_URL, vec![route]).expect("update");
+        assert_eq!(update.version, 2);
+        assert_eq!(state.get_type_version_number(CLUSTER_TYPE_URL), 3);
+        assert_eq!(state.get_version_number(), 3);
+    }
+
+    #[tokio::test]
+    async fn persisted_versions_survive_restart_without_pushes() {
+        let pool: DbPool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap()
+            .into();
+        crate::storage::run_migrations(&pool).await.unwrap();
+
+        let first = XdsState::with_database(build_state().config, pool.clone());
+        first.refresh_clusters_from_repository().await.unwrap();
+        let cluster_version = first.get_type_version_number(CLUSTER_TYPE_URL);
+        assert_eq!(cluster_version, 2);
+
+        let restarted = XdsState::with_database(build_state().config, pool);
+        let mut updates = restarted.subscribe_updates();
+        restarted.refresh_clusters_from_repository().await.unwrap();
+
+        assert_eq!(restarted.get_type_version_number(CLUSTER_TYPE_URL), cluster_version);
+        assert_eq!(restarted.cached_resources(CLUSTER_TYPE_URL).len(), 1);
+        assert!(updates.try_recv().is_err(), "unchanged snapshot must not be pushed");
+    }
+
+    #[tokio::test]
+    async fn row_changes_re_encode_only_the_named_rows() {
+        let pool: DbPool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap()
+            .into();
+        crate::storage::run_migrations(&pool).await.unwrap();
+        let state = XdsState::with_database(build_state().config, pool.clone());
+        let repository = state.cluster_repository.clone().unwrap();
+        for name in ["orders", "billing"] {
+            repository
+                .create(crate::storage::CreateClusterRequest {
+                    name: name.to_string(),
+                    service_name: name.to_string(),
+                    configuration: serde_json::json!({
+                        "endpoints": [{"host": format!("{}.internal", name), "port": 8080}]
+                    }),
+                })
+                .await
+                .unwrap();
+        }
+        state.refresh_clusters_from_repository().await.unwrap();
+        assert_eq!(state.encoded_rows(), 2);
+        let billing = state
+            .cached_resources(CLUSTER_TYPE_URL)
+            .into_iter()
+            .find(|cached| cached.name == "billing")
+            .unwrap();
+
+        let orders = repository.get_by_name("orders").await.unwrap();
+        repository
+            .update(
+                &orders.id,
+                crate::storage::UpdateClusterRequest {
+                    service_name: None,
+                    configuration: Some(serde_json::json!({
+                        "endpoints": [{"host": "orders-v2.internal", "port": 8080}]
+                    })),
+                },
+            )
+            .await
+            .unwrap();
+        state.refresh_row(ChangeKind::Clusters, "orders").await.unwrap();
+
+        // Only the named row is encoded again; the other keeps its resource.
+        assert_eq!(state.encoded_rows(), 3);
+        let cached = state.cached_resources(CLUSTER_TYPE_URL);
+        assert_eq!(cached.len(), 2);
+        let unchanged = cached.iter().find(|c| c.name == "billing").unwrap();
+        assert_eq!((unchanged.version, &unchanged.body), (billing.version, &billing.body));
+        let updated = cached.iter().find(|c| c.name == "orders").unwrap();
+        assert!(String::from_utf8_lossy(&updated.body.value).contains("orders-v2.internal"));
+
+        repository.delete(&orders.id).await.unwrap();
+        state.refresh_row(ChangeKind::Clusters, "orders").await.unwrap();
+        let names: Vec<String> =
+            state.cached_resources(CLUSTER_TYPE_URL).into_iter().map(|c| c.name).collect();
+        assert_eq!(names, vec!["billing"]);
+    }
+
+    #[tokio::test]
+    async fn platform_routes_merge_into_default_gateway_routes() {
+        use crate::openapi::defaults::{ensure_default_gateway_resources, DEFAULT_GATEWAY_ROUTES};
+        use crate::storage::{CreateApiDefinitionRequest, CreateApiRouteRequest};
+        use envoy_types::pb::envoy::config::route::v3::RouteConfiguration;
+        use prost::Message;
+
+        let pool: DbPool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap()
+            .into();
+        crate::storage::run_migrations(&pool).await.unwrap();
+        let state = XdsState::with_database(build_state().config, pool);
+        ensure_default_gateway_resources(&state).await.unwrap();
+
+        let definitions = state.api_definition_repository.clone().unwrap();
+        for (domain, listener_isolation) in
+            [("shared.example.com", false), ("isolated.example.com", true)]
+        {
+            let definition = definitions
+                .create_definition(CreateApiDefinitionRequest {
+                    team: "payments".into(),
+                    domain: domain.into(),
+                    listener_isolation,
+                    tls_config: None,
+                    metadata: None,
+                })
+                .await
+                .unwrap();
+            definitions
+                .create_route(CreateApiRouteRequest {
+                    api_definition_id: definition.id,
+                    match_type: "prefix".into(),
+                    match_value: "/".into(),
+                    case_sensitive: true,
+                    rewrite_prefix: None,
+                    rewrite_regex: None,
+                    rewrite_substitution: None,
+                    upstream_targets: serde_json::json!({
+                        "targets": [{ "name": "api", "endpoint": "api.svc:8080" }]
+                    }),
+                    timeout_seconds: None,
+                    override_config: None,
+                    deployment_note: None,
+                    route_order: 0,
+                })
+                .await
+                .unwrap();
+        }
+        state.ensure_caches_loaded().await;
+
+        let routes = state.cached_resources(ROUTE_TYPE_URL);
+        assert_eq!(routes.len(), 1);
+        assert_eq!(routes[0].name, DEFAULT_GATEWAY_ROUTES);
+        let merged = RouteConfiguration::decode(routes[0].body.value.as_slice()).unwrap();
+        let domains: Vec<&str> = merged
+            .virtual_hosts
+            .iter()
+            .flat_map(|virtual_host| virtual_host.domains.iter().map(String::as_str))
+            .collect();
+        assert!(domains.contains(&"shared.example.com"));
+        assert!(!domains.contains(&"isolated.example.com"));
+
+        // Both definitions publish their clusters, but no platform listener.
+        let platform_clusters = state
+            .cached_resources(CLUSTER_TYPE_URL)
+            .into_iter()
+            .filter(|cached| cached.name.starts_with("platform-"))
+            .count();
+        assert_eq!(platform_clusters, 2);
+        assert!(state
+            .cached_resources(LISTENER_TYPE_URL)
+            .iter()
+            .all(|cached| !cached.name.starts_with("platform-api-")));
+    }
+
+    #[tokio::test]
+    async fn coordinated_replicas_agree_on_versions() {
+        let pool: DbPool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap()
+            .into();
+        crate::storage::run_migrations(&pool).await.unwrap();
+
+        let replica = |replica_id: &str| {
+            let mut config = build_state().config;
+            config.coordination = Some(crate::config::CoordinationConfig {
+                replica_id: replica_id.to_string(),
+                lease_ttl_secs: 30,
+            });
+            XdsState::with_database(config, pool.clone())
+        };
+        let first = replica("replica-a");
+        let second = replica("replica-b");
+        first.refresh_clusters_from_repository().await.unwrap();
+        second.refresh_clusters_from_repository().await.unwrap();
+        assert_eq!(second.get_type_version_number(CLUSTER_TYPE_URL), 2);
+
+        // The first replica observes an intermediate state the second one misses.
+        let clusters = first.cluster_repository.clone().unwrap();
+        let created = clusters
+            .create(crate::storage::CreateClusterRequest {
+                name: "orders".into(),
+                service_name: "orders".into(),
+                configuration: serde_json::json!({ "endpoints": [{ "host": "10.0.0.1", "port": 8080 }] }),
+            })
+            .await
+            .unwrap();
+        first.refresh_clusters_from_repository().await.unwrap();
+        clusters
+            .update(
+                &created.id,
+                crate::storage::UpdateClusterRequest {
+                    service_name: None,
+                    configuration: Some(
+                        serde_json::json!({ "endpoints": [{ "host": "10.0.0.2", "port": 8080 }] }),
+                    ),
+                },
+            )
+            .await
+            .unwrap();
+        first.refresh_clusters_from_repository().await.unwrap();
+        second.refresh_clusters_from_repository().await.unwrap();
+
+        assert_eq!(first.get_type_version_number(CLUSTER_TYPE_URL), 4);
+        assert_eq!(second.get_type_version_number(CLUSTER_TYPE_URL), 4);
+    }
+
+    fn rejection(message: &str) -> RpcStatus {
+        RpcStatus { code: 3, message: message.to_string(), details: vec![] }
+    }
+
+    #[tokio::test]
+    async fn pin_policy_serves_known_good_until_newer_version() {
+        let mut state = build_state();
+        state.config.nack_policy = NackPolicy::Pin;
+
+        state.apply_built_resources(CLUSTER_TYPE_URL, vec![fake_resource("cluster-1", b"good")]);
+        state.record_ack(CLUSTER_TYPE_URL, "2").await;
+        state.apply_built_resources(CLUSTER_TYPE_URL, vec![fake_resource("cluster-1", b"bad")]);
+
+        state.handle_nack("envoy-1", CLUSTER_TYPE_URL, Some("3"), &rejection("invalid")).await;
+
+        let pinned = state.pinned_snapshot("envoy-1", CLUSTER_TYPE_URL).expect("node pinned");
+        assert_eq!(pinned.version, 2);
+        assert_eq!(pinned.resources[0].body.value, b"good".to_vec());
+        assert!(state.pinned_snapshot("envoy-2", CLUSTER_TYPE_URL).is_none());
+
+        state.apply_built_resources(CLUSTER_TYPE_URL, vec![fake_resource("cluster-1", b"fixed")]);
+        assert!(state.pinned_snapshot("envoy-1", CLUSTER_TYPE_URL).is_none());
+    }
+
+    #[tokio::test]
+    async fn revert_policy_restores_rejected_rows_and_audits() {
+        let pool: DbPool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap()
+            .into();
+        crate::storage::run_migrations(&pool).await.unwrap();
+
+        let mut config = build_state().config;
+        config.nack_policy = NackPolicy::Revert;
+        let state = XdsState::with_database(config, pool.clone());
+        let clusters = state.cluster_repository.clone().unwrap();
+
+        let good = serde_json::json!({ "endpoints": [{ "host": "10.0.0.1", "port": 8080 }] });
+        let created = clusters
+            .create(crate::storage::CreateClusterRequest {
+                name: "orders".into(),
+                service_name: "orders".into(),
+                configuration: good.clone(),
+            })
+            .await
+            .unwrap();
+        state.refresh_clusters_from_repository().await.unwrap();
+        state.record_ack(CLUSTER_TYPE_URL, &state.get_type_version(CLUSTER_TYPE_URL)).await;
+
+        clusters
+            .update(
+                &created.id,
+                crate::storage::UpdateClusterRequest {
+                    service_name: None,
+                    configuration: Some(
+                        serde_json::json!({ "endpoints": [{ "host": "10.0.0.2", "port": 9 }] }),
+                    ),
+                },
+            )
+            .await
+            .unwrap();
+        state.refresh_clusters_from_repository().await.unwrap();
+        let rejected = state.get_type_version(CLUSTER_TYPE_URL);
+
+        state
+            .handle_nack("envoy-1", CLUSTER_TYPE_URL, Some(&rejected), &rejection("bad port"))
+            .await;
+
+        let restored = clusters.get_by_name("orders").await.unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&restored.configuration).unwrap(),
+            good
+        );
+
+        let actions: Vec<String> = sqlx::query_scalar(
+            "SELECT action FROM audit_log WHERE resource_type = 'xds.delivery' ORDER BY id",
+        )
+        .fetch_all(pool.as_sqlite().unwrap())
+        .await
+        .unwrap();
+        assert_eq!(actions, vec!["xds.nack".to_string(), "xds.nack.revert".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn revert_policy_captures_rows_only_for_the_cached_version() {
+        let pool: DbPool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap()
+            .into();
+        crate::storage::run_migrations(&pool).await.unwrap();
+
+        let mut config = build_state().config;
+        config.nack_policy = NackPolicy::Revert;
+        let state = Arc::new(XdsState::with_database(config, pool.clone()));
+        state.apply_built_resources(CLUSTER_TYPE_URL, vec![fake_resource("cluster-1", b"v1")]);
+        let acked = state.get_type_version(CLUSTER_TYPE_URL);
+
+        // Hold the only connection so capturing rows blocks until released.
+        let connection = pool.as_sqlite().unwrap().acquire().await.unwrap();
+
+        // A snapshot applied while rows are captured invalidates them.
+        let ack = tokio::spawn({
+            let state = state.clone();
+            let acked = acked.clone();
+            async move { state.record_ack(CLUSTER_TYPE_URL, &acked).await }
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        state.apply_built_resources(CLUSTER_TYPE_URL, vec![fake_resource("cluster-1", b"v2")]);
+        drop(connection);
+        ack.await.unwrap();
+        assert!(state.known_good_snapshot(CLUSTER_TYPE_URL).is_none());
+
+        // A stale ACK is dropped before touching the database at all.
+        let _connection = pool.as_sqlite().unwrap().acquire().await.unwrap();
+        tokio::time::timeout(
+            std::time::Duration::from_secs(1),
+            state.record_ack(CLUSTER_TYPE_URL, &acked),
+        )
+        .await
+        .expect("stale ACK returns without capturing rows");
+        assert!(state.known_good_snapshot(CLUSTER_TYPE_URL).is_none());
+    }
+
+    fn node_with_metadata(id: &str, canary: bool) -> Node {
+        use envoy_types::pb::google::protobuf::{value::Kind, Struct, Value};
+        let mut fields = HashMap::new();
+        fields.insert("canary".to_string(), Value { kind: Some(Kind::BoolValue(canary)) });
+        Node { id: id.to_string(), metadata: Some(Struct { fields }), ..Default::default() }
+    }
+
+    fn canary_state(bake_time_secs: u64) -> XdsState {
+        let mut state = build_state();
+        state.config.canary = Some(crate::config::CanaryConfig {
+            selector: "canary=true".parse().unwrap(),
+            bake_time_secs,
+        });
+        state
+    }
+
+    #[tokio::test]
+    async fn rollout_holds_non_canary_nodes_until_promoted() {
+        let state = canary_state(300);
+        let canary = state.connect_node(&node_with_metadata("envoy-canary", true));
+        let stable = state.connect_node(&node_with_metadata("envoy-stable", false));
+
+        state.apply_built_resources(CLUSTER_TYPE_URL, vec![fake_resource("cluster-1", b"v2")]);
+        assert!(state.rollouts().is_empty(), "first snapshot has nothing to hold back to");
+
+        state.apply_built_resources(CLUSTER_TYPE_URL, vec![fake_resource("cluster-1", b"v3")]);
+        let rollouts = state.rollouts();
+        assert_eq!(rollouts.len(), 1);
+        assert_eq!(rollouts[0].resource_type, "cluster");
+        assert_eq!((rollouts[0].stable_version, rollouts[0].candidate_version), (2, 3));
+
+        assert!(state.held_snapshot(&canary, CLUSTER_TYPE_URL).is_none());
+        let held = state.held_snapshot(&stable, CLUSTER_TYPE_URL).expect("stable node held");
+        assert_eq!(held.resources[0].body.value, b"v2".to_vec());
+
+        state.handle_nack(&canary, CLUSTER_TYPE_URL, Some("3"), &rejection("bad")).await;
+        assert_eq!(state.rollouts()[0].nack_count, 1);
+
+        state.abort_rollout("cluster").expect("rollout aborted");
+        assert!(state.held_snapshot(&canary, CLUSTER_TYPE_URL).is_some());
+
+        state.promote_rollout("cluster").expect("rollout promoted");
+        assert!(state.rollouts().is_empty());
+        assert!(state.held_snapshot(&stable, CLUSTER_TYPE_URL).is_none());
+    }
+
+    #[tokio::test]
+    async fn baked_rollouts_promote_only_without_nacks() {
+        let state = canary_state(0);
+        let canary = state.connect_node(&node_with_metadata("envoy-canary", true));
+
+        state.apply_built_resources(ROUTE_TYPE_URL, vec![fake_resource("route-1", b"v2")]);
+        state.apply_built_resources(ROUTE_TYPE_URL, vec![fake_resource("route-1", b"v3")]);
+        state.handle_nack(&canary, ROUTE_TYPE_URL, Some("3"), &rejection("bad")).await;
+        state.promote_baked_rollouts();
+        assert_eq!(state.rollouts().len(), 1);
+
+        state.apply_built_resources(ROUTE_TYPE_URL, vec![fake_resource("route-1", b"v4")]);
+        state.promote_baked_rollouts();
+        assert!(state.rollouts().is_empty());
+    }
 }
@@ -18,12 +18,12 @@ use flowplane::{
 use hyper::Response;
 use serde::de::DeserializeOwned;
 use serde_json::Value;
-use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
 use tower::ServiceExt;
 
 pub struct TestApp {
     state: Arc<XdsState>,
-    pub pool: DbPool,
+    pub pool: SqlitePool,
     pub token_service: TokenService,
 }
 
@@ -55,15 +55,16 @@ pub async fn setup_test_app() -> TestApp {
 
     initialize_schema(&pool).await;
 
-    let state = Arc::new(XdsState::with_database(SimpleXdsConfig::default(), pool.clone()));
+    let db_pool = DbPool::from(pool.clone());
+    let state = Arc::new(XdsState::with_database(SimpleXdsConfig::default(), db_pool.clone()));
 
-    let audit_repo = Arc::new(AuditLogRepository::new(pool.clone()));
-    let token_service = TokenService::with_sqlx(pool.clone(), audit_repo);
+    let audit_repo = Arc::new(AuditLogRepository::new(db_pool.clone()));
+    let token_service = TokenService::with_sqlx(db_pool, audit_repo);
 
     TestApp { state, pool, token_service }
 }
 
-async fn initialize_schema(pool: &DbPool) {
+async fn initialize_schema(pool: &SqlitePool) {
     sqlx::query(
         r#"
         CREATE TABLE personal_access_tokens (
@@ -70,7 +70,7 @@ async fn setup_pool() -> DbPool {
     .await
     .unwrap();
 
-    pool
+    pool.into()
 }
 
 async fn setup_services() -> (TokenService, AuthService) {
@@ -71,7 +71,7 @@ async fn setup_pool() -> DbPool {
     .await
     .unwrap();
 
-    pool
+    pool.into()
 }
 
 #[tokio::test]
@@ -103,7 +103,7 @@ async fn run_once_marks_expired_tokens() {
         "SELECT COUNT(*) FROM audit_log WHERE action = 'auth.token.expired' AND resource_id = $1",
     )
     .bind(&token_id)
-    .fetch_one(&pool)
+    .fetch_one(pool.as_sqlite().unwrap())
     .await
     .unwrap();
     assert_eq!(audit_count, 1);
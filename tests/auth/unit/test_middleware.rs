@@ -78,7 +78,7 @@ async fn setup_pool() -> flowplane::storage::DbPool {
     .await
     .unwrap();
 
-    pool
+    pool.into()
 }
 
 async fn setup_services() -> (TokenService, Arc<AuthService>) {
@@ -47,7 +47,7 @@ async fn setup_pool() -> DbPool {
     .await
     .unwrap();
 
-    pool
+    pool.into()
 }
 
 fn sample_token(id: &str) -> NewPersonalAccessToken {
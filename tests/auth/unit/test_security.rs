@@ -10,7 +10,7 @@ use std::sync::Arc;
 use tokio::time::Instant;
 
 async fn setup_service() -> (TokenService, Arc<SqlxTokenRepository>, String) {
-    let pool: DbPool = SqlitePoolOptions::new()
+    let sqlite_pool = SqlitePoolOptions::new()
         .max_connections(5)
         .connect("sqlite::memory:?cache=shared")
         .await
@@ -32,7 +32,7 @@ async fn setup_service() -> (TokenService, Arc<SqlxTokenRepository>, String) {
         );
         "#,
     )
-    .execute(&pool)
+    .execute(&sqlite_pool)
     .await
     .unwrap();
 
@@ -47,7 +47,7 @@ async fn setup_service() -> (TokenService, Arc<SqlxTokenRepository>, String) {
         );
         "#,
     )
-    .execute(&pool)
+    .execute(&sqlite_pool)
     .await
     .unwrap();
 
@@ -68,10 +68,11 @@ async fn setup_service() -> (TokenService, Arc<SqlxTokenRepository>, String) {
         );
         "#,
     )
-    .execute(&pool)
+    .execute(&sqlite_pool)
     .await
     .unwrap();
 
+    let pool = DbPool::from(sqlite_pool);
     let repo = Arc::new(SqlxTokenRepository::new(pool.clone()));
     let audit = Arc::new(AuditLogRepository::new(pool));
     let service = TokenService::new(repo.clone(), audit);
@@ -73,7 +73,7 @@ async fn setup_pool() -> DbPool {
     .await
     .unwrap();
 
-    pool
+    pool.into()
 }
 
 async fn setup_service() -> (TokenService, Arc<SqlxTokenRepository>, Arc<AuditLogRepository>, DbPool)
@@ -161,14 +161,14 @@ async fn ensure_bootstrap_token_creates_when_empty() {
     assert!(service.ensure_bootstrap_token().await.unwrap().is_none());
 
     let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM personal_access_tokens")
-        .fetch_one(&pool)
+        .fetch_one(pool.as_sqlite().unwrap())
         .await
         .unwrap();
     assert_eq!(count, 1);
 
     let seeded_events: i64 =
         sqlx::query_scalar("SELECT COUNT(*) FROM audit_log WHERE action = 'auth.token.seeded'")
-            .fetch_one(&pool)
+            .fetch_one(pool.as_sqlite().unwrap())
             .await
             .unwrap();
     assert_eq!(seeded_events, 1);
@@ -208,7 +208,7 @@ async fn migrations_create_platform_api_tables() -> Result<()> {
         .await
         .map_err(|err| Error::database(err, "Failed to create in-memory pool".to_string()))?;
 
-    flowplane::storage::run_migrations(&pool).await?;
+    flowplane::storage::run_migrations(&pool.clone().into()).await?;
 
     let api_definitions: Option<String> = sqlx::query_scalar(
         "SELECT name FROM sqlite_master WHERE type = 'table' AND name = 'api_definitions'",
@@ -39,6 +39,13 @@ impl ControlPlaneHandle {
             port: xds_addr.port(),
             resources: XdsResourceConfig { listener_port: 10000, ..Default::default() },
             tls: None,
+            change_poll_interval_ms: None,
+            update_debounce_ms: None,
+            nack_policy: Default::default(),
+            canary: None,
+            secret_cipher: None,
+            ca: None,
+            coordination: None,
         };
 
         let state = Arc::new(XdsState::with_database(simple_config, pool));
@@ -87,6 +94,13 @@ impl ControlPlaneHandle {
             port: xds_addr.port(),
             resources: XdsResourceConfig { listener_port: 10000, ..Default::default() },
             tls: xds_tls,
+            change_poll_interval_ms: None,
+            update_debounce_ms: None,
+            nack_policy: Default::default(),
+            canary: None,
+            secret_cipher: None,
+            ca: None,
+            coordination: None,
         };
 
         let state = Arc::new(XdsState::with_database(simple_config, pool));
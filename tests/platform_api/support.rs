@@ -12,18 +12,18 @@ use flowplane::{
         validation::CreateTokenRequest,
     },
     config::SimpleXdsConfig,
-    storage::{self, repository_simple::AuditLogRepository, DbPool},
+    storage::{self, repository_simple::AuditLogRepository, ChangeNotifier, DbPool},
     xds::XdsState,
 };
 use hyper::Response;
 use serde::de::DeserializeOwned;
 use serde_json::Value;
-use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
 use tower::ServiceExt;
 
 pub struct PlatformApiApp {
     state: Arc<XdsState>,
-    pub pool: DbPool,
+    pub pool: SqlitePool,
     token_service: TokenService,
 }
 
@@ -32,6 +32,14 @@ impl PlatformApiApp {
         flowplane::api::routes::build_router(self.state.clone())
     }
 
+    pub fn db_pool(&self) -> DbPool {
+        DbPool::from(self.pool.clone())
+    }
+
+    pub fn change_notifier(&self) -> ChangeNotifier {
+        self.state.change_notifier.clone()
+    }
+
     pub async fn issue_token(&self, name: &str, scopes: &[&str]) -> TokenSecretResponse {
         self.token_service
             .create_token(CreateTokenRequest {
@@ -53,11 +61,12 @@ pub async fn setup_platform_api_app() -> PlatformApiApp {
         .await
         .expect("create sqlite pool");
 
-    storage::run_migrations(&pool).await.expect("run migrations for tests");
+    let db_pool = DbPool::from(pool.clone());
+    storage::run_migrations(&db_pool).await.expect("run migrations for tests");
 
-    let state = Arc::new(XdsState::with_database(SimpleXdsConfig::default(), pool.clone()));
-    let audit_repo = Arc::new(AuditLogRepository::new(pool.clone()));
-    let token_service = TokenService::with_sqlx(pool.clone(), audit_repo);
+    let state = Arc::new(XdsState::with_database(SimpleXdsConfig::default(), db_pool.clone()));
+    let audit_repo = Arc::new(AuditLogRepository::new(db_pool.clone()));
+    let token_service = TokenService::with_sqlx(db_pool, audit_repo);
 
     PlatformApiApp { state, pool, token_service }
 }
@@ -39,3 +39,40 @@ async fn bootstrap_returns_yaml_with_team_scope_defaults() {
     assert!(text.contains("team: payments"));
     // include_default should be false by default; presence not guaranteed in YAML, so don't assert its literal
 }
+
+#[tokio::test]
+async fn team_bootstrap_points_ads_at_the_xds_server() {
+    let app = setup_platform_api_app().await;
+    let token = app.issue_token("bootstrap-team", &["routes:read"]).await;
+
+    let resp = send_request(
+        &app,
+        Method::GET,
+        "/api/v1/bootstrap?team=payments&includeDefault=true&format=json&nodeId=edge-1",
+        Some(&token.token),
+        None,
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let bootstrap: serde_json::Value = read_json(resp).await;
+
+    assert_eq!(bootstrap["node"]["id"], "edge-1");
+    assert_eq!(
+        bootstrap["node"]["metadata"],
+        json!({ "team": "payments", "include_default": true })
+    );
+    assert_eq!(bootstrap["dynamic_resources"]["ads_config"]["api_type"], "GRPC");
+    assert_eq!(bootstrap["dynamic_resources"]["lds_config"]["ads"], json!({}));
+    assert_eq!(bootstrap["static_resources"]["clusters"][0]["name"], "xds_cluster");
+    assert!(bootstrap["admin"]["address"].is_object());
+
+    let resp = send_request(
+        &app,
+        Method::GET,
+        "/api/v1/bootstrap?scope=allowlist&format=json",
+        Some(&token.token),
+        None,
+    )
+    .await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
@@ -8,7 +8,7 @@ use super::support::setup_platform_api_app;
 #[tokio::test]
 async fn detecting_domain_collision_for_different_team() {
     let app = setup_platform_api_app().await;
-    let repo = ApiDefinitionRepository::new(app.pool.clone());
+    let repo = ApiDefinitionRepository::new(app.db_pool(), app.change_notifier());
 
     let created = repo
         .create_definition(CreateApiDefinitionRequest {
@@ -44,7 +44,7 @@ async fn create_isolated_api_creates_dedicated_listener() {
     assert!(body.get("bootstrapUri").is_some());
 
     // Verify listener exists via repository query
-    let repo = ListenerRepository::new(app.pool.clone());
+    let repo = ListenerRepository::new(app.db_pool(), app.change_notifier());
     let listeners = repo.list(Some(100), None).await.expect("list listeners");
     assert!(listeners.iter().any(|l| l.name == "iso-listener-1" && l.port == Some(10011)));
 
@@ -62,7 +62,7 @@ async fn isolated_port_conflict_rolls_back_definition() {
     let token = app.issue_token("platform-admin", &["routes:write", "listeners:write"]).await;
 
     // Seed a conflicting listener at 0.0.0.0:10012
-    let repo = ListenerRepository::new(app.pool.clone());
+    let repo = ListenerRepository::new(app.db_pool(), app.change_notifier());
     let _existing = repo
         .create(CreateListenerRequest {
             name: "pre-existing".into(),
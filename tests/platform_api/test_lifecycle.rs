@@ -39,10 +39,6 @@ async fn platform_api_create_append_and_persist_routes() {
     let api_id = body.get("id").and_then(|v| v.as_str()).expect("api id");
     let bootstrap_uri = body.get("bootstrapUri").and_then(|v| v.as_str()).expect("bootstrap uri");
 
-    // Bootstrap should be materialized on disk for the dataplane
-    let bootstrap_path = format!("data/bootstrap/{}.yaml", api_id);
-    assert!(std::path::Path::new(&bootstrap_path).exists(), "bootstrap file should exist");
-
     let append_payload = json!({
         "route": {
             "match": { "prefix": "/admin/" },
@@ -91,6 +87,6 @@ async fn platform_api_create_append_and_persist_routes() {
         "canonical CORS policy should be stored"
     );
 
-    // Ensure bootstrap URI points to the stored location
-    assert!(bootstrap_uri.contains(&format!("/api-definitions/{}.yaml", api_id)));
+    // Ensure bootstrap URI points to the bootstrap endpoint
+    assert_eq!(bootstrap_uri, format!("/api/v1/api-definitions/{}/bootstrap", api_id));
 }
@@ -0,0 +1,163 @@
+//! Storage tests against a real PostgreSQL server.
+//!
+//! Ignored by default; run with `--ignored` once `FLOWPLANE_TEST_POSTGRES_URL`
+//! (or a `postgres://` `DATABASE_URL`) points at a database the tests may
+//! write to, e.g.
+//! `FLOWPLANE_TEST_POSTGRES_URL=postgres://postgres@localhost:5432/flowplane_test`.
+
+use std::time::Duration;
+
+use flowplane::config::SimpleXdsConfig;
+use flowplane::storage::events::CHANGE_NOTIFY_CHANNEL;
+use flowplane::storage::{
+    create_pool, list_applied_migrations, validate_migrations, Change, ChangeKind, ChangeNotifier,
+    ClusterRepository, CreateClusterRequest, CreateListenerRequest, CreateRouteRepositoryRequest,
+    DatabaseBackend, DatabaseConfig, DbPool, ListenerRepository, RouteRepository,
+    UpdateClusterRequest,
+};
+use flowplane::xds::XdsState;
+use serde_json::json;
+use sqlx::postgres::PgListener;
+
+const CLUSTER_TYPE_URL: &str = "type.googleapis.com/envoy.config.cluster.v3.Cluster";
+const ENDPOINT_TYPE_URL: &str =
+    "type.googleapis.com/envoy.config.endpoint.v3.ClusterLoadAssignment";
+const NOTIFY_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn postgres_url() -> Option<String> {
+    std::env::var("FLOWPLANE_TEST_POSTGRES_URL")
+        .ok()
+        .or_else(|| std::env::var("DATABASE_URL").ok().filter(|url| url.starts_with("postgres")))
+}
+
+async fn connect(url: String) -> DbPool {
+    let config = DatabaseConfig { url, auto_migrate: true, ..Default::default() };
+    create_pool(&config).await.expect("connect to PostgreSQL and run migrations")
+}
+
+fn cluster_config(port: u16) -> serde_json::Value {
+    json!({ "endpoints": [format!("127.0.0.1:{}", port)], "useEds": true })
+}
+
+#[tokio::test]
+#[ignore = "requires FLOWPLANE_TEST_POSTGRES_URL"]
+async fn postgres_backend_round_trip() {
+    let url = postgres_url().expect("FLOWPLANE_TEST_POSTGRES_URL points at a PostgreSQL database");
+    let pool = connect(url.clone()).await;
+    assert_eq!(pool.backend(), DatabaseBackend::Postgres);
+
+    // Migrations: applied once, recorded, and idempotent on reconnect.
+    let applied = list_applied_migrations(&pool).await.expect("list migrations");
+    assert!(!applied.is_empty());
+    assert!(validate_migrations(&pool).await.expect("validate migrations"));
+    let reconnected = connect(url).await;
+    assert_eq!(list_applied_migrations(&reconnected).await.unwrap().len(), applied.len());
+    reconnected.close().await;
+
+    let suffix = uuid::Uuid::new_v4().simple().to_string();
+    let cluster_name = format!("pg-cluster-{}", suffix);
+    let route_name = format!("pg-route-{}", suffix);
+    let listener_name = format!("pg-listener-{}", suffix);
+
+    // A second notifier stands in for another replica sharing the database.
+    let notifier = ChangeNotifier::new();
+    let replica = ChangeNotifier::new();
+    let mut local_changes = notifier.subscribe();
+    let mut remote = PgListener::connect_with(pool.as_postgres().expect("postgres pool"))
+        .await
+        .expect("connect change listener");
+    remote.listen(CHANGE_NOTIFY_CHANNEL).await.expect("listen for changes");
+
+    let clusters = ClusterRepository::new(pool.clone(), notifier.clone());
+    let routes = RouteRepository::new(pool.clone(), notifier.clone());
+    let listeners = ListenerRepository::new(pool.clone(), notifier.clone());
+
+    let version_before = pool.data_version().await.expect("read data_version");
+
+    // Cluster CRUD, publishing each committed write locally and over NOTIFY.
+    let created = clusters
+        .create(CreateClusterRequest {
+            name: cluster_name.clone(),
+            service_name: "pg-service".to_string(),
+            configuration: cluster_config(8080),
+        })
+        .await
+        .expect("create cluster");
+    assert_eq!(created.version, 1);
+    assert_eq!(
+        local_changes.recv().await.unwrap(),
+        Change::row(ChangeKind::Clusters, &cluster_name)
+    );
+
+    let notification = tokio::time::timeout(NOTIFY_TIMEOUT, remote.recv())
+        .await
+        .expect("NOTIFY delivered")
+        .expect("receive NOTIFY");
+    assert_eq!(
+        replica.decode_remote(notification.payload()),
+        Some(Change::row(ChangeKind::Clusters, &cluster_name))
+    );
+    assert_eq!(notifier.decode_remote(notification.payload()), None);
+
+    assert_ne!(pool.data_version().await.unwrap(), version_before);
+
+    let fetched = clusters.get_by_name(&cluster_name).await.expect("get cluster");
+    assert_eq!(fetched.id, created.id);
+    assert!(clusters.exists_by_name(&cluster_name).await.unwrap());
+    assert!(clusters
+        .list(Some(1000), None)
+        .await
+        .unwrap()
+        .iter()
+        .any(|cluster| cluster.name == cluster_name));
+
+    let updated = clusters
+        .update(
+            &created.id,
+            UpdateClusterRequest { service_name: None, configuration: Some(cluster_config(9090)) },
+        )
+        .await
+        .expect("update cluster");
+    assert_eq!(updated.version, 2);
+    assert!(updated.configuration.contains("9090"));
+
+    // Route and listener rows round-trip through the same pool.
+    let route = routes
+        .create(CreateRouteRepositoryRequest {
+            name: route_name.clone(),
+            path_prefix: "/".to_string(),
+            cluster_name: cluster_name.clone(),
+            configuration: json!({ "name": route_name, "virtualHosts": [] }),
+        })
+        .await
+        .expect("create route");
+    assert_eq!(routes.get_by_name(&route_name).await.unwrap().id, route.id);
+
+    let listener = listeners
+        .create(CreateListenerRequest {
+            name: listener_name.clone(),
+            address: "0.0.0.0".to_string(),
+            port: Some(18080),
+            protocol: None,
+            configuration: json!({ "name": listener_name }),
+        })
+        .await
+        .expect("create listener");
+    assert_eq!(listeners.get_by_name(&listener_name).await.unwrap().id, listener.id);
+
+    // The xDS cache re-encodes the row named by a change on PostgreSQL too.
+    let state = XdsState::with_database(SimpleXdsConfig::default(), pool.clone());
+    state.refresh_row(ChangeKind::Clusters, &cluster_name).await.expect("refresh cluster row");
+    assert!(state.cached_resources(CLUSTER_TYPE_URL).iter().any(|c| c.name == cluster_name));
+    assert!(state.cached_resources(ENDPOINT_TYPE_URL).iter().any(|c| c.name == cluster_name));
+
+    listeners.delete(&listener.id).await.expect("delete listener");
+    routes.delete(&route.id).await.expect("delete route");
+    clusters.delete(&created.id).await.expect("delete cluster");
+    assert!(!clusters.exists_by_name(&cluster_name).await.unwrap());
+
+    state.refresh_row(ChangeKind::Clusters, &cluster_name).await.expect("refresh deleted row");
+    assert!(!state.cached_resources(CLUSTER_TYPE_URL).iter().any(|c| c.name == cluster_name));
+
+    pool.close().await;
+}
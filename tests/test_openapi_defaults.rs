@@ -123,7 +123,7 @@ async fn setup_pool() -> DbPool {
     .await
     .unwrap();
 
-    pool
+    pool.into()
 }
 
 #[tokio::test]
@@ -132,9 +132,10 @@ async fn ensure_default_gateway_resources_seeds_bootstrap_token() {
     let state = Arc::new(XdsState::with_database(SimpleXdsConfig::default(), pool.clone()));
 
     ensure_default_gateway_resources(&state).await.expect("default resources");
+    let sqlite = pool.as_sqlite().expect("sqlite pool");
 
     let token_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM personal_access_tokens")
-        .fetch_one(&pool)
+        .fetch_one(sqlite)
         .await
         .unwrap();
     assert_eq!(token_count, 1);
@@ -142,7 +143,7 @@ async fn ensure_default_gateway_resources_seeds_bootstrap_token() {
     let audit_count: i64 = sqlx::query_scalar(
         "SELECT COUNT(*) FROM audit_log WHERE action = 'auth.token.seeded' AND resource_type = 'auth.token'",
     )
-    .fetch_one(&pool)
+    .fetch_one(sqlite)
     .await
     .unwrap();
     assert_eq!(audit_count, 1);